@@ -0,0 +1,72 @@
+//! Detection of the game events hooked by a file.
+
+use std::sync::Arc;
+
+use lsp_types::Range;
+use sourcepawn_lexer::{Literal, TokenKind};
+use vfs::FileId;
+
+use crate::SourceDatabase;
+
+/// A `HookEvent("player_death", Event_PlayerDeath)` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventHook {
+    /// Name of the hooked event.
+    pub event: String,
+
+    /// Name of the callback of the hook.
+    pub callback: String,
+
+    /// Range of the event name in the file.
+    pub range: Range,
+}
+
+/// Returns all the `HookEvent` and `HookEventEx` calls of a file.
+///
+/// # Note
+/// Only calls with a string literal event name and an identifier callback are returned.
+pub(crate) fn file_event_hooks_query(
+    db: &dyn SourceDatabase,
+    file_id: FileId,
+) -> Arc<Vec<EventHook>> {
+    let mut res = vec![];
+    let input = db.file_text(file_id);
+    let mut lexer = sourcepawn_lexer::SourcepawnLexer::new(&input).filter(|symbol| {
+        !matches!(
+            symbol.token_kind,
+            TokenKind::Newline | TokenKind::Comment(_)
+        )
+    });
+    while let Some(symbol) = lexer.next() {
+        if symbol.token_kind != TokenKind::Identifier
+            || !matches!(symbol.text().as_str(), "HookEvent" | "HookEventEx")
+        {
+            continue;
+        }
+        if !matches!(lexer.next(), Some(symbol) if symbol.token_kind == TokenKind::LParen) {
+            continue;
+        }
+        let Some(event) = lexer.next() else {
+            break;
+        };
+        if event.token_kind != TokenKind::Literal(Literal::StringLiteral) {
+            continue;
+        }
+        if !matches!(lexer.next(), Some(symbol) if symbol.token_kind == TokenKind::Comma) {
+            continue;
+        }
+        let Some(callback) = lexer.next() else {
+            break;
+        };
+        if callback.token_kind != TokenKind::Identifier {
+            continue;
+        }
+        res.push(EventHook {
+            event: event.text().trim_matches('"').to_string(),
+            callback: callback.text().to_string(),
+            range: event.range,
+        });
+    }
+
+    Arc::new(res)
+}
@@ -0,0 +1,219 @@
+//! Detection of the paths passed as string literals to SourceMod functions.
+
+use std::sync::Arc;
+
+use lsp_types::Range;
+use sourcepawn_lexer::{Literal, Symbol, TokenKind};
+use vfs::FileId;
+
+use crate::SourceDatabase;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathLiteralKind {
+    /// A path relative to the `addons/sourcemod` folder, e.g. the path of
+    /// `BuildPath(Path_SM, buffer, sizeof(buffer), "configs/foo.cfg")`.
+    SourceMod,
+
+    /// A path relative to the game folder, e.g. `cfg/sourcemod/foo.cfg` for
+    /// `AutoExecConfig(true, "foo")`.
+    Game,
+}
+
+/// A path passed as a string literal to `BuildPath(Path_SM, ...)` or `AutoExecConfig`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathLiteral {
+    pub kind: PathLiteralKind,
+
+    /// The path, relative to the folder of its kind.
+    pub path: String,
+
+    /// Range of the string literal in the file.
+    pub range: Range,
+}
+
+/// Returns all the paths passed as string literals to `BuildPath(Path_SM, ...)` and
+/// `AutoExecConfig` in a file.
+///
+/// # Note
+/// Paths which contain format specifiers are not returned.
+pub(crate) fn file_path_literals_query(
+    db: &dyn SourceDatabase,
+    file_id: FileId,
+) -> Arc<Vec<PathLiteral>> {
+    Arc::new(path_literals(&db.file_text(file_id)))
+}
+
+/// Returns the paths passed as string literals to `BuildPath(Path_SM, ...)` and
+/// `AutoExecConfig` in `input`.
+fn path_literals(input: &str) -> Vec<PathLiteral> {
+    let mut res = vec![];
+    let mut lexer = sourcepawn_lexer::SourcepawnLexer::new(input).filter(|symbol| {
+        !matches!(
+            symbol.token_kind,
+            TokenKind::Newline | TokenKind::Comment(_)
+        )
+    });
+    while let Some(symbol) = lexer.next() {
+        if symbol.token_kind != TokenKind::Identifier
+            || !matches!(symbol.text().as_str(), "BuildPath" | "AutoExecConfig")
+        {
+            continue;
+        }
+        if !matches!(lexer.next(), Some(symbol) if symbol.token_kind == TokenKind::LParen) {
+            continue;
+        }
+        let path = if symbol.text() == "BuildPath" {
+            build_path_literal(&mut lexer)
+        } else {
+            auto_exec_config_literal(&mut lexer)
+        };
+        res.extend(path.filter(|it| !it.path.contains('%')));
+    }
+
+    res
+}
+
+/// Returns the path of a `BuildPath(Path_SM, buffer, maxlength, "path")` call, the lexer being
+/// after the opening parenthesis.
+fn build_path_literal(lexer: &mut impl Iterator<Item = Symbol>) -> Option<PathLiteral> {
+    let path_type = lexer.next()?;
+    if path_type.token_kind != TokenKind::Identifier || path_type.text() != "Path_SM" {
+        return None;
+    }
+    if lexer.next()?.token_kind != TokenKind::Comma {
+        return None;
+    }
+    // Skip the buffer and its length.
+    for _ in 0..2 {
+        if skip_argument(lexer)? != TokenKind::Comma {
+            return None;
+        }
+    }
+    let path = lexer.next()?;
+    if path.token_kind != TokenKind::Literal(Literal::StringLiteral) {
+        return None;
+    }
+
+    Some(PathLiteral {
+        kind: PathLiteralKind::SourceMod,
+        path: path.text().trim_matches('"').to_string(),
+        range: path.range,
+    })
+}
+
+/// Returns the path of the config of an `AutoExecConfig(autoCreate, "name", "folder")` call,
+/// the lexer being after the opening parenthesis.
+fn auto_exec_config_literal(lexer: &mut impl Iterator<Item = Symbol>) -> Option<PathLiteral> {
+    if skip_argument(lexer)? != TokenKind::Comma {
+        return None;
+    }
+    let name = lexer.next()?;
+    if name.token_kind != TokenKind::Literal(Literal::StringLiteral) {
+        return None;
+    }
+    let folder = match lexer.next()?.token_kind {
+        TokenKind::Comma => {
+            let folder = lexer.next()?;
+            if folder.token_kind != TokenKind::Literal(Literal::StringLiteral) {
+                return None;
+            }
+            folder.text().trim_matches('"').to_string()
+        }
+        TokenKind::RParen => "sourcemod".to_string(),
+        _ => return None,
+    };
+    let file_name = name.text().trim_matches('"').to_string();
+    if file_name.is_empty() {
+        // The config is named after the plugin.
+        return None;
+    }
+
+    Some(PathLiteral {
+        kind: PathLiteralKind::Game,
+        path: format!("cfg/{}/{}.cfg", folder, file_name),
+        range: name.range,
+    })
+}
+
+/// Consumes the tokens of an argument and returns the token which ends it, which is either a
+/// comma or the closing parenthesis of the call.
+fn skip_argument(lexer: &mut impl Iterator<Item = Symbol>) -> Option<TokenKind> {
+    let mut depth = 0u32;
+    for symbol in lexer {
+        match symbol.token_kind {
+            TokenKind::LParen => depth += 1,
+            TokenKind::RParen if depth == 0 => return Some(TokenKind::RParen),
+            TokenKind::RParen => depth -= 1,
+            TokenKind::Comma if depth == 0 => return Some(TokenKind::Comma),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::*;
+
+    #[test]
+    fn build_path_literals() {
+        let literals = path_literals(
+            r#"
+void Load()
+{
+    BuildPath(Path_SM, path, sizeof(path), "configs/foo.cfg");
+    BuildPath(Path_SM, path, GetMaxLength(path, 1), "data/bar.txt");
+    BuildPath(Path_SM, path, sizeof(path), "logs/%s.log", name);
+    BuildPath(Path_SM, path, sizeof(path), name);
+    // BuildPath(Path_SM, path, sizeof(path), "configs/commented.cfg");
+}
+"#,
+        );
+        assert_eq!(
+            literals,
+            vec![
+                PathLiteral {
+                    kind: PathLiteralKind::SourceMod,
+                    path: "configs/foo.cfg".to_string(),
+                    range: Range::new(Position::new(3, 43), Position::new(3, 60)),
+                },
+                PathLiteral {
+                    kind: PathLiteralKind::SourceMod,
+                    path: "data/bar.txt".to_string(),
+                    range: Range::new(Position::new(4, 52), Position::new(4, 66)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_exec_config_literals() {
+        let literals = path_literals(
+            r#"
+public void OnPluginStart()
+{
+    AutoExecConfig(true, "plugin.foo");
+    AutoExecConfig(true, "bar", "custom");
+    AutoExecConfig(true, "");
+    AutoExecConfig();
+}
+"#,
+        );
+        assert_eq!(
+            literals
+                .into_iter()
+                .map(|literal| (literal.kind, literal.path))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    PathLiteralKind::Game,
+                    "cfg/sourcemod/plugin.foo.cfg".to_string()
+                ),
+                (PathLiteralKind::Game, "cfg/custom/bar.cfg".to_string()),
+            ]
+        );
+    }
+}
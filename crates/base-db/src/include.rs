@@ -32,6 +32,9 @@ pub struct Include {
     kind: IncludeKind,
     type_: IncludeType,
     extension: FileExtension,
+    /// Range of the path of the directive in the including file, delimiters included, with
+    /// columns in bytes. `None` for the implicit `sourcemod.inc` include.
+    range: Option<Range>,
 }
 
 impl Include {
@@ -40,12 +43,14 @@ impl Include {
         kind: IncludeKind,
         type_: IncludeType,
         extension: FileExtension,
+        range: Option<Range>,
     ) -> Self {
         Self {
             id,
             kind,
             type_,
             extension,
+            range,
         }
     }
 
@@ -64,6 +69,14 @@ impl Include {
     pub fn extension(&self) -> FileExtension {
         self.extension
     }
+
+    pub fn line(&self) -> Option<u32> {
+        self.range.map(|range| range.start.line)
+    }
+
+    pub fn range(&self) -> Option<Range> {
+        self.range
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -96,6 +109,7 @@ pub(crate) fn file_includes_query(
             IncludeKind::Chevrons,
             IncludeType::TryInclude,
             FileExtension::Inc,
+            None,
         ));
     }
 
@@ -111,15 +125,27 @@ pub(crate) fn file_includes_query(
                     IncludeType::TryInclude
                 };
                 let text = symbol.inline_text().trim().to_string();
+                let line = symbol.range.start.line;
+                let start = symbol.range.start.character;
                 let symbol = Symbol::new(
                     symbol.token_kind,
                     Some(&text),
                     Range::new(
-                        Position::new(symbol.range.start.line, symbol.range.start.character),
-                        Position::new(symbol.range.start.line, text.len() as u32),
+                        Position::new(line, start),
+                        Position::new(line, start + text.len() as u32),
                     ),
                     symbol.delta,
                 );
+                let path_range = RE_QUOTE
+                    .captures(&text)
+                    .or_else(|| RE_CHEVRON.captures(&text))
+                    .and_then(|caps| caps.get(0))
+                    .map(|m| {
+                        Range::new(
+                            Position::new(line, start + m.start() as u32),
+                            Position::new(line, start + m.end() as u32),
+                        )
+                    });
 
                 let mut kind = IncludeKind::Chevrons;
                 let mut path = None;
@@ -132,7 +158,13 @@ pub(crate) fn file_includes_query(
                     if let Some(include_file_id) =
                         db.resolve_path(AnchoredPath::new(file_id, &raw_path))
                     {
-                        res.push(Include::new(include_file_id, kind, type_, raw_ext));
+                        res.push(Include::new(
+                            include_file_id,
+                            kind,
+                            type_,
+                            raw_ext,
+                            path_range,
+                        ));
                         continue;
                     }
                     // Hack to detect `include` folders when it's a relative include.
@@ -140,7 +172,13 @@ pub(crate) fn file_includes_query(
                     if let Some(include_file_id) =
                         db.resolve_path(AnchoredPath::new(file_id, &raw_path_with_include))
                     {
-                        res.push(Include::new(include_file_id, kind, type_, raw_ext));
+                        res.push(Include::new(
+                            include_file_id,
+                            kind,
+                            type_,
+                            raw_ext,
+                            path_range,
+                        ));
                         continue;
                     }
                     path = Some(raw_path);
@@ -174,7 +212,7 @@ pub(crate) fn file_includes_query(
                 };
                 match db.resolve_path_relative_to_roots(&path) {
                     Some(include_file_id) => {
-                        res.push(Include::new(include_file_id, kind, type_, ext));
+                        res.push(Include::new(include_file_id, kind, type_, ext, path_range));
                         continue;
                     }
                     None => {
@@ -42,6 +42,18 @@ impl SourceRoot {
         self.file_set.path_for_file(file)
     }
 
+    /// Returns the path of the file relative to the root of the source root, with `/` as
+    /// the separator, e.g. `sdktools/sdktools_functions.inc`.
+    pub fn relative_path_for_file(&self, file: &FileId) -> Option<String> {
+        let path = self.path_for_file(file)?.strip_prefix(&self.root)?;
+        let components = path
+            .components()
+            .map(|it| it.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(components.join("/"))
+    }
+
     pub fn file_for_path(&self, path: &VfsPath) -> Option<&FileId> {
         self.file_set.file_for_path(path)
     }
@@ -1,23 +1,38 @@
 use std::{hash::Hash, sync::Arc};
 
+use events::file_event_hooks_query;
+use file_paths::file_path_literals_query;
 use include::file_includes_query;
 use input::{SourceRoot, SourceRootId};
 use syntax::utils::lsp_position_to_ts_point;
+use translations::{file_loaded_translations_query, phrases_file_query};
+use unincluded::unincluded_files_query;
 use vfs::{AnchoredPath, FileId};
 
 mod change;
+mod events;
+mod file_paths;
 mod graph;
 mod include;
 mod input;
+mod translations;
+mod unincluded;
 
 pub use {
     change::Change,
+    events::EventHook,
+    file_paths::{PathLiteral, PathLiteralKind},
     graph::{Graph, SubGraph},
     include::{
         infer_include_ext, Include, IncludeKind, IncludeType, UnresolvedInclude, RE_CHEVRON,
         RE_QUOTE,
     },
     input::SourceRootConfig,
+    translations::{
+        normalize_translation_name, parse_key_values, KeyValue, KeyValueValue, LoadedTranslation,
+        Phrase, PhrasesFile,
+    },
+    unincluded::UnincludedFiles,
 };
 
 pub const DEFAULT_PARSE_LRU_CAP: usize = 128;
@@ -29,6 +44,9 @@ pub trait FileLoader {
     /// Known files.
     fn known_files(&self) -> Vec<(FileId, FileExtension)>;
 
+    /// Known translation files, with their normalized name.
+    fn known_translation_files(&self) -> Arc<Vec<(FileId, String)>>;
+
     /// Resolve a path to a file.
     fn resolve_path(&self, path: AnchoredPath<'_>) -> Option<FileId>;
 
@@ -121,6 +139,18 @@ pub trait SourceDatabase: FileLoader + std::fmt::Debug {
 
     #[salsa::invoke(graph::Graph::projet_subgraph_query)]
     fn projet_subgraph(&self, file_id: FileId) -> Option<Arc<graph::SubGraph>>;
+
+    #[salsa::invoke(phrases_file_query)]
+    fn phrases_file(&self, file_id: FileId) -> Arc<PhrasesFile>;
+
+    #[salsa::invoke(file_loaded_translations_query)]
+    fn file_loaded_translations(&self, file_id: FileId) -> Arc<Vec<LoadedTranslation>>;
+
+    #[salsa::invoke(file_event_hooks_query)]
+    fn file_event_hooks(&self, file_id: FileId) -> Arc<Vec<EventHook>>;
+
+    #[salsa::invoke(file_path_literals_query)]
+    fn file_path_literals(&self, file_id: FileId) -> Arc<Vec<PathLiteral>>;
 }
 
 /// We don't want to give HIR knowledge of source roots, hence we extract these
@@ -134,6 +164,9 @@ pub trait SourceDatabaseExt: SourceDatabase {
     #[salsa::input]
     fn known_files(&self) -> Vec<(FileId, FileExtension)>;
 
+    #[salsa::input]
+    fn known_translation_files(&self) -> Arc<Vec<(FileId, String)>>;
+
     /// Source root of the file.
     #[salsa::input]
     fn file_source_root(&self, file_id: FileId) -> SourceRootId;
@@ -145,6 +178,10 @@ pub trait SourceDatabaseExt: SourceDatabase {
     /// Source roots
     #[salsa::input]
     fn source_roots(&self) -> Vec<Arc<SourceRoot>>;
+
+    /// Files of the include directories which are not included by the project of the file.
+    #[salsa::invoke(unincluded_files_query)]
+    fn unincluded_files(&self, file_id: FileId) -> Arc<Vec<UnincludedFiles>>;
 }
 
 /// Silly workaround for cyclic deps between the traits
@@ -157,6 +194,9 @@ impl<T: SourceDatabaseExt> FileLoader for FileLoaderDelegate<&'_ T> {
     fn known_files(&self) -> Vec<(FileId, FileExtension)> {
         SourceDatabaseExt::known_files(self.0)
     }
+    fn known_translation_files(&self) -> Arc<Vec<(FileId, String)>> {
+        SourceDatabaseExt::known_translation_files(self.0)
+    }
     fn resolve_path(&self, path: AnchoredPath<'_>) -> Option<FileId> {
         // FIXME: this *somehow* should be platform agnostic...
         let source_root = self.0.file_source_root(path.anchor);
@@ -0,0 +1,420 @@
+//! Support for SourceMod translation files (`translations/*.phrases.txt`).
+//!
+//! Translation files are KeyValues documents of the following shape:
+//!
+//! ```text
+//! "Phrases"
+//! {
+//!     "Welcome"
+//!     {
+//!         "#format"   "{1:s}"
+//!         "en"        "Welcome {1}"
+//!         "fr"        "Bienvenue {1}"
+//!     }
+//! }
+//! ```
+
+use std::sync::Arc;
+
+use lsp_types::{Position, Range};
+use sourcepawn_lexer::{Literal, TokenKind};
+use vfs::FileId;
+
+use crate::SourceDatabase;
+
+/// A node of a KeyValues document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyValue {
+    pub key: String,
+    /// Range of the key, quotes included.
+    pub key_range: Range,
+    pub value: KeyValueValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyValueValue {
+    /// A `"key" "value"` pair.
+    String { value: String, range: Range },
+
+    /// A `"key" { ... }` section.
+    Section(Vec<KeyValue>),
+}
+
+impl KeyValue {
+    /// Returns the children of the node if it is a section.
+    pub fn children(&self) -> &[KeyValue] {
+        match &self.value {
+            KeyValueValue::Section(children) => children,
+            KeyValueValue::String { .. } => &[],
+        }
+    }
+
+    /// Returns the value of the node if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            KeyValueValue::String { value, .. } => Some(value),
+            KeyValueValue::Section(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum KvToken {
+    String(String),
+    Open,
+    Close,
+}
+
+/// Tokenize a KeyValues document.
+///
+/// Columns of the ranges are counted in UTF-16 code units, like LSP positions.
+///
+/// Comments (`//`) and conditionals (`[$WIN32]`) are skipped.
+fn tokenize(input: &str) -> Vec<(KvToken, Range)> {
+    let mut res = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    let mut line = 0u32;
+    let mut col = 0u32;
+
+    macro_rules! bump {
+        ($c:expr) => {
+            if $c == '\n' {
+                line += 1;
+                col = 0;
+            } else {
+                col += $c.len_utf16() as u32;
+            }
+        };
+    }
+
+    while let Some((_, c)) = chars.next() {
+        let start = Position::new(line, col);
+        match c {
+            '{' => {
+                bump!(c);
+                res.push((KvToken::Open, Range::new(start, Position::new(line, col))));
+            }
+            '}' => {
+                bump!(c);
+                res.push((KvToken::Close, Range::new(start, Position::new(line, col))));
+            }
+            '"' => {
+                bump!(c);
+                let mut value = String::new();
+                while let Some((_, c)) = chars.next() {
+                    bump!(c);
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            let Some((_, escaped)) = chars.next() else {
+                                break;
+                            };
+                            bump!(escaped);
+                            match escaped {
+                                'n' => value.push('\n'),
+                                't' => value.push('\t'),
+                                _ => value.push(escaped),
+                            }
+                        }
+                        _ => value.push(c),
+                    }
+                }
+                res.push((
+                    KvToken::String(value),
+                    Range::new(start, Position::new(line, col)),
+                ));
+            }
+            '/' if matches!(chars.peek(), Some((_, '/'))) => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        bump!(c);
+                        break;
+                    }
+                }
+            }
+            '[' => {
+                bump!(c);
+                for (_, c) in chars.by_ref() {
+                    bump!(c);
+                    if c == ']' || c == '\n' {
+                        break;
+                    }
+                }
+            }
+            _ if c.is_whitespace() => bump!(c),
+            _ => {
+                bump!(c);
+                let mut value = c.to_string();
+                while let Some((_, c)) = chars.peek().copied() {
+                    if c.is_whitespace() || matches!(c, '{' | '}' | '"') {
+                        break;
+                    }
+                    chars.next();
+                    bump!(c);
+                    value.push(c);
+                }
+                res.push((
+                    KvToken::String(value),
+                    Range::new(start, Position::new(line, col)),
+                ));
+            }
+        }
+    }
+
+    res
+}
+
+/// Parse a KeyValues document into a list of root nodes.
+///
+/// The parser is lenient: unbalanced braces and dangling keys are ignored.
+pub fn parse_key_values(input: &str) -> Vec<KeyValue> {
+    let tokens = tokenize(input);
+    let mut idx = 0;
+    parse_section(&tokens, &mut idx)
+}
+
+fn parse_section(tokens: &[(KvToken, Range)], idx: &mut usize) -> Vec<KeyValue> {
+    let mut res = Vec::new();
+    while let Some((token, key_range)) = tokens.get(*idx) {
+        *idx += 1;
+        let key = match token {
+            KvToken::String(key) => key.clone(),
+            KvToken::Close => break,
+            KvToken::Open => {
+                // Anonymous section, flatten it.
+                res.extend(parse_section(tokens, idx));
+                continue;
+            }
+        };
+        match tokens.get(*idx) {
+            Some((KvToken::String(value), range)) => {
+                *idx += 1;
+                res.push(KeyValue {
+                    key,
+                    key_range: *key_range,
+                    value: KeyValueValue::String {
+                        value: value.clone(),
+                        range: *range,
+                    },
+                });
+            }
+            Some((KvToken::Open, _)) => {
+                *idx += 1;
+                let children = parse_section(tokens, idx);
+                res.push(KeyValue {
+                    key,
+                    key_range: *key_range,
+                    value: KeyValueValue::Section(children),
+                });
+            }
+            Some((KvToken::Close, _)) | None => (),
+        }
+    }
+
+    res
+}
+
+/// A translation phrase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phrase {
+    /// Name of the phrase.
+    pub name: String,
+
+    /// Range of the name of the phrase in the translation file.
+    pub range: Range,
+
+    /// The `#format` specifier of the phrase, if any.
+    pub format: Option<String>,
+
+    /// The translations of the phrase, keyed by language code (e.g. `en`).
+    pub translations: Vec<(String, String)>,
+}
+
+impl Phrase {
+    /// Number of parameters expected by the `#format` specifier of the phrase.
+    ///
+    /// The format is a comma separated list such as `{1:s},{2:d}`.
+    pub fn format_params(&self) -> usize {
+        self.format
+            .as_deref()
+            .map(|format| format.matches('{').count())
+            .unwrap_or_default()
+    }
+
+    /// Returns the translation of the phrase for the given language code.
+    pub fn translation(&self, lang: &str) -> Option<&str> {
+        self.translations
+            .iter()
+            .find(|(it, _)| it == lang)
+            .map(|(_, text)| text.as_str())
+    }
+}
+
+/// A parsed translation file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PhrasesFile {
+    phrases: Vec<Phrase>,
+}
+
+impl PhrasesFile {
+    pub fn phrases(&self) -> &[Phrase] {
+        &self.phrases
+    }
+
+    pub fn phrase(&self, name: &str) -> Option<&Phrase> {
+        self.phrases.iter().find(|phrase| phrase.name == name)
+    }
+}
+
+impl From<&str> for PhrasesFile {
+    fn from(input: &str) -> Self {
+        let phrases = parse_key_values(input)
+            .iter()
+            .filter(|root| root.key.eq_ignore_ascii_case("Phrases"))
+            .flat_map(|root| root.children())
+            .map(|phrase| {
+                let mut format = None;
+                let mut translations = Vec::new();
+                for child in phrase.children() {
+                    let Some(value) = child.as_str() else {
+                        continue;
+                    };
+                    if child.key == "#format" {
+                        format = Some(value.to_string());
+                    } else {
+                        translations.push((child.key.clone(), value.to_string()));
+                    }
+                }
+                Phrase {
+                    name: phrase.key.clone(),
+                    range: phrase.key_range,
+                    format,
+                    translations,
+                }
+            })
+            .collect();
+
+        Self { phrases }
+    }
+}
+
+/// A `LoadTranslations("foo.phrases")` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedTranslation {
+    /// Normalized name of the translation file (e.g. `foo` for `foo.phrases.txt`).
+    pub name: String,
+
+    /// Range of the string literal in the file.
+    pub range: Range,
+}
+
+/// Normalize the name of a translation file, as it can be passed to `LoadTranslations`
+/// or found on disk.
+///
+/// `foo.phrases.txt`, `foo.phrases` and `foo` are all normalized to `foo`.
+pub fn normalize_translation_name(name: &str) -> &str {
+    let name = name.strip_suffix(".txt").unwrap_or(name);
+    name.strip_suffix(".phrases").unwrap_or(name)
+}
+
+/// Returns the parsed content of a translation file.
+pub(crate) fn phrases_file_query(db: &dyn SourceDatabase, file_id: FileId) -> Arc<PhrasesFile> {
+    Arc::new(PhrasesFile::from(db.file_text(file_id).as_ref()))
+}
+
+/// Returns all the `LoadTranslations` calls of a file.
+///
+/// # Note
+/// Only calls with a string literal argument are returned.
+pub(crate) fn file_loaded_translations_query(
+    db: &dyn SourceDatabase,
+    file_id: FileId,
+) -> Arc<Vec<LoadedTranslation>> {
+    let mut res = vec![];
+    let input = db.file_text(file_id);
+    let mut lexer = sourcepawn_lexer::SourcepawnLexer::new(&input).filter(|symbol| {
+        !matches!(
+            symbol.token_kind,
+            TokenKind::Newline | TokenKind::Comment(_)
+        )
+    });
+    while let Some(symbol) = lexer.next() {
+        if symbol.token_kind != TokenKind::Identifier || symbol.text() != "LoadTranslations" {
+            continue;
+        }
+        if !matches!(lexer.next(), Some(symbol) if symbol.token_kind == TokenKind::LParen) {
+            continue;
+        }
+        let Some(arg) = lexer.next() else {
+            break;
+        };
+        if arg.token_kind != TokenKind::Literal(Literal::StringLiteral) {
+            continue;
+        }
+        res.push(LoadedTranslation {
+            name: normalize_translation_name(arg.text().trim_matches('"')).to_string(),
+            range: arg.range,
+        });
+    }
+
+    Arc::new(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_phrases_file() {
+        let file = PhrasesFile::from(
+            r##""Phrases"
+{
+    // A comment
+    "Welcome"
+    {
+        "#format"   "{1:s},{2:d}"
+        "en"        "Welcome {1}, you have {2} points"
+        "fr"        "Bienvenue {1}"
+    }
+    "Goodbye"
+    {
+        "en"        "Goodbye \"friend\""
+    }
+}"##,
+        );
+        assert_eq!(file.phrases().len(), 2);
+        let welcome = file.phrase("Welcome").unwrap();
+        assert_eq!(welcome.format.as_deref(), Some("{1:s},{2:d}"));
+        assert_eq!(welcome.format_params(), 2);
+        assert_eq!(welcome.translation("fr"), Some("Bienvenue {1}"));
+        assert_eq!(
+            welcome.range,
+            Range::new(Position::new(3, 4), Position::new(3, 13))
+        );
+        let goodbye = file.phrase("Goodbye").unwrap();
+        assert_eq!(goodbye.translation("en"), Some("Goodbye \"friend\""));
+        assert_eq!(goodbye.format_params(), 0);
+    }
+
+    #[test]
+    fn utf16_ranges() {
+        let file = PhrasesFile::from(
+            r##""Phrases"
+{
+    "Café"    { "fr" "😀 Café" }    "Bye"    { "en" "Bye" }
+}"##,
+        );
+        let bye = file.phrase("Bye").unwrap();
+        assert_eq!(
+            bye.range,
+            Range::new(Position::new(2, 36), Position::new(2, 41))
+        );
+    }
+
+    #[test]
+    fn normalize_names() {
+        assert_eq!(normalize_translation_name("foo.phrases.txt"), "foo");
+        assert_eq!(normalize_translation_name("foo.phrases"), "foo");
+        assert_eq!(normalize_translation_name("foo"), "foo");
+    }
+}
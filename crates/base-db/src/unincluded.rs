@@ -0,0 +1,173 @@
+//! Detection of the files of the include directories which are not included by a project.
+
+use std::sync::Arc;
+
+use fxhash::{FxHashMap, FxHashSet};
+use vfs::FileId;
+
+use crate::{FileExtension, SourceDatabaseExt};
+
+/// Files of the include directories which are not included by a project, and which all get
+/// included by the same `#include <...>` directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnincludedFiles {
+    /// Path to use in the `#include <...>` directive, e.g. `sdktools`.
+    pub include_path: String,
+
+    /// The file included by the directive.
+    pub include_file_id: FileId,
+
+    /// The files included by the directive, directly or through the includes of
+    /// `include_file_id`.
+    pub file_ids: Vec<FileId>,
+}
+
+/// Returns the files of the include directories which are not included by the project of
+/// `file_id`, grouped by the file to include to get them, sorted by include path.
+///
+/// A file which is included by other files of the include directories, such as
+/// `sdktools_functions.inc` which is included by `sdktools.inc`, is reported with the
+/// outermost files which include it.
+pub(crate) fn unincluded_files_query(
+    db: &dyn SourceDatabaseExt,
+    file_id: FileId,
+) -> Arc<Vec<UnincludedFiles>> {
+    let included = db
+        .projet_subgraph(file_id)
+        .map(|subgraph| subgraph.file_ids())
+        .unwrap_or_else(|| [file_id].into_iter().collect());
+    let paths = include_paths(db);
+    let mut parents: FxHashMap<FileId, Vec<FileId>> = FxHashMap::default();
+    for edge in db.graph().edges.iter() {
+        if edge.source.extension == FileExtension::Inc && paths.contains_key(&edge.source.file_id) {
+            parents
+                .entry(edge.target.file_id)
+                .or_default()
+                .push(edge.source.file_id);
+        }
+    }
+
+    let mut res: Vec<UnincludedFiles> = Vec::new();
+    for child_id in paths.keys() {
+        if included.contains(child_id) {
+            continue;
+        }
+        for root_id in include_roots(&parents, *child_id) {
+            match res.iter_mut().find(|it| it.include_file_id == root_id) {
+                Some(it) => it.file_ids.push(*child_id),
+                None => res.push(UnincludedFiles {
+                    include_path: paths[&root_id].clone(),
+                    include_file_id: root_id,
+                    file_ids: vec![*child_id],
+                }),
+            }
+        }
+    }
+    res.iter_mut().for_each(|it| it.file_ids.sort());
+    res.sort_by(|a, b| a.include_path.cmp(&b.include_path));
+
+    Arc::new(res)
+}
+
+/// Returns the path used to include each `.inc` file of the include directories, without
+/// the extension.
+fn include_paths(db: &dyn SourceDatabaseExt) -> FxHashMap<FileId, String> {
+    let mut res = FxHashMap::default();
+    for root in db.source_roots().iter().filter(|root| root.is_include_dir) {
+        for file_id in root.iter() {
+            let Some(path) = root.relative_path_for_file(&file_id) else {
+                continue;
+            };
+            if let Some(path) = path.strip_suffix(".inc") {
+                res.insert(file_id, path.to_string());
+            }
+        }
+    }
+
+    res
+}
+
+/// Returns the outermost files which include `file_id`, i.e. the files which include it,
+/// directly or not, and which are not included by another file.
+///
+/// Returns `file_id` itself if it is not included by any file. Files which are only
+/// included through a cycle are their own roots.
+fn include_roots(parents: &FxHashMap<FileId, Vec<FileId>>, file_id: FileId) -> Vec<FileId> {
+    let mut res = Vec::new();
+    let mut visited = FxHashSet::default();
+    let mut stack = vec![file_id];
+    while let Some(current) = stack.pop() {
+        if !visited.insert(current) {
+            continue;
+        }
+        let unvisited = parents
+            .get(&current)
+            .into_iter()
+            .flatten()
+            .filter(|parent| !visited.contains(*parent))
+            .collect::<Vec<_>>();
+        if unvisited.is_empty() {
+            if parents.get(&current).map_or(true, |it| it.is_empty()) {
+                res.push(current);
+            }
+            continue;
+        }
+        stack.extend(unvisited);
+    }
+    if res.is_empty() {
+        res.push(file_id);
+    }
+    res.sort();
+    res.dedup();
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parents(edges: &[(u32, u32)]) -> FxHashMap<FileId, Vec<FileId>> {
+        let mut res: FxHashMap<FileId, Vec<FileId>> = FxHashMap::default();
+        for (source, target) in edges {
+            res.entry(FileId(*target))
+                .or_default()
+                .push(FileId(*source));
+        }
+
+        res
+    }
+
+    #[test]
+    fn not_included() {
+        assert_eq!(include_roots(&parents(&[]), FileId(0)), [FileId(0)]);
+    }
+
+    #[test]
+    fn included_transitively() {
+        // 0 includes 1, which includes 2.
+        let parents = parents(&[(0, 1), (1, 2)]);
+        assert_eq!(include_roots(&parents, FileId(2)), [FileId(0)]);
+        assert_eq!(include_roots(&parents, FileId(1)), [FileId(0)]);
+    }
+
+    #[test]
+    fn included_by_several_files() {
+        // 0 and 1 include 2, 3 includes 0.
+        let parents = parents(&[(0, 2), (1, 2), (3, 0)]);
+        assert_eq!(include_roots(&parents, FileId(2)), [FileId(1), FileId(3)]);
+    }
+
+    #[test]
+    fn include_cycle() {
+        // 0 and 1 include each other, 2 includes 1.
+        assert_eq!(
+            include_roots(&parents(&[(0, 1), (1, 0), (2, 1)]), FileId(0)),
+            [FileId(2)]
+        );
+        assert_eq!(
+            include_roots(&parents(&[(0, 1), (1, 0)]), FileId(0)),
+            [FileId(0)]
+        );
+    }
+}
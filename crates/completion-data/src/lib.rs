@@ -1,6 +1,10 @@
 //! Completion data for game events, scrapped from Alliedmodders.
+//!
+//! The built-in data only contains events. Users can provide additional data files, with
+//! the same schema, to add games or to extend the built-in ones with ConVars, console
+//! commands, netprops, entity classnames and sounds.
 
-use std::{borrow::Cow, io::Read};
+use std::{borrow::Cow, collections::hash_map::Entry, io::Read};
 
 use flate2::read::GzDecoder;
 use fxhash::FxHashMap;
@@ -9,25 +13,90 @@ use serde::Deserialize;
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Database<'a>(#[serde(borrow)] FxHashMap<&'a str, Game<'a>>);
+pub struct Database<'a>(#[serde(borrow)] FxHashMap<Cow<'a, str>, Game<'a>>);
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Game<'a> {
     #[serde(borrow)]
     name: Cow<'a, str>,
-    #[serde(borrow)]
+    #[serde(borrow, default)]
     events: Vec<Event<'a>>,
+    #[serde(borrow, default)]
+    convars: Vec<ConVar<'a>>,
+    #[serde(borrow, default)]
+    commands: Vec<Command<'a>>,
+    #[serde(borrow, default)]
+    netprops: Vec<NetProp<'a>>,
+    #[serde(borrow, default)]
+    classnames: Vec<Classname<'a>>,
+    #[serde(borrow, default)]
+    sounds: Vec<Sound<'a>>,
 }
 
-impl Game<'_> {
+impl<'a> Game<'a> {
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    pub fn events(&self) -> &[Event] {
+    /// Whether the data of the game applies to all the games.
+    pub fn is_generic(&self) -> bool {
+        GENERIC_GAMES.contains(&self.name())
+    }
+
+    pub fn events(&self) -> &[Event<'a>] {
         &self.events
     }
+
+    pub fn convars(&self) -> &[ConVar<'a>] {
+        &self.convars
+    }
+
+    pub fn commands(&self) -> &[Command<'a>] {
+        &self.commands
+    }
+
+    pub fn netprops(&self) -> &[NetProp<'a>] {
+        &self.netprops
+    }
+
+    pub fn classnames(&self) -> &[Classname<'a>] {
+        &self.classnames
+    }
+
+    pub fn sounds(&self) -> &[Sound<'a>] {
+        &self.sounds
+    }
+
+    /// Merge the data of another game into this one.
+    fn merge(&mut self, other: Game<'a>) {
+        self.events.extend(other.events);
+        self.convars.extend(other.convars);
+        self.commands.extend(other.commands);
+        self.netprops.extend(other.netprops);
+        self.classnames.extend(other.classnames);
+        self.sounds.extend(other.sounds);
+    }
+
+    fn into_owned(self) -> Game<'static> {
+        Game {
+            name: owned(self.name),
+            events: self.events.into_iter().map(Event::into_owned).collect(),
+            convars: self.convars.into_iter().map(ConVar::into_owned).collect(),
+            commands: self.commands.into_iter().map(Command::into_owned).collect(),
+            netprops: self.netprops.into_iter().map(NetProp::into_owned).collect(),
+            classnames: self
+                .classnames
+                .into_iter()
+                .map(Classname::into_owned)
+                .collect(),
+            sounds: self.sounds.into_iter().map(Sound::into_owned).collect(),
+        }
+    }
+}
+
+fn owned(cow: Cow<'_, str>) -> Cow<'static, str> {
+    Cow::Owned(cow.into_owned())
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
@@ -55,6 +124,18 @@ impl Event<'_> {
     pub fn attributes(&self) -> &[Attribute] {
         &self.attributes
     }
+
+    fn into_owned(self) -> Event<'static> {
+        Event {
+            name: owned(self.name),
+            note: self.note.map(owned),
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(Attribute::into_owned)
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
@@ -82,23 +163,227 @@ impl Attribute<'_> {
     pub fn description(&self) -> Option<&str> {
         self.description.as_deref()
     }
+
+    fn into_owned(self) -> Attribute<'static> {
+        Attribute {
+            name: owned(self.name),
+            r#type: owned(self.r#type),
+            description: self.description.map(owned),
+        }
+    }
+}
+
+/// A console variable, e.g. `mp_friendlyfire`.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConVar<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+
+    #[serde(borrow, default)]
+    default_value: Option<Cow<'a, str>>,
+
+    #[serde(borrow, default)]
+    description: Option<Cow<'a, str>>,
+}
+
+impl ConVar<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    fn into_owned(self) -> ConVar<'static> {
+        ConVar {
+            name: owned(self.name),
+            default_value: self.default_value.map(owned),
+            description: self.description.map(owned),
+        }
+    }
+}
+
+/// A console command, e.g. `say`.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Command<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+
+    #[serde(borrow, default)]
+    description: Option<Cow<'a, str>>,
+}
+
+impl Command<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    fn into_owned(self) -> Command<'static> {
+        Command {
+            name: owned(self.name),
+            description: self.description.map(owned),
+        }
+    }
+}
+
+/// Kind of a netprop, matching the `PropType` enum of SourceMod.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+pub enum NetPropKind {
+    /// A networked property (`Prop_Send`).
+    #[serde(rename = "send")]
+    Send,
+
+    /// A datamap property (`Prop_Data`).
+    #[serde(rename = "data")]
+    Data,
+}
+
+/// A netprop or a datamap property of a server class, e.g. `m_iHealth` of `CBasePlayer`.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetProp<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+
+    #[serde(borrow)]
+    class: Cow<'a, str>,
+
+    kind: NetPropKind,
+
+    #[serde(borrow, default)]
+    r#type: Option<Cow<'a, str>>,
+}
+
+impl NetProp<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    pub fn kind(&self) -> NetPropKind {
+        self.kind
+    }
+
+    pub fn r#type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    fn into_owned(self) -> NetProp<'static> {
+        NetProp {
+            name: owned(self.name),
+            class: owned(self.class),
+            kind: self.kind,
+            r#type: self.r#type.map(owned),
+        }
+    }
+}
+
+/// An entity classname, e.g. `weapon_ak47`.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Classname<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+
+    #[serde(borrow, default)]
+    description: Option<Cow<'a, str>>,
+}
+
+impl Classname<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    fn into_owned(self) -> Classname<'static> {
+        Classname {
+            name: owned(self.name),
+            description: self.description.map(owned),
+        }
+    }
+}
+
+/// A sound file or a game sound, e.g. `weapons/ak47/ak47-1.wav`.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sound<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+}
+
+impl Sound<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn into_owned(self) -> Sound<'static> {
+        Sound {
+            name: owned(self.name),
+        }
+    }
 }
 
 impl<'a> Database<'a> {
+    /// Parse a data file.
+    pub fn from_json(json: &'a str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Merge another database into this one.
+    ///
+    /// Games with the same name are merged together, other games are added.
+    pub fn merge(&mut self, other: Database<'a>) {
+        for (key, game) in other.0 {
+            match self.0.entry(key) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(game),
+                Entry::Vacant(entry) => {
+                    entry.insert(game);
+                }
+            }
+        }
+    }
+
+    /// Convert the database into a database which does not borrow its input.
+    pub fn into_owned(self) -> Database<'static> {
+        Database(
+            self.0
+                .into_iter()
+                .map(|(key, game)| (owned(key), game.into_owned()))
+                .collect(),
+        )
+    }
+
     // FIXME: Get rid of the double borrow
-    pub fn iter(&self) -> impl Iterator<Item = (&&str, &Game)> + '_ {
+    pub fn iter(&self) -> impl Iterator<Item = (&Cow<'a, str>, &Game<'a>)> + '_ {
         self.0.iter()
     }
 
-    pub fn get(&self, game: &str) -> Option<&Game> {
+    pub fn get(&self, game: &str) -> Option<&Game<'a>> {
         self.0.get(game)
     }
 
-    pub fn get_event(&self, game: &str, event: &str) -> Option<&Event> {
+    pub fn get_event(&self, game: &str, event: &str) -> Option<&Event<'a>> {
         self.0.get(game)?.events.iter().find(|ev| ev.name == event)
     }
 
-    pub fn get_events(&self, name: &str) -> Vec<(String, Event)> {
+    pub fn get_events(&self, name: &str) -> Vec<(String, Event<'a>)> {
         let mut res = Vec::new();
         self.0.iter().for_each(|(_, game)| {
             if let Some(ev) = game.events().iter().find(|ev| ev.name() == name) {
@@ -110,10 +395,9 @@ impl<'a> Database<'a> {
     }
 
     /// Returns all the generic events as a vector of owned [`Events`](Event).
-    pub fn generic_events(&self) -> Vec<Event> {
+    pub fn generic_events(&self) -> Vec<Event<'a>> {
         let mut res = Vec::new();
-        let names = ["Generic Source", "Generic Source Server"];
-        for name in names {
+        for name in GENERIC_GAMES {
             res.extend(
                 self.0
                     .get(name)
@@ -126,12 +410,37 @@ impl<'a> Database<'a> {
 
         res
     }
+
+    /// Returns the games to use for the given game names.
+    ///
+    /// If some of the games exist in the database, these games and the generic games are
+    /// returned. Otherwise, all the games are returned.
+    pub fn games(&self, game_names: &[String]) -> Vec<&Game<'a>> {
+        let selected: Vec<_> = game_names
+            .iter()
+            .filter_map(|name| self.get(name))
+            .collect();
+        if selected.is_empty() {
+            return self.0.values().collect();
+        }
+        GENERIC_GAMES
+            .iter()
+            .filter(|name| !game_names.iter().any(|it| it == *name))
+            .filter_map(|name| self.get(name))
+            .chain(selected)
+            .collect()
+    }
 }
 
+/// Names of the games whose data applies to all the games.
+const GENERIC_GAMES: [&str; 2] = ["Generic Source", "Generic Source Server"];
+
 /// Bytes of the compressed JSON data for events completion.
 const EVENTS_JSON_GZ: &[u8] = include_bytes!("../data/events.json.gz");
 
-/// The completion data database.
+/// The built-in completion data database.
+///
+/// See [`merged_database`] to add the data provided by the user.
 pub static DATABASE: Lazy<Database<'static>> = Lazy::new(|| {
     let mut decoder = GzDecoder::new(EVENTS_JSON_GZ);
     let json = Box::leak(Box::default());
@@ -140,3 +449,75 @@ pub static DATABASE: Lazy<Database<'static>> = Lazy::new(|| {
 
     db
 });
+
+/// Returns the built-in completion data, merged with the data files provided by the user.
+pub fn merged_database(databases: Vec<Database<'static>>) -> Database<'static> {
+    let mut db = DATABASE.clone();
+    databases.into_iter().for_each(|it| db.merge(it));
+
+    db
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_user_database() {
+        let json = r#"{
+            "Team Fortress 2": {
+                "name": "Team Fortress 2",
+                "convars": [{ "name": "tf_bot_quota", "defaultValue": "0" }],
+                "netprops": [{ "name": "m_iClass", "class": "CTFPlayer", "kind": "send" }]
+            },
+            "My Mod": {
+                "name": "My Mod",
+                "classnames": [{ "name": "my_entity" }]
+            }
+        }"#;
+        let db = merged_database(vec![Database::from_json(json).unwrap().into_owned()]);
+
+        let tf2 = db.get("Team Fortress 2").unwrap();
+        assert!(!tf2.events().is_empty());
+        assert_eq!(tf2.convars()[0].default_value(), Some("0"));
+        assert_eq!(tf2.netprops()[0].kind(), NetPropKind::Send);
+        assert_eq!(
+            db.get("My Mod").unwrap().classnames()[0].name(),
+            "my_entity"
+        );
+        assert_eq!(db.games(&["My Mod".to_string()]).len(), 3);
+    }
+
+    #[test]
+    fn select_multiple_games() {
+        let json = r#"{
+            "My Mod": { "name": "My Mod" },
+            "My Other Mod": { "name": "My Other Mod" }
+        }"#;
+        let db = merged_database(vec![Database::from_json(json).unwrap().into_owned()]);
+
+        let names = |games: Vec<&Game>| {
+            let mut names: Vec<_> = games.iter().map(|it| it.name().to_string()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(db.games(&[
+                "My Mod".to_string(),
+                "My Other Mod".to_string(),
+                "Unknown".to_string()
+            ])),
+            [
+                "Generic Source",
+                "Generic Source Server",
+                "My Mod",
+                "My Other Mod"
+            ]
+        );
+        assert_eq!(
+            db.games(&["Generic Source".to_string()]).len(),
+            GENERIC_GAMES.len()
+        );
+        assert_eq!(db.games(&[]).len(), db.iter().count());
+    }
+}
@@ -0,0 +1,138 @@
+//! Detection of the version of spcomp, and of the include directory shipped with it.
+
+use std::{
+    io,
+    path::Path,
+    process::{Command, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use paths::AbsPathBuf;
+use regex::Regex;
+
+/// A SourcePawn compiler, with what could be detected about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compiler {
+    /// Path to the spcomp executable, or its name if it is in the `PATH`.
+    pub command: String,
+
+    /// Version reported by spcomp, for example `1.12.0.7130`.
+    pub version: Option<String>,
+
+    /// [Path](AbsPathBuf) of the `include` directory next to the executable, which holds the
+    /// includes of the matching SourceMod version.
+    pub include_directory: Option<AbsPathBuf>,
+}
+
+impl Compiler {
+    /// A compiler whose detection has not completed yet, known only by its command.
+    pub fn undetected(command: String) -> Self {
+        Self {
+            command,
+            version: None,
+            include_directory: None,
+        }
+    }
+
+    /// Detect the version of the compiler by running it once without arguments, which makes
+    /// spcomp print its version and usage.
+    ///
+    /// The compiler is killed if it does not exit within `timeout`, in which case its version
+    /// is unknown. This blocks, so it should be run on a worker thread.
+    pub fn detect(command: String, timeout: Duration) -> Self {
+        #[cfg(all(target_arch = "aarch64", target_os = "macos"))]
+        let cmd = {
+            let mut cmd = Command::new("arch");
+            cmd.arg("-x86_64").arg(&command);
+            cmd
+        };
+        #[cfg(not(all(target_arch = "aarch64", target_os = "macos")))]
+        let cmd = Command::new(&command);
+
+        let version = match output_with_timeout(cmd, timeout) {
+            Ok(output) => parse_version(&String::from_utf8_lossy(&output.stdout))
+                .or_else(|| parse_version(&String::from_utf8_lossy(&output.stderr))),
+            Err(err) => {
+                tracing::warn!("Failed to run {} to detect its version: {}", command, err);
+                None
+            }
+        };
+        let include_directory = Path::new(&command)
+            .parent()
+            .map(|parent| parent.join("include"))
+            .filter(|dir| dir.is_dir())
+            .and_then(|dir| AbsPathBuf::try_from(dir).ok());
+
+        Self {
+            command,
+            version,
+            include_directory,
+        }
+    }
+}
+
+/// Run `command` and collect its output, killing it if it does not exit within `timeout`.
+fn output_with_timeout(mut command: Command, timeout: Duration) -> io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let start = Instant::now();
+    while child.try_wait()?.is_none() {
+        if start.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("did not exit within {:?}", timeout),
+            ));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    child.wait_with_output()
+}
+
+/// Parse the version from the banner of spcomp, for example
+/// `SourcePawn Compiler 1.12.0.7130`.
+fn parse_version(output: &str) -> Option<String> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"SourcePawn Compiler (?:version )?(\d+(?:\.\d+)+)")
+            .expect("Failed to compile spcomp version regex.");
+    }
+    RE.captures(output).map(|caps| caps[1].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_of_banner() {
+        assert_eq!(
+            parse_version(
+                "SourcePawn Compiler 1.12.0.7130\nCopyright (c) 1997-2006 ITB CompuPhase\n"
+            ),
+            Some("1.12.0.7130".to_string())
+        );
+        assert_eq!(
+            parse_version("SourcePawn Compiler version 1.7.3\n"),
+            Some("1.7.3".to_string())
+        );
+        assert_eq!(parse_version("Usage:   spcomp <filename>\n"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_command_after_timeout() {
+        let mut command = Command::new("sleep");
+        command.arg("5");
+        let start = Instant::now();
+        let err = output_with_timeout(command, Duration::from_millis(100)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -8,10 +8,11 @@
 )]
 
 use std::{
+    collections::HashMap,
     ffi::OsString,
     fmt, io,
     path::PathBuf,
-    process::{ChildStderr, ChildStdout, Command, Stdio},
+    process::{ChildStderr, ChildStdout, Command, ExitStatus, Stdio},
     time::Duration,
 };
 
@@ -19,12 +20,20 @@ use command_group::{CommandGroup, GroupChild};
 use crossbeam::channel::{never, select, unbounded, Receiver, Sender};
 use paths::AbsPathBuf;
 use rand::Rng;
-use spcomp::build_args;
+use spcomp::{build_args, SpCompOutputParser};
 use stdx::process::streaming_output;
 
+mod compiler;
+mod overlay;
 mod spcomp;
 
-pub use spcomp::{SpCompDiagnostic, SpCompSeverity};
+pub use compiler::Compiler;
+pub use overlay::{Overlay, OverlayFile};
+pub use spcomp::{SpCompDiagnostic, SpCompNote, SpCompSeverity};
+
+/// Delay without new changes after which an [`Overlay`] is checked, so that spcomp does not
+/// run on every keystroke.
+const OVERLAY_DEBOUNCE: Duration = Duration::from_millis(500);
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum InvocationStrategy {
@@ -45,14 +54,23 @@ pub struct FlycheckConfig {
     command: String,
     args: Vec<String>,
     include_directories: Vec<AbsPathBuf>,
+    /// Directory where the plugins are written when building.
+    /// Defaults to the directory of the project root.
+    output_directory: Option<AbsPathBuf>,
 }
 
 impl FlycheckConfig {
-    pub fn new(command: String, args: Vec<String>, include_directories: Vec<AbsPathBuf>) -> Self {
+    pub fn new(
+        command: String,
+        args: Vec<String>,
+        include_directories: Vec<AbsPathBuf>,
+        output_directory: Option<AbsPathBuf>,
+    ) -> Self {
         FlycheckConfig {
             command,
             args,
             include_directories,
+            output_directory,
         }
     }
 }
@@ -66,6 +84,8 @@ pub struct FlycheckHandle {
     sender: Sender<StateChange>,
     _thread: stdx::thread::JoinHandle,
     id: u32,
+    /// Path of the plugin written by a build.
+    build_output_path: AbsPathBuf,
 }
 
 impl FlycheckHandle {
@@ -76,6 +96,7 @@ impl FlycheckHandle {
         project_root: AbsPathBuf,
         tempdir: AbsPathBuf,
     ) -> FlycheckHandle {
+        let build_output_path = build_output_path(&config, &project_root, &tempdir);
         let actor = FlycheckActor::new(id, sender, config, project_root, tempdir);
         let (sender, receiver) = unbounded::<StateChange>();
         let thread = stdx::thread::Builder::new(stdx::thread::ThreadIntent::Worker)
@@ -86,6 +107,7 @@ impl FlycheckHandle {
             id,
             sender,
             _thread: thread,
+            build_output_path,
         }
     }
 
@@ -94,6 +116,18 @@ impl FlycheckHandle {
         self.sender.send(StateChange::Restart).unwrap();
     }
 
+    /// Schedule a check of the project with the unsaved contents of some of its files.
+    pub fn check_overlay(&self, overlay: Overlay) {
+        self.sender
+            .send(StateChange::CheckOverlay(overlay))
+            .unwrap();
+    }
+
+    /// Schedule a build of the project, which writes the plugin to the output directory.
+    pub fn build(&self) {
+        self.sender.send(StateChange::Build).unwrap();
+    }
+
     /// Stop this spcomp worker.
     pub fn cancel(&self) {
         self.sender.send(StateChange::Cancel).unwrap();
@@ -102,6 +136,11 @@ impl FlycheckHandle {
     pub fn id(&self) -> u32 {
         self.id
     }
+
+    /// Path of the plugin written by a build of the project.
+    pub fn build_output_path(&self) -> &AbsPathBuf {
+        &self.build_output_path
+    }
 }
 
 pub enum Message {
@@ -148,12 +187,25 @@ pub enum Progress {
     // FIXME: Implement this
     DidCheckCrate(String),
     DidFinish(io::Result<()>),
+    DidBuild(io::Result<BuiltPlugin>),
     DidCancel,
     DidFailToRestart(String),
 }
 
+/// A plugin written by a build.
+#[derive(Debug, Clone)]
+pub struct BuiltPlugin {
+    /// [Path](AbsPathBuf) of the `.smx` file.
+    pub path: AbsPathBuf,
+
+    /// Size of the `.smx` file, in bytes.
+    pub size: u64,
+}
+
 enum StateChange {
     Restart,
+    Build,
+    CheckOverlay(Overlay),
     Cancel,
 }
 
@@ -173,6 +225,12 @@ struct FlycheckActor {
     /// have to wrap sub-processes output handling in a thread and pass messages
     /// back over a channel.
     command_handle: Option<CommandHandle>,
+    /// Output path of the build being run, if the running command is a build and not a
+    /// check.
+    build: Option<AbsPathBuf>,
+    /// Paths of the files on disk, mapped from the paths of their copies, if the running
+    /// command checks an [`Overlay`].
+    overlay_paths: HashMap<AbsPathBuf, AbsPathBuf>,
 }
 
 enum Event {
@@ -195,6 +253,8 @@ impl FlycheckActor {
             config,
             root: workspace_root,
             command_handle: None,
+            build: None,
+            overlay_paths: HashMap::new(),
         }
     }
 
@@ -227,25 +287,91 @@ impl FlycheckActor {
                     tracing::debug!(flycheck_id = self.id, "flycheck cancelled");
                     self.cancel_check_process();
                 }
-                Event::RequestStateChange(StateChange::Restart) => {
+                Event::RequestStateChange(StateChange::CheckOverlay(_)) if self.is_building() => {
+                    // The build checks the files on disk, and must report its result, so
+                    // it is not interrupted by a check of the unsaved files.
+                    tracing::debug!(flycheck_id = self.id, "overlay check skipped during build");
+                }
+                Event::RequestStateChange(
+                    change @ (StateChange::Restart
+                    | StateChange::Build
+                    | StateChange::CheckOverlay(_)),
+                ) => {
+                    // A build interrupted by a restart is restarted, so that its result is
+                    // still reported.
+                    let mut build = self.is_building();
                     // Cancel the previously spawned process
-                    self.cancel_check_process();
-                    while let Ok(restart) = inbox.recv_timeout(Duration::from_millis(50)) {
-                        // restart chained with a stop, so just cancel
-                        if let StateChange::Cancel = restart {
-                            continue 'event;
+                    self.kill_check_process();
+                    let mut overlay = None;
+                    match change {
+                        StateChange::Build => build = true,
+                        StateChange::CheckOverlay(it) => overlay = Some(it),
+                        _ => (),
+                    }
+                    loop {
+                        let debounce = if overlay.is_some() {
+                            OVERLAY_DEBOUNCE
+                        } else {
+                            Duration::from_millis(50)
+                        };
+                        let Ok(restart) = inbox.recv_timeout(debounce) else {
+                            break;
+                        };
+                        match restart {
+                            // restart chained with a stop, so just cancel
+                            StateChange::Cancel => {
+                                if build {
+                                    self.report_build_cancelled();
+                                }
+                                continue 'event;
+                            }
+                            // a build also checks the project, so it takes precedence
+                            StateChange::Build => {
+                                build = true;
+                                overlay = None;
+                            }
+                            // the files were saved, so the files on disk are up to date
+                            StateChange::Restart => overlay = None,
+                            StateChange::CheckOverlay(it) if !build => overlay = Some(it),
+                            StateChange::CheckOverlay(_) => (),
                         }
                     }
 
-                    let command = self.check_command();
+                    // Reported before anything can fail, so that every result follows a
+                    // `DidStart`.
+                    self.report_progress(Progress::DidStart);
+                    let (command, main_path) = match self.check_command(build, overlay.as_ref()) {
+                        Ok(it) => it,
+                        Err(error) if build => {
+                            self.build = None;
+                            self.report_progress(Progress::DidBuild(Err(error)));
+                            continue;
+                        }
+                        Err(error) => {
+                            self.report_progress(Progress::DidFailToRestart(format!(
+                                "Failed to write the unsaved files to check: {}",
+                                error
+                            )));
+                            continue;
+                        }
+                    };
                     let formatted_command = format!("{:?}", command);
 
                     tracing::debug!(?command, "will restart flycheck");
-                    match CommandHandle::spawn(command) {
+                    match CommandHandle::spawn(command, main_path) {
                         Ok(command_handle) => {
                             tracing::debug!(command = formatted_command, "did  restart flycheck");
                             self.command_handle = Some(command_handle);
-                            self.report_progress(Progress::DidStart);
+                        }
+                        Err(error) if build => {
+                            self.build = None;
+                            self.report_progress(Progress::DidBuild(Err(io::Error::new(
+                                error.kind(),
+                                format!(
+                                    "Failed to run the following command: {} error={}",
+                                    formatted_command, error
+                                ),
+                            ))));
                         }
                         Err(error) => {
                             self.report_progress(Progress::DidFailToRestart(format!(
@@ -269,20 +395,44 @@ impl FlycheckActor {
                             formatted_handle
                         );
                     }
-                    self.report_progress(Progress::DidFinish(res));
+                    match self.build.take() {
+                        Some(path) => self.report_progress(Progress::DidBuild(
+                            res.and_then(|status| built_plugin(path, status)),
+                        )),
+                        None => self.report_progress(Progress::DidFinish(res.map(|_| ()))),
+                    }
+                }
+                Event::SpCompEvent(Some(mut diagnostic)) => {
+                    diagnostic.map_paths(|path| self.overlay_paths.get(path).cloned());
+                    self.send(Message::AddDiagnostic {
+                        id: self.id,
+                        workspace_root: self.root.clone(),
+                        diagnostic,
+                    })
                 }
-                Event::SpCompEvent(Some(diagnostic)) => self.send(Message::AddDiagnostic {
-                    id: self.id,
-                    workspace_root: self.root.clone(),
-                    diagnostic,
-                }),
             }
         }
         // If we rerun the thread, we need to discard the previous check results first
         self.cancel_check_process();
     }
 
+    /// Whether the running command is a build.
+    fn is_building(&self) -> bool {
+        self.command_handle.is_some() && self.build.is_some()
+    }
+
+    /// Cancels the running command. A cancelled build is reported as failed.
     fn cancel_check_process(&mut self) {
+        if self.is_building() {
+            self.kill_check_process();
+            self.report_build_cancelled();
+            return;
+        }
+        self.kill_check_process();
+    }
+
+    /// Kills the running command, and reports it as cancelled.
+    fn kill_check_process(&mut self) {
         if let Some(command_handle) = self.command_handle.take() {
             tracing::debug!(
                 command = ?command_handle,
@@ -291,14 +441,54 @@ impl FlycheckActor {
             command_handle.cancel();
             self.report_progress(Progress::DidCancel);
         }
+        self.build = None;
+        self.overlay_paths.clear();
     }
 
-    fn check_command(&self) -> Command {
+    fn report_build_cancelled(&self) {
+        self.report_progress(Progress::DidBuild(Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "the build was cancelled",
+        ))));
+    }
+
+    /// Returns the command to run spcomp, and the path of the file it compiles.
+    fn check_command(
+        &mut self,
+        build: bool,
+        overlay: Option<&Overlay>,
+    ) -> io::Result<(Command, AbsPathBuf)> {
+        let out_path = if build {
+            let out_path = build_output_path(&self.config, &self.root, &self.tempdir);
+            if let Some(parent) = out_path.parent() {
+                // If this fails, spcomp will report the error.
+                let _ = std::fs::create_dir_all(parent);
+            }
+            self.build = Some(out_path.clone());
+            out_path
+        } else {
+            self.build = None;
+            self.output_path()
+        };
+        self.overlay_paths = match overlay {
+            Some(overlay) if overlay.contains(&self.root) => {
+                overlay.write(&self.tempdir.join(format!("overlay-{}", self.id)))?
+            }
+            _ => HashMap::new(),
+        };
+        let main_path = self
+            .overlay_paths
+            .iter()
+            .find(|(_, path)| **path == self.root)
+            .map(|(copy, _)| copy.clone())
+            .unwrap_or_else(|| self.root.clone());
         let args = build_args(
             &self.root,
-            &self.output_path(),
+            &main_path,
+            &out_path,
             &self.config.include_directories,
             &self.config.args,
+            !build,
         );
         #[cfg(all(target_arch = "aarch64", target_os = "macos"))]
         let program = "arch";
@@ -312,7 +502,7 @@ impl FlycheckActor {
 
         command.args(args);
 
-        command
+        Ok((command, main_path))
     }
 
     fn send(&self, check_task: Message) {
@@ -325,6 +515,42 @@ impl FlycheckActor {
     }
 }
 
+/// Path of the plugin written by a build of the project whose main file is `root`, e.g.
+/// `plugins/foo.smx` for `scripting/foo.sp`.
+fn build_output_path(
+    config: &FlycheckConfig,
+    root: &AbsPathBuf,
+    tempdir: &AbsPathBuf,
+) -> AbsPathBuf {
+    let name = root
+        .name_and_extension()
+        .map(|(name, _)| name)
+        .unwrap_or("plugin");
+    let directory = config
+        .output_directory
+        .clone()
+        .or_else(|| root.parent().map(|it| it.to_owned()))
+        .unwrap_or_else(|| tempdir.clone());
+
+    directory.join(format!("{}.smx", name))
+}
+
+/// Returns the plugin written at `path` by a build whose spcomp process exited with `status`.
+fn built_plugin(path: AbsPathBuf, status: ExitStatus) -> io::Result<BuiltPlugin> {
+    if !status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("spcomp failed to build {} ({})", path, status),
+        ));
+    }
+    let metadata = std::fs::metadata(&path)?;
+
+    Ok(BuiltPlugin {
+        path,
+        size: metadata.len(),
+    })
+}
+
 struct JodGroupChild(GroupChild);
 
 impl Drop for JodGroupChild {
@@ -357,7 +583,7 @@ impl fmt::Debug for CommandHandle {
 }
 
 impl CommandHandle {
-    fn spawn(mut command: Command) -> std::io::Result<CommandHandle> {
+    fn spawn(mut command: Command, main_path: AbsPathBuf) -> std::io::Result<CommandHandle> {
         command
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -375,7 +601,7 @@ impl CommandHandle {
         let stderr = child.0.inner().stderr.take().unwrap();
 
         let (sender, receiver) = unbounded();
-        let actor = SpCompActor::new(sender, stdout, stderr);
+        let actor = SpCompActor::new(sender, stdout, stderr, main_path);
         let thread = stdx::thread::Builder::new(stdx::thread::ThreadIntent::Worker)
             .name("CargoHandle".to_owned())
             .spawn(move || actor.run())
@@ -395,12 +621,13 @@ impl CommandHandle {
         let _ = self.child.0.wait();
     }
 
-    fn join(mut self) -> io::Result<()> {
-        let _ = self.child.0.kill();
-        let exit_status = self.child.0.wait()?;
+    /// Waits for the process to exit, and returns its exit status.
+    fn join(mut self) -> io::Result<ExitStatus> {
+        // The output is read until the process closes it, i.e. until it exits.
         let (read_at_least_one_message, error) = self.thread.join()?;
+        let exit_status = self.child.0.wait()?;
         if read_at_least_one_message || exit_status.success() {
-            Ok(())
+            Ok(exit_status)
         } else {
             Err(io::Error::new(io::ErrorKind::Other, format!(
                 "Cargo watcher failed, the command produced no valid metadata (exit code: {exit_status:?}):\n{error}"
@@ -413,6 +640,8 @@ struct SpCompActor {
     sender: Sender<SpCompDiagnostic>,
     stdout: ChildStdout,
     stderr: ChildStderr,
+    /// [Path](AbsPathBuf) of the compiled file.
+    main_path: AbsPathBuf,
 }
 
 impl SpCompActor {
@@ -420,11 +649,13 @@ impl SpCompActor {
         sender: Sender<SpCompDiagnostic>,
         stdout: ChildStdout,
         stderr: ChildStderr,
+        main_path: AbsPathBuf,
     ) -> SpCompActor {
         SpCompActor {
             sender,
             stdout,
             stderr,
+            main_path,
         }
     }
 
@@ -433,9 +664,19 @@ impl SpCompActor {
         let mut stderr_errors = String::new();
         let mut read_at_least_one_stdout_message = false;
         let mut read_at_least_one_stderr_message = false;
-        let process_line = |line: &str, error: &mut String| {
-            if let Some(diag) = SpCompDiagnostic::try_from_line(line) {
-                self.sender.send(diag).unwrap();
+        // Each stream has its own parser, as the lines of a diagnostic are printed together.
+        let mut stdout_parser = SpCompOutputParser::new(self.main_path.clone());
+        let mut stderr_parser = SpCompOutputParser::new(self.main_path.clone());
+        let send = |diagnostics: Vec<SpCompDiagnostic>| {
+            for diagnostic in diagnostics {
+                self.sender.send(diagnostic).unwrap();
+            }
+        };
+        let process_line = |parser: &mut SpCompOutputParser, line: &str, error: &mut String| {
+            let mut diagnostics = Vec::new();
+            let recognized = parser.push_line(line, &mut diagnostics);
+            send(diagnostics);
+            if recognized {
                 return true;
             }
             error.push_str(line);
@@ -446,16 +687,21 @@ impl SpCompActor {
             self.stdout,
             self.stderr,
             &mut |line| {
-                if process_line(line, &mut stdout_errors) {
+                if process_line(&mut stdout_parser, line, &mut stdout_errors) {
                     read_at_least_one_stdout_message = true;
                 }
             },
             &mut |line| {
-                if process_line(line, &mut stderr_errors) {
+                if process_line(&mut stderr_parser, line, &mut stderr_errors) {
                     read_at_least_one_stderr_message = true;
                 }
             },
         );
+        for mut parser in [stdout_parser, stderr_parser] {
+            let mut diagnostics = Vec::new();
+            parser.finish(&mut diagnostics);
+            send(diagnostics);
+        }
 
         let read_at_least_one_message =
             read_at_least_one_stdout_message || read_at_least_one_stderr_message;
@@ -467,3 +713,92 @@ impl SpCompActor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(output_directory: Option<AbsPathBuf>) -> FlycheckConfig {
+        FlycheckConfig::new(
+            "spcomp".to_string(),
+            Vec::new(),
+            Vec::new(),
+            output_directory,
+        )
+    }
+
+    #[cfg(unix)]
+    fn exit_status(code: i32) -> ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+
+        ExitStatus::from_raw(code << 8)
+    }
+
+    #[test]
+    fn output_path_next_to_root() {
+        let root = AbsPathBuf::try_from("/scripting/foo.sp").unwrap();
+        let tempdir = AbsPathBuf::try_from("/tmp/flycheck").unwrap();
+        assert_eq!(
+            build_output_path(&config(None), &root, &tempdir),
+            AbsPathBuf::try_from("/scripting/foo.smx").unwrap()
+        );
+    }
+
+    #[test]
+    fn output_path_in_output_directory() {
+        let root = AbsPathBuf::try_from("/scripting/foo.sp").unwrap();
+        let tempdir = AbsPathBuf::try_from("/tmp/flycheck").unwrap();
+        let output_directory = AbsPathBuf::try_from("/plugins").unwrap();
+        assert_eq!(
+            build_output_path(&config(Some(output_directory)), &root, &tempdir),
+            AbsPathBuf::try_from("/plugins/foo.smx").unwrap()
+        );
+    }
+
+    #[test]
+    fn output_path_in_relative_output_directory() {
+        // A relative `compiler.outputDirectory` is joined to the root of the workspace.
+        let workspace = AbsPathBuf::try_from("/addons/sourcemod").unwrap();
+        let root = workspace.join("scripting/foo.sp");
+        let tempdir = AbsPathBuf::try_from("/tmp/flycheck").unwrap();
+        assert_eq!(
+            build_output_path(&config(Some(workspace.join("plugins"))), &root, &tempdir),
+            AbsPathBuf::try_from("/addons/sourcemod/plugins/foo.smx").unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn built_plugin_of_successful_build() {
+        let path = AbsPathBuf::assert(
+            std::env::temp_dir().join(format!("flycheck-built-{}.smx", std::process::id())),
+        );
+        std::fs::write(&path, [0u8; 16]).unwrap();
+        let plugin = built_plugin(path.clone(), exit_status(0));
+        std::fs::remove_file(&path).unwrap();
+        let plugin = plugin.unwrap();
+        assert_eq!(plugin.path, path);
+        assert_eq!(plugin.size, 16);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn built_plugin_of_failed_build() {
+        let path = AbsPathBuf::try_from("/plugins/foo.smx").unwrap();
+        let err = built_plugin(path, exit_status(1)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert!(err
+            .to_string()
+            .starts_with("spcomp failed to build /plugins/foo.smx"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn built_plugin_without_smx() {
+        let path = AbsPathBuf::assert(
+            std::env::temp_dir().join(format!("flycheck-missing-{}.smx", std::process::id())),
+        );
+        let err = built_plugin(path, exit_status(0)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
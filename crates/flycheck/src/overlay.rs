@@ -0,0 +1,163 @@
+//! Overlays of the unsaved contents of the files of a project, which are written to a
+//! temporary directory so that spcomp can check them.
+
+use std::{collections::HashMap, io};
+
+use lsp_types::Range;
+use paths::{AbsPath, AbsPathBuf};
+
+/// Unsaved contents of some files of a project, which replace the files on disk when
+/// checking the project.
+///
+/// The overlay must contain the files including the modified files, up to the root of the
+/// project, so that spcomp reads the copies of the modified files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overlay {
+    files: Vec<OverlayFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlayFile {
+    /// [Path](AbsPathBuf) of the file on disk.
+    pub path: AbsPathBuf,
+
+    /// Unsaved contents of the file.
+    pub text: String,
+
+    /// Includes of the file to rewrite, with the range of the path of their directive,
+    /// delimiters included and with columns in bytes, and the [path](AbsPathBuf) of the
+    /// included file on disk.
+    pub includes: Vec<(Range, AbsPathBuf)>,
+}
+
+impl Overlay {
+    pub fn push(&mut self, file: OverlayFile) {
+        self.files.push(file);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn contains(&self, path: &AbsPath) -> bool {
+        self.files.iter().any(|file| file.path == *path)
+    }
+
+    /// Write the files of the overlay to `dir`, and return the paths of the copies, mapped
+    /// to the paths of the files on disk.
+    ///
+    /// The includes of the copies are rewritten to the absolute paths of the included files,
+    /// using their copies if they are part of the overlay.
+    pub(crate) fn write(&self, dir: &AbsPath) -> io::Result<HashMap<AbsPathBuf, AbsPathBuf>> {
+        if std::fs::metadata(dir).is_ok() {
+            std::fs::remove_dir_all(dir)?;
+        }
+        // Each file gets its own directory to keep its name while avoiding clashes.
+        let copies: HashMap<AbsPathBuf, AbsPathBuf> = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(idx, file)| {
+                let name = file
+                    .path
+                    .file_name()
+                    .and_then(|it| it.to_str())
+                    .unwrap_or("file.sp");
+                (file.path.clone(), dir.join(idx.to_string()).join(name))
+            })
+            .collect();
+
+        for file in self.files.iter() {
+            let copy = &copies[&file.path];
+            let includes = file
+                .includes
+                .iter()
+                .map(|(range, target)| (*range, copies.get(target).unwrap_or(target).as_path()))
+                .collect::<Vec<_>>();
+            let text = rewrite_includes(&file.text, &includes);
+            if let Some(parent) = copy.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(copy, text)?;
+        }
+
+        Ok(copies
+            .into_iter()
+            .map(|(path, copy)| (copy, path))
+            .collect())
+    }
+}
+
+/// Replace the paths of the include directives at the given ranges by absolute paths.
+fn rewrite_includes(text: &str, includes: &[(Range, &AbsPath)]) -> String {
+    text.split_inclusive('\n')
+        .enumerate()
+        .map(|(idx, line)| {
+            let mut line = line.to_string();
+            let mut line_includes = includes
+                .iter()
+                .filter(|(range, _)| range.start.line as usize == idx)
+                .collect::<Vec<_>>();
+            // Replace from the end of the line, so that the columns stay valid.
+            line_includes.sort_by_key(|(range, _)| std::cmp::Reverse(range.start.character));
+            for (range, target) in line_includes {
+                let start = range.start.character as usize;
+                let end = range.end.character as usize;
+                if end > line.len() || !line.is_char_boundary(start) || !line.is_char_boundary(end)
+                {
+                    continue;
+                }
+                // spcomp accepts forward slashes on all platforms, which do not need to be
+                // escaped.
+                line.replace_range(
+                    start..end,
+                    &format!("\"{}\"", target.to_string().replace('\\', "/")),
+                );
+            }
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::*;
+
+    fn range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[test]
+    fn rewrite_chevron_and_quote_includes() {
+        let target = AbsPathBuf::try_from("/tmp/overlay/0/foo.inc").unwrap();
+        let other = AbsPathBuf::try_from("/scripting/include/bar.inc").unwrap();
+        let text = "#include <sourcemod>\n#include <foo>\n#tryinclude \"bar\"\nint x;\n";
+        assert_eq!(
+            rewrite_includes(
+                text,
+                &[(range(1, 9, 14), &target), (range(2, 12, 17), &other)]
+            ),
+            "#include <sourcemod>\n#include \"/tmp/overlay/0/foo.inc\"\n#tryinclude \"/scripting/include/bar.inc\"\nint x;\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_keeps_the_rest_of_the_line() {
+        let target = AbsPathBuf::try_from("/foo.inc").unwrap();
+        assert_eq!(
+            rewrite_includes("#include <foo> // Foo\r\n", &[(range(0, 9, 14), &target)]),
+            "#include \"/foo.inc\" // Foo\r\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_ignores_invalid_ranges() {
+        let target = AbsPathBuf::try_from("/foo.inc").unwrap();
+        assert_eq!(
+            rewrite_includes("#include <foo>", &[(range(0, 9, 40), &target)]),
+            "#include <foo>"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use lazy_static::lazy_static;
+use lsp_types::{Position, Range};
 use paths::AbsPathBuf;
 use regex::Regex;
 
@@ -30,6 +31,12 @@ pub struct SpCompDiagnostic {
     /// Line index of the error.
     line_index: u32,
 
+    /// Line index of the end of the error, for errors spanning several lines.
+    end_line_index: u32,
+
+    /// Range of the error, if spcomp pointed at it with a column or a caret.
+    precise_range: Option<Range>,
+
     /// Severity of the error.
     severity: SpCompSeverity,
 
@@ -38,6 +45,39 @@ pub struct SpCompDiagnostic {
 
     /// Message of the error.
     message: String,
+
+    /// Notes attached to the error.
+    notes: Vec<SpCompNote>,
+}
+
+/// A note printed by spcomp after an error, e.g. the location of a previous definition.
+#[derive(Debug, Clone)]
+pub struct SpCompNote {
+    /// [Path](AbsPathBuf) of the document the note points to.
+    pub path: AbsPathBuf,
+
+    /// Line index the note points to.
+    pub line_index: u32,
+
+    /// Message of the note.
+    pub message: String,
+}
+
+lazy_static! {
+    static ref HEADER_RE: Regex = Regex::new(
+        r"^\s*(.+?)\((\d+)(?:\s*--\s*(\d+))?(?::(\d+))?\)\s*:\s*(error|fatal error|warning)\s+(\d*):\s*(.*)$"
+    )
+    .expect("Failed to compile spcomp error regex.");
+    static ref NOTE_RE: Regex =
+        Regex::new(r"^\s*(?:(.+?)\((\d+)(?::\d+)?\)\s*:\s*)?note:\s*(.*)$")
+            .expect("Failed to compile spcomp note regex.");
+    static ref SOURCE_RE: Regex =
+        Regex::new(r"^(\s*(\d+)\s*\| ?)(.*)$").expect("Failed to compile spcomp source regex.");
+    static ref CARET_RE: Regex =
+        Regex::new(r"^(-*)\^+\s*$").expect("Failed to compile spcomp caret regex.");
+    static ref SUMMARY_RE: Regex =
+        Regex::new(r"^\s*(?:\d+ (?:Errors?|Warnings?)\.|Code size:|Data size:|Stack/heap size:|Total requirements:|SourcePawn Compiler|Copyright)")
+            .expect("Failed to compile spcomp summary regex.");
 }
 
 impl SpCompDiagnostic {
@@ -61,37 +101,253 @@ impl SpCompDiagnostic {
         &self.message
     }
 
-    pub fn try_from_line(line: &str) -> Option<Self> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"([:/\\A-Za-z\-_0-9. ]*)\((\d+)+\) : (?:(error|fatal error|warning) ([0-9]*)):\s+(.*)"
+    pub fn notes(&self) -> &[SpCompNote] {
+        &self.notes
+    }
+
+    /// Range of the error, which spans the whole lines of the error if spcomp did not point
+    /// at a column.
+    pub fn range(&self) -> Range {
+        self.precise_range.unwrap_or_else(|| {
+            Range::new(
+                Position::new(self.line_index, 0),
+                Position::new(self.end_line_index, 1000),
             )
-            .expect("Failed to compile spcomp error regex.");
+        })
+    }
+
+    /// Replace the paths of the diagnostic and of its notes for which `f` returns a path.
+    pub(crate) fn map_paths(&mut self, f: impl Fn(&AbsPathBuf) -> Option<AbsPathBuf>) {
+        if let Some(path) = f(&self.path) {
+            self.path = path;
         }
-        let capture = RE.captures(line)?;
+        for note in self.notes.iter_mut() {
+            if let Some(path) = f(&note.path) {
+                note.path = path;
+            }
+        }
+    }
+
+    /// Parse the first line of a diagnostic, e.g. `foo.sp(12) : error 017: undefined symbol "bar"`.
+    ///
+    /// Lines spanning several lines (`foo.sp(12 -- 14)`) and columns (`foo.sp(12:5)`) are
+    /// supported.
+    pub fn try_from_line(line: &str) -> Option<Self> {
+        let capture = HEADER_RE.captures(line)?;
+        let line_index = capture
+            .get(2)?
+            .as_str()
+            .parse::<u32>()
+            .ok()?
+            .saturating_sub(1);
+        let end_line_index = match capture.get(3) {
+            Some(end) => end.as_str().parse::<u32>().ok()?.saturating_sub(1),
+            None => line_index,
+        };
+        let precise_range = match capture.get(4) {
+            Some(column) => {
+                let column = column.as_str().parse::<u32>().ok()?.saturating_sub(1);
+                Some(Range::new(
+                    Position::new(line_index, column),
+                    Position::new(line_index, column + 1),
+                ))
+            }
+            None => None,
+        };
         Some(Self {
-            path: AbsPathBuf::try_from(capture.get(1)?.as_str()).ok()?,
-            line_index: capture.get(2)?.as_str().parse::<u32>().ok()? - 1,
-            severity: match capture.get(3)?.as_str() {
+            path: AbsPathBuf::try_from(capture.get(1)?.as_str().trim()).ok()?,
+            line_index,
+            end_line_index,
+            precise_range,
+            severity: match capture.get(5)?.as_str() {
                 "warning" => SpCompSeverity::Warning,
                 "error" => SpCompSeverity::Error,
                 "fatal error" => SpCompSeverity::FatalError,
                 _ => unreachable!(),
             },
-            code: capture.get(4)?.as_str().to_string(),
-            message: capture.get(5)?.as_str().to_string(),
+            code: capture.get(6)?.as_str().to_string(),
+            message: capture.get(7)?.as_str().to_string(),
+            notes: Vec::new(),
         })
     }
 }
 
+/// Parser of the output of spcomp, which groups the lines printed for each diagnostic.
+///
+/// After the first line of a diagnostic, spcomp 1.11+ prints the source line and a caret
+/// pointing at the error, e.g.
+/// ```text
+/// foo.sp(5) : error 017: undefined symbol "bar"
+///    5 |     bar();
+/// -----------^
+/// ```
+/// Indented continuation lines are appended to the message, and `note:` lines are attached
+/// as notes. A diagnostic is complete when the next one starts or when the output ends.
+#[derive(Debug)]
+pub(crate) struct SpCompOutputParser {
+    /// [Path](AbsPathBuf) of the compiled file, used for errors without a location.
+    main_path: AbsPathBuf,
+
+    /// Diagnostic being parsed.
+    pending: Option<SpCompDiagnostic>,
+
+    /// Line index, width of the gutter and text of the last source line printed for the
+    /// pending diagnostic.
+    source: Option<(u32, usize, String)>,
+
+    /// Whether a fatal error was parsed.
+    had_fatal: bool,
+
+    /// Lines which are not part of a diagnostic.
+    unrecognized: Vec<String>,
+}
+
+impl SpCompOutputParser {
+    pub(crate) fn new(main_path: AbsPathBuf) -> Self {
+        Self {
+            main_path,
+            pending: None,
+            source: None,
+            had_fatal: false,
+            unrecognized: Vec::new(),
+        }
+    }
+
+    /// Parse a line of output, pushing the diagnostics it completes to `acc`.
+    ///
+    /// Returns whether the line is part of a diagnostic.
+    pub(crate) fn push_line(&mut self, line: &str, acc: &mut Vec<SpCompDiagnostic>) -> bool {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if let Some(diagnostic) = SpCompDiagnostic::try_from_line(line) {
+            self.flush(acc);
+            if matches!(diagnostic.severity, SpCompSeverity::FatalError) {
+                self.had_fatal = true;
+            }
+            self.pending = Some(diagnostic);
+            return true;
+        }
+        if line.trim() == "Compilation aborted." {
+            self.flush(acc);
+            if !self.had_fatal {
+                // The fatal error could not be parsed, report the output as its context.
+                let mut message = "Compilation aborted.".to_string();
+                for line in self.unrecognized.iter().filter(|it| !it.trim().is_empty()) {
+                    message.push('\n');
+                    message.push_str(line.trim());
+                }
+                acc.push(SpCompDiagnostic {
+                    path: self.main_path.clone(),
+                    line_index: 0,
+                    end_line_index: 0,
+                    precise_range: None,
+                    severity: SpCompSeverity::FatalError,
+                    code: String::new(),
+                    message,
+                    notes: Vec::new(),
+                });
+                self.had_fatal = true;
+            }
+            return true;
+        }
+        if line.trim().is_empty() || SUMMARY_RE.is_match(line) {
+            self.flush(acc);
+            return false;
+        }
+        let Some(pending) = &mut self.pending else {
+            self.unrecognized.push(line.to_string());
+            return false;
+        };
+        if let Some(capture) = NOTE_RE.captures(line) {
+            let path = capture
+                .get(1)
+                .and_then(|path| AbsPathBuf::try_from(path.as_str().trim()).ok());
+            let line_index = capture
+                .get(2)
+                .and_then(|line| line.as_str().parse::<u32>().ok())
+                .map(|line| line.saturating_sub(1));
+            let (path, line_index) = match (path, line_index) {
+                (Some(path), Some(line_index)) => (path, line_index),
+                _ => (pending.path.clone(), pending.line_index),
+            };
+            pending.notes.push(SpCompNote {
+                path,
+                line_index,
+                message: capture[3].to_string(),
+            });
+            return true;
+        }
+        if let Some(capture) = SOURCE_RE.captures(line) {
+            if let Ok(line) = capture[2].parse::<u32>() {
+                self.source = Some((
+                    line.saturating_sub(1),
+                    capture[1].len(),
+                    capture[3].to_string(),
+                ));
+                return true;
+            }
+        }
+        if let Some(capture) = CARET_RE.captures(line) {
+            if let Some((line_index, gutter, text)) = &self.source {
+                let column = capture[1].len().saturating_sub(*gutter);
+                pending.precise_range = Some(token_range(*line_index, column, text));
+            }
+            return true;
+        }
+        if line.starts_with(char::is_whitespace) {
+            pending.message.push('\n');
+            pending.message.push_str(line.trim());
+            return true;
+        }
+        self.flush(acc);
+        self.unrecognized.push(line.to_string());
+
+        false
+    }
+
+    /// Finish parsing, pushing the last diagnostic to `acc`.
+    pub(crate) fn finish(&mut self, acc: &mut Vec<SpCompDiagnostic>) {
+        self.flush(acc);
+    }
+
+    fn flush(&mut self, acc: &mut Vec<SpCompDiagnostic>) {
+        self.source = None;
+        if let Some(diagnostic) = self.pending.take() {
+            acc.push(diagnostic);
+        }
+    }
+}
+
+/// Returns the range of the token starting at `column` in the source line `text`, or of the
+/// character at `column` if it does not start an identifier or a number.
+fn token_range(line_index: u32, column: usize, text: &str) -> Range {
+    let len = text
+        .chars()
+        .skip(column)
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .count()
+        .max(1);
+
+    Range::new(
+        Position::new(line_index, column as u32),
+        Position::new(line_index, (column + len) as u32),
+    )
+}
+
 /// Return a [vector](Vec) of [strings](String) of the arguments to run spcomp.
+///
+/// `main_path` is the file to compile, which is a copy of `root_path` when checking an
+/// [overlay](crate::Overlay). The include directories are still relative to `root_path`.
+///
+/// If `syntax_only` is `true`, spcomp only checks the plugin and does not write `out_path`.
 pub fn build_args(
     root_path: &AbsPathBuf,
+    main_path: &AbsPathBuf,
     out_path: &AbsPathBuf,
     includes_directories: &[AbsPathBuf],
     linter_arguments: &[String],
+    syntax_only: bool,
 ) -> Vec<String> {
-    let mut args = vec![root_path.to_string()];
+    let mut args = vec![main_path.to_string()];
     args.extend(
         includes_directories
             .iter()
@@ -106,9 +362,124 @@ pub fn build_args(
     }
 
     args.push(format!("-o{}", out_path));
-    args.push("--syntax-only".to_string());
+    if syntax_only {
+        args.push("--syntax-only".to_string());
+    }
 
     args.extend_from_slice(linter_arguments);
 
     args
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(output: &str) -> Vec<SpCompDiagnostic> {
+        let mut parser = SpCompOutputParser::new(AbsPathBuf::try_from("/plugins/foo.sp").unwrap());
+        let mut res = Vec::new();
+        for line in output.lines() {
+            parser.push_line(line, &mut res);
+        }
+        parser.finish(&mut res);
+        res
+    }
+
+    #[test]
+    fn parse_single_line() {
+        let res = parse(
+            r#"/plugins/foo.sp(12) : warning 204: symbol is assigned a value that is never used: "bar""#,
+        );
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].code(), "204");
+        assert!(matches!(res[0].severity(), SpCompSeverity::Warning));
+        assert_eq!(
+            res[0].range(),
+            Range::new(Position::new(11, 0), Position::new(11, 1000))
+        );
+    }
+
+    #[test]
+    fn parse_line_span() {
+        let res =
+            parse(r#"/plugins/foo.sp(12 -- 14) : error 001: expected token: ";", but found "}""#);
+        assert_eq!(
+            res[0].range(),
+            Range::new(Position::new(11, 0), Position::new(13, 1000))
+        );
+    }
+
+    #[test]
+    fn parse_caret() {
+        let res = parse(
+            r#"/plugins/foo.sp(5) : error 017: undefined symbol "bar"
+   5 |     bar();
+-----------^
+/plugins/foo.sp(6) : error 017: undefined symbol "baz"
+"#,
+        );
+        assert_eq!(res.len(), 2);
+        assert_eq!(
+            res[0].range(),
+            Range::new(Position::new(4, 4), Position::new(4, 7))
+        );
+        assert_eq!(res[0].message(), r#"undefined symbol "bar""#);
+    }
+
+    #[test]
+    fn parse_column() {
+        let res = parse(r#"/plugins/foo.sp(5:9) : error 017: undefined symbol "bar""#);
+        assert_eq!(
+            res[0].range(),
+            Range::new(Position::new(4, 8), Position::new(4, 9))
+        );
+    }
+
+    #[test]
+    fn parse_continuation_and_notes() {
+        let res = parse(
+            r#"/plugins/foo.sp(5) : error 021: symbol already defined: "bar"
+    in this scope
+/plugins/foo.inc(2) : note: previous definition of "bar"
+"#,
+        );
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0].message(),
+            "symbol already defined: \"bar\"\nin this scope"
+        );
+        assert_eq!(res[0].notes().len(), 1);
+        assert_eq!(res[0].notes()[0].path.to_string(), "/plugins/foo.inc");
+        assert_eq!(res[0].notes()[0].line_index, 1);
+    }
+
+    #[test]
+    fn parse_compilation_aborted() {
+        let res = parse(
+            r#"spcomp: cannot open the output file
+
+Compilation aborted.
+1 Error.
+"#,
+        );
+        assert_eq!(res.len(), 1);
+        assert!(matches!(res[0].severity(), SpCompSeverity::FatalError));
+        assert_eq!(res[0].path().to_string(), "/plugins/foo.sp");
+        assert_eq!(
+            res[0].message(),
+            "Compilation aborted.\nspcomp: cannot open the output file"
+        );
+    }
+
+    #[test]
+    fn parse_compilation_aborted_after_fatal_error() {
+        let res = parse(
+            r#"/plugins/foo.sp(1) : fatal error 183: cannot read from file: "bar"
+
+Compilation aborted.
+"#,
+        );
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].code(), "183");
+    }
+}
@@ -11,6 +11,7 @@ use std::sync::Arc;
 use syntax::TSKind;
 use vfs::FileId;
 
+pub mod cfg;
 pub mod lower;
 pub mod scope;
 
@@ -0,0 +1,383 @@
+//! Control-flow graph of a [`Body`], at the granularity of its statements.
+
+use std::sync::Arc;
+
+use fxhash::FxHashSet;
+use la_arena::{Arena, Idx};
+use syntax::TSKind;
+
+use crate::{
+    consteval,
+    hir::{Expr, ExprId, LoopKind},
+    DefDatabase, DefWithBodyId,
+};
+
+use super::Body;
+
+pub type BasicBlockId = Idx<BasicBlock>;
+
+/// A sequence of statements which are executed one after the other.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub statements: Vec<ExprId>,
+    pub terminator: Terminator,
+}
+
+/// How the control leaves a [`BasicBlock`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Terminator {
+    /// Continues in another block.
+    Goto(BasicBlockId),
+
+    /// Continues in one of the blocks, depending on the value of the condition.
+    Branch {
+        condition: ExprId,
+        targets: Box<[BasicBlockId]>,
+    },
+
+    /// Returns from the function, with an optional value.
+    Return(Option<ExprId>),
+
+    /// Reaches the end of the body without returning.
+    #[default]
+    FallOff,
+}
+
+impl Terminator {
+    pub fn successors(&self) -> &[BasicBlockId] {
+        match self {
+            Terminator::Goto(target) => std::slice::from_ref(target),
+            Terminator::Branch { targets, .. } => targets,
+            Terminator::Return(_) | Terminator::FallOff => &[],
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    blocks: Arena<BasicBlock>,
+    entry: Option<BasicBlockId>,
+
+    /// First statement of each block, for the blocks which start with a statement.
+    block_starts: Vec<(BasicBlockId, ExprId)>,
+
+    /// Loops whose condition is always true, with the block after the loop and whether the
+    /// loop contains a `return`.
+    infinite_loops: Vec<(ExprId, BasicBlockId, bool)>,
+
+    /// Conditions of `if` statements which are compile-time constants, with their value.
+    constant_conditions: Vec<(ExprId, bool)>,
+}
+
+impl ControlFlowGraph {
+    pub(crate) fn control_flow_graph_query(
+        db: &dyn DefDatabase,
+        def: DefWithBodyId,
+    ) -> Arc<ControlFlowGraph> {
+        let body = db.body(def);
+        let mut builder = CfgBuilder {
+            body: &body,
+            cfg: ControlFlowGraph::default(),
+            loops: Vec::new(),
+            returns: 0,
+        };
+        if let Some(body_expr) = body.body_expr {
+            let entry = builder.new_block();
+            builder.cfg.entry = Some(entry);
+            builder.lower_stmt(body_expr, entry);
+        }
+
+        Arc::new(builder.cfg)
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = (BasicBlockId, &BasicBlock)> {
+        self.blocks.iter()
+    }
+
+    pub fn entry(&self) -> Option<BasicBlockId> {
+        self.entry
+    }
+
+    /// Returns the blocks which can be reached from the entry of the body.
+    pub fn reachable_blocks(&self) -> FxHashSet<BasicBlockId> {
+        let mut res = FxHashSet::default();
+        let mut stack = self.entry.into_iter().collect::<Vec<_>>();
+        while let Some(block) = stack.pop() {
+            if res.insert(block) {
+                stack.extend(self.blocks[block].terminator.successors());
+            }
+        }
+
+        res
+    }
+
+    /// Whether the control can reach the end of the body without a `return`.
+    pub fn can_fall_off(&self) -> bool {
+        self.reachable_blocks()
+            .iter()
+            .any(|block| self.blocks[*block].terminator == Terminator::FallOff)
+    }
+
+    /// Returns the values returned by the reachable `return` statements.
+    pub fn returned_values(&self) -> Vec<ExprId> {
+        self.reachable_blocks()
+            .iter()
+            .filter_map(|block| match self.blocks[*block].terminator {
+                Terminator::Return(value) => value,
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the first statements of the code which can never be executed, e.g. the code
+    /// after a `return`, a `break` or a `continue`.
+    ///
+    /// Only the first statement of each unreachable region is returned.
+    pub fn unreachable_statements(&self) -> Vec<ExprId> {
+        let reachable = self.reachable_blocks();
+        let unreachable_starts = self
+            .block_starts
+            .iter()
+            .filter(|(block, _)| !reachable.contains(block))
+            .collect::<Vec<_>>();
+
+        // Blocks which follow the start of an unreachable region.
+        let mut covered = FxHashSet::default();
+        for (block, _) in unreachable_starts.iter() {
+            let mut stack = self.blocks[*block].terminator.successors().to_vec();
+            while let Some(block) = stack.pop() {
+                if !reachable.contains(&block) && covered.insert(block) {
+                    stack.extend(self.blocks[block].terminator.successors());
+                }
+            }
+        }
+
+        unreachable_starts
+            .into_iter()
+            .filter(|(block, _)| !covered.contains(block))
+            .map(|(_, stmt)| *stmt)
+            .collect()
+    }
+
+    /// Returns the reachable loops whose condition is always true and which can never be
+    /// exited, either with a `break` or a `return`.
+    pub fn infinite_loops(&self) -> Vec<ExprId> {
+        let reachable = self.reachable_blocks();
+        self.infinite_loops
+            .iter()
+            .filter(|(expr, exit, returns)| {
+                !returns
+                    && !reachable.contains(exit)
+                    && self
+                        .block_of(*expr)
+                        .is_some_and(|it| reachable.contains(&it))
+            })
+            .map(|(expr, ..)| *expr)
+            .collect()
+    }
+
+    /// Returns the conditions of the `if` statements which are compile-time constants, with
+    /// their value.
+    pub fn constant_conditions(&self) -> &[(ExprId, bool)] {
+        &self.constant_conditions
+    }
+
+    fn block_of(&self, expr: ExprId) -> Option<BasicBlockId> {
+        self.blocks
+            .iter()
+            .find(|(_, block)| block.statements.contains(&expr))
+            .map(|(id, _)| id)
+    }
+}
+
+/// Returns the value of a condition if it is a compile-time constant.
+pub fn constant_condition(body: &Body, expr: ExprId) -> Option<bool> {
+    consteval::eval_expr(body, expr, &|_| None).map(|it| it != 0)
+}
+
+struct CfgBuilder<'a> {
+    body: &'a Body,
+    cfg: ControlFlowGraph,
+
+    /// Blocks to jump to for a `continue` and a `break` in the enclosing loops.
+    loops: Vec<(BasicBlockId, BasicBlockId)>,
+
+    /// Number of `return` statements lowered so far.
+    returns: usize,
+}
+
+impl CfgBuilder<'_> {
+    fn new_block(&mut self) -> BasicBlockId {
+        self.cfg.blocks.alloc(BasicBlock::default())
+    }
+
+    fn terminate(&mut self, block: BasicBlockId, terminator: Terminator) {
+        self.cfg.blocks[block].terminator = terminator;
+    }
+
+    fn push(&mut self, block: BasicBlockId, stmt: ExprId) {
+        self.cfg.blocks[block].statements.push(stmt);
+    }
+
+    /// Lowers the statement at the end of `current`, and returns the block in which the
+    /// control continues after it.
+    ///
+    /// If the statement never completes, e.g. a `return`, the returned block is a new block
+    /// without predecessors.
+    fn lower_stmt(&mut self, stmt: ExprId, current: BasicBlockId) -> BasicBlockId {
+        let body = self.body;
+        if matches!(body[stmt], Expr::Missing) {
+            return current;
+        }
+        if self.cfg.blocks[current].statements.is_empty()
+            && !matches!(body[stmt], Expr::Block { .. })
+        {
+            self.cfg.block_starts.push((current, stmt));
+        }
+        match &body[stmt] {
+            Expr::Block { statements, .. } => {
+                let mut current = current;
+                for stmt in statements.iter() {
+                    current = self.lower_stmt(*stmt, current);
+                }
+                current
+            }
+            Expr::Condition {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.push(current, stmt);
+                if let Some(value) = constant_condition(body, *condition) {
+                    self.cfg.constant_conditions.push((*condition, value));
+                }
+                let then_block = self.new_block();
+                let else_block = self.new_block();
+                let join = self.new_block();
+                self.terminate(
+                    current,
+                    Terminator::Branch {
+                        condition: *condition,
+                        targets: Box::new([then_block, else_block]),
+                    },
+                );
+                let then_end = self.lower_stmt(*then_branch, then_block);
+                self.terminate(then_end, Terminator::Goto(join));
+                let else_end = match else_branch {
+                    Some(else_branch) => self.lower_stmt(*else_branch, else_block),
+                    None => else_block,
+                };
+                self.terminate(else_end, Terminator::Goto(join));
+                join
+            }
+            Expr::Loop {
+                kind,
+                initialization,
+                condition,
+                iteration,
+                body: loop_body,
+            } => {
+                self.push(current, stmt);
+                for init in initialization.iter() {
+                    self.push(current, *init);
+                }
+                let value = match condition {
+                    Some(condition) => constant_condition(body, *condition),
+                    None => Some(true),
+                };
+                let body_block = self.new_block();
+                let latch = self.new_block();
+                let exit = self.new_block();
+                let test = match (value, condition) {
+                    (Some(true), _) | (None, None) => Terminator::Goto(body_block),
+                    (Some(false), _) => Terminator::Goto(exit),
+                    (None, Some(condition)) => Terminator::Branch {
+                        condition: *condition,
+                        targets: Box::new([body_block, exit]),
+                    },
+                };
+                // The condition of a `do while` loop is only evaluated after the body.
+                if *kind == LoopKind::DoWhile {
+                    self.terminate(current, Terminator::Goto(body_block));
+                    self.terminate(latch, test);
+                } else {
+                    let header = self.new_block();
+                    self.terminate(current, Terminator::Goto(header));
+                    self.terminate(header, test);
+                    if let Some(iteration) = iteration {
+                        self.push(latch, *iteration);
+                    }
+                    self.terminate(latch, Terminator::Goto(header));
+                }
+
+                let returns = self.returns;
+                self.loops.push((latch, exit));
+                let body_end = match loop_body {
+                    Some(loop_body) => self.lower_stmt(*loop_body, body_block),
+                    None => body_block,
+                };
+                self.loops.pop();
+                self.terminate(body_end, Terminator::Goto(latch));
+                if value == Some(true) {
+                    self.cfg
+                        .infinite_loops
+                        .push((stmt, exit, self.returns > returns));
+                }
+                exit
+            }
+            Expr::Switch { condition, cases } => {
+                self.push(current, stmt);
+                let exit = self.new_block();
+                let mut targets = Vec::new();
+                for case in cases.iter() {
+                    let case_block = self.new_block();
+                    targets.push(case_block);
+                    let case_end = self.lower_stmt(case.body(), case_block);
+                    self.terminate(case_end, Terminator::Goto(exit));
+                }
+                // Cases do not fall through in SourcePawn, the `default` case has no value.
+                if !cases.iter().any(|case| case.values().is_empty()) {
+                    targets.push(exit);
+                }
+                self.terminate(
+                    current,
+                    Terminator::Branch {
+                        condition: *condition,
+                        targets: targets.into_boxed_slice(),
+                    },
+                );
+                exit
+            }
+            Expr::Control {
+                keyword: TSKind::anon_return_,
+                operand,
+            } => {
+                self.push(current, stmt);
+                self.returns += 1;
+                self.terminate(current, Terminator::Return(*operand));
+                self.new_block()
+            }
+            Expr::Control {
+                keyword: keyword @ (TSKind::anon_break | TSKind::anon_continue),
+                ..
+            } => {
+                self.push(current, stmt);
+                if let Some((continue_target, break_target)) = self.loops.last().copied() {
+                    let target = if *keyword == TSKind::anon_break {
+                        break_target
+                    } else {
+                        continue_target
+                    };
+                    self.terminate(current, Terminator::Goto(target));
+                    self.new_block()
+                } else {
+                    current
+                }
+            }
+            _ => {
+                self.push(current, stmt);
+                current
+            }
+        }
+    }
+}
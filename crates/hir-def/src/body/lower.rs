@@ -6,7 +6,8 @@ use vfs::FileId;
 
 use crate::{
     ast_id_map::AstIdMap,
-    hir::{type_ref::TypeRef, Expr, ExprId, FloatTypeWrapper, Literal, SwitchCase},
+    consteval,
+    hir::{type_ref::TypeRef, Expr, ExprId, FloatTypeWrapper, Literal, LoopKind, SwitchCase},
     item_tree::Name,
     BlockLoc, DefDatabase, DefWithBodyId, InFile, NodePtr,
 };
@@ -194,6 +195,7 @@ impl ExprCollector<'_> {
                     initialization.push(self.collect_expr(init));
                 }
                 let for_loop = Expr::Loop {
+                    kind: LoopKind::For,
                     initialization: initialization.into_boxed_slice(),
                     condition: expr
                         .child_by_field_name("condition")
@@ -208,7 +210,13 @@ impl ExprCollector<'_> {
                 Some(self.alloc_expr(for_loop, NodePtr::from(&expr)))
             }
             TSKind::while_statement | TSKind::do_while_statement => {
+                let kind = if TSKind::from(expr) == TSKind::do_while_statement {
+                    LoopKind::DoWhile
+                } else {
+                    LoopKind::While
+                };
                 let loop_expr = Expr::Loop {
+                    kind,
                     initialization: Default::default(),
                     condition: expr
                         .child_by_field_name("condition")
@@ -269,10 +277,11 @@ impl ExprCollector<'_> {
                 Some(self.alloc_expr(switch, NodePtr::from(&expr)))
             }
             TSKind::return_statement => {
-                let expr = expr.child_by_field_name("expression")?;
                 let control_expr = Expr::Control {
                     keyword: TSKind::anon_return_,
-                    operand: self.maybe_collect_expr(expr),
+                    operand: expr
+                        .child_by_field_name("expression")
+                        .and_then(|it| self.maybe_collect_expr(it)),
                 };
                 Some(self.alloc_expr(control_expr, NodePtr::from(&expr)))
             }
@@ -292,6 +301,7 @@ impl ExprCollector<'_> {
             // region: Expressions
             TSKind::assignment_expression
             | TSKind::binary_expression
+            | TSKind::case_binary_expression
             | TSKind::preproc_binary_expression => {
                 let lhs = self.collect_expr(expr.child_by_field_name("left")?);
                 let rhs = self.collect_expr(expr.child_by_field_name("right")?);
@@ -371,13 +381,14 @@ impl ExprCollector<'_> {
                 Some(self.alloc_expr(access, NodePtr::from(&field)))
             }
             TSKind::unary_expression
+            | TSKind::case_unary_expression
             | TSKind::update_expression
             | TSKind::preproc_unary_expression => {
                 // For our needs, unary and update expressions are the same
-                let expr = expr.child_by_field_name("argument")?;
+                let argument = expr.child_by_field_name("argument")?;
                 let op = expr.child_by_field_name("operator").map(TSKind::from);
                 let unary = Expr::UnaryOp {
-                    operand: self.collect_expr(expr),
+                    operand: self.collect_expr(argument),
                     op,
                 };
                 Some(self.alloc_expr(unary, NodePtr::from(&expr)))
@@ -407,8 +418,8 @@ impl ExprCollector<'_> {
             TSKind::this => Some(self.alloc_expr(Expr::This, NodePtr::from(&expr))),
             TSKind::int_literal => {
                 let text = expr.utf8_text(self.source.as_bytes()).unwrap();
-                // FIXME: The unwrap_or_default() is a workaround for hex literals
-                let int = text.parse().unwrap_or_default();
+                // FIXME: The unwrap_or_default() is a workaround for malformed literals
+                let int = consteval::parse_int(text).unwrap_or_default();
                 Some(self.alloc_expr(Expr::Literal(Literal::Int(int)), NodePtr::from(&expr)))
             }
             TSKind::float_literal => {
@@ -0,0 +1,178 @@
+//! Evaluation of the integer constant expressions, such as `case` labels and enum values.
+//!
+//! Values are 32-bit cells, like in spcomp: `0xFFFFFFFF` and `-1` are the same value.
+
+use syntax::TSKind;
+
+use crate::{
+    body::Body,
+    hir::{Expr, ExprId, Literal},
+    item_tree::Name,
+};
+
+/// Evaluates an expression of a body, if it is a compile-time constant.
+///
+/// `resolve_ident` returns the value of the identifiers which refer to a constant, such as an
+/// enum variant.
+pub(crate) fn eval_expr(
+    body: &Body,
+    expr: ExprId,
+    resolve_ident: &dyn Fn(&Name) -> Option<i32>,
+) -> Option<i32> {
+    match &body[expr] {
+        Expr::Literal(Literal::Int(value)) => Some(*value as i32),
+        Expr::Literal(Literal::Bool(value)) => Some(*value as i32),
+        Expr::Literal(Literal::Char(value)) => Some(*value as i32),
+        Expr::Ident(name) => resolve_ident(name),
+        Expr::UnaryOp {
+            operand,
+            op: Some(op),
+        } => eval_unary(*op, eval_expr(body, *operand, resolve_ident)?),
+        Expr::BinaryOp {
+            lhs,
+            rhs,
+            op: Some(op),
+        } => eval_binary(
+            *op,
+            eval_expr(body, *lhs, resolve_ident)?,
+            eval_expr(body, *rhs, resolve_ident)?,
+        ),
+        Expr::TernaryOp {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            if eval_expr(body, *condition, resolve_ident)? != 0 {
+                eval_expr(body, *then_branch, resolve_ident)
+            } else {
+                eval_expr(body, *else_branch, resolve_ident)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Evaluates an expression node, if it is a compile-time constant.
+///
+/// This is used before the bodies are lowered, e.g. for the values of the enum variants.
+pub(crate) fn eval_node(
+    node: &tree_sitter::Node,
+    source: &str,
+    resolve_ident: &dyn Fn(&str) -> Option<i32>,
+) -> Option<i32> {
+    let text = || node.utf8_text(source.as_bytes()).ok();
+    match TSKind::from(node) {
+        TSKind::int_literal => parse_int(text()?).map(|it| it as i32),
+        TSKind::bool_literal => text()?.parse::<bool>().ok().map(|it| it as i32),
+        TSKind::char_literal => text()?.chars().nth(1).map(|it| it as i32),
+        TSKind::identifier => resolve_ident(text()?),
+        TSKind::parenthesized_expression => eval_node(
+            &node.child_by_field_name("expression")?,
+            source,
+            resolve_ident,
+        ),
+        TSKind::unary_expression => eval_unary(
+            TSKind::from(node.child_by_field_name("operator")?),
+            eval_node(
+                &node.child_by_field_name("argument")?,
+                source,
+                resolve_ident,
+            )?,
+        ),
+        TSKind::binary_expression => eval_binary(
+            TSKind::from(node.child_by_field_name("operator")?),
+            eval_node(&node.child_by_field_name("left")?, source, resolve_ident)?,
+            eval_node(&node.child_by_field_name("right")?, source, resolve_ident)?,
+        ),
+        _ => None,
+    }
+}
+
+/// Parses an integer literal, in decimal, hexadecimal, binary or octal notation.
+pub(crate) fn parse_int(text: &str) -> Option<i64> {
+    let text = text.replace('_', "");
+    let (digits, radix) = if let Some(digits) = text.strip_prefix("0x") {
+        (digits, 16)
+    } else if let Some(digits) = text.strip_prefix("0b") {
+        (digits, 2)
+    } else if let Some(digits) = text.strip_prefix("0o") {
+        (digits, 8)
+    } else {
+        (text.as_str(), 10)
+    };
+
+    i64::from_str_radix(digits, radix).ok()
+}
+
+fn eval_unary(op: TSKind, operand: i32) -> Option<i32> {
+    let value = match op {
+        TSKind::anon_DASH => operand.wrapping_neg(),
+        TSKind::anon_PLUS => operand,
+        TSKind::anon_TILDE => !operand,
+        TSKind::anon_BANG => (operand == 0) as i32,
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+fn eval_binary(op: TSKind, lhs: i32, rhs: i32) -> Option<i32> {
+    let value = match op {
+        TSKind::anon_PLUS => lhs.wrapping_add(rhs),
+        TSKind::anon_DASH => lhs.wrapping_sub(rhs),
+        TSKind::anon_STAR => lhs.wrapping_mul(rhs),
+        TSKind::anon_SLASH => lhs.checked_div(rhs)?,
+        TSKind::anon_PERCENT => lhs.checked_rem(rhs)?,
+        TSKind::anon_LT_LT => lhs.checked_shl(u32::try_from(rhs).ok()?)?,
+        TSKind::anon_GT_GT => lhs.checked_shr(u32::try_from(rhs).ok()?)?,
+        TSKind::anon_GT_GT_GT => ((lhs as u32).checked_shr(u32::try_from(rhs).ok()?)?) as i32,
+        TSKind::anon_AMP => lhs & rhs,
+        TSKind::anon_PIPE => lhs | rhs,
+        TSKind::anon_CARET => lhs ^ rhs,
+        TSKind::anon_AMP_AMP => (lhs != 0 && rhs != 0) as i32,
+        TSKind::anon_PIPE_PIPE => (lhs != 0 || rhs != 0) as i32,
+        TSKind::anon_EQ_EQ => (lhs == rhs) as i32,
+        TSKind::anon_BANG_EQ => (lhs != rhs) as i32,
+        TSKind::anon_LT => (lhs < rhs) as i32,
+        TSKind::anon_LT_EQ => (lhs <= rhs) as i32,
+        TSKind::anon_GT => (lhs > rhs) as i32,
+        TSKind::anon_GT_EQ => (lhs >= rhs) as i32,
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_literals() {
+        assert_eq!(parse_int("42"), Some(42));
+        assert_eq!(parse_int("1_000"), Some(1000));
+        assert_eq!(parse_int("0x1F"), Some(31));
+        assert_eq!(parse_int("0b101"), Some(5));
+        assert_eq!(parse_int("0o17"), Some(15));
+        assert_eq!(parse_int("0xFFFFFFFF").map(|it| it as i32), Some(-1));
+        assert_eq!(parse_int("0xZ"), None);
+    }
+
+    #[test]
+    fn cells_wrap_at_32_bits() {
+        assert_eq!(eval_unary(TSKind::anon_DASH, i32::MIN), Some(i32::MIN));
+        assert_eq!(eval_unary(TSKind::anon_TILDE, 0), Some(-1));
+        assert_eq!(eval_binary(TSKind::anon_PLUS, i32::MAX, 1), Some(i32::MIN));
+        assert_eq!(eval_binary(TSKind::anon_LT_LT, 1, 31), Some(i32::MIN));
+        assert_eq!(eval_binary(TSKind::anon_GT_GT, -8, 1), Some(-4));
+        assert_eq!(eval_binary(TSKind::anon_GT_GT_GT, -1, 28), Some(0xF));
+    }
+
+    #[test]
+    fn invalid_operations() {
+        assert_eq!(eval_binary(TSKind::anon_SLASH, 1, 0), None);
+        assert_eq!(eval_binary(TSKind::anon_PERCENT, i32::MIN, -1), None);
+        assert_eq!(eval_binary(TSKind::anon_LT_LT, 1, -1), None);
+        assert_eq!(eval_binary(TSKind::anon_EQ, 1, 1), None);
+    }
+}
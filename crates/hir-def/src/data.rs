@@ -670,6 +670,7 @@ impl EnumData {
             let variant = &item_tree[variant_idx];
             let variant_data = VariantData {
                 name: variant.name.clone(),
+                value: variant.value,
                 deprecated: variant.deprecated,
             };
             let variant_id = variants.alloc(variant_data);
@@ -689,6 +690,7 @@ impl EnumData {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VariantData {
     pub name: Name,
+    pub value: Option<i32>,
     pub deprecated: bool,
 }
 
@@ -700,6 +702,7 @@ impl VariantData {
 
         VariantData {
             name: variant.name.clone(),
+            value: variant.value,
             deprecated: variant.deprecated,
         }
         .into()
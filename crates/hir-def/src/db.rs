@@ -12,7 +12,7 @@ use vfs::{AnchoredPath, FileId};
 
 use crate::{
     ast_id_map::AstIdMap,
-    body::{scope::ExprScopes, Body, BodySourceMap},
+    body::{cfg::ControlFlowGraph, scope::ExprScopes, Body, BodySourceMap},
     data::{
         EnumData, EnumStructData, FuncenumData, FunctagData, FunctionData, GlobalData, MacroData,
         MethodmapData, PropertyData, StructData, TypedefData, TypesetData, VariantData,
@@ -91,6 +91,9 @@ pub trait DefDatabase: InternDatabase + PreprocDatabase {
     #[salsa::invoke(ExprScopes::expr_scopes_query)]
     fn expr_scopes(&self, def: DefWithBodyId, file_id: FileId) -> Arc<ExprScopes>;
 
+    #[salsa::invoke(ControlFlowGraph::control_flow_graph_query)]
+    fn control_flow_graph(&self, def: DefWithBodyId) -> Arc<ControlFlowGraph>;
+
     // region: data
     #[salsa::invoke(FunctionData::function_data_query)]
     fn function_data(&self, id: FunctionId) -> Arc<FunctionData>;
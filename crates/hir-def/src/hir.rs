@@ -77,6 +77,7 @@ pub enum Expr {
         else_branch: ExprId,
     },
     Loop {
+        kind: LoopKind,
         initialization: Box<[ExprId]>,
         condition: Option<ExprId>,
         iteration: Option<ExprId>,
@@ -113,6 +114,13 @@ pub enum Expr {
     Literal(Literal),
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LoopKind {
+    For,
+    While,
+    DoWhile,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SwitchCase {
     values: Box<[ExprId]>,
@@ -1,17 +1,19 @@
 use std::sync::Arc;
 
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use smallvec::smallvec;
 use stdx::impl_from;
+use syntax::TSKind;
 
 use crate::{
     body::Body,
+    consteval,
     data::{EnumStructItemData, FunctionData, MethodmapItemData},
-    hir::{type_ref::TypeRef, Expr, Literal},
+    hir::{type_ref::TypeRef, Expr, Literal, SwitchCase},
     item_tree::Name,
     resolver::{HasResolver, Resolver, ValueNs},
-    DefDatabase, DefWithBodyId, ExprId, FieldId, FunctionId, InFile, ItemContainerId, Lookup,
-    PropertyId,
+    DefDatabase, DefWithBodyId, EnumId, ExprId, FieldId, FileDefId, FunctionId, GlobalId, InFile,
+    ItemContainerId, Lookup, PropertyId, VariantId,
 };
 
 pub(crate) fn infer_query(db: &dyn DefDatabase, def: DefWithBodyId) -> Arc<InferenceResult> {
@@ -61,6 +63,50 @@ pub enum InferenceDiagnostic {
     InvalidUseOfThis {
         expr: ExprId,
     },
+    DuplicateCaseValue {
+        expr: ExprId,
+        first: ExprId,
+        value: i32,
+    },
+    NonConstantCaseLabel {
+        expr: ExprId,
+    },
+    MissingSwitchCases {
+        expr: ExprId,
+        enum_name: Name,
+        missing: Vec<Name>,
+    },
+    ShadowedVariable {
+        expr: ExprId,
+        name: Name,
+        shadowed: ShadowedVariable,
+    },
+    LoopVariableUsedAfterLoop {
+        expr: ExprId,
+        name: Name,
+        binding: ExprId,
+    },
+    DeprecatedUsage {
+        expr: ExprId,
+        item: DeprecatedItem,
+    },
+}
+
+/// An item marked as deprecated with `#pragma deprecated`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DeprecatedItem {
+    FileDefId(FileDefId),
+    AttributeId(AttributeId),
+}
+
+impl_from!(FileDefId, AttributeId for DeprecatedItem);
+
+/// The variable a local variable shadows.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ShadowedVariable {
+    Parameter(ExprId),
+    Local(ExprId),
+    Global(InFile<GlobalId>),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -77,6 +123,14 @@ pub enum AttributeId {
 
 impl_from!(FieldId, PropertyId for AttributeId);
 
+/// The enum a `switch` statement is over, with the variants which have no `case`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SwitchResolution {
+    pub enum_id: EnumId,
+    pub missing_variants: Vec<VariantId>,
+    pub has_default: bool,
+}
+
 /// The result of type inference: A mapping from expressions and patterns to types.
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct InferenceResult {
@@ -86,6 +140,8 @@ pub struct InferenceResult {
     method_resolutions: FxHashMap<ExprId, FunctionId>,
     /// For each named argument, records the local it resolves to.
     named_arg_resolutions: FxHashMap<ExprId, (DefWithBodyId, ExprId)>,
+    /// For each switch over an enum, records the enum and the variants without a case.
+    switch_resolutions: FxHashMap<ExprId, SwitchResolution>,
 
     pub diagnostics: Vec<InferenceDiagnostic>,
 }
@@ -102,6 +158,10 @@ impl InferenceResult {
     pub fn named_arg_resolution(&self, expr: ExprId) -> Option<(DefWithBodyId, ExprId)> {
         self.named_arg_resolutions.get(&expr).copied()
     }
+
+    pub fn switch_resolution(&self, expr: ExprId) -> Option<&SwitchResolution> {
+        self.switch_resolutions.get(&expr)
+    }
 }
 
 /// The inference context contains all information needed during type inference.
@@ -113,6 +173,19 @@ pub(crate) struct InferenceContext<'a> {
     pub(crate) result: InferenceResult,
     pub(crate) resolver: Resolver,
     call_stack: Vec<Callee>,
+
+    /// Loops whose initialization, condition or body is being inferred.
+    loops: Vec<ExprId>,
+
+    /// For each binding declared in the initialization of a loop, the loop.
+    loop_bindings: FxHashMap<ExprId, ExprId>,
+
+    /// Bindings declared so far in each of the blocks being inferred, innermost last.
+    block_bindings: Vec<Vec<ExprId>>,
+
+    /// Bindings of loops which shadow a local declared before the loop in the same block.
+    /// After the loop, their name refers to that local.
+    shadowing_loop_bindings: FxHashSet<ExprId>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -135,6 +208,10 @@ impl<'a> InferenceContext<'a> {
             body,
             resolver,
             call_stack: Vec::new(),
+            loops: Vec::new(),
+            loop_bindings: loop_bindings(body),
+            block_bindings: Vec::new(),
+            shadowing_loop_bindings: FxHashSet::default(),
         }
     }
 
@@ -189,9 +266,11 @@ impl InferenceContext<'_> {
                 let g = self
                     .resolver
                     .update_to_inner_scope(self.db, self.owner, *expr);
+                self.block_bindings.push(Vec::new());
                 for expr_id in statements.iter() {
                     self.infer_expr(expr_id);
                 }
+                self.block_bindings.pop();
                 self.resolver.reset_to_guard(g);
                 None
             }
@@ -208,7 +287,9 @@ impl InferenceContext<'_> {
                 condition,
                 iteration,
                 body,
+                ..
             } => {
+                self.loops.push(*expr);
                 for init in initialization.iter() {
                     self.infer_expr(init);
                 }
@@ -217,6 +298,7 @@ impl InferenceContext<'_> {
                     self.infer_expr(iteration);
                 }
                 body.map(|idx| self.infer_expr(&idx));
+                self.loops.pop();
                 None
             }
             Expr::Condition {
@@ -232,13 +314,14 @@ impl InferenceContext<'_> {
                 None
             }
             Expr::Switch { condition, cases } => {
-                self.infer_expr(condition);
+                let ty = self.infer_expr(condition);
                 for case in cases.iter() {
                     for value in case.values() {
                         self.infer_expr(value);
                     }
                     self.infer_expr(&case.body());
                 }
+                self.check_switch(*expr, ty, cases);
                 None
             }
             Expr::NamedArg { name, value } => {
@@ -300,6 +383,7 @@ impl InferenceContext<'_> {
             }
             Expr::ViewAs { operand, type_ref } => {
                 let _ = self.infer_expr(operand);
+                self.check_deprecated_type(*expr, type_ref);
                 Some(type_ref.clone())
             }
             Expr::Literal(lit) => {
@@ -355,6 +439,10 @@ impl InferenceContext<'_> {
             Expr::Ident(name) => {
                 let name: String = name.clone().into();
                 let res = self.resolver.resolve_ident(&name)?; // TODO: Should we emit a diagnostic here?
+                if let ValueNs::LocalId((_, _, binding)) = &res {
+                    self.check_loop_variable(*expr, *binding);
+                }
+                self.check_deprecated(*expr, &res);
                 match &res {
                     ValueNs::GlobalId(it) => {
                         let item_tree = self.db.file_item_tree(it.file_id);
@@ -457,7 +545,20 @@ impl InferenceContext<'_> {
                 ty
             }
             Expr::Decl(bindings) => {
+                let mut previous_type = None;
                 for binding in bindings.iter() {
+                    if let Expr::Binding {
+                        type_ref: Some(type_ref),
+                        ..
+                    } = &self.body[*binding]
+                    {
+                        // The bindings of a declaration usually share the same type node, only
+                        // report it once.
+                        if previous_type != Some(type_ref) {
+                            self.check_deprecated_type(*binding, type_ref);
+                        }
+                        previous_type = Some(type_ref);
+                    }
                     self.infer_expr(binding);
                 }
                 None
@@ -467,6 +568,7 @@ impl InferenceContext<'_> {
                 type_ref,
                 ..
             } => {
+                self.check_shadowing(*expr);
                 if let Some(initializer) = initializer {
                     self.infer_expr(initializer);
                 }
@@ -477,6 +579,15 @@ impl InferenceContext<'_> {
     }
 
     pub(crate) fn collect_fn(&mut self, _func: FunctionId) {
+        for (_, param) in self.body.params.iter() {
+            if let Expr::Binding {
+                type_ref: Some(type_ref),
+                ..
+            } = &self.body[*param]
+            {
+                self.check_deprecated_type(*param, type_ref);
+            }
+        }
         if let Some(id) = self.body.body_expr {
             self.infer_expr(&id);
         }
@@ -501,6 +612,11 @@ impl InferenceContext<'_> {
                     self.result
                         .method_resolutions
                         .insert(*expr, *constructor_id);
+                    if self.db.function_data(*constructor_id).deprecated {
+                        self.push_deprecated_usage(*expr, FileDefId::from(*constructor_id));
+                    } else {
+                        self.check_deprecated(*expr, &ValueNs::MethodmapId(it));
+                    }
                     TypeRef::Name(name.clone()).into()
                 } else {
                     self.result
@@ -542,7 +658,7 @@ impl InferenceContext<'_> {
                 let data = self.db.enum_struct_data(it.value);
                 if let Some(item) = data.items(name) {
                     match data.item(item) {
-                        EnumStructItemData::Field(_) => {
+                        EnumStructItemData::Field(field_data) => {
                             let field_id = FieldId {
                                 parent: it.value,
                                 local_id: item,
@@ -550,6 +666,9 @@ impl InferenceContext<'_> {
                             self.result
                                 .attribute_resolutions
                                 .insert(*receiver, field_id.into());
+                            if field_data.deprecated {
+                                self.push_deprecated_usage(*receiver, AttributeId::from(field_id));
+                            }
                             return Some(data.field_type(item)?.clone());
                         }
                         EnumStructItemData::Method(_) => {
@@ -574,6 +693,12 @@ impl InferenceContext<'_> {
                             self.result
                                 .attribute_resolutions
                                 .insert(*receiver, property_data.id.into());
+                            if property_data.deprecated {
+                                self.push_deprecated_usage(
+                                    *receiver,
+                                    AttributeId::from(property_data.id),
+                                );
+                            }
                             let property = property_data.id.lookup(self.db);
                             let item_tree = property.id.item_tree(self.db);
                             return Some(item_tree[property.id.value].type_ref.clone());
@@ -638,6 +763,7 @@ impl InferenceContext<'_> {
                         }
                         EnumStructItemData::Method(method) => {
                             self.result.method_resolutions.insert(*receiver, *method);
+                            self.check_deprecated_method(*receiver, *method);
                             if let Some(current_call) = self.current_call_mut() {
                                 if current_call.id.is_none() {
                                     let res = InFile::new(it.file_id, *method);
@@ -671,6 +797,7 @@ impl InferenceContext<'_> {
                         | MethodmapItemData::Constructor(method)
                         | MethodmapItemData::Destructor(method) => {
                             self.result.method_resolutions.insert(*receiver, *method);
+                            self.check_deprecated_method(*receiver, *method);
                             let function = method.lookup(self.db);
                             let item_tree = function.id.item_tree(self.db);
                             return item_tree[function.id.value].ret_type.clone();
@@ -691,4 +818,372 @@ impl InferenceContext<'_> {
 
         None
     }
+
+    /// Reports the usage of `res` by `expr` if it resolves to a deprecated item.
+    fn check_deprecated(&mut self, expr: ExprId, res: &ValueNs) {
+        let db = self.db;
+        let id = match res {
+            ValueNs::FunctionId(it) => it
+                .iter()
+                .find(|it| db.function_data(it.value).deprecated)
+                .map(|it| FileDefId::from(it.value)),
+            ValueNs::EnumStructId(it) => db
+                .enum_struct_data(it.value)
+                .deprecated
+                .then_some(FileDefId::EnumStructId(it.value)),
+            ValueNs::MethodmapId(it) => db
+                .methodmap_data(it.value)
+                .deprecated
+                .then_some(FileDefId::MethodmapId(it.value)),
+            ValueNs::EnumId(it) => db
+                .enum_data(it.value)
+                .deprecated
+                .then_some(FileDefId::EnumId(it.value)),
+            ValueNs::VariantId(it) => db
+                .variant_data(it.value)
+                .deprecated
+                .then_some(FileDefId::VariantId(it.value)),
+            ValueNs::TypedefId(it) => db
+                .typedef_data(it.value)
+                .deprecated
+                .then_some(FileDefId::TypedefId(it.value)),
+            ValueNs::TypesetId(it) => db
+                .typeset_data(it.value)
+                .deprecated
+                .then_some(FileDefId::TypesetId(it.value)),
+            ValueNs::FunctagId(it) => db
+                .functag_data(it.value)
+                .deprecated
+                .then_some(FileDefId::FunctagId(it.value)),
+            ValueNs::FuncenumId(it) => db
+                .funcenum_data(it.value)
+                .deprecated
+                .then_some(FileDefId::FuncenumId(it.value)),
+            ValueNs::StructId(it) => db
+                .struct_data(it.value)
+                .deprecated
+                .then_some(FileDefId::StructId(it.value)),
+            // Macros are expanded before the body is lowered.
+            ValueNs::LocalId(_) | ValueNs::GlobalId(_) | ValueNs::MacroId(_) => None,
+        };
+        if let Some(id) = id {
+            self.push_deprecated_usage(expr, id);
+        }
+    }
+
+    /// Reports the call of `method` by `expr` if it is deprecated.
+    fn check_deprecated_method(&mut self, expr: ExprId, method: FunctionId) {
+        if self.db.function_data(method).deprecated {
+            self.push_deprecated_usage(expr, FileDefId::from(method));
+        }
+    }
+
+    /// Reports the declaration or cast `expr` if its type is deprecated.
+    fn check_deprecated_type(&mut self, expr: ExprId, type_ref: &TypeRef) {
+        let name = match type_ref {
+            TypeRef::Name(name) | TypeRef::OldName(name) => name,
+            TypeRef::Array((type_ref, _)) => return self.check_deprecated_type(expr, type_ref),
+            _ => return,
+        };
+        if let Some(res) = self.resolver.resolve_ident(&String::from(name.clone())) {
+            self.check_deprecated(expr, &res);
+        }
+    }
+
+    fn push_deprecated_usage(&mut self, expr: ExprId, item: impl Into<DeprecatedItem>) {
+        self.result
+            .diagnostics
+            .push(InferenceDiagnostic::DeprecatedUsage {
+                expr,
+                item: item.into(),
+            });
+    }
+
+    /// Checks the `case` labels of a `switch` statement, and records the enum it is over.
+    fn check_switch(&mut self, expr: ExprId, ty: Option<TypeRef>, cases: &[SwitchCase]) {
+        let mut values: FxHashMap<i32, ExprId> = FxHashMap::default();
+        let mut variants = FxHashSet::default();
+        for label in cases.iter().flat_map(|case| case.values()) {
+            if !self.is_constant(*label) {
+                self.result
+                    .diagnostics
+                    .push(InferenceDiagnostic::NonConstantCaseLabel { expr: *label });
+                continue;
+            }
+            if let Some(variant) = self.resolve_variant(*label) {
+                variants.insert(variant);
+            }
+            let Some(value) =
+                consteval::eval_expr(self.body, *label, &|name| self.variant_value(name))
+            else {
+                continue;
+            };
+            match values.get(&value) {
+                Some(first) => {
+                    self.result
+                        .diagnostics
+                        .push(InferenceDiagnostic::DuplicateCaseValue {
+                            expr: *label,
+                            first: *first,
+                            value,
+                        })
+                }
+                None => {
+                    values.insert(value, *label);
+                }
+            }
+        }
+
+        // Fallback to the enum of the labels if the type of the condition is unknown.
+        let enum_id = match ty {
+            Some(TypeRef::Name(name)) => match self.resolver.resolve_ident(&String::from(name)) {
+                Some(ValueNs::EnumId(it)) => Some(it.value),
+                _ => None,
+            },
+            _ => None,
+        }
+        .or_else(|| {
+            let mut enums = variants.iter().map(|it| variant_enum(self.db, *it));
+            let first = enums.next()??;
+            enums.all(|it| it == Some(first)).then_some(first)
+        });
+        let Some(enum_id) = enum_id else {
+            return;
+        };
+        let missing_variants = enum_id
+            .variants(self.db)
+            .into_iter()
+            .filter(|it| !variants.contains(it))
+            .collect::<Vec<_>>();
+        let has_default = cases.iter().any(|case| case.values().is_empty());
+        if !has_default && !missing_variants.is_empty() {
+            self.result
+                .diagnostics
+                .push(InferenceDiagnostic::MissingSwitchCases {
+                    expr,
+                    enum_name: self.db.enum_data(enum_id).name.clone(),
+                    missing: missing_variants
+                        .iter()
+                        .map(|it| self.db.variant_data(*it).name.clone())
+                        .collect(),
+                });
+        }
+        self.result.switch_resolutions.insert(
+            expr,
+            SwitchResolution {
+                enum_id,
+                missing_variants,
+                has_default,
+            },
+        );
+    }
+
+    /// Whether the expression can be evaluated at compile time.
+    ///
+    /// Globals are constant only if they are declared `const`. Identifiers which cannot be
+    /// resolved are assumed to be constant, they are reported elsewhere.
+    fn is_constant(&self, expr: ExprId) -> bool {
+        match &self.body[expr] {
+            Expr::Ident(name) => match self.resolver.resolve_ident(&String::from(name.clone())) {
+                Some(ValueNs::LocalId(_) | ValueNs::FunctionId(_)) => false,
+                Some(ValueNs::GlobalId(it)) => {
+                    let item_tree = self.db.file_item_tree(it.file_id);
+                    item_tree[it.value.lookup(self.db).value].is_const
+                }
+                _ => true,
+            },
+            // `sizeof` is evaluated at compile time, even for a local array.
+            Expr::UnaryOp {
+                op: Some(TSKind::sizeof_expression),
+                ..
+            } => true,
+            Expr::UnaryOp { operand, op } => {
+                !matches!(op, Some(TSKind::anon_PLUS_PLUS_ | TSKind::anon_DASH_DASH_))
+                    && self.is_constant(*operand)
+            }
+            Expr::BinaryOp { lhs, rhs, op } => {
+                !matches!(
+                    op,
+                    Some(
+                        TSKind::anon_EQ
+                            | TSKind::anon_PLUS_EQ
+                            | TSKind::anon_DASH_EQ
+                            | TSKind::anon_STAR_EQ
+                            | TSKind::anon_SLASH_EQ
+                            | TSKind::anon_PIPE_EQ
+                            | TSKind::anon_AMP_EQ
+                            | TSKind::anon_CARET_EQ
+                            | TSKind::anon_TILDE_EQ
+                            | TSKind::anon_LT_LT_EQ
+                            | TSKind::anon_GT_GT_EQ
+                            | TSKind::anon_GT_GT_GT_EQ_
+                            | TSKind::anon_PERCENT_EQ_
+                    )
+                ) && self.is_constant(*lhs)
+                    && self.is_constant(*rhs)
+            }
+            Expr::TernaryOp {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.is_constant(*condition)
+                    && self.is_constant(*then_branch)
+                    && self.is_constant(*else_branch)
+            }
+            Expr::ViewAs { operand, .. } => self.is_constant(*operand),
+            Expr::Call { .. }
+            | Expr::MethodCall { .. }
+            | Expr::FieldAccess { .. }
+            | Expr::ArrayIndexedAccess { .. }
+            | Expr::New { .. }
+            | Expr::This => false,
+            _ => true,
+        }
+    }
+
+    fn resolve_variant(&self, expr: ExprId) -> Option<VariantId> {
+        let Expr::Ident(name) = &self.body[expr] else {
+            return None;
+        };
+        match self.resolver.resolve_ident(&String::from(name.clone()))? {
+            ValueNs::VariantId(it) => Some(it.value),
+            _ => None,
+        }
+    }
+
+    fn variant_value(&self, name: &Name) -> Option<i32> {
+        match self.resolver.resolve_ident(&String::from(name.clone()))? {
+            ValueNs::VariantId(it) => self.db.variant_data(it.value).value,
+            _ => None,
+        }
+    }
+
+    /// Reports the `binding` if it shadows a parameter, a global or a local of an enclosing
+    /// block, or a local declared before its loop for a loop variable (spcomp warning 219).
+    fn check_shadowing(&mut self, binding: ExprId) {
+        let Expr::Binding { ident_id, .. } = &self.body[binding] else {
+            return;
+        };
+        let name = self.body.idents[*ident_id].clone();
+        if let Some(bindings) = self.block_bindings.last_mut() {
+            bindings.push(binding);
+        }
+        if let Some(other) = self.shadowed_by_loop_binding(binding, &name) {
+            self.shadowing_loop_bindings.insert(binding);
+            self.result
+                .diagnostics
+                .push(InferenceDiagnostic::ShadowedVariable {
+                    expr: binding,
+                    name,
+                    shadowed: ShadowedVariable::Local(other),
+                });
+            return;
+        }
+        let shadowed = match self
+            .resolver
+            .resolve_ident_in_enclosing_scopes(&String::from(name.clone()))
+        {
+            Some(ValueNs::LocalId((_, _, other))) => {
+                if self.body.params.iter().any(|(_, param)| *param == other) {
+                    ShadowedVariable::Parameter(other)
+                } else if other < binding && self.is_visible(other) {
+                    ShadowedVariable::Local(other)
+                } else {
+                    // The other local is declared after the binding, or in a loop which has ended.
+                    return;
+                }
+            }
+            Some(ValueNs::GlobalId(it)) => ShadowedVariable::Global(it),
+            _ => return,
+        };
+        self.result
+            .diagnostics
+            .push(InferenceDiagnostic::ShadowedVariable {
+                expr: binding,
+                name,
+                shadowed,
+            });
+    }
+
+    /// Returns the local of the enclosing block which the variable `binding`, declared in the
+    /// initialization of a loop, shadows.
+    ///
+    /// The bindings of a loop belong to the scope which encloses the loop, so the locals
+    /// declared before the loop in the same block are not in an enclosing scope.
+    fn shadowed_by_loop_binding(&self, binding: ExprId, name: &Name) -> Option<ExprId> {
+        let loop_expr = self.loop_bindings.get(&binding)?;
+        self.block_bindings
+            .last()?
+            .iter()
+            .copied()
+            .filter(|other| *other < binding && self.is_visible(*other))
+            .filter(|other| self.loop_bindings.get(other) != Some(loop_expr))
+            .find(|other| {
+                matches!(
+                    &self.body[*other],
+                    Expr::Binding { ident_id, .. } if self.body.idents[*ident_id] == *name
+                )
+            })
+    }
+
+    /// Reports the identifier `expr` if it refers to the variable of a loop after that loop.
+    fn check_loop_variable(&mut self, expr: ExprId, binding: ExprId) {
+        if binding > expr
+            || self.is_visible(binding)
+            || self.shadowing_loop_bindings.contains(&binding)
+        {
+            return;
+        }
+        let Expr::Binding { ident_id, .. } = &self.body[binding] else {
+            return;
+        };
+        self.result
+            .diagnostics
+            .push(InferenceDiagnostic::LoopVariableUsedAfterLoop {
+                expr,
+                name: self.body.idents[*ident_id].clone(),
+                binding,
+            });
+    }
+
+    /// Whether a local binding is still in scope.
+    ///
+    /// The bindings of the initialization of a loop are only in scope inside of that loop,
+    /// even though they belong to the scope which encloses the loop.
+    fn is_visible(&self, binding: ExprId) -> bool {
+        self.loop_bindings
+            .get(&binding)
+            .map_or(true, |loop_expr| self.loops.contains(loop_expr))
+    }
+}
+
+fn variant_enum(db: &dyn DefDatabase, variant: VariantId) -> Option<EnumId> {
+    match variant.lookup(db).container {
+        ItemContainerId::EnumId(it) => Some(it),
+        _ => None,
+    }
+}
+
+/// Maps the bindings declared in the initialization of the loops of the body to their loop.
+fn loop_bindings(body: &Body) -> FxHashMap<ExprId, ExprId> {
+    let mut res = FxHashMap::default();
+    for (loop_expr, expr) in body.exprs.iter() {
+        let Expr::Loop { initialization, .. } = expr else {
+            continue;
+        };
+        for init in initialization.iter() {
+            match &body[*init] {
+                Expr::Decl(bindings) => {
+                    res.extend(bindings.iter().map(|binding| (*binding, loop_expr)));
+                }
+                Expr::Binding { .. } => {
+                    res.insert(*init, loop_expr);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    res
 }
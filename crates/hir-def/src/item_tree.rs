@@ -109,6 +109,19 @@ impl RawVisibilityId {
     }
 }
 
+/// Returns whether the variable declaration `node` has a `const` storage class.
+pub(crate) fn is_const_declaration(node: &tree_sitter::Node) -> bool {
+    let is_const = |node: tree_sitter::Node| {
+        TSKind::from(node) == TSKind::anon_const
+            || node
+                .children(&mut node.walk())
+                .any(|child| TSKind::from(child) == TSKind::anon_const)
+    };
+    node.child_by_field_name("storage_class")
+        .map_or(false, is_const)
+        || is_const(*node)
+}
+
 /// The item tree of a source file.
 #[derive(Debug, Default, Eq, PartialEq)]
 pub struct ItemTree {
@@ -147,6 +160,7 @@ impl ItemTree {
                                     ),
                                     visibility: RawVisibilityId::NONE,
                                     type_ref: type_ref.clone(),
+                                    is_const: is_const_declaration(&child),
                                     ast_id: ast_id_map.ast_id_of(&sub_child),
                                 };
                                 let id = item_tree.data_mut().variables.alloc(res);
@@ -167,6 +181,7 @@ impl ItemTree {
                                 name: Name::from(name_node.utf8_text(source.as_bytes()).unwrap()),
                                 visibility: RawVisibilityId::NONE,
                                 type_ref: type_ref.clone(),
+                                is_const: is_const_declaration(&child),
                                 ast_id: ast_id_map.ast_id_of(&sub_child),
                             };
                             let id = item_tree.data_mut().variables.alloc(res);
@@ -297,6 +312,7 @@ pub struct Variable {
     pub name: Name,
     pub visibility: RawVisibilityId,
     pub type_ref: Option<TypeRef>,
+    pub is_const: bool,
     pub ast_id: AstId,
 }
 
@@ -438,6 +454,8 @@ pub struct Enum {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Variant {
     pub name: Name,
+    /// Value of the variant, if it can be evaluated.
+    pub value: Option<i32>,
     pub ast_id: AstId,
     pub deprecated: bool,
 }
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use base_db::Tree;
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
 use la_arena::{Idx, IdxRange, RawIdx};
 use lazy_static::lazy_static;
 use syntax::TSKind;
@@ -9,13 +9,14 @@ use tree_sitter::QueryCursor;
 use vfs::FileId;
 
 use crate::{
-    ast_id_map::AstIdMap, hir::type_ref::TypeRef, item_tree::Macro, DefDatabase, FileItem, Name,
+    ast_id_map::AstIdMap, consteval, hir::type_ref::TypeRef, item_tree::Macro, DefDatabase,
+    FileItem, Name,
 };
 
 use super::{
-    Enum, EnumStruct, EnumStructItemId, Field, Funcenum, Functag, Function, FunctionKind, ItemTree,
-    Methodmap, MethodmapItemId, Param, Property, RawVisibilityId, SpecialMethod, Struct,
-    StructField, Typedef, Typeset, Variable, Variant,
+    is_const_declaration, Enum, EnumStruct, EnumStructItemId, Field, Funcenum, Functag, Function,
+    FunctionKind, ItemTree, Methodmap, MethodmapItemId, Param, Property, RawVisibilityId,
+    SpecialMethod, Struct, StructField, Typedef, Typeset, Variable, Variant,
 };
 
 pub(super) struct Ctx<'db> {
@@ -141,6 +142,7 @@ impl<'db> Ctx<'db> {
                 name: Name::from(name_node.utf8_text(self.source.as_bytes()).unwrap()),
                 visibility,
                 type_ref: type_ref.clone(),
+                is_const: is_const_declaration(node),
                 ast_id: self.source_ast_id_map.ast_id_of(node),
             };
             let id = self.tree.data_mut().variables.alloc(res);
@@ -161,6 +163,7 @@ impl<'db> Ctx<'db> {
                         name: Name::from(name_node.utf8_text(self.source.as_bytes()).unwrap()),
                         visibility,
                         type_ref: type_ref.clone(),
+                        is_const: is_const_declaration(node),
                         ast_id: self.source_ast_id_map.ast_id_of(&child),
                     };
                     let id = self.tree.data_mut().variables.alloc(res);
@@ -182,6 +185,7 @@ impl<'db> Ctx<'db> {
                     name: Name::from(name_node.utf8_text(self.source.as_bytes()).unwrap()),
                     visibility,
                     type_ref: type_ref.clone(),
+                    is_const: is_const_declaration(node),
                     ast_id: self.source_ast_id_map.ast_id_of(&child),
                 };
                 let id = self.tree.data_mut().variables.alloc(res);
@@ -196,7 +200,13 @@ impl<'db> Ctx<'db> {
 
     fn lower_enum(&mut self, node: &tree_sitter::Node) {
         let start_idx = self.next_variant_idx();
+        // Enums with a custom increment, such as `enum Flags (<<= 1)`, are not evaluated.
+        let custom_increment = node
+            .children(&mut node.walk())
+            .any(|child| TSKind::from(child) == TSKind::anon_LPAREN);
         if let Some(entries_node) = node.child_by_field_name("entries") {
+            let mut values: FxHashMap<String, i32> = FxHashMap::default();
+            let mut next_value = Some(0);
             entries_node
                 .children(&mut entries_node.walk())
                 .filter(|e| TSKind::from(e) == TSKind::enum_entry)
@@ -204,10 +214,24 @@ impl<'db> Ctx<'db> {
                     let Some(variant_name_node) = e.child_by_field_name("name") else {
                         return;
                     };
-                    let name =
-                        Name::from(variant_name_node.utf8_text(self.source.as_bytes()).unwrap());
+                    let text = variant_name_node.utf8_text(self.source.as_bytes()).unwrap();
+                    let value = match e.child_by_field_name("value") {
+                        Some(value_node) => {
+                            consteval::eval_node(&value_node, &self.source, &|name| {
+                                values.get(name).copied()
+                            })
+                        }
+                        None => next_value,
+                    };
+                    next_value = value
+                        .filter(|_| !custom_increment)
+                        .and_then(|it| it.checked_add(1));
+                    if let Some(value) = value {
+                        values.insert(text.to_string(), value);
+                    }
                     let res = Variant {
-                        name,
+                        name: Name::from(text),
+                        value,
                         ast_id: self.source_ast_id_map.ast_id_of(&e),
                         deprecated: self.is_deprecated(&e),
                     };
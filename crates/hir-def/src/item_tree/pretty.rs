@@ -80,6 +80,7 @@ impl<'a> Printer<'a> {
             name,
             visibility,
             type_ref,
+            is_const,
             ast_id,
         } = &self.tree[*idx];
         self.push(format!("// {}", ast_id).as_str());
@@ -88,6 +89,9 @@ impl<'a> Printer<'a> {
             self.push(&visibility.to_string());
             self.push(" ");
         }
+        if *is_const {
+            self.push("const ");
+        }
         if let Some(type_ref) = type_ref {
             self.push(&type_ref.to_string());
             self.push(" ");
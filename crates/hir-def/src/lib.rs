@@ -15,6 +15,7 @@ use vfs::FileId;
 mod ast_id_map;
 pub mod body;
 pub mod child_by_source;
+mod consteval;
 mod data;
 pub mod db;
 mod diagnostics;
@@ -26,16 +27,20 @@ pub mod resolver;
 pub mod src;
 
 pub use ast_id_map::NodePtr;
-pub use data::{MethodmapExtension, PropertyItem};
+pub use data::{EnumStructItemData, MethodmapExtension, MethodmapItemData, PropertyItem};
 pub use db::resolve_include_node;
 pub use db::DefDatabase;
 pub use db::{BlockDefMapQuery, BlockItemTreeQuery, BodyQuery, FileDefMapQuery, FileItemTreeQuery};
 pub use diagnostics::DefDiagnostic;
-pub use hir::type_ref::type_string_from_node;
+pub use hir::type_ref::{type_string_from_node, TypeRef};
 pub use hir::ExprId;
-pub use infer::{AttributeId, ConstructorDiagnosticKind, InferenceDiagnostic, InferenceResult};
+pub use infer::{
+    AttributeId, ConstructorDiagnosticKind, DeprecatedItem, InferenceDiagnostic, InferenceResult,
+    ShadowedVariable, SwitchResolution,
+};
 pub use item_tree::{
-    print_item_tree, FileItem, FunctionKind, Name, RawVisibilityId, SpecialMethod,
+    print_item_tree, EnumStructItemId, FileItem, FunctionKind, MethodmapItemId, Name,
+    RawVisibilityId, SpecialMethod,
 };
 
 trait Intern {
@@ -213,6 +218,25 @@ type EnumLoc = AssocItemLoc<Enum>;
 impl_intern!(EnumId, EnumLoc, intern_enum, lookup_intern_enum);
 impl_serde!(EnumId);
 
+impl EnumId {
+    /// Returns the variants of the enum, in their order of declaration.
+    pub fn variants(self, db: &dyn DefDatabase) -> Vec<VariantId> {
+        let loc = self.lookup(db).id;
+        let item_tree = loc.item_tree(db);
+        item_tree[loc.value]
+            .variants
+            .clone()
+            .map(|idx| {
+                VariantLoc {
+                    container: self.into(),
+                    id: ItemTreeId::new(loc.tree_id(), idx),
+                }
+                .intern(db)
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct VariantId(salsa::InternId);
 type VariantLoc = AssocItemLoc<Variant>;
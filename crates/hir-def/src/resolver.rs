@@ -153,6 +153,20 @@ impl Resolver {
         None
     }
 
+    /// Resolves `name` in the scopes which enclose the innermost local scope, i.e. the
+    /// variable a declaration of the innermost scope would shadow.
+    pub fn resolve_ident_in_enclosing_scopes(&self, name: &str) -> Option<ValueNs> {
+        let innermost = self
+            .scopes
+            .iter()
+            .rposition(|scope| matches!(scope, Scope::Expr(_)))?;
+        Resolver {
+            scopes: self.scopes[..innermost].to_vec(),
+            file_id: self.file_id,
+        }
+        .resolve_ident(name)
+    }
+
     /// `expr_id` is required to be an expression id that comes after the top level expression scope in the given resolver
     #[must_use]
     pub fn update_to_inner_scope(
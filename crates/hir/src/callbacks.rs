@@ -0,0 +1,89 @@
+//! Index of the functions passed as callbacks in the calls of a file.
+//!
+//! A callback is a function passed by name where a typedef, typeset, functag or funcenum is
+//! expected. The index is computed once per file, and used to find the implementations of
+//! the callback types.
+
+use std::sync::Arc;
+
+use syntax::TSKind;
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::{db::HirDatabase, semantics::SemanticsImpl, DefResolution, Function};
+
+/// Returns the functions passed as arguments of the calls of the file, with the callback
+/// type expected by their parameter.
+pub(crate) fn file_callbacks_query(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+) -> Arc<Vec<(DefResolution, Function)>> {
+    let sema = SemanticsImpl::new(db);
+    let tree = db.parse(file_id);
+    let mut res = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if TSKind::from(node) == TSKind::call_arguments {
+            collect_callbacks(&sema, file_id, node, &mut res);
+        }
+        stack.extend(node.children(&mut node.walk()));
+    }
+
+    Arc::new(res)
+}
+
+fn collect_callbacks(
+    sema: &SemanticsImpl,
+    file_id: FileId,
+    call_arguments: Node,
+    acc: &mut Vec<(DefResolution, Function)>,
+) {
+    let args = call_arguments
+        .named_children(&mut call_arguments.walk())
+        .filter(|n| TSKind::from(n) != TSKind::comment)
+        .collect::<Vec<_>>();
+    // Callbacks are passed by name, avoid resolving the other calls.
+    if !args
+        .iter()
+        .any(|arg| TSKind::from(arg) == TSKind::identifier)
+    {
+        return;
+    }
+    let Some(DefResolution::Function(function)) =
+        callee(call_arguments).and_then(|callee| sema.find_def(file_id, &callee))
+    else {
+        return;
+    };
+    for (idx, arg) in args.iter().enumerate() {
+        if TSKind::from(arg) != TSKind::identifier {
+            continue;
+        }
+        let Some(type_def) = function.parameter_type_def(sema.db, idx) else {
+            continue;
+        };
+        if !matches!(
+            type_def,
+            DefResolution::Typedef(_)
+                | DefResolution::Typeset(_)
+                | DefResolution::Functag(_)
+                | DefResolution::Funcenum(_)
+        ) {
+            continue;
+        }
+        if let Some(DefResolution::Function(callback)) = sema.find_def(file_id, arg) {
+            if !acc.contains(&(type_def.clone(), callback)) {
+                acc.push((type_def, callback));
+            }
+        }
+    }
+}
+
+/// Returns the node of the name of the called function of a call.
+fn callee(call_arguments: Node) -> Option<Node> {
+    let function = call_arguments.prev_named_sibling()?;
+    match TSKind::from(function) {
+        TSKind::identifier => Some(function),
+        TSKind::field_access => function.child_by_field_name("field"),
+        _ => None,
+    }
+}
@@ -0,0 +1,87 @@
+//! Diagnostics computed from the control-flow graph of a body.
+
+use hir_def::DefDatabase;
+use syntax::utils::ts_range_to_lsp_range;
+
+use crate::{
+    db::HirDatabase, AnyDiagnostic, ConstantCondition, DefWithBody, InfiniteLoop, MissingReturn,
+    UnreachableCode,
+};
+
+pub(crate) fn control_flow_diagnostics(
+    db: &dyn HirDatabase,
+    def: DefWithBody,
+    acc: &mut Vec<AnyDiagnostic>,
+) {
+    let DefWithBody::Function(function) = def else {
+        return;
+    };
+    let (body, source_map) = db.body_with_source_map(def.into());
+    let Some(body_expr) = body.body_expr else {
+        return;
+    };
+    let Some(body_ptr) = source_map.expr_source(body_expr) else {
+        return;
+    };
+    let cfg = db.control_flow_graph(def.into());
+    let tree = db.parse(body_ptr.file_id);
+
+    // Functions without a return type are only expected to return a value if they return one
+    // somewhere.
+    let returns_value = match function.type_ref(db) {
+        Some(type_ref) => type_ref != "void",
+        None => !cfg.returned_values().is_empty(),
+    };
+    if returns_value && cfg.can_fall_off() {
+        // Report the diagnostic on the closing brace of the function.
+        let range = body_ptr.value.to_node(&tree).map(|node| {
+            let last = node
+                .child(node.child_count().saturating_sub(1))
+                .unwrap_or(node);
+            ts_range_to_lsp_range(&last.range())
+        });
+        if let Some(range) = range {
+            acc.push(
+                MissingReturn {
+                    range,
+                    name: function.name(db),
+                }
+                .into(),
+            );
+        }
+    }
+
+    for stmt in cfg.unreachable_statements() {
+        if let Some(expr) = source_map.expr_source(stmt) {
+            acc.push(UnreachableCode { expr }.into());
+        }
+    }
+
+    for loop_expr in cfg.infinite_loops() {
+        let Some(ptr) = source_map.expr_source(loop_expr) else {
+            continue;
+        };
+        // Only highlight the keyword of the loop, not its whole body.
+        let Some(keyword) = ptr.value.to_node(&tree).and_then(|node| node.child(0)) else {
+            continue;
+        };
+        acc.push(
+            InfiniteLoop {
+                range: ts_range_to_lsp_range(&keyword.range()),
+            }
+            .into(),
+        );
+    }
+
+    for (condition, value) in cfg.constant_conditions() {
+        if let Some(expr) = source_map.expr_source(*condition) {
+            acc.push(
+                ConstantCondition {
+                    expr,
+                    value: *value,
+                }
+                .into(),
+            );
+        }
+    }
+}
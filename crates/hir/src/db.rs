@@ -1,5 +1,48 @@
+use std::sync::Arc;
+
 use base_db::Upcast;
-use hir_def::DefDatabase;
+use fxhash::{FxHashMap, FxHashSet};
+use hir_def::{DefDatabase, FileItem, Name};
+use vfs::FileId;
+
+use crate::{duplicates::ProjectDefinitions, translations::PhraseDef, DefResolution, Function};
 
 #[salsa::query_group(HirDatabaseStorage)]
-pub trait HirDatabase: DefDatabase + Upcast<dyn DefDatabase> {}
+pub trait HirDatabase: DefDatabase + Upcast<dyn DefDatabase> {
+    /// Completion data of the games, i.e. the built-in data merged with the data files
+    /// provided by the user.
+    #[salsa::input]
+    fn completion_data(&self) -> Arc<completion_data::Database<'static>>;
+
+    /// Names of the games selected by the user.
+    ///
+    /// When none of these games are in the completion data, the data of all the games is used.
+    #[salsa::input]
+    fn selected_games(&self) -> Arc<Vec<String>>;
+
+    /// Returns the phrases of all the known translation files, by name.
+    #[salsa::invoke(crate::translations::phrase_defs_query)]
+    fn phrase_defs(&self) -> Arc<FxHashMap<String, Vec<PhraseDef>>>;
+
+    /// Returns the normalized names of the translation files loaded by the project of the file.
+    #[salsa::invoke(crate::translations::loaded_translations_query)]
+    fn loaded_translations(&self, file_id: FileId) -> Arc<FxHashSet<String>>;
+
+    /// Returns the forwards declared in the project of the file, keyed by name.
+    #[salsa::invoke(crate::forwards::project_forwards_query)]
+    fn project_forwards(&self, file_id: FileId) -> Arc<FxHashMap<Name, (FileId, FileItem)>>;
+
+    /// Returns the functions passed as callbacks in the calls of the file, with the typedef,
+    /// typeset, functag or funcenum expected by their parameter.
+    #[salsa::invoke(crate::callbacks::file_callbacks_query)]
+    fn file_callbacks(&self, file_id: FileId) -> Arc<Vec<(DefResolution, Function)>>;
+
+    /// Returns the named top-level definitions of the file.
+    #[salsa::invoke(crate::file_definitions_query)]
+    fn file_definitions(&self, file_id: FileId) -> Arc<Vec<(Name, DefResolution)>>;
+
+    /// Returns the top-level definitions and macros of the project whose main file is `root`,
+    /// in include order.
+    #[salsa::invoke(crate::duplicates::project_definitions_query)]
+    fn project_definitions(&self, root: FileId) -> Arc<ProjectDefinitions>;
+}
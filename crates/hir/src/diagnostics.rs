@@ -5,6 +5,9 @@
 //! be expressed in terms of hir types themselves.
 
 use hir_def::{InFile, Name, NodePtr};
+use vfs::FileId;
+
+use crate::DefResolution;
 
 macro_rules! diagnostics {
     ($($diag:ident,)*) => {
@@ -35,6 +38,23 @@ diagnostics![
     UnresolvedMacro,
     InactiveCode,
     InvalidUseOfThis,
+    UnresolvedPhrase,
+    PhraseNotLoaded,
+    UnresolvedEventField,
+    IncorrectEventFieldAccessor,
+    ForwardSignatureMismatch,
+    DuplicateDefinition,
+    MissingReturn,
+    UnreachableCode,
+    InfiniteLoop,
+    ConstantCondition,
+    DuplicateCaseValue,
+    NonConstantCaseLabel,
+    MissingSwitchCases,
+    ShadowedVariable,
+    LoopVariableUsedAfterLoop,
+    DeprecatedUsage,
+    UnknownDocParam,
 ];
 
 #[derive(Debug)]
@@ -115,3 +135,139 @@ pub struct UnresolvedMacro {
 pub struct InactiveCode {
     pub range: lsp_types::Range,
 }
+
+#[derive(Debug)]
+pub struct UnresolvedPhrase {
+    pub range: lsp_types::Range,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct PhraseNotLoaded {
+    pub range: lsp_types::Range,
+    pub name: String,
+    pub translation_files: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedEventField {
+    pub range: lsp_types::Range,
+    pub field: String,
+    pub events: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct IncorrectEventFieldAccessor {
+    pub range: lsp_types::Range,
+    pub field: String,
+    pub field_type: String,
+    pub accessor: String,
+}
+
+#[derive(Debug)]
+pub struct ForwardSignatureMismatch {
+    pub range: lsp_types::Range,
+    pub signature_range: lsp_types::Range,
+    pub name: String,
+    pub forward_signature: String,
+    /// Signature of the function rewritten from the forward, with the names of the
+    /// parameters of the function.
+    pub fixed_signature: String,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub struct DuplicateDefinition {
+    pub range: lsp_types::Range,
+    pub name: String,
+    pub kind: DuplicateDefinitionKind,
+    pub other_file_id: FileId,
+    pub other_range: lsp_types::Range,
+}
+
+#[derive(Debug)]
+pub enum DuplicateDefinitionKind {
+    Item,
+    Macro,
+    MethodmapMember(String),
+    EnumStructMember(String),
+}
+
+#[derive(Debug)]
+pub struct MissingReturn {
+    pub range: lsp_types::Range,
+    pub name: Name,
+}
+
+#[derive(Debug)]
+pub struct UnreachableCode {
+    pub expr: InFile<NodePtr>,
+}
+
+#[derive(Debug)]
+pub struct InfiniteLoop {
+    pub range: lsp_types::Range,
+}
+
+#[derive(Debug)]
+pub struct ConstantCondition {
+    pub expr: InFile<NodePtr>,
+    pub value: bool,
+}
+
+#[derive(Debug)]
+pub struct DuplicateCaseValue {
+    pub expr: InFile<NodePtr>,
+    pub first: InFile<NodePtr>,
+    pub value: i32,
+}
+
+#[derive(Debug)]
+pub struct NonConstantCaseLabel {
+    pub expr: InFile<NodePtr>,
+}
+
+#[derive(Debug)]
+pub struct MissingSwitchCases {
+    pub expr: InFile<NodePtr>,
+    pub enum_name: Name,
+    pub missing: Vec<Name>,
+}
+
+#[derive(Debug)]
+pub struct ShadowedVariable {
+    pub expr: InFile<NodePtr>,
+    pub name: Name,
+    pub kind: ShadowedVariableKind,
+    /// Declaration of the shadowed variable.
+    pub shadowed: InFile<NodePtr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowedVariableKind {
+    Parameter,
+    Local,
+    Global,
+}
+
+#[derive(Debug)]
+pub struct LoopVariableUsedAfterLoop {
+    pub expr: InFile<NodePtr>,
+    pub name: Name,
+    /// Declaration of the variable in the initialization of the loop.
+    pub binding: InFile<NodePtr>,
+}
+
+#[derive(Debug)]
+pub struct DeprecatedUsage {
+    pub expr: InFile<NodePtr>,
+    /// Deprecated item the expression uses.
+    pub def: DefResolution,
+}
+
+#[derive(Debug)]
+pub struct UnknownDocParam {
+    pub range: lsp_types::Range,
+    pub name: String,
+    pub function: String,
+}
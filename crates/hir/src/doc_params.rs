@@ -0,0 +1,167 @@
+//! Validation of the `@param` tags of the documentation of the functions.
+//!
+//! A tag whose name does not match any parameter is usually left over after a parameter was
+//! renamed or removed.
+
+use lsp_types::{Position, Range};
+use syntax::TSKind;
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::{db::HirDatabase, UnknownDocParam};
+
+/// Returns the `@param` tags of the functions and methods of the file whose name does not
+/// match any of their parameters.
+pub(crate) fn unknown_doc_params(db: &dyn HirDatabase, file_id: FileId) -> Vec<UnknownDocParam> {
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let mut res = Vec::new();
+    collect_unknown_doc_params(tree.root_node(), &source, &mut res);
+
+    res
+}
+
+fn collect_unknown_doc_params(node: Node, source: &str, acc: &mut Vec<UnknownDocParam>) {
+    match TSKind::from(node) {
+        TSKind::function_declaration
+        | TSKind::function_definition
+        | TSKind::enum_struct_method
+        | TSKind::methodmap_method
+        | TSKind::methodmap_method_constructor
+        | TSKind::methodmap_native
+        | TSKind::methodmap_native_constructor => {
+            check_function(node, source, acc);
+            // Functions cannot contain other functions.
+            return;
+        }
+        _ => (),
+    }
+    for child in node.children(&mut node.walk()) {
+        collect_unknown_doc_params(child, source, acc);
+    }
+}
+
+fn check_function(node: Node, source: &str, acc: &mut Vec<UnknownDocParam>) -> Option<()> {
+    let name = node
+        .child_by_field_name("name")?
+        .utf8_text(source.as_bytes())
+        .ok()?;
+    let parameters = parameter_names(node, source);
+    acc.extend(
+        doc_params(node, source)
+            .into_iter()
+            .filter(|(param, _)| !parameters.contains(param))
+            .map(|(param, range)| UnknownDocParam {
+                range,
+                name: param,
+                function: name.to_string(),
+            }),
+    );
+
+    Some(())
+}
+
+/// Returns the names of the parameters of a function node, with `...` for a rest parameter.
+fn parameter_names(node: Node, source: &str) -> Vec<String> {
+    let Some(params) = node.child_by_field_name("parameters") else {
+        return Vec::new();
+    };
+    params
+        .children(&mut params.walk())
+        .flat_map(|param| match TSKind::from(&param) {
+            TSKind::parameter_declaration => param
+                .child_by_field_name("name")
+                .and_then(|name| name.utf8_text(source.as_bytes()).ok())
+                .map(String::from),
+            TSKind::rest_parameter => Some("...".to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the names of the `@param` tags in the comments above a function node, with their
+/// range.
+fn doc_params(mut node: Node, source: &str) -> Vec<(String, Range)> {
+    let mut res = Vec::new();
+    while let Some(prev_node) = node.prev_sibling() {
+        if node
+            .range()
+            .start_point
+            .row
+            .saturating_sub(prev_node.range().end_point.row)
+            != 1
+        {
+            break;
+        }
+        match TSKind::from(prev_node) {
+            TSKind::preproc_pragma => (),
+            TSKind::comment => {
+                if let Ok(text) = prev_node.utf8_text(source.as_bytes()) {
+                    res.extend(params_in_comment(prev_node.start_position(), text));
+                }
+            }
+            _ => break,
+        }
+        node = prev_node;
+    }
+
+    res
+}
+
+/// Returns the names of the `@param` tags of a comment which starts at `start`, with their
+/// range.
+fn params_in_comment(start: tree_sitter::Point, text: &str) -> Vec<(String, Range)> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let tag = line.find("@param")? + "@param".len();
+            let rest = &line[tag..];
+            if !rest.starts_with(char::is_whitespace) {
+                // E.g. `@params`.
+                return None;
+            }
+            let rest = rest.trim_start();
+            let name = rest.split(char::is_whitespace).next()?;
+            if name.is_empty() {
+                return None;
+            }
+            let column = if i == 0 { start.column } else { 0 } + line.len() - rest.len();
+            let line = (start.row + i) as u32;
+            Some((
+                name.to_string(),
+                Range::new(
+                    Position::new(line, column as u32),
+                    Position::new(line, (column + name.len()) as u32),
+                ),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_of_block_comment() {
+        let text = "/**\n * Does something.\n *\n * @param client    Client index.\n * @params ignored\n * @param\n */";
+        assert_eq!(
+            params_in_comment(tree_sitter::Point::new(2, 4), text),
+            vec![(
+                "client".to_string(),
+                Range::new(Position::new(5, 10), Position::new(5, 16))
+            )]
+        );
+    }
+
+    #[test]
+    fn params_of_line_comment() {
+        assert_eq!(
+            params_in_comment(tree_sitter::Point::new(3, 4), "// @param value The value"),
+            vec![(
+                "value".to_string(),
+                Range::new(Position::new(3, 14), Position::new(3, 19))
+            )]
+        );
+    }
+}
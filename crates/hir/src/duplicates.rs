@@ -0,0 +1,423 @@
+//! Detection of the definitions which conflict with another definition of the project.
+//!
+//! spcomp only reports them once the project is compiled, with error 021.
+
+use std::sync::Arc;
+
+use base_db::Tree;
+use fxhash::{FxHashMap, FxHashSet};
+use hir_def::{
+    DefDatabase, EnumStructItemId, FileItem, FunctionKind, MethodmapItemId, Name, NodePtr,
+};
+use lsp_types::Position;
+use syntax::utils::ts_range_to_lsp_range;
+use vfs::FileId;
+
+use crate::{db::HirDatabase, DuplicateDefinition, DuplicateDefinitionKind};
+
+/// Kind of a top-level item, used to know which items share a namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ItemKind {
+    Function(FunctionKind),
+    Global,
+    Variant,
+    Enum,
+    Methodmap,
+    Type,
+}
+
+impl ItemKind {
+    /// Whether two items with the same name conflict.
+    ///
+    /// A function can implement a forward, and a methodmap can share the name of an enum,
+    /// such as `Handle`.
+    fn conflicts_with(self, other: Self) -> bool {
+        use ItemKind::*;
+        match (self, other) {
+            (Function(FunctionKind::Forward), Function(FunctionKind::Def))
+            | (Function(FunctionKind::Def), Function(FunctionKind::Forward)) => false,
+            (Function(_) | Global | Variant, Function(_) | Global | Variant) => true,
+            (Enum, Methodmap) | (Methodmap, Enum) => false,
+            (Enum | Methodmap | Type, Enum | Methodmap | Type) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Position of a definition in the text the compiler reads: the positions of the `#include`
+/// directives from the main file of the project to the file of the definition, followed by
+/// the position of the definition in its file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct SourceOrder(Vec<Position>);
+
+impl SourceOrder {
+    fn at(&self, position: Position) -> Self {
+        let mut res = self.0.clone();
+        res.push(position);
+        Self(res)
+    }
+}
+
+/// A definition of the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Def {
+    kind: ItemKind,
+    file_id: FileId,
+    /// Range of the name of the definition.
+    range: lsp_types::Range,
+    order: SourceOrder,
+}
+
+impl Def {
+    /// Whether `self` conflicts with `other`, and should be reported.
+    ///
+    /// Only the definition which comes last in the include order is reported.
+    fn is_duplicate_of(&self, other: &Def) -> bool {
+        other.order < self.order && self.kind.conflicts_with(other.kind)
+    }
+}
+
+/// A macro of the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MacroDef {
+    file_id: FileId,
+    /// Range of the name of the macro.
+    range: lsp_types::Range,
+    order: SourceOrder,
+    value: Option<String>,
+}
+
+/// The top-level definitions, macros and `#undef` directives of a project.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ProjectDefinitions {
+    defs: FxHashMap<Name, Vec<Def>>,
+    macros: FxHashMap<Name, Vec<MacroDef>>,
+    undefs: FxHashMap<Name, Vec<SourceOrder>>,
+}
+
+impl ProjectDefinitions {
+    /// Whether the macro is undefined between the two positions, which allows to redefine it.
+    fn is_undefined_between(&self, name: &Name, start: &SourceOrder, end: &SourceOrder) -> bool {
+        self.undefs
+            .get(name)
+            .is_some_and(|undefs| undefs.iter().any(|undef| start < undef && undef < end))
+    }
+}
+
+/// Returns the definitions of the project whose main file is `root`.
+pub(crate) fn project_definitions_query(
+    db: &dyn HirDatabase,
+    root: FileId,
+) -> Arc<ProjectDefinitions> {
+    let mut res = ProjectDefinitions::default();
+    for (file_id, prefix) in include_order(db, root) {
+        let tree = db.parse(file_id);
+        let location = |ptr: NodePtr| {
+            let range = name_range(&tree, ptr);
+            (range, prefix.at(range.start))
+        };
+        for (name, kind, ptr) in file_defs(db, file_id) {
+            let (range, order) = location(ptr);
+            res.defs.entry(name).or_default().push(Def {
+                kind,
+                file_id,
+                range,
+                order,
+            });
+        }
+        for (name, ptr) in file_macros(db, file_id) {
+            let (range, order) = location(ptr);
+            res.macros.entry(name).or_default().push(MacroDef {
+                file_id,
+                range,
+                order,
+                value: macro_value(db, file_id, ptr),
+            });
+        }
+        for (name, line) in file_undefs(db, file_id) {
+            res.undefs
+                .entry(name)
+                .or_default()
+                .push(prefix.at(Position::new(line, 0)));
+        }
+    }
+
+    Arc::new(res)
+}
+
+/// Returns the files of the project whose main file is `root`, in the order the compiler
+/// reads them, with the positions of the `#include` directives which include them first.
+fn include_order(db: &dyn HirDatabase, root: FileId) -> Vec<(FileId, SourceOrder)> {
+    fn visit(
+        db: &dyn HirDatabase,
+        file_id: FileId,
+        prefix: SourceOrder,
+        visited: &mut FxHashSet<FileId>,
+        res: &mut Vec<(FileId, SourceOrder)>,
+    ) {
+        if !visited.insert(file_id) {
+            return;
+        }
+        res.push((file_id, prefix.clone()));
+        let mut includes = db
+            .file_includes(file_id)
+            .0
+            .iter()
+            // The implicit include of `sourcemod.inc` has no range and comes first.
+            .map(|include| (include.range().unwrap_or_default().start, include.file_id()))
+            .collect::<Vec<_>>();
+        includes.sort();
+        for (position, include) in includes {
+            visit(db, include, prefix.at(position), visited, res);
+        }
+    }
+
+    let mut res = Vec::new();
+    visit(
+        db,
+        root,
+        SourceOrder::default(),
+        &mut FxHashSet::default(),
+        &mut res,
+    );
+
+    res
+}
+
+/// Returns the top-level definitions of the file, except its macros.
+fn file_defs(db: &dyn HirDatabase, file_id: FileId) -> Vec<(Name, ItemKind, NodePtr)> {
+    let item_tree = db.file_item_tree(file_id);
+    let ast_id_map = db.ast_id_map(file_id);
+    let mut res = Vec::new();
+    for item in item_tree.top_level_items().iter() {
+        let (name, kind, ast_id) = match item {
+            FileItem::Function(id) => {
+                let it = &item_tree[*id];
+                (it.name.clone(), ItemKind::Function(it.kind), it.ast_id)
+            }
+            FileItem::Variable(id) => {
+                let it = &item_tree[*id];
+                (it.name.clone(), ItemKind::Global, it.ast_id)
+            }
+            FileItem::Variant(id) => {
+                let it = &item_tree[*id];
+                (it.name.clone(), ItemKind::Variant, it.ast_id)
+            }
+            FileItem::Enum(id) => {
+                let it = &item_tree[*id];
+                if it.name.to_string().starts_with("unnamed_enum_") {
+                    continue;
+                }
+                (it.name.clone(), ItemKind::Enum, it.ast_id)
+            }
+            FileItem::Methodmap(id) => {
+                let it = &item_tree[*id];
+                (it.name.clone(), ItemKind::Methodmap, it.ast_id)
+            }
+            FileItem::EnumStruct(id) => {
+                let it = &item_tree[*id];
+                (it.name.clone(), ItemKind::Type, it.ast_id)
+            }
+            FileItem::Typeset(id) => {
+                let it = &item_tree[*id];
+                (it.name.clone(), ItemKind::Type, it.ast_id)
+            }
+            FileItem::Funcenum(id) => {
+                let it = &item_tree[*id];
+                (it.name.clone(), ItemKind::Type, it.ast_id)
+            }
+            FileItem::Struct(id) => {
+                let it = &item_tree[*id];
+                (it.name.clone(), ItemKind::Type, it.ast_id)
+            }
+            FileItem::Typedef(id) => {
+                let it = &item_tree[*id];
+                let Some(name) = it.name.clone() else {
+                    continue;
+                };
+                (name, ItemKind::Type, it.ast_id)
+            }
+            FileItem::Functag(id) => {
+                let it = &item_tree[*id];
+                let Some(name) = it.name.clone() else {
+                    continue;
+                };
+                (name, ItemKind::Type, it.ast_id)
+            }
+            FileItem::Macro(_) | FileItem::Property(_) => continue,
+        };
+        res.push((name, kind, ast_id_map[ast_id]));
+    }
+
+    res
+}
+
+/// Returns the macros defined in the file.
+fn file_macros(db: &dyn HirDatabase, file_id: FileId) -> Vec<(Name, NodePtr)> {
+    let item_tree = db.file_item_tree(file_id);
+    let ast_id_map = db.ast_id_map(file_id);
+    item_tree
+        .top_level_items()
+        .iter()
+        .filter_map(|item| match item {
+            FileItem::Macro(id) => Some((
+                item_tree[*id].name.clone(),
+                ast_id_map[item_tree[*id].ast_id],
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the range of the name of the node, or of the node itself if it has no name.
+fn name_range(tree: &Tree, ptr: NodePtr) -> lsp_types::Range {
+    match ptr.to_node(tree) {
+        Some(node) => {
+            ts_range_to_lsp_range(&node.child_by_field_name("name").unwrap_or(node).range())
+        }
+        None => Default::default(),
+    }
+}
+
+/// Returns the value of a macro, with its parameters, without the redundant whitespaces.
+fn macro_value(db: &dyn HirDatabase, file_id: FileId, ptr: NodePtr) -> Option<String> {
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let node = ptr.to_node(&tree)?;
+    let name = node.child_by_field_name("name")?;
+    let value = source.get(name.end_byte()..node.end_byte())?;
+
+    Some(value.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Returns the macros undefined by the file, with the line of their `#undef` directive.
+fn file_undefs(db: &dyn HirDatabase, file_id: FileId) -> Vec<(Name, u32)> {
+    db.preprocessed_text(file_id)
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let name = text.trim_start().strip_prefix("#undef")?.trim();
+            (!name.is_empty()).then(|| (Name::from(name), line as u32))
+        })
+        .collect()
+}
+
+/// Returns the members of the methodmaps and enum structs of the file which have the name
+/// of a previous member of the same methodmap or enum struct.
+fn duplicate_members(db: &dyn HirDatabase, file_id: FileId) -> Vec<DuplicateDefinition> {
+    let item_tree = db.file_item_tree(file_id);
+    let ast_id_map = db.ast_id_map(file_id);
+    let tree = db.parse(file_id);
+    let mut res = Vec::new();
+    for item in item_tree.top_level_items().iter() {
+        let (parent, members, kind): (_, _, fn(String) -> DuplicateDefinitionKind) = match item {
+            FileItem::Methodmap(id) => {
+                let it = &item_tree[*id];
+                let members = it
+                    .items
+                    .iter()
+                    .map(|member| match member {
+                        MethodmapItemId::Method(idx) => {
+                            (item_tree[*idx].name.clone(), item_tree[*idx].ast_id)
+                        }
+                        MethodmapItemId::Property(idx) => {
+                            (item_tree[*idx].name.clone(), item_tree[*idx].ast_id)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                (
+                    it.name.to_string(),
+                    members,
+                    DuplicateDefinitionKind::MethodmapMember,
+                )
+            }
+            FileItem::EnumStruct(id) => {
+                let it = &item_tree[*id];
+                let members = it
+                    .items
+                    .iter()
+                    .map(|member| match member {
+                        EnumStructItemId::Method(idx) => {
+                            (item_tree[*idx].name.clone(), item_tree[*idx].ast_id)
+                        }
+                        EnumStructItemId::Field(idx) => {
+                            (item_tree[*idx].name.clone(), item_tree[*idx].ast_id)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                (
+                    it.name.to_string(),
+                    members,
+                    DuplicateDefinitionKind::EnumStructMember,
+                )
+            }
+            _ => continue,
+        };
+        for (idx, (name, ast_id)) in members.iter().enumerate() {
+            let Some((_, other)) = members[..idx].iter().find(|(other, _)| other == name) else {
+                continue;
+            };
+            res.push(DuplicateDefinition {
+                range: name_range(&tree, ast_id_map[*ast_id]),
+                name: name.to_string(),
+                kind: kind(parent.clone()),
+                other_file_id: file_id,
+                other_range: name_range(&tree, ast_id_map[*other]),
+            });
+        }
+    }
+
+    res
+}
+
+/// Returns the definitions of the file which conflict with a previous definition of the
+/// project of the file.
+pub(crate) fn duplicate_definitions(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+) -> Vec<DuplicateDefinition> {
+    let root = db
+        .projet_subgraph(file_id)
+        .map_or(file_id, |subgraph| subgraph.root.file_id);
+    let definitions = db.project_definitions(root);
+
+    let mut res = Vec::new();
+    for (name, defs) in definitions.defs.iter() {
+        for def in defs.iter().filter(|def| def.file_id == file_id) {
+            let Some(other) = defs.iter().find(|other| def.is_duplicate_of(other)) else {
+                continue;
+            };
+            res.push(DuplicateDefinition {
+                range: def.range,
+                name: name.to_string(),
+                kind: DuplicateDefinitionKind::Item,
+                other_file_id: other.file_id,
+                other_range: other.range,
+            });
+        }
+    }
+
+    for (name, macros) in definitions.macros.iter() {
+        for macro_ in macros.iter().filter(|macro_| macro_.file_id == file_id) {
+            let Some(other) = macros.iter().find(|other| {
+                other.order < macro_.order
+                    && other.value != macro_.value
+                    && !definitions.is_undefined_between(name, &other.order, &macro_.order)
+            }) else {
+                continue;
+            };
+            res.push(DuplicateDefinition {
+                range: macro_.range,
+                name: name.to_string(),
+                kind: DuplicateDefinitionKind::Macro,
+                other_file_id: other.file_id,
+                other_range: other.range,
+            });
+        }
+    }
+
+    res.extend(duplicate_members(db, file_id));
+    res.sort_by_key(|it| (it.range.start, it.range.end));
+
+    res
+}
@@ -0,0 +1,422 @@
+//! Resolution of the fields of game events read or written in event callbacks.
+
+use base_db::Tree;
+use completion_data::{Event, Game};
+use syntax::{utils::ts_range_to_lsp_range, TSKind};
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::{db::HirDatabase, IncorrectEventFieldAccessor, UnresolvedEventField};
+
+/// Type of the value of an event field, as seen by its accessors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFieldType {
+    Int,
+    Bool,
+    Float,
+    String,
+}
+
+impl EventFieldType {
+    /// Returns the type of an event attribute from its type in the events database.
+    ///
+    /// Types which cannot be mapped (e.g. `local` or `none`) return `None`.
+    pub fn from_attribute_type(type_: &str) -> Option<Self> {
+        match type_ {
+            "byte" | "short" | "long" | "int" => Some(Self::Int),
+            "bool" => Some(Self::Bool),
+            "float" => Some(Self::Float),
+            "string" | "wstring" => Some(Self::String),
+            _ => None,
+        }
+    }
+
+    /// Returns the type accessed by an `Event` method or native, and whether the accessor
+    /// is a native (e.g. `GetEventInt`) taking the event as its first argument.
+    fn from_accessor(name: &str) -> Option<(Self, bool)> {
+        let res = match name {
+            "GetInt" | "SetInt" => (Self::Int, false),
+            "GetBool" | "SetBool" => (Self::Bool, false),
+            "GetFloat" | "SetFloat" => (Self::Float, false),
+            "GetString" | "SetString" => (Self::String, false),
+            "GetEventInt" | "SetEventInt" => (Self::Int, true),
+            "GetEventBool" | "SetEventBool" => (Self::Bool, true),
+            "GetEventFloat" | "SetEventFloat" => (Self::Float, true),
+            "GetEventString" | "SetEventString" => (Self::String, true),
+            _ => return None,
+        };
+
+        Some(res)
+    }
+
+    /// Whether a field of type `self` can be accessed with an accessor of type `accessor`.
+    ///
+    /// Booleans are stored as integers, so they are interchangeable.
+    pub fn is_compatible_with(self, accessor: Self) -> bool {
+        matches!(
+            (self, accessor),
+            (Self::Int | Self::Bool, Self::Int | Self::Bool)
+                | (Self::Float, Self::Float)
+                | (Self::String, Self::String)
+        )
+    }
+}
+
+/// A string literal used as an event field name, e.g. `"userid"` in `event.GetInt("userid")`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventFieldAccess {
+    /// Names of the events hooked with the callback the access is in.
+    pub events: Vec<String>,
+
+    /// Name of the field.
+    pub field: String,
+
+    /// Name of the method or native used to access the field.
+    pub accessor: String,
+
+    /// Type of the accessor.
+    pub type_: EventFieldType,
+}
+
+/// Returns the event field access if the node is the field name argument of an `Event`
+/// accessor, called on the event parameter of a callback hooked with `HookEvent` or
+/// `HookEventEx` in the project.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `file_id`: The [`FileId`] of the file of the node
+/// - `node`: The node to check
+/// - `source`: The preprocessed source code
+pub fn event_field_access(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+    node: &Node,
+    source: &str,
+) -> Option<EventFieldAccess> {
+    if TSKind::from(node) != TSKind::string_literal {
+        return None;
+    }
+    let call_arguments = node.parent()?;
+    if TSKind::from(call_arguments) != TSKind::call_arguments {
+        return None;
+    }
+    let text = |node: &Node| node.utf8_text(source.as_bytes()).ok();
+    let args = call_arguments
+        .named_children(&mut call_arguments.walk())
+        .filter(|n| TSKind::from(n) != TSKind::comment)
+        .collect::<Vec<_>>();
+
+    let function = call_arguments.prev_named_sibling()?;
+    let (receiver, accessor) = match TSKind::from(function) {
+        TSKind::field_access => (
+            function.child_by_field_name("target")?,
+            function.child_by_field_name("field")?,
+        ),
+        TSKind::identifier => (*args.first()?, function),
+        _ => return None,
+    };
+    let accessor = text(&accessor)?;
+    let (type_, is_native) = EventFieldType::from_accessor(accessor)?;
+    if TSKind::from(function) == TSKind::field_access && is_native {
+        return None;
+    }
+    if args.get(is_native as usize) != Some(node) {
+        return None;
+    }
+
+    let callback = enclosing_function(node)?;
+    let params = callback.child_by_field_name("parameters")?;
+    let receiver = text(&receiver)?;
+    // The receiver must be an `Event` (or `Handle`) parameter of the callback.
+    params
+        .named_children(&mut params.walk())
+        .filter(|n| TSKind::from(n) == TSKind::parameter_declaration)
+        .find(|param| {
+            param
+                .child_by_field_name("name")
+                .and_then(|name| text(&name))
+                == Some(receiver)
+                && param
+                    .child_by_field_name("type")
+                    .and_then(|type_| text(&type_))
+                    .is_some_and(|type_| matches!(type_.trim_end_matches(':'), "Event" | "Handle"))
+        })?;
+    let events = hooked_events(db, file_id, text(&callback.child_by_field_name("name")?)?);
+    if events.is_empty() {
+        return None;
+    }
+
+    Some(EventFieldAccess {
+        events,
+        field: text(node)?.trim_matches('"').to_string(),
+        accessor: accessor.to_string(),
+        type_,
+    })
+}
+
+fn enclosing_function<'tree>(node: &Node<'tree>) -> Option<Node<'tree>> {
+    let mut container = node.parent()?;
+    while TSKind::from(container) != TSKind::function_definition {
+        container = container.parent()?;
+    }
+
+    Some(container)
+}
+
+/// Returns the names of the events hooked with the callback in the project of the file.
+fn hooked_events(db: &dyn HirDatabase, file_id: FileId, callback: &str) -> Vec<String> {
+    let file_ids = db
+        .projet_subgraph(file_id)
+        .map(|subgraph| subgraph.file_ids())
+        .unwrap_or_else(|| [file_id].into_iter().collect());
+    let mut res = file_ids
+        .into_iter()
+        .flat_map(|file_id| {
+            db.file_event_hooks(file_id)
+                .iter()
+                .filter(|hook| hook.callback == callback)
+                .map(|hook| hook.event.clone())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    res.sort();
+    res.dedup();
+
+    res
+}
+
+fn collect_event_field_accesses<'tree>(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+    node: Node<'tree>,
+    source: &str,
+    acc: &mut Vec<(Node<'tree>, EventFieldAccess)>,
+) {
+    if let Some(access) = event_field_access(db, file_id, &node, source) {
+        acc.push((node, access));
+        return;
+    }
+    for child in node.children(&mut node.walk()) {
+        collect_event_field_accesses(db, file_id, child, source, acc);
+    }
+}
+
+/// Error of an event field access.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EventFieldError {
+    /// The field does not exist in the events.
+    Unresolved,
+
+    /// The field is accessed with an accessor which is incompatible with its type.
+    IncorrectAccessor { field_type: String },
+}
+
+/// Returns the definitions of the events with the given names in the given games.
+///
+/// The definition of an event in a game overrides its definition in the generic games.
+fn resolve_events<'a>(games: &[&'a Game<'static>], names: &[String]) -> Vec<&'a Event<'static>> {
+    let mut res = Vec::new();
+    for name in names {
+        let defs = games
+            .iter()
+            .filter_map(|game| {
+                game.events()
+                    .iter()
+                    .find(|ev| ev.name() == name)
+                    .map(|ev| (game.is_generic(), ev))
+            })
+            .collect::<Vec<_>>();
+        let has_specific = defs.iter().any(|(is_generic, _)| !is_generic);
+        res.extend(
+            defs.into_iter()
+                .filter(|(is_generic, _)| !has_specific || !is_generic)
+                .map(|(_, ev)| ev),
+        );
+    }
+
+    res
+}
+
+/// Checks an access to an event field against the definitions of the events.
+///
+/// The field is reported if none of the definitions have it, or if none of the definitions
+/// which have it can be accessed with the accessor.
+fn check_event_field(
+    events: &[&Event],
+    field: &str,
+    type_: EventFieldType,
+) -> Option<EventFieldError> {
+    let attributes = events
+        .iter()
+        .flat_map(|ev| ev.attributes())
+        .filter(|attr| attr.name() == field)
+        .collect::<Vec<_>>();
+    if attributes.is_empty() {
+        return Some(EventFieldError::Unresolved);
+    }
+    let types = attributes
+        .iter()
+        .filter_map(|attr| EventFieldType::from_attribute_type(attr.r#type()))
+        .collect::<Vec<_>>();
+    if types.len() == attributes.len() && !types.iter().any(|it| it.is_compatible_with(type_)) {
+        return Some(EventFieldError::IncorrectAccessor {
+            field_type: attributes[0].r#type().to_string(),
+        });
+    }
+
+    None
+}
+
+/// Compute the diagnostics of the event fields accessed in the file.
+///
+/// The events are looked up in the games selected by the user, or in all the games if none
+/// are selected. Events that are not in the events database are ignored.
+pub(crate) fn event_field_diagnostics(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+    tree: &Tree,
+    source: &str,
+) -> (Vec<UnresolvedEventField>, Vec<IncorrectEventFieldAccessor>) {
+    let mut unresolved = Vec::new();
+    let mut incorrect = Vec::new();
+    let mut accesses = Vec::new();
+    collect_event_field_accesses(db, file_id, tree.root_node(), source, &mut accesses);
+    if accesses.is_empty() {
+        return (unresolved, incorrect);
+    }
+
+    let data = db.completion_data();
+    let games = data.games(&db.selected_games());
+
+    for (node, access) in accesses {
+        let events = resolve_events(&games, &access.events);
+        if events.is_empty() {
+            continue;
+        }
+        let range = ts_range_to_lsp_range(&node.range());
+        match check_event_field(&events, &access.field, access.type_) {
+            Some(EventFieldError::Unresolved) => unresolved.push(UnresolvedEventField {
+                range,
+                field: access.field,
+                events: access.events,
+            }),
+            Some(EventFieldError::IncorrectAccessor { field_type }) => {
+                incorrect.push(IncorrectEventFieldAccessor {
+                    range,
+                    field: access.field,
+                    field_type,
+                    accessor: access.accessor,
+                })
+            }
+            None => (),
+        }
+    }
+
+    (unresolved, incorrect)
+}
+
+#[cfg(test)]
+mod tests {
+    use completion_data::Database;
+
+    use super::*;
+
+    fn database() -> Database<'static> {
+        let json = r#"{
+            "Generic Source": {
+                "name": "Generic Source",
+                "events": [
+                    {
+                        "name": "player_death",
+                        "attributes": [{ "name": "userid", "type": "short" }]
+                    }
+                ]
+            },
+            "My Mod": {
+                "name": "My Mod",
+                "events": [
+                    {
+                        "name": "player_death",
+                        "attributes": [
+                            { "name": "userid", "type": "short" },
+                            { "name": "weapon", "type": "string" }
+                        ]
+                    }
+                ]
+            },
+            "My Other Mod": {
+                "name": "My Other Mod",
+                "events": [
+                    {
+                        "name": "player_death",
+                        "attributes": [
+                            { "name": "userid", "type": "short" },
+                            { "name": "headshot", "type": "bool" }
+                        ]
+                    }
+                ]
+            }
+        }"#;
+
+        Database::from_json(json).unwrap().into_owned()
+    }
+
+    fn check(games: &[&str], field: &str, type_: EventFieldType) -> Option<EventFieldError> {
+        let db = database();
+        let games = db.games(&games.iter().map(|it| it.to_string()).collect::<Vec<_>>());
+        let events = resolve_events(&games, &["player_death".to_string()]);
+        assert!(!events.is_empty());
+
+        check_event_field(&events, field, type_)
+    }
+
+    #[test]
+    fn game_overrides_generic_event() {
+        let db = database();
+        let games = db.games(&["My Mod".to_string()]);
+        let events = resolve_events(&games, &["player_death".to_string()]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].attributes().len(), 2);
+    }
+
+    #[test]
+    fn field_of_another_game() {
+        assert_eq!(
+            check(&["My Mod"], "headshot", EventFieldType::Bool),
+            Some(EventFieldError::Unresolved)
+        );
+        assert_eq!(check(&["My Mod"], "weapon", EventFieldType::String), None);
+        assert_eq!(
+            check(
+                &["My Mod", "My Other Mod"],
+                "headshot",
+                EventFieldType::Bool
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn no_selected_game() {
+        assert_eq!(check(&[], "headshot", EventFieldType::Int), None);
+        assert_eq!(
+            check(&[], "unknown", EventFieldType::Int),
+            Some(EventFieldError::Unresolved)
+        );
+        assert_eq!(
+            check(&["Unknown Game"], "unknown", EventFieldType::Int),
+            Some(EventFieldError::Unresolved)
+        );
+    }
+
+    #[test]
+    fn incorrect_accessor() {
+        assert_eq!(
+            check(&["My Mod"], "weapon", EventFieldType::Int),
+            Some(EventFieldError::IncorrectAccessor {
+                field_type: "string".to_string()
+            })
+        );
+        assert_eq!(check(&["My Mod"], "userid", EventFieldType::Bool), None);
+    }
+}
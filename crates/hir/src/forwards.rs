@@ -0,0 +1,457 @@
+//! Validation of the `public` functions implementing a `forward` declared in the includes.
+//!
+//! A public function whose signature does not match the forward it implements compiles
+//! fine, but it is never called by SourceMod.
+
+use std::{fmt, sync::Arc};
+
+use fxhash::FxHashMap;
+use hir_def::{DefDatabase, FileItem, FunctionKind, Name, RawVisibilityId};
+use syntax::{utils::ts_range_to_lsp_range, TSKind};
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::{db::HirDatabase, ForwardSignatureMismatch};
+
+/// Returns the name of a type, as used to compare the signatures of a function and of a
+/// forward.
+///
+/// Old syntax tags are ignored, and old syntax types are mapped to their new syntax
+/// equivalent. Untagged old syntax parameters are `int`.
+fn normalized_type(type_: Option<&str>) -> String {
+    match type_.map(|it| it.trim_end_matches(':')) {
+        None | Some("_") => "int".to_string(),
+        Some("String") => "char".to_string(),
+        Some("Float") => "float".to_string(),
+        Some(type_) => type_.to_string(),
+    }
+}
+
+/// Signature of a parameter, as used to compare the signatures of a function and of a
+/// forward.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ParamSignature {
+    type_: String,
+    is_const: bool,
+    is_ref: bool,
+    /// Number of dimensions, whether they are written after the type or after the name.
+    dims: usize,
+    is_rest: bool,
+}
+
+impl ParamSignature {
+    fn from_node(node: &Node, source: &str) -> Self {
+        let mut res = Self {
+            type_: normalized_type(
+                node.children_by_field_name("type", &mut node.walk())
+                    .find(|child| {
+                        !matches!(
+                            TSKind::from(child),
+                            TSKind::dimension | TSKind::fixed_dimension
+                        )
+                    })
+                    .and_then(|child| child.utf8_text(source.as_bytes()).ok()),
+            ),
+            is_const: node.child_by_field_name("storage_class").is_some(),
+            is_ref: false,
+            dims: 0,
+            is_rest: TSKind::from(node) == TSKind::rest_parameter,
+        };
+        for child in node.children(&mut node.walk()) {
+            match TSKind::from(child) {
+                TSKind::anon_AMP => res.is_ref = true,
+                TSKind::dimension | TSKind::fixed_dimension => res.dims += 1,
+                _ => (),
+            }
+        }
+
+        res
+    }
+
+    /// Whether a parameter with this signature can implement the parameter `expected` of a
+    /// forward.
+    ///
+    /// Parameters of type `any` accept any type. `const` only matters for arrays, which are
+    /// passed by reference.
+    fn matches(&self, expected: &ParamSignature) -> bool {
+        (expected.type_ == "any" || expected.type_ == self.type_)
+            && expected.is_ref == self.is_ref
+            && expected.dims == self.dims
+            && (self.dims == 0 || expected.is_const == self.is_const)
+    }
+}
+
+impl fmt::Display for ParamSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_const {
+            write!(f, "const ")?;
+        }
+        write!(f, "{}", self.type_)?;
+        if self.is_ref {
+            write!(f, "&")?;
+        }
+        for _ in 0..self.dims {
+            write!(f, "[]")?;
+        }
+        if self.is_rest {
+            write!(f, " ...")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the parameter nodes of a function declaration or definition.
+fn param_nodes<'tree>(node: &Node<'tree>) -> Vec<Node<'tree>> {
+    let Some(params) = node.child_by_field_name("parameters") else {
+        return Vec::new();
+    };
+    params
+        .children(&mut params.walk())
+        .filter(|child| {
+            matches!(
+                TSKind::from(child),
+                TSKind::parameter_declaration | TSKind::rest_parameter
+            )
+        })
+        .collect()
+}
+
+/// Returns the text of the signature of a function declaration or definition, from its
+/// return type (or its name if it has none) to the end of its parameters.
+fn signature_text<'a>(node: &Node, source: &'a str) -> Option<&'a str> {
+    let start = node
+        .child_by_field_name("returnType")
+        .or_else(|| node.child_by_field_name("name"))?;
+    let end = node.child_by_field_name("parameters")?;
+
+    source.get(start.start_byte()..end.end_byte())
+}
+
+/// Returns the signature of the forward `forward`, with the name and the names of the
+/// parameters of the function `function`.
+///
+/// The function keeps the number of parameters it declares, as it may omit the trailing
+/// parameters of the forward.
+fn fixed_signature(
+    function: &Node,
+    source: &str,
+    forward: &Node,
+    forward_source: &str,
+) -> Option<String> {
+    fn text(node: &Node, source: &str) -> Option<String> {
+        node.utf8_text(source.as_bytes())
+            .ok()
+            .map(ToOwned::to_owned)
+    }
+
+    let name = text(&function.child_by_field_name("name")?, source)?;
+    let params = param_nodes(function);
+    let forward_params = param_nodes(forward);
+    let mut fixed_params = Vec::new();
+    for (idx, forward_param) in forward_params.iter().enumerate() {
+        if idx >= params.len() {
+            break;
+        }
+        let mut param = text(forward_param, forward_source)?;
+        if let (Some(forward_name), Some(name)) = (
+            forward_param.child_by_field_name("name"),
+            params[idx].child_by_field_name("name"),
+        ) {
+            let start = forward_name.start_byte() - forward_param.start_byte();
+            let end = forward_name.end_byte() - forward_param.start_byte();
+            param.replace_range(start..end, &text(&name, source)?);
+        }
+        fixed_params.push(param);
+    }
+
+    let mut res = "public ".to_string();
+    if let Some(ret_type) = forward.child_by_field_name("returnType") {
+        res.push_str(&text(&ret_type, forward_source)?);
+        res.push(' ');
+    }
+    res.push_str(&format!("{}({})", name, fixed_params.join(", ")));
+
+    Some(res)
+}
+
+/// Returns the forwards declared in the project of the file, keyed by name.
+pub(crate) fn project_forwards_query(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+) -> Arc<FxHashMap<Name, (FileId, FileItem)>> {
+    let file_ids = db
+        .projet_subgraph(file_id)
+        .map(|subgraph| subgraph.file_ids())
+        .unwrap_or_else(|| [file_id].into_iter().collect());
+    let mut res = FxHashMap::default();
+    for file_id in file_ids {
+        let item_tree = db.file_item_tree(file_id);
+        for item in item_tree.top_level_items().iter() {
+            let FileItem::Function(id) = item else {
+                continue;
+            };
+            if item_tree[*id].kind == FunctionKind::Forward {
+                res.insert(item_tree[*id].name.clone(), (file_id, *item));
+            }
+        }
+    }
+
+    Arc::new(res)
+}
+
+/// Returns why the parameters and return type of a function do not match the ones of a
+/// forward, or `None` if they match.
+///
+/// A function may omit trailing parameters of the forward. The return type is only
+/// compared if both declare one.
+fn mismatch_reason(
+    params: &[ParamSignature],
+    ret_type: Option<String>,
+    forward_params: &[ParamSignature],
+    forward_ret_type: Option<String>,
+) -> Option<String> {
+    let has_rest = forward_params.last().is_some_and(|it| it.is_rest);
+    if params.len() > forward_params.len() && !has_rest {
+        return Some(format!(
+            "it has {} parameters but the forward has {}",
+            params.len(),
+            forward_params.len()
+        ));
+    }
+    for (idx, param) in params.iter().enumerate() {
+        let Some(expected) = forward_params
+            .get(idx)
+            .or_else(|| forward_params.last().filter(|it| it.is_rest))
+        else {
+            continue;
+        };
+        if !param.matches(expected) {
+            return Some(format!(
+                "parameter {} is `{}` but the forward expects `{}`",
+                idx + 1,
+                param,
+                expected
+            ));
+        }
+    }
+    if let (Some(ret_type), Some(forward_ret_type)) = (ret_type, forward_ret_type) {
+        if ret_type != forward_ret_type {
+            return Some(format!(
+                "it returns `{}` but the forward returns `{}`",
+                ret_type, forward_ret_type
+            ));
+        }
+    }
+
+    None
+}
+
+/// Returns the `public` functions of the file whose signature does not match the signature
+/// of the forward with the same name, declared in the project of the file.
+pub fn forward_signature_mismatches(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+) -> Vec<ForwardSignatureMismatch> {
+    let item_tree = db.file_item_tree(file_id);
+    let publics = item_tree
+        .top_level_items()
+        .iter()
+        .filter_map(|item| match item {
+            FileItem::Function(id)
+                if item_tree[*id].kind == FunctionKind::Def
+                    && item_tree[*id].visibility.contains(RawVisibilityId::PUBLIC) =>
+            {
+                Some(*id)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if publics.is_empty() {
+        return Vec::new();
+    }
+    let forwards = db.project_forwards(file_id);
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let ast_id_map = db.ast_id_map(file_id);
+
+    let mut res = Vec::new();
+    for id in publics {
+        let function = &item_tree[id];
+        let Some((forward_file_id, FileItem::Function(forward_id))) = forwards.get(&function.name)
+        else {
+            continue;
+        };
+        let forward_item_tree = db.file_item_tree(*forward_file_id);
+        let forward = &forward_item_tree[*forward_id];
+        let forward_tree = db.parse(*forward_file_id);
+        let forward_source = db.preprocessed_text(*forward_file_id);
+        let (Some(node), Some(forward_node)) = (
+            ast_id_map[function.ast_id].to_node(&tree),
+            db.ast_id_map(*forward_file_id)[forward.ast_id].to_node(&forward_tree),
+        ) else {
+            continue;
+        };
+        let params = param_nodes(&node)
+            .iter()
+            .map(|it| ParamSignature::from_node(it, &source))
+            .collect::<Vec<_>>();
+        let forward_params = param_nodes(&forward_node)
+            .iter()
+            .map(|it| ParamSignature::from_node(it, &forward_source))
+            .collect::<Vec<_>>();
+        let Some(reason) = mismatch_reason(
+            &params,
+            function
+                .ret_type
+                .as_ref()
+                .map(|it| normalized_type(Some(&it.type_as_string()))),
+            &forward_params,
+            forward
+                .ret_type
+                .as_ref()
+                .map(|it| normalized_type(Some(&it.type_as_string()))),
+        ) else {
+            continue;
+        };
+
+        let (Some(forward_signature), Some(fixed_signature)) = (
+            signature_text(&forward_node, &forward_source),
+            fixed_signature(&node, &source, &forward_node, &forward_source),
+        ) else {
+            continue;
+        };
+        let (Some(name_node), Some(params_node)) = (
+            node.child_by_field_name("name"),
+            node.child_by_field_name("parameters"),
+        ) else {
+            continue;
+        };
+        let mut signature_range = ts_range_to_lsp_range(&node.range());
+        signature_range.end = ts_range_to_lsp_range(&params_node.range()).end;
+
+        res.push(ForwardSignatureMismatch {
+            range: ts_range_to_lsp_range(&name_node.range()),
+            signature_range,
+            name: function.name.to_string(),
+            forward_signature: format!("public {}", forward_signature),
+            fixed_signature,
+            reason,
+        });
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(type_: &str) -> ParamSignature {
+        ParamSignature {
+            type_: type_.to_string(),
+            is_const: false,
+            is_ref: false,
+            dims: 0,
+            is_rest: false,
+        }
+    }
+
+    fn array(type_: &str, is_const: bool) -> ParamSignature {
+        ParamSignature {
+            is_const,
+            dims: 1,
+            ..param(type_)
+        }
+    }
+
+    fn reference(type_: &str) -> ParamSignature {
+        ParamSignature {
+            is_ref: true,
+            ..param(type_)
+        }
+    }
+
+    fn rest(type_: &str) -> ParamSignature {
+        ParamSignature {
+            is_rest: true,
+            ..param(type_)
+        }
+    }
+
+    #[test]
+    fn omitted_trailing_parameters_match() {
+        let forward = [param("int"), param("int")];
+        assert_eq!(
+            mismatch_reason(&[param("int")], None, &forward, Some("Action".to_string())),
+            None
+        );
+    }
+
+    #[test]
+    fn wrong_parameters_do_not_match() {
+        let forward = [param("int")];
+        assert!(mismatch_reason(&[param("int"), param("int")], None, &forward, None).is_some());
+        assert!(mismatch_reason(&[param("float")], None, &forward, None).is_some());
+        assert!(mismatch_reason(
+            &[param("int")],
+            Some("void".to_string()),
+            &forward,
+            Some("Action".to_string())
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn any_and_rest_parameters_match() {
+        let forward = [param("any"), rest("int")];
+        assert_eq!(
+            mismatch_reason(
+                &[param("Handle"), param("int"), param("int")],
+                None,
+                &forward,
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn references_and_arrays_do_not_match_values() {
+        let forward = [reference("float"), array("char", true)];
+        assert_eq!(
+            mismatch_reason(
+                &[reference("float"), array("char", true)],
+                None,
+                &forward,
+                None
+            ),
+            None
+        );
+        assert_eq!(
+            mismatch_reason(&[param("float")], None, &forward, None),
+            Some("parameter 1 is `float` but the forward expects `float&`".to_string())
+        );
+        assert_eq!(
+            mismatch_reason(&[reference("float"), param("char")], None, &forward, None),
+            Some("parameter 2 is `char` but the forward expects `const char[]`".to_string())
+        );
+        assert!(mismatch_reason(
+            &[reference("float"), array("char", false)],
+            None,
+            &forward,
+            None
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn const_scalars_match() {
+        let mut const_int = param("int");
+        const_int.is_const = true;
+        assert_eq!(
+            mismatch_reason(&[const_int], None, &[param("int")], None),
+            None
+        );
+    }
+}
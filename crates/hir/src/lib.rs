@@ -1,14 +1,15 @@
 use core::fmt;
-use std::hash::Hash;
+use std::{hash::Hash, sync::Arc};
 
 use base_db::Tree;
 use db::HirDatabase;
 use hir_def::{
     resolver::{HasResolver, ValueNs},
-    type_string_from_node, DefDiagnostic, DefWithBodyId, EnumId, EnumStructId, ExprId, FuncenumId,
-    FunctagId, FunctionId, FunctionKind, GlobalId, InFile, InferenceDiagnostic, ItemContainerId,
-    LocalFieldId, LocalStructFieldId, Lookup, MacroId, MethodmapExtension, MethodmapId, Name,
-    NodePtr, PropertyId, SpecialMethod, StructId, TypedefId, TypesetId, VariantId,
+    type_string_from_node, AttributeId, DefDiagnostic, DefWithBodyId, DeprecatedItem, EnumId,
+    EnumStructId, EnumStructItemData, ExprId, FuncenumId, FunctagId, FunctionId, FunctionKind,
+    GlobalId, InFile, InferenceDiagnostic, ItemContainerId, LocalFieldId, LocalStructFieldId,
+    Lookup, MacroId, MethodmapExtension, MethodmapId, MethodmapItemData, Name, NodePtr, PropertyId,
+    PropertyItem, SpecialMethod, StructId, TypedefId, TypesetId, VariantId,
 };
 use itertools::Itertools;
 use la_arena::RawIdx;
@@ -23,13 +24,21 @@ use syntax::TSKind;
 use tree_sitter::Node;
 use vfs::FileId;
 
+mod callbacks;
+mod control_flow;
 pub mod db;
 mod diagnostics;
+mod doc_params;
+pub mod duplicates;
+pub mod events;
+pub mod forwards;
 mod from_id;
 mod has_source;
 mod semantics;
 mod source_analyzer;
 mod source_to_def;
+pub mod switches;
+pub mod translations;
 
 pub use crate::{diagnostics::*, has_source::HasSource, semantics::Semantics};
 
@@ -101,6 +110,25 @@ impl From<Local> for DefResolution {
     }
 }
 
+impl From<FileDef> for DefResolution {
+    fn from(def: FileDef) -> Self {
+        match def {
+            FileDef::Function(it) => DefResolution::Function(it),
+            FileDef::Macro(it) => DefResolution::Macro(it),
+            FileDef::EnumStruct(it) => DefResolution::EnumStruct(it),
+            FileDef::Methodmap(it) => DefResolution::Methodmap(it),
+            FileDef::Global(it) => DefResolution::Global(it),
+            FileDef::Enum(it) => DefResolution::Enum(it),
+            FileDef::Variant(it) => DefResolution::Variant(it),
+            FileDef::Typedef(it) => DefResolution::Typedef(it),
+            FileDef::Typeset(it) => DefResolution::Typeset(it),
+            FileDef::Functag(it) => DefResolution::Functag(it),
+            FileDef::Funcenum(it) => DefResolution::Funcenum(it),
+            FileDef::Struct(it) => DefResolution::Struct(it),
+        }
+    }
+}
+
 impl DefResolution {
     fn try_from(value: ValueNs) -> Option<Self> {
         match value {
@@ -229,6 +257,29 @@ impl DefResolution {
             DefResolution::File(_) => None,
         }
     }
+
+    /// Returns whether the definition is deprecated.
+    ///
+    /// Globals, locals and files cannot be deprecated.
+    pub fn is_deprecated(&self, db: &dyn HirDatabase) -> bool {
+        match self {
+            DefResolution::Function(it) => it.is_deprecated(db),
+            DefResolution::Macro(it) => it.is_deprecated(db),
+            DefResolution::EnumStruct(it) => it.is_deprecated(db),
+            DefResolution::Methodmap(it) => it.is_deprecated(db),
+            DefResolution::Property(it) => it.is_deprecated(db),
+            DefResolution::Enum(it) => it.is_deprecated(db),
+            DefResolution::Variant(it) => it.is_deprecated(db),
+            DefResolution::Typedef(it) => it.is_deprecated(db),
+            DefResolution::Typeset(it) => it.is_deprecated(db),
+            DefResolution::Functag(it) => it.is_deprecated(db),
+            DefResolution::Funcenum(it) => it.is_deprecated(db),
+            DefResolution::Struct(it) => it.is_deprecated(db),
+            DefResolution::StructField(it) => it.is_deprecated(db),
+            DefResolution::Field(it) => it.is_deprecated(db),
+            DefResolution::Global(_) | DefResolution::Local(_) | DefResolution::File(_) => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -293,12 +344,75 @@ impl File {
                 .iter()
                 .map(|range| AnyDiagnostic::InactiveCode(InactiveCode { range: *range }.into())),
         );
+        let (unresolved_phrases, phrases_not_loaded) = translations::phrase_diagnostics(
+            db,
+            self.id,
+            &db.parse(self.id),
+            &db.preprocessed_text(self.id),
+        );
+        acc.extend(
+            unresolved_phrases
+                .into_iter()
+                .map(|it| AnyDiagnostic::UnresolvedPhrase(it.into())),
+        );
+        acc.extend(
+            phrases_not_loaded
+                .into_iter()
+                .map(|it| AnyDiagnostic::PhraseNotLoaded(it.into())),
+        );
+        let (unresolved_event_fields, incorrect_event_field_accessors) =
+            events::event_field_diagnostics(
+                db,
+                self.id,
+                &db.parse(self.id),
+                &db.preprocessed_text(self.id),
+            );
+        acc.extend(
+            unresolved_event_fields
+                .into_iter()
+                .map(|it| AnyDiagnostic::UnresolvedEventField(it.into())),
+        );
+        acc.extend(
+            incorrect_event_field_accessors
+                .into_iter()
+                .map(|it| AnyDiagnostic::IncorrectEventFieldAccessor(it.into())),
+        );
+        acc.extend(
+            forwards::forward_signature_mismatches(db, self.id)
+                .into_iter()
+                .map(|it| AnyDiagnostic::ForwardSignatureMismatch(it.into())),
+        );
+        acc.extend(
+            duplicates::duplicate_definitions(db, self.id)
+                .into_iter()
+                .map(|it| AnyDiagnostic::DuplicateDefinition(it.into())),
+        );
+        acc.extend(
+            doc_params::unknown_doc_params(db, self.id)
+                .into_iter()
+                .map(|it| AnyDiagnostic::UnknownDocParam(it.into())),
+        );
         self.declarations(db)
             .iter()
             .for_each(|it| acc.extend(it.diagnostics(db)));
     }
 }
 
+/// Returns the top-level definitions of the file which have a name, with their name.
+pub(crate) fn file_definitions_query(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+) -> Arc<Vec<(Name, DefResolution)>> {
+    let defs = File::from(file_id)
+        .declarations(db)
+        .into_iter()
+        .map(DefResolution::from)
+        .filter_map(|def| Some((def.name(db)?, def)))
+        .collect_vec();
+
+    Arc::new(defs)
+}
+
 impl<'tree> File {
     fn source(
         self,
@@ -513,8 +627,99 @@ impl DefWithBody {
                     }
                     .into(),
                 ),
+                InferenceDiagnostic::DuplicateCaseValue { expr, first, value } => acc.push(
+                    DuplicateCaseValue {
+                        expr: expr_syntax(*expr),
+                        first: expr_syntax(*first),
+                        value: *value,
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::NonConstantCaseLabel { expr } => acc.push(
+                    NonConstantCaseLabel {
+                        expr: expr_syntax(*expr),
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::MissingSwitchCases {
+                    expr,
+                    enum_name,
+                    missing,
+                } => acc.push(
+                    MissingSwitchCases {
+                        expr: expr_syntax(*expr),
+                        enum_name: enum_name.clone(),
+                        missing: missing.clone(),
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::ShadowedVariable {
+                    expr,
+                    name,
+                    shadowed,
+                } => {
+                    let (kind, shadowed) = match shadowed {
+                        hir_def::ShadowedVariable::Parameter(it) => {
+                            (ShadowedVariableKind::Parameter, expr_syntax(*it))
+                        }
+                        hir_def::ShadowedVariable::Local(it) => {
+                            (ShadowedVariableKind::Local, expr_syntax(*it))
+                        }
+                        hir_def::ShadowedVariable::Global(it) => {
+                            let tree = db.parse(it.file_id);
+                            let Some(source) = Global::from(it.value).source(db, &tree) else {
+                                continue;
+                            };
+                            (
+                                ShadowedVariableKind::Global,
+                                InFile::new(source.file_id, NodePtr::from(&source.value)),
+                            )
+                        }
+                    };
+                    acc.push(
+                        ShadowedVariable {
+                            expr: expr_syntax(*expr),
+                            name: name.clone(),
+                            kind,
+                            shadowed,
+                        }
+                        .into(),
+                    )
+                }
+                InferenceDiagnostic::LoopVariableUsedAfterLoop {
+                    expr,
+                    name,
+                    binding,
+                } => acc.push(
+                    LoopVariableUsedAfterLoop {
+                        expr: expr_syntax(*expr),
+                        name: name.clone(),
+                        binding: expr_syntax(*binding),
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::DeprecatedUsage { expr, item } => {
+                    let def = match *item {
+                        DeprecatedItem::FileDefId(id) => FileDef::from(id).into(),
+                        DeprecatedItem::AttributeId(AttributeId::FieldId(id)) => {
+                            DefResolution::Field(id.into())
+                        }
+                        DeprecatedItem::AttributeId(AttributeId::PropertyId(id)) => {
+                            DefResolution::Property(id.into())
+                        }
+                    };
+                    acc.push(
+                        DeprecatedUsage {
+                            expr: expr_syntax(*expr),
+                            def,
+                        }
+                        .into(),
+                    )
+                }
             }
         }
+
+        control_flow::control_flow_diagnostics(db, self, acc);
     }
 }
 
@@ -558,6 +763,17 @@ impl Function {
             .and_then(DefResolution::try_from)
     }
 
+    /// Returns the definition of the type of the parameter at index `idx`.
+    pub fn parameter_type_def(self, db: &dyn HirDatabase, idx: usize) -> Option<DefResolution> {
+        let data = db.function_data(self.id);
+        let type_ref = data.params().get(idx)?.type_ref.clone()?;
+        let ty_str = type_ref.type_as_string();
+        self.id
+            .resolver(db.upcast())
+            .resolve_ident(&ty_str)
+            .and_then(DefResolution::try_from)
+    }
+
     pub fn type_def(self, db: &dyn HirDatabase) -> Vec<DefResolution> {
         let mut res = Vec::new();
         if let Some(return_type_def) = self.return_type_def(db) {
@@ -650,7 +866,18 @@ impl Function {
                         return FunctionType::Destructor;
                     }
                 }
-                FunctionType::Method
+                let accessor = method_map.items.iter().find_map(|(_, item)| match item {
+                    MethodmapItemData::Property(property_data) => property_data
+                        .getters_setters
+                        .iter()
+                        .find(|it| it.function_id() == self.id),
+                    _ => None,
+                });
+                match accessor {
+                    Some(PropertyItem::Getter(_)) => FunctionType::Getter,
+                    Some(PropertyItem::Setter(_)) => FunctionType::Setter,
+                    None => FunctionType::Method,
+                }
             }
 
             ItemContainerId::EnumStructId(_) => FunctionType::Method,
@@ -659,6 +886,27 @@ impl Function {
         }
     }
 
+    /// Returns the property of a getter or a setter.
+    pub fn property(self, db: &dyn HirDatabase) -> Option<Property> {
+        let ItemContainerId::MethodmapId(parent_id) = self.id.lookup(db.upcast()).container else {
+            return None;
+        };
+        db.methodmap_data(parent_id)
+            .items
+            .iter()
+            .find_map(|(_, item)| match item {
+                MethodmapItemData::Property(property_data)
+                    if property_data
+                        .getters_setters
+                        .iter()
+                        .any(|it| it.function_id() == self.id) =>
+                {
+                    Some(Property::from(property_data.id))
+                }
+                _ => None,
+            })
+    }
+
     pub fn as_snippet(self, db: &dyn HirDatabase) -> Option<String> {
         let loc = self.id.lookup(db.upcast());
         let source = db.preprocessed_text(loc.id.file_id());
@@ -808,6 +1056,28 @@ impl EnumStruct {
         format!("enum struct {}", self.name(db)).into()
     }
 
+    pub fn fields(self, db: &dyn HirDatabase) -> Vec<Field> {
+        db.enum_struct_data(self.id)
+            .items
+            .iter()
+            .filter_map(|(id, item)| match item {
+                EnumStructItemData::Field(_) => Some(Field { parent: self, id }),
+                EnumStructItemData::Method(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn methods(self, db: &dyn HirDatabase) -> Vec<Function> {
+        db.enum_struct_data(self.id)
+            .items
+            .iter()
+            .filter_map(|(_, item)| match item {
+                EnumStructItemData::Method(id) => Some(Function::from(*id)),
+                EnumStructItemData::Field(_) => None,
+            })
+            .collect()
+    }
+
     /// Returns whether the enum struct is deprecated.
     ///
     /// This method is "fast" as it does not do a lookup of the node in the tree.
@@ -834,6 +1104,33 @@ impl Methodmap {
         db.methodmap_data(self.id).extension.clone()
     }
 
+    /// Returns the methods declared in the methodmap, without the inherited ones.
+    pub fn methods(self, db: &dyn HirDatabase) -> Vec<Function> {
+        db.methodmap_data(self.id)
+            .local_items()
+            .filter_map(|(_, item)| match item {
+                MethodmapItemData::Method(id)
+                | MethodmapItemData::Static(id)
+                | MethodmapItemData::Constructor(id)
+                | MethodmapItemData::Destructor(id) => Some(Function::from(*id)),
+                MethodmapItemData::Property(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns the properties declared in the methodmap, without the inherited ones.
+    pub fn properties(self, db: &dyn HirDatabase) -> Vec<Property> {
+        db.methodmap_data(self.id)
+            .local_items()
+            .filter_map(|(_, item)| match item {
+                MethodmapItemData::Property(property_data) => {
+                    Some(Property::from(property_data.id))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn render(self, db: &dyn HirDatabase) -> Option<String> {
         let mut buf = format!("methodmap {}", self.name(db));
         match self.extension(db) {
@@ -889,6 +1186,26 @@ impl Property {
         buf.into()
     }
 
+    pub fn getter(self, db: &dyn HirDatabase) -> Option<Function> {
+        db.property_data(self.id)
+            .getters_setters
+            .iter()
+            .find_map(|item| match item {
+                PropertyItem::Getter(id) => Some(Function::from(*id)),
+                PropertyItem::Setter(_) => None,
+            })
+    }
+
+    pub fn setter(self, db: &dyn HirDatabase) -> Option<Function> {
+        db.property_data(self.id)
+            .getters_setters
+            .iter()
+            .find_map(|item| match item {
+                PropertyItem::Setter(id) => Some(Function::from(*id)),
+                PropertyItem::Getter(_) => None,
+            })
+    }
+
     pub fn type_(self, db: &dyn HirDatabase) -> Option<DefResolution> {
         let ty = db.property_data(self.id).type_ref.clone();
         let ty_str = ty.type_as_string();
@@ -941,6 +1258,15 @@ impl Enum {
         format!("enum {}", self.name(db)).into()
     }
 
+    /// Returns the variants of the enum, in their order of declaration.
+    pub fn variants(self, db: &dyn HirDatabase) -> Vec<Variant> {
+        self.id
+            .variants(db.upcast())
+            .into_iter()
+            .map(Variant::from)
+            .collect()
+    }
+
     /// Returns whether the enum is deprecated.
     ///
     /// This method is "fast" as it does not do a lookup of the node in the tree.
@@ -1154,6 +1480,22 @@ impl Typedef {
         buf.into()
     }
 
+    /// Returns the declaration of a new callback named `name` matching the typedef.
+    pub fn as_callback_stub(self, db: &dyn HirDatabase, name: &str) -> Option<String> {
+        let file_id = self.id.lookup(db.upcast()).id.file_id();
+        let source = db.preprocessed_text(file_id);
+        let tree = db.parse(file_id);
+        let node = self.source(db, &tree)?.value;
+        let typedef_expr = if TSKind::from(&node) == TSKind::typedef_expression {
+            node
+        } else {
+            node.children(&mut node.walk())
+                .find(|n| TSKind::from(n) == TSKind::typedef_expression)?
+        };
+
+        callback_stub(&typedef_expr, &source, name)
+    }
+
     /// Returns whether the typedef is deprecated.
     ///
     /// This method is "fast" as it does not do a lookup of the node in the tree.
@@ -1346,6 +1688,16 @@ impl Functag {
         buf.into()
     }
 
+    /// Returns the declaration of a new callback named `name` matching the functag.
+    pub fn as_callback_stub(self, db: &dyn HirDatabase, name: &str) -> Option<String> {
+        let file_id = self.id.lookup(db.upcast()).id.file_id();
+        let source = db.preprocessed_text(file_id);
+        let tree = db.parse(file_id);
+        let node = self.source(db, &tree)?.value;
+
+        callback_stub(&node, &source, name)
+    }
+
     /// Returns whether the functag is deprecated.
     ///
     /// This method is "fast" as it does not do a lookup of the node in the tree.
@@ -1354,6 +1706,37 @@ impl Functag {
     }
 }
 
+/// Render the declaration of a new public function named `name`, with the return type and
+/// the parameters of a typedef expression or of a functag.
+///
+/// Old style return types (`Action:`) are converted to the new syntax.
+fn callback_stub(node: &Node, source: &str, name: &str) -> Option<String> {
+    let return_type = node
+        .child_by_field_name("returnType")
+        .and_then(|it| it.utf8_text(source.as_bytes()).ok())
+        .map(|it| it.trim().trim_end_matches(':').trim())
+        .filter(|it| !it.is_empty() && *it != "_")
+        .unwrap_or("int");
+    let params = node
+        .child_by_field_name("parameters")?
+        .utf8_text(source.as_bytes())
+        .ok()?;
+    let mut buf = format!("public {} {}{}\n{{\n", return_type, name, params);
+    let default_value = match return_type {
+        "void" => None,
+        "Action" => Some("Plugin_Continue"),
+        "bool" => Some("false"),
+        "float" | "Float" => Some("0.0"),
+        _ => Some("0"),
+    };
+    if let Some(value) = default_value {
+        buf.push_str(&format!("\treturn {};\n", value));
+    }
+    buf.push('}');
+
+    buf.into()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Funcenum {
     pub(crate) id: FuncenumId,
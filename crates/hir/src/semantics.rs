@@ -800,7 +800,7 @@ macro_rules! to_def_methods {
 }
 
 impl<'db> SemanticsImpl<'db> {
-    fn new(db: &'db dyn HirDatabase) -> Self {
+    pub(crate) fn new(db: &'db dyn HirDatabase) -> Self {
         SemanticsImpl {
             db,
             s2d_cache: Default::default(),
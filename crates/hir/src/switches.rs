@@ -0,0 +1,75 @@
+//! Resolution of the `switch` statements over an enum, used by the assist which fills in the
+//! missing `case` arms and by the completion of the `case` labels.
+
+use hir_def::{DefDatabase, InFile};
+use vfs::FileId;
+
+use crate::{db::HirDatabase, DefWithBody, Enum, File, FileDef, Function, Variant};
+
+/// The enum a `switch` statement is over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitchCases {
+    pub enum_: Enum,
+
+    /// Variants of the enum which do not have a `case`, in their order of declaration.
+    pub missing: Vec<Variant>,
+
+    /// Whether the `switch` has a `default` case.
+    pub has_default: bool,
+}
+
+/// Returns the defs of the file which have a body, including the methods of its methodmaps
+/// and enum structs.
+fn file_bodies(db: &dyn HirDatabase, file_id: FileId) -> Vec<DefWithBody> {
+    let mut res = Vec::new();
+    for def in (File { id: file_id }).declarations(db) {
+        match def {
+            FileDef::Methodmap(it) => {
+                let data = db.methodmap_data(it.id);
+                res.extend(
+                    data.methods()
+                        .chain(data.getters_setters())
+                        .map(|id| DefWithBody::from(Function::from(id))),
+                );
+            }
+            FileDef::EnumStruct(it) => {
+                let data = db.enum_struct_data(it.id);
+                res.extend(
+                    data.methods()
+                        .map(|id| DefWithBody::from(Function::from(id))),
+                );
+            }
+            _ => res.extend(def.as_def_with_body()),
+        }
+    }
+
+    res
+}
+
+/// Returns the enum the `switch_statement` node is over, with the variants which do not have
+/// a `case`.
+///
+/// Returns `None` if the type of the condition of the `switch` is not an enum, and its labels
+/// are not the variants of a single enum.
+pub fn switch_cases(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+    switch_node: &tree_sitter::Node,
+) -> Option<SwitchCases> {
+    file_bodies(db, file_id).into_iter().find_map(|def| {
+        let (_, source_map) = db.body_with_source_map(def.into());
+        let expr = source_map.node_expr(InFile::new(file_id, switch_node))?;
+        let infer = db.infer(def.into());
+        let resolution = infer.switch_resolution(expr)?;
+
+        Some(SwitchCases {
+            enum_: resolution.enum_id.into(),
+            missing: resolution
+                .missing_variants
+                .iter()
+                .map(|it| Variant::from(*it))
+                .collect(),
+            has_default: resolution.has_default,
+        })
+    })
+}
@@ -0,0 +1,463 @@
+//! Resolution of translation phrases used in format strings (`%t`/`%T`) and
+//! translation natives.
+
+use std::sync::Arc;
+
+use base_db::{Phrase, Tree};
+use fxhash::{FxHashMap, FxHashSet};
+use hir_def::{DefDatabase, TypeRef};
+use syntax::{utils::ts_range_to_lsp_range, TSKind};
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::{
+    db::HirDatabase, semantics::SemanticsImpl, DefResolution, PhraseNotLoaded, UnresolvedPhrase,
+};
+
+/// A phrase of a translation file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhraseDef {
+    /// [`FileId`] of the translation file.
+    pub file_id: FileId,
+    /// Normalized name of the translation file (e.g. `common` for `common.phrases.txt`).
+    pub file_name: String,
+    pub phrase: Phrase,
+}
+
+/// Result of the resolution of a phrase name from a plugin file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhraseResolution {
+    /// The phrase was found in a translation file loaded by the project.
+    Loaded(Vec<PhraseDef>),
+    /// The phrase exists, but none of the translation files that define it are loaded
+    /// by a `LoadTranslations` call of the project.
+    NotLoaded(Vec<PhraseDef>),
+    /// The phrase does not exist in any known translation file.
+    NotFound,
+}
+
+/// Functions which take a phrase name as their first argument.
+const PHRASE_FUNCTIONS: &[&str] = &["TranslationPhraseExists", "IsTranslatedForLanguage"];
+
+/// Format natives, with the index of their format string argument.
+const FORMAT_FUNCTIONS: &[(&str, usize)] = &[
+    ("Format", 2),
+    ("FormatEx", 2),
+    ("PrintToServer", 0),
+    ("PrintToConsole", 1),
+    ("PrintToConsoleAll", 0),
+    ("PrintToChat", 1),
+    ("PrintToChatAll", 0),
+    ("PrintCenterText", 1),
+    ("PrintCenterTextAll", 0),
+    ("PrintHintText", 1),
+    ("PrintHintTextAll", 0),
+    ("ReplyToCommand", 1),
+    ("ShowActivity", 1),
+    ("ShowActivity2", 2),
+    ("ShowActivityEx", 2),
+    ("ShowHudText", 2),
+    ("ShowSyncHudText", 2),
+    ("LogMessage", 0),
+    ("LogError", 0),
+    ("LogAction", 2),
+    ("LogToFile", 1),
+    ("LogToFileEx", 1),
+    ("ThrowError", 0),
+    ("SetFailState", 0),
+    ("KickClient", 1),
+    ("KickClientEx", 1),
+    ("ServerCommand", 0),
+    ("ClientCommand", 1),
+    ("FakeClientCommand", 1),
+    ("FakeClientCommandEx", 1),
+    ("SetMenuTitle", 1),
+];
+
+/// Returns the indices of the arguments that are phrase names for a format string.
+///
+/// # Parameters
+/// - `format`: The format string, quotes included
+/// - `format_params`: Returns the number of `#format` parameters of a phrase
+/// - `args`: The text of the arguments that follow the format string
+fn phrase_args_of_format(
+    format: &str,
+    args: &[&str],
+    format_params: &dyn Fn(&str) -> usize,
+) -> Vec<usize> {
+    let mut res = Vec::new();
+    let mut idx = 0;
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        // Skip the flags, width and precision of the specifier.
+        let mut conversion = None;
+        for c in chars.by_ref() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | ' ' | '.' | '#') {
+                continue;
+            }
+            conversion = Some(c);
+            break;
+        }
+        match conversion {
+            Some('%') | None => (),
+            Some('t') | Some('T') => {
+                let Some(phrase) = args.get(idx) else {
+                    break;
+                };
+                res.push(idx);
+                idx += 1;
+                if conversion == Some('T') {
+                    // Client index.
+                    idx += 1;
+                }
+                idx += format_params(phrase.trim_matches('"'));
+            }
+            Some(_) => idx += 1,
+        }
+    }
+
+    res
+}
+
+/// Returns the string literal nodes of a call which are phrase names.
+///
+/// # Parameters
+/// - `call_arguments`: The `call_arguments` node of the call
+/// - `source`: The preprocessed source code
+/// - `format_params`: Returns the number of `#format` parameters of a phrase
+/// - `format_index`: Returns the index of the format string parameter of the function called
+///   by a callee node which is not a known format native
+fn phrase_nodes_of_call<'tree>(
+    call_arguments: &Node<'tree>,
+    source: &str,
+    format_params: &dyn Fn(&str) -> usize,
+    format_index: &dyn Fn(&Node) -> Option<usize>,
+) -> Vec<Node<'tree>> {
+    let args = call_arguments
+        .named_children(&mut call_arguments.walk())
+        .filter(|n| TSKind::from(n) != TSKind::comment)
+        .collect::<Vec<_>>();
+    let text = |node: &Node| node.utf8_text(source.as_bytes()).unwrap_or_default();
+
+    let callee =
+        call_arguments
+            .prev_named_sibling()
+            .and_then(|function| match TSKind::from(function) {
+                TSKind::field_access => function.child_by_field_name("field"),
+                _ => Some(function),
+            });
+    let function_name = callee.map(|function| text(&function));
+    if function_name.is_some_and(|name| PHRASE_FUNCTIONS.contains(&name)) {
+        return args
+            .first()
+            .filter(|n| TSKind::from(*n) == TSKind::string_literal)
+            .copied()
+            .into_iter()
+            .collect();
+    }
+
+    let format_idx =
+        match function_name.and_then(|name| FORMAT_FUNCTIONS.iter().find(|(it, _)| *it == name)) {
+            Some((_, idx)) => *idx,
+            None => {
+                // Only resolve the other functions if one of the strings may use a phrase.
+                if !args.iter().any(|n| {
+                    TSKind::from(n) == TSKind::string_literal && {
+                        let text = text(n);
+                        text.contains("%t") || text.contains("%T")
+                    }
+                }) {
+                    return Vec::new();
+                }
+                match callee.and_then(|callee| format_index(&callee)) {
+                    Some(idx) => idx,
+                    None => return Vec::new(),
+                }
+            }
+        };
+    let Some(format_node) = args
+        .get(format_idx)
+        .filter(|n| TSKind::from(*n) == TSKind::string_literal)
+    else {
+        return Vec::new();
+    };
+    let format = text(format_node);
+    if !format.contains("%t") && !format.contains("%T") {
+        return Vec::new();
+    }
+    let following = &args[format_idx + 1..];
+    let following_text = following.iter().map(text).collect::<Vec<_>>();
+    phrase_args_of_format(format, &following_text, format_params)
+        .into_iter()
+        .filter_map(|idx| following.get(idx))
+        .filter(|n| TSKind::from(*n) == TSKind::string_literal)
+        .copied()
+        .collect()
+}
+
+/// Returns the phrase name if the node is a string literal used as a phrase name.
+///
+/// This will check if the node is either:
+/// - the first argument of `TranslationPhraseExists` or `IsTranslatedForLanguage`,
+/// - an argument matching a `%t` or `%T` specifier of the format string of a format native,
+///   or of a function whose last parameters are declared as `const char[] format, any ...`.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `file_id`: The [`FileId`] of the file of the node
+/// - `node`: The node to check
+/// - `source`: The preprocessed source code
+pub fn phrase_name(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+    node: &Node,
+    source: &str,
+) -> Option<String> {
+    if TSKind::from(node) != TSKind::string_literal {
+        return None;
+    }
+    let parent = node.parent()?;
+    if TSKind::from(parent) != TSKind::call_arguments {
+        return None;
+    }
+    let params = |name: &str| format_params(db, file_id, name);
+    let sema = SemanticsImpl::new(db);
+    let format_index = |callee: &Node| declared_format_index(&sema, file_id, callee);
+    if !phrase_nodes_of_call(&parent, source, &params, &format_index).contains(node) {
+        return None;
+    }
+
+    node.utf8_text(source.as_bytes())
+        .ok()
+        .map(|it| it.trim_matches('"').to_string())
+}
+
+/// Returns the index of the format string parameter of the function called by `callee`, if
+/// its last parameters are declared as `const char[] format, any ...`.
+fn declared_format_index(sema: &SemanticsImpl, file_id: FileId, callee: &Node) -> Option<usize> {
+    let DefResolution::Function(function) = sema.find_def(file_id, callee)? else {
+        return None;
+    };
+    let data = sema.db.function_data(function.id());
+    let params = data.params();
+    let [.., format, rest] = params else {
+        return None;
+    };
+    let is_string = |type_ref: &TypeRef| matches!(type_ref, TypeRef::Char | TypeRef::OldString);
+    let is_format = format.is_const
+        && match &format.type_ref {
+            Some(TypeRef::Array((type_ref, 1))) => is_string(type_ref),
+            // The dimension is after the name, e.g. `const char format[]`.
+            Some(type_ref) => is_string(type_ref),
+            None => false,
+        };
+    let is_any_rest = rest.is_rest
+        && match &rest.type_ref {
+            Some(TypeRef::Any) | None => true,
+            Some(TypeRef::OldName(name)) => name.to_string() == "any",
+            Some(_) => false,
+        };
+
+    (is_format && is_any_rest).then_some(params.len() - 2)
+}
+
+/// Returns the number of `#format` parameters of the phrase, 0 if it cannot be resolved.
+fn format_params(db: &dyn HirDatabase, file_id: FileId, name: &str) -> usize {
+    match resolve_phrase(db, file_id, name) {
+        PhraseResolution::Loaded(defs) | PhraseResolution::NotLoaded(defs) => defs
+            .first()
+            .map(|def| def.phrase.format_params())
+            .unwrap_or_default(),
+        PhraseResolution::NotFound => 0,
+    }
+}
+
+/// Returns the normalized names of the translation files loaded by a `LoadTranslations`
+/// call in the project of the file.
+pub(crate) fn loaded_translations_query(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+) -> Arc<FxHashSet<String>> {
+    let file_ids = db
+        .projet_subgraph(file_id)
+        .map(|subgraph| subgraph.file_ids())
+        .unwrap_or_else(|| [file_id].into_iter().collect());
+    let res = file_ids
+        .into_iter()
+        .flat_map(|file_id| {
+            db.file_loaded_translations(file_id)
+                .iter()
+                .map(|it| it.name.clone())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    Arc::new(res)
+}
+
+/// Returns the phrases of all the known translation files, by name.
+///
+/// Only the first phrase with a given name of each translation file is kept.
+pub(crate) fn phrase_defs_query(db: &dyn HirDatabase) -> Arc<FxHashMap<String, Vec<PhraseDef>>> {
+    let mut res: FxHashMap<String, Vec<PhraseDef>> = FxHashMap::default();
+    for (translation_file_id, file_name) in db.known_translation_files().iter() {
+        for phrase in db.phrases_file(*translation_file_id).phrases() {
+            let defs = res.entry(phrase.name.clone()).or_default();
+            if defs
+                .last()
+                .is_some_and(|def| def.file_id == *translation_file_id)
+            {
+                continue;
+            }
+            defs.push(PhraseDef {
+                file_id: *translation_file_id,
+                file_name: file_name.clone(),
+                phrase: phrase.clone(),
+            });
+        }
+    }
+
+    Arc::new(res)
+}
+
+/// Returns all the phrases that can be used from the file, i.e the phrases of the
+/// translation files loaded by the project.
+pub fn available_phrases(db: &dyn HirDatabase, file_id: FileId) -> Vec<PhraseDef> {
+    let loaded = db.loaded_translations(file_id);
+    db.known_translation_files()
+        .iter()
+        .filter(|(_, name)| loaded.contains(name))
+        .flat_map(|(translation_file_id, name)| {
+            db.phrases_file(*translation_file_id)
+                .phrases()
+                .iter()
+                .map(|phrase| PhraseDef {
+                    file_id: *translation_file_id,
+                    file_name: name.clone(),
+                    phrase: phrase.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Resolve a phrase name used in the file.
+pub fn resolve_phrase(db: &dyn HirDatabase, file_id: FileId, name: &str) -> PhraseResolution {
+    let Some(defs) = db.phrase_defs().get(name).cloned() else {
+        return PhraseResolution::NotFound;
+    };
+    let loaded = db.loaded_translations(file_id);
+    if defs.iter().any(|def| loaded.contains(&def.file_name)) {
+        PhraseResolution::Loaded(
+            defs.into_iter()
+                .filter(|def| loaded.contains(&def.file_name))
+                .collect(),
+        )
+    } else {
+        PhraseResolution::NotLoaded(defs)
+    }
+}
+
+fn collect_phrase_nodes<'tree>(
+    node: Node<'tree>,
+    source: &str,
+    format_params: &dyn Fn(&str) -> usize,
+    format_index: &dyn Fn(&Node) -> Option<usize>,
+    acc: &mut Vec<Node<'tree>>,
+) {
+    if TSKind::from(node) == TSKind::call_arguments {
+        acc.extend(phrase_nodes_of_call(
+            &node,
+            source,
+            format_params,
+            format_index,
+        ));
+    }
+    for child in node.children(&mut node.walk()) {
+        collect_phrase_nodes(child, source, format_params, format_index, acc);
+    }
+}
+
+/// Compute the diagnostics of the phrases used in the file.
+///
+/// Phrases are only reported as missing if all the translation files loaded by the
+/// project are known, to avoid false positives when the translations folder is not
+/// part of the workspace.
+pub(crate) fn phrase_diagnostics(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+    tree: &Tree,
+    source: &str,
+) -> (Vec<UnresolvedPhrase>, Vec<PhraseNotLoaded>) {
+    let mut unresolved = Vec::new();
+    let mut not_loaded = Vec::new();
+    let mut nodes = Vec::new();
+    let params = |name: &str| format_params(db, file_id, name);
+    let sema = SemanticsImpl::new(db);
+    let format_index = |callee: &Node| declared_format_index(&sema, file_id, callee);
+    collect_phrase_nodes(tree.root_node(), source, &params, &format_index, &mut nodes);
+    if nodes.is_empty() {
+        return (unresolved, not_loaded);
+    }
+
+    let known_files = db.known_translation_files();
+    let all_loaded_known = db
+        .loaded_translations(file_id)
+        .iter()
+        .all(|name| known_files.iter().any(|(_, it)| it == name));
+    for node in nodes {
+        let Ok(text) = node.utf8_text(source.as_bytes()) else {
+            continue;
+        };
+        let name = text.trim_matches('"').to_string();
+        let range = ts_range_to_lsp_range(&node.range());
+        match resolve_phrase(db, file_id, &name) {
+            PhraseResolution::Loaded(_) => (),
+            PhraseResolution::NotLoaded(defs) => not_loaded.push(PhraseNotLoaded {
+                range,
+                name,
+                translation_files: defs.into_iter().map(|def| def.file_name).collect(),
+            }),
+            PhraseResolution::NotFound if all_loaded_known => {
+                unresolved.push(UnresolvedPhrase { range, name })
+            }
+            PhraseResolution::NotFound => (),
+        }
+    }
+
+    (unresolved, not_loaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::phrase_args_of_format;
+
+    #[test]
+    fn phrase_args() {
+        let no_params = |_: &str| 0;
+        assert_eq!(
+            phrase_args_of_format(r#""%t""#, &[r#""Foo""#], &no_params),
+            vec![0]
+        );
+        assert_eq!(
+            phrase_args_of_format(
+                r#""%s %T %d%% %t""#,
+                &["name", r#""Foo""#, "client", "1", r#""Bar""#],
+                &no_params
+            ),
+            vec![1, 4]
+        );
+        let two_params = |name: &str| if name == "Foo" { 2 } else { 0 };
+        assert_eq!(
+            phrase_args_of_format(
+                r#""%t %t""#,
+                &[r#""Foo""#, "a", "b", r#""Bar""#],
+                &two_params
+            ),
+            vec![0, 3]
+        );
+    }
+}
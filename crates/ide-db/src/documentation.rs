@@ -1,8 +1,29 @@
+use std::sync::Arc;
+
+use base_db::Upcast;
 use completion_data::Event;
+use hir::{db::HirDatabase, DefResolution, HasSource};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::Serialize;
 use syntax::TSKind;
 
+#[salsa::query_group(DocumentationDatabaseStorage)]
+pub trait DocumentationDatabase: HirDatabase + Upcast<dyn HirDatabase> {
+    /// Returns the parsed documentation of a definition.
+    fn doc_comment(&self, def: DefResolution) -> Option<Arc<DocComment>>;
+}
+
+fn doc_comment(db: &dyn DocumentationDatabase, def: DefResolution) -> Option<Arc<DocComment>> {
+    let file_id = def.file_id(db.upcast());
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let node = def.source(db.upcast(), &tree)?.value;
+    let docs = Documentation::from_node(node, source.as_bytes())?;
+
+    Some(Arc::new(docs.parse()))
+}
+
 /// Holds documentation
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Documentation(String);
@@ -61,6 +82,18 @@ impl Documentation {
         &self.0
     }
 
+    /// Returns the documentation written in comments, in their order in the source, without
+    /// the `#pragma deprecated` directives.
+    pub fn from_comments<'a>(comments: impl IntoIterator<Item = &'a str>) -> Documentation {
+        Documentation::new(
+            comments
+                .into_iter()
+                .map(comment_to_doc)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
     pub fn from_node(mut node: tree_sitter::Node, source: &[u8]) -> Option<Documentation> {
         let mut pragma = None;
         let mut docs = Vec::new();
@@ -110,13 +143,7 @@ impl Documentation {
                 let name = node.child_by_field_name("name")?.utf8_text(source).ok()?;
                 let fn_node = node.parent()?.parent()?;
                 let fn_doc = Documentation::from_node(fn_node, source)?;
-                docs = fn_doc
-                    .param_description(name)?
-                    .0
-                    .lines()
-                    .map(|l| l.to_string())
-                    .collect();
-                docs.reverse();
+                docs.push(fn_doc.parse().param(name)?.to_string());
             }
 
             TSKind::function_declaration
@@ -132,6 +159,7 @@ impl Documentation {
             | TSKind::enum_struct_method
             | TSKind::methodmap
             | TSKind::methodmap_alias
+            | TSKind::methodmap_property
             | TSKind::methodmap_method
             | TSKind::methodmap_method_constructor
             | TSKind::methodmap_method_destructor
@@ -186,52 +214,245 @@ impl Documentation {
         Documentation::new(docs.join("\n")).into()
     }
 
+    /// Parses the documentation into its description and its SourceMod tags.
+    pub fn parse(&self) -> DocComment {
+        DocComment::parse(self.as_str())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        self.parse().to_markdown()
+    }
+
+    /// Extracts the deprecation message from the documentation, either from the
+    /// `#pragma deprecated` directive or from the `@deprecated` tag of the comments.
+    pub fn deprecation_message(&self) -> Option<String> {
+        self.parse().deprecation_message().map(String::from)
+    }
+}
+
+/// Documentation comment of a definition, split into its description and the SourceMod tags
+/// (`@param`, `@return`, `@error`, `@note`, `@deprecated` and `@see`).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocComment {
+    pub description: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<ParamDoc>,
+    pub returns: Option<String>,
+    pub errors: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// Message of the `#pragma deprecated` directive, or of the `@deprecated` tag.
+    pub deprecated: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub see: Vec<String>,
+    /// Other tags, such as `@noreturn`, with their text.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub others: Vec<(String, String)>,
+}
+
+/// Description of a parameter, from a `@param` tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParamDoc {
+    pub name: String,
+    pub description: String,
+}
+
+/// Section of a [`DocComment`] the lines of a comment are appended to.
+#[derive(Debug, Clone, Copy)]
+enum Section {
+    Description,
+    Param(usize),
+    Returns,
+    Errors,
+    Note(usize),
+    Deprecated,
+    See(usize),
+    Other(usize),
+}
+
+impl DocComment {
+    /// Parses the text of a documentation, as returned by [`Documentation::from_node`].
+    ///
+    /// The text of a tag continues until the next tag or the next empty line.
+    pub fn parse(text: &str) -> DocComment {
+        let mut res = DocComment::default();
+        let mut section = Section::Description;
+        for line in text.lines() {
+            let line = line.trim();
+            if let Some(message) = line.strip_prefix("DEPRECATED:") {
+                // Added by `Documentation::from_node` for the `#pragma deprecated` directives.
+                res.deprecated = Some(message.trim().to_string());
+                continue;
+            }
+            if line.is_empty() {
+                // Start a new paragraph of the description.
+                let len = res.description.trim_end().len();
+                res.description.truncate(len);
+                if !res.description.is_empty() {
+                    res.description.push_str("\n\n");
+                }
+                section = Section::Description;
+                continue;
+            }
+            let Some(tagged) = line.strip_prefix('@') else {
+                res.append(section, line);
+                continue;
+            };
+            let (tag, text) = tagged
+                .split_once(char::is_whitespace)
+                .map(|(tag, text)| (tag, text.trim()))
+                .unwrap_or((tagged, ""));
+            section = match tag {
+                "param" => {
+                    let (name, description) = text
+                        .split_once(char::is_whitespace)
+                        .map(|(name, description)| (name, description.trim()))
+                        .unwrap_or((text, ""));
+                    res.params.push(ParamDoc {
+                        name: name.to_string(),
+                        description: description.to_string(),
+                    });
+                    Section::Param(res.params.len() - 1)
+                }
+                "return" | "returns" => {
+                    res.returns = Some(text.to_string());
+                    Section::Returns
+                }
+                "error" => {
+                    res.errors = Some(text.to_string());
+                    Section::Errors
+                }
+                "note" => {
+                    res.notes.push(text.to_string());
+                    Section::Note(res.notes.len() - 1)
+                }
+                "deprecated" => {
+                    // The message of the `#pragma deprecated` directive takes precedence.
+                    if res.deprecated.as_deref().map_or(true, str::is_empty) {
+                        res.deprecated = Some(text.to_string());
+                        Section::Deprecated
+                    } else {
+                        Section::Description
+                    }
+                }
+                "see" => {
+                    res.see.push(text.to_string());
+                    Section::See(res.see.len() - 1)
+                }
+                _ => {
+                    res.others.push((tag.to_string(), text.to_string()));
+                    Section::Other(res.others.len() - 1)
+                }
+            };
+        }
+        res.description = res.description.trim().to_string();
+
+        res
+    }
+
+    /// Appends a line of the comment to a section.
+    fn append(&mut self, section: Section, line: &str) {
+        let text = match section {
+            Section::Description => &mut self.description,
+            Section::Param(idx) => &mut self.params[idx].description,
+            Section::Returns => self.returns.get_or_insert_with(String::new),
+            Section::Errors => self.errors.get_or_insert_with(String::new),
+            Section::Note(idx) => &mut self.notes[idx],
+            Section::Deprecated => self.deprecated.get_or_insert_with(String::new),
+            Section::See(idx) => &mut self.see[idx],
+            Section::Other(idx) => &mut self.others[idx].1,
+        };
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(line);
+    }
+
+    /// Returns the description of a parameter.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| param.description.as_str())
+    }
+
+    /// Returns the deprecation message, if the definition is deprecated with a reason.
+    pub fn deprecation_message(&self) -> Option<&str> {
+        self.deprecated
+            .as_deref()
+            .map(str::trim)
+            .filter(|it| !it.is_empty())
+    }
+
     pub fn to_markdown(&self) -> String {
-        lazy_static! {
-            static ref RE1: Regex = Regex::new(r"^\*<").unwrap();
-            static ref RE2: Regex = Regex::new(r"\*\s*\r?\n\s*\*").unwrap();
-            static ref RE3: Regex = Regex::new(r"\r?\n\s*\*").unwrap();
-            static ref RE4: Regex = Regex::new(r"^\*").unwrap();
-            static ref RE5: Regex = Regex::new(r"<").unwrap();
-            static ref RE6: Regex = Regex::new(r">").unwrap();
-            static ref RE7: Regex = Regex::new(r"\s*(@[A-Za-z]+)\s+").unwrap();
-            static ref RE8: Regex = Regex::new(r"(_@param_) ([A-Za-z0-9_.]+)\s*").unwrap();
-            static ref RE9: Regex = Regex::new(r"(\w+\([A-Za-z0-9_ :]*\))").unwrap();
+        let mut sections = Vec::new();
+        match self.deprecated.as_deref().map(str::trim) {
+            Some("") => sections.push("**DEPRECATED**".to_string()),
+            Some(message) => sections.push(format!("**DEPRECATED**: {}", format_text(message))),
+            None => (),
+        }
+        if !self.description.is_empty() {
+            sections.push(format_text(&self.description));
+        }
+        for param in self.params.iter() {
+            sections.push(format!(
+                "_@param_ `{}` — {}",
+                param.name,
+                format_text(&param.description)
+            ));
+        }
+        if let Some(returns) = &self.returns {
+            sections.push(format!("_@return_ {}", format_text(returns)));
+        }
+        if let Some(errors) = &self.errors {
+            sections.push(format!("_@error_ {}", format_text(errors)));
+        }
+        for note in self.notes.iter() {
+            sections.push(format!("_@note_ {}", format_text(note)));
+        }
+        for see in self.see.iter() {
+            sections.push(format!("_@see_ {}", format_text(see)));
+        }
+        for (tag, text) in self.others.iter() {
+            sections.push(
+                format!("_@{}_ {}", tag, format_text(text))
+                    .trim()
+                    .to_string(),
+            );
         }
-        let text = RE1.replace_all(self.as_str(), "").into_owned();
-        let text = RE2.replace_all(&text, "\n\n").into_owned();
-        let text = RE3.replace_all(&text, "").into_owned();
-        let text = RE4.replace_all(&text, "").into_owned();
-        let text = RE5.replace_all(&text, "\\<").into_owned();
-        let text = RE6.replace_all(&text, "\\>").into_owned();
 
-        let text = RE7.replace_all(&text, "\n\n_${1}_ ").into_owned();
-        let text = RE8.replace_all(&text, "${1} `${2}` — >").into_owned();
-        let text = RE9.replace_all(&text, "`${1}`").into_owned();
-        text.replace("DEPRECATED", "\n\n**DEPRECATED**")
-            .trim()
-            .to_string()
+        sections.join("\n\n")
     }
+}
 
-    /// Extracts the description of a parameter from the documentation.
-    pub fn param_description(&self, param_name: &str) -> Option<Self> {
-        let re = Regex::new(&format!(r"\s*@param\s+{}\s+", regex::escape(param_name))).ok()?;
-        let start = re.find(self.as_str())?.end();
-        let end = self.as_str()[start..]
-            .find(|c: char| c == '@')
-            .map(|i| start + i)
-            .unwrap_or_else(|| self.as_str().len());
-        Some(self.as_str()[start..end].trim().to_string().into())
+impl From<&DocComment> for lsp_types::Documentation {
+    fn from(val: &DocComment) -> Self {
+        lsp_types::Documentation::MarkupContent(lsp_types::MarkupContent {
+            kind: lsp_types::MarkupKind::Markdown,
+            value: val.to_markdown(),
+        })
     }
 }
 
+/// Escapes the text of a documentation for markdown, and formats the calls as code.
+fn format_text(text: &str) -> String {
+    lazy_static! {
+        static ref CALL_RE: Regex = Regex::new(r"(\w+\([A-Za-z0-9_ :]*\))").unwrap();
+    }
+    let text = text.replace('<', "\\<").replace('>', "\\>");
+
+    CALL_RE.replace_all(&text, "`${1}`").into_owned()
+}
+
 fn comment_to_doc(text: &str) -> String {
     lazy_static! {
         static ref RE1: Regex = Regex::new(r"^\s*/(?:\*){2}<?\s*").unwrap();
         static ref RE2: Regex = Regex::new(r"\*/$").unwrap();
         static ref RE3: Regex = Regex::new(r"^\s*//\s*").unwrap();
-        static ref RE4: Regex = Regex::new(r"\r?\n\s*\*\s*").unwrap();
-        static ref RE5: Regex = Regex::new(r"^\s*\*\s*").unwrap();
+        // Only strip the leading star of each line, to keep the empty lines.
+        static ref RE4: Regex = Regex::new(r"\r?\n[ \t]*\*[ \t]*").unwrap();
+        static ref RE5: Regex = Regex::new(r"^\s*\*[ \t]*").unwrap();
     }
     let text = RE1.replace_all(text, "").into_owned();
     let text = RE2.replace_all(&text, "").into_owned();
@@ -241,3 +462,85 @@ fn comment_to_doc(text: &str) -> String {
 
     text
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags() {
+        let docs = DocComment::parse(
+            "Kicks a client.\n\n@param client    Client index.\n@param reason    Reason.\n@return          True on success.\n@error           Invalid client.\n@note            First note.\n@note            Second note.\n@see             BanClient\n@noreturn",
+        );
+        assert_eq!(
+            docs,
+            DocComment {
+                description: "Kicks a client.".to_string(),
+                params: vec![
+                    ParamDoc {
+                        name: "client".to_string(),
+                        description: "Client index.".to_string(),
+                    },
+                    ParamDoc {
+                        name: "reason".to_string(),
+                        description: "Reason.".to_string(),
+                    },
+                ],
+                returns: Some("True on success.".to_string()),
+                errors: Some("Invalid client.".to_string()),
+                notes: vec!["First note.".to_string(), "Second note.".to_string()],
+                deprecated: None,
+                see: vec!["BanClient".to_string()],
+                others: vec![("noreturn".to_string(), String::new())],
+            }
+        );
+        assert_eq!(docs.param("reason"), Some("Reason."));
+        assert_eq!(docs.param("unknown"), None);
+    }
+
+    #[test]
+    fn multi_line_tags() {
+        let docs = DocComment::parse(
+            "First paragraph\ncontinued.\n\nSecond paragraph.\n@param client    Client index,\n                 or 0 for the server.\n@return          Number of\n                 clients.\n\nTrailing description.",
+        );
+        assert_eq!(
+            docs.description,
+            "First paragraph\ncontinued.\n\nSecond paragraph.\n\nTrailing description."
+        );
+        assert_eq!(
+            docs.param("client"),
+            Some("Client index,\nor 0 for the server.")
+        );
+        assert_eq!(docs.returns.as_deref(), Some("Number of\nclients."));
+    }
+
+    #[test]
+    fn deprecation() {
+        let docs = DocComment::parse("Old.\n@deprecated    Use New()\n                instead.");
+        assert_eq!(docs.deprecation_message(), Some("Use New()\ninstead."));
+
+        // The message of the `#pragma deprecated` directive takes precedence.
+        let docs = DocComment::parse("DEPRECATED: Use Other()\nOld.\n@deprecated Use New()");
+        assert_eq!(docs.deprecation_message(), Some("Use Other()"));
+        assert_eq!(docs.description, "Old.");
+
+        let docs = DocComment::parse("DEPRECATED: \nOld.");
+        assert_eq!(docs.deprecated.as_deref(), Some(""));
+        assert_eq!(docs.deprecation_message(), None);
+    }
+
+    #[test]
+    fn line_and_block_comments() {
+        let block = Documentation::from_comments([
+            "/**\n * Kicks a client.\n *\n * @param client    Client index.\n */",
+        ]);
+        let line = Documentation::from_comments([
+            "// Kicks a client.",
+            "//",
+            "// @param client    Client index.",
+        ]);
+        assert_eq!(block.parse(), line.parse());
+        assert_eq!(block.parse().description, "Kicks a client.");
+        assert_eq!(block.parse().param("client"), Some("Client index."));
+    }
+}
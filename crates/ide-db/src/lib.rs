@@ -18,7 +18,9 @@ use salsa::{Cancelled, Durability};
 use vfs::FileId;
 
 pub use call_item::{CallItem, IncomingCallItem, OutgoingCallItem};
-pub use documentation::Documentation;
+pub use documentation::{
+    DocComment, Documentation, DocumentationDatabase, DocumentationDatabaseStorage, ParamDoc,
+};
 pub use source_change::SourceChange;
 pub use symbols::{Symbol, SymbolId, Symbols, SymbolsBuilder};
 
@@ -34,7 +36,8 @@ pub type FxIndexMap<K, V> =
     hir_def::db::InternDatabaseStorage,
     hir_def::db::DefDatabaseStorage,
     preprocessor::db::PreprocDatabaseStorage,
-    hir::db::HirDatabaseStorage
+    hir::db::HirDatabaseStorage,
+    DocumentationDatabaseStorage
 )]
 pub struct RootDatabase {
     // We use `ManuallyDrop` here because every codegen unit that contains a
@@ -77,6 +80,9 @@ impl FileLoader for RootDatabase {
     fn known_files(&self) -> Vec<(FileId, base_db::FileExtension)> {
         FileLoaderDelegate(self).known_files()
     }
+    fn known_translation_files(&self) -> Arc<Vec<(FileId, String)>> {
+        FileLoaderDelegate(self).known_translation_files()
+    }
     fn resolve_path(&self, uri: vfs::AnchoredPath<'_>) -> Option<FileId> {
         FileLoaderDelegate(self).resolve_path(uri)
     }
@@ -99,7 +105,13 @@ impl RootDatabase {
             storage: ManuallyDrop::new(salsa::Storage::default()),
         };
         db.set_known_files_with_durability(Default::default(), Durability::HIGH);
+        db.set_known_translation_files_with_durability(Default::default(), Durability::HIGH);
         db.set_source_roots_with_durability(Default::default(), Durability::HIGH);
+        db.set_completion_data_with_durability(
+            Arc::new(completion_data::DATABASE.clone()),
+            Durability::HIGH,
+        );
+        db.set_selected_games_with_durability(Default::default(), Durability::HIGH);
         db.update_parse_query_lru_capacity(lru_capacity);
         db
     }
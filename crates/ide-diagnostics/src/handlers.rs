@@ -1,11 +1,28 @@
+pub(crate) mod constant_condition;
+pub(crate) mod deprecated_usage;
+pub(crate) mod duplicate_case_value;
+pub(crate) mod duplicate_definition;
+pub(crate) mod forward_signature_mismatch;
 pub(crate) mod inactive_code;
+pub(crate) mod incorrect_event_field_accessor;
 pub(crate) mod incorrect_number_of_arguments;
+pub(crate) mod infinite_loop;
 pub(crate) mod invalid_use_of_this;
+pub(crate) mod loop_variable_used_after_loop;
+pub(crate) mod missing_return;
+pub(crate) mod missing_switch_cases;
+pub(crate) mod non_constant_case_label;
+pub(crate) mod phrase_not_loaded;
 pub(crate) mod preprocessor_evaluation_error;
+pub(crate) mod shadowed_variable;
+pub(crate) mod unknown_doc_param;
+pub(crate) mod unreachable_code;
 pub(crate) mod unresolved_constructor;
+pub(crate) mod unresolved_event_field;
 pub(crate) mod unresolved_field;
 pub(crate) mod unresolved_include;
 pub(crate) mod unresolved_inherit;
 pub(crate) mod unresolved_macro;
 pub(crate) mod unresolved_method_call;
 pub(crate) mod unresolved_named_arg;
+pub(crate) mod unresolved_phrase;
@@ -0,0 +1,86 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::constant_condition as f;
+
+// Diagnostic: constant-condition
+//
+// This diagnostic is triggered if the condition of an `if` statement is a compile-time
+// constant, e.g. `if (0)`, which makes one of its branches dead.
+pub(crate) fn constant_condition(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::ConstantCondition,
+) -> Diagnostic {
+    let (code, message) = if d.value {
+        ("206", "redundant test: constant expression is non-zero")
+    } else {
+        ("205", "redundant code: constant expression is zero")
+    };
+    Diagnostic::new_with_syntax_node_ptr(ctx, DiagnosticCode::SpCompWarning(code), message, d.expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn constant_conditions() {
+        let text = r#"
+void main(int a) {
+    if (!0) {
+        a++;
+    }
+    if (0x0) {
+        a++;
+    }
+    if (a) {
+        a++;
+    }
+}
+"#;
+        let files = [("/project/main.sp", text)];
+        assert_eq!(
+            check_diagnostics(&files, "206"),
+            vec![(
+                "!0".to_string(),
+                "redundant test: constant expression is non-zero".to_string()
+            )]
+        );
+        assert_eq!(
+            check_diagnostics(&files, "205"),
+            vec![(
+                "0x0".to_string(),
+                "redundant code: constant expression is zero".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn unary_conditions_span_the_whole_expression() {
+        // The unary expressions are stored with their own node, not the node of their operand.
+        let text = r#"
+void main(int a) {
+    if (~0) {
+        a++;
+    }
+    if (!(1)) {
+        a++;
+    }
+}
+"#;
+        let files = [("/project/main.sp", text)];
+        assert_eq!(
+            check_diagnostics(&files, "206"),
+            vec![(
+                "~0".to_string(),
+                "redundant test: constant expression is non-zero".to_string()
+            )]
+        );
+        assert_eq!(
+            check_diagnostics(&files, "205"),
+            vec![(
+                "!(1)".to_string(),
+                "redundant code: constant expression is zero".to_string()
+            )]
+        );
+    }
+}
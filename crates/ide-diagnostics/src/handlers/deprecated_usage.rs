@@ -0,0 +1,163 @@
+use base_db::FilePosition;
+use hir::DefResolution;
+use hir_def::{InFile, NodePtr};
+use ide_db::DocumentationDatabase;
+use syntax::{utils::ts_range_to_lsp_range, TSKind};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::deprecated_usage as f;
+
+// Diagnostic: deprecated-usage
+//
+// This diagnostic is triggered if an expression, or the type of a declaration or of a
+// `view_as` cast, resolves to an item marked as deprecated with `#pragma deprecated`.
+pub(crate) fn deprecated_usage(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::DeprecatedUsage,
+) -> Diagnostic {
+    let message = deprecation_message(ctx, &d.def);
+    let diagnostic = match usage_range(ctx, d.expr) {
+        Some(range) => Diagnostic::new_for_s_range(ctx, code(), message, range),
+        None => Diagnostic::new_with_syntax_node_ptr(ctx, code(), message, d.expr),
+    };
+
+    diagnostic.with_deprecated(true)
+}
+
+// Diagnostic: deprecated-usage
+//
+// This diagnostic is also triggered if a macro marked as deprecated is expanded. Macros are
+// expanded before the bodies are lowered, so they are found from the preprocessor offsets.
+pub(crate) fn deprecated_macro_usages(ctx: &DiagnosticsContext<'_>, acc: &mut Vec<Diagnostic>) {
+    let preprocessing_results = ctx.sema.preprocess_file(ctx.file_id);
+    for offset in preprocessing_results.offsets().values().flatten() {
+        let fpos = FilePosition {
+            file_id: ctx.file_id,
+            position: offset.range.start,
+        };
+        let Some((_, def)) = ctx.sema.find_macro_def(&fpos) else {
+            continue;
+        };
+        if !def.is_deprecated(ctx.sema.db) {
+            continue;
+        }
+        acc.push(
+            Diagnostic::new_for_u_range(code(), deprecation_message(ctx, &def), offset.range)
+                .with_deprecated(true),
+        );
+    }
+}
+
+fn code() -> DiagnosticCode {
+    DiagnosticCode::Lint("deprecated-usage", Severity::Warning)
+}
+
+/// Returns the range of the type of a declaration or of a cast, or of the usage itself.
+fn usage_range(ctx: &DiagnosticsContext<'_>, ptr: InFile<NodePtr>) -> Option<lsp_types::Range> {
+    let tree = ctx.sema.parse(ptr.file_id);
+    let node = ptr.value.to_node(&tree)?;
+    let type_ = match TSKind::from(node) {
+        // The type of a new style declaration is shared by its variables.
+        TSKind::variable_declaration | TSKind::dynamic_array_declaration => {
+            node.parent()?.child_by_field_name("type")
+        }
+        TSKind::old_variable_declaration
+        | TSKind::parameter_declaration
+        | TSKind::view_as
+        | TSKind::old_type_cast => node.child_by_field_name("type"),
+        _ => None,
+    };
+
+    Some(ts_range_to_lsp_range(&type_.unwrap_or(node).range()))
+}
+
+/// Returns the message of the diagnostic, with the reason of the deprecation if the
+/// documentation of the definition gives one.
+fn deprecation_message(ctx: &DiagnosticsContext<'_>, def: &DefResolution) -> String {
+    let db = ctx.sema.db;
+    let name = def
+        .name(db)
+        .map(|name| name.to_string())
+        .unwrap_or_default();
+    let docs = db.doc_comment(def.clone());
+
+    match docs.as_deref().and_then(|docs| docs.deprecation_message()) {
+        Some(reason) => format!("`{}` is deprecated: {}", name, reason),
+        None => format!("`{}` is deprecated", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn deprecated_calls_and_accesses() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+#pragma deprecated Use Bar instead
+void Foo() {}
+
+enum struct Point {
+#pragma deprecated
+    int x;
+    int y;
+}
+
+void main() {
+    Foo();
+    Function callback = Foo;
+    Point point;
+    point.x = point.y;
+}
+"#,
+            )],
+            "deprecated-usage",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                (
+                    "Foo".to_string(),
+                    "`Foo` is deprecated: Use Bar instead".to_string()
+                ),
+                (
+                    "Foo".to_string(),
+                    "`Foo` is deprecated: Use Bar instead".to_string()
+                ),
+                ("x".to_string(), "`x` is deprecated".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn deprecated_types_of_declarations() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+#pragma deprecated
+methodmap OldMap < Handle {}
+
+void Use(OldMap map) {
+    OldMap a, b;
+    Handle c = view_as<OldMap>(map);
+    Handle d;
+}
+"#,
+            )],
+            "deprecated-usage",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                ("OldMap".to_string(), "`OldMap` is deprecated".to_string()),
+                ("OldMap".to_string(), "`OldMap` is deprecated".to_string()),
+                ("OldMap".to_string(), "`OldMap` is deprecated".to_string()),
+            ]
+        );
+    }
+}
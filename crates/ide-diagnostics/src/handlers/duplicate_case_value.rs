@@ -0,0 +1,88 @@
+use preprocessor::s_range_to_u_range;
+use syntax::utils::ts_range_to_lsp_range;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, RelatedInformation};
+
+pub(crate) use self::duplicate_case_value as f;
+
+// Diagnostic: duplicate-case-value
+//
+// This diagnostic is triggered if two `case` labels of a `switch` statement have the same
+// value, e.g. `case 1, 1:` or two variants with the same value.
+pub(crate) fn duplicate_case_value(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::DuplicateCaseValue,
+) -> Diagnostic {
+    let tree = ctx.sema.parse(d.first.file_id);
+    let related = d
+        .first
+        .value
+        .to_node(&tree)
+        .map(|node| {
+            let preprocessing_results = ctx.sema.preprocess_file(d.first.file_id);
+            RelatedInformation {
+                file_id: d.first.file_id,
+                range: s_range_to_u_range(
+                    preprocessing_results.offsets(),
+                    ts_range_to_lsp_range(&node.range()),
+                ),
+                message: format!("value `{}` is first used here", d.value),
+            }
+        })
+        .into_iter()
+        .collect();
+
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::SpCompError("040"),
+        format!("duplicate `case` label (value {})", d.value),
+        d.expr,
+    )
+    .with_related(related)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn duplicate_cells() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+enum Flags {
+    Flag_None,
+    Flag_All = -1,
+}
+
+void main(int value) {
+    switch (value) {
+        case 0xFFFFFFFF, 1 << 1: {}
+        case -1, 2: {}
+        case Flag_None, Flag_All, 3: {}
+    }
+}
+"#,
+            )],
+            "040",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                (
+                    "-1".to_string(),
+                    "duplicate `case` label (value -1)".to_string()
+                ),
+                (
+                    "2".to_string(),
+                    "duplicate `case` label (value 2)".to_string()
+                ),
+                (
+                    "Flag_All".to_string(),
+                    "duplicate `case` label (value -1)".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use hir::DuplicateDefinitionKind;
+use preprocessor::s_range_to_u_range;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, RelatedInformation};
+
+pub(crate) use self::duplicate_definition as f;
+
+// Diagnostic: duplicate-definition
+//
+// This diagnostic is triggered if a definition has the name of another definition of the
+// project, if a methodmap or an enum struct has two members with the same name, or if a
+// macro is redefined with a different value.
+pub(crate) fn duplicate_definition(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::DuplicateDefinition,
+) -> Diagnostic {
+    let (code, message) = match &d.kind {
+        DuplicateDefinitionKind::Item => (
+            DiagnosticCode::SpCompError("021"),
+            format!("`{}` is already defined", d.name),
+        ),
+        DuplicateDefinitionKind::Macro => (
+            DiagnosticCode::SpCompWarning("201"),
+            format!("macro `{}` is redefined with a different value", d.name),
+        ),
+        DuplicateDefinitionKind::MethodmapMember(parent) => (
+            DiagnosticCode::SpCompError("021"),
+            format!("methodmap `{}` already has a member `{}`", parent, d.name),
+        ),
+        DuplicateDefinitionKind::EnumStructMember(parent) => (
+            DiagnosticCode::SpCompError("021"),
+            format!("enum struct `{}` already has a member `{}`", parent, d.name),
+        ),
+    };
+    let other_offsets = ctx.sema.preprocess_file(d.other_file_id);
+
+    Diagnostic::new_for_s_range(ctx, code, message, d.range).with_related(vec![
+        RelatedInformation {
+            file_id: d.other_file_id,
+            range: s_range_to_u_range(other_offsets.offsets(), d.other_range),
+            message: format!("`{}` is first defined here", d.name),
+        },
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn later_definition_in_include_order() {
+        let files = [
+            ("/project/main.sp", "#include \"a.sp\"\nint foo;\n"),
+            ("/project/a.sp", "int foo;\n"),
+        ];
+        assert_eq!(
+            check_diagnostics(&files, "021"),
+            vec![("foo".to_string(), "`foo` is already defined".to_string())]
+        );
+        assert_eq!(check_diagnostics(&[files[1], files[0]], "021"), vec![]);
+    }
+
+    #[test]
+    fn definition_before_include() {
+        let files = [
+            ("/project/main.sp", "int foo;\n#include \"a.sp\"\n"),
+            ("/project/a.sp", "int foo;\n"),
+        ];
+        assert_eq!(check_diagnostics(&files, "021"), vec![]);
+        assert_eq!(
+            check_diagnostics(&[files[1], files[0]], "021"),
+            vec![("foo".to_string(), "`foo` is already defined".to_string())]
+        );
+    }
+
+    #[test]
+    fn forward_and_implementation() {
+        let files = [(
+            "/project/main.sp",
+            "forward void OnPluginStart();\npublic void OnPluginStart() {}\nint OnPluginStart;\n",
+        )];
+        assert_eq!(
+            check_diagnostics(&files, "021"),
+            vec![(
+                "OnPluginStart".to_string(),
+                "`OnPluginStart` is already defined".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn macro_redefined_after_undef() {
+        let files = [(
+            "/project/main.sp",
+            "#define MAX 1\n#undef MAX\n#define MAX 2\n#define MIN 1\n#define MIN 2\n#undef MIN\n",
+        )];
+        assert_eq!(
+            check_diagnostics(&files, "201"),
+            vec![(
+                "MIN".to_string(),
+                "macro `MIN` is redefined with a different value".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::forward_signature_mismatch as f;
+
+// Diagnostic: forward-signature-mismatch
+//
+// This diagnostic is triggered if a `public` function has the name of a forward but does
+// not match its signature, e.g. `public void OnClientPutInServer(int client, int team)`.
+// SourceMod never calls such a function.
+pub(crate) fn forward_signature_mismatch(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::ForwardSignatureMismatch,
+) -> Diagnostic {
+    Diagnostic::new_for_s_range(
+        ctx,
+        DiagnosticCode::Lint("forward-signature-mismatch", Severity::Warning),
+        format!(
+            "`{}` does not match the signature of its forward `{}`: {}",
+            d.name, d.forward_signature, d.reason
+        ),
+        d.range,
+    )
+}
@@ -0,0 +1,22 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::incorrect_event_field_accessor as f;
+
+// Diagnostic: incorrect-event-field-accessor
+//
+// This diagnostic is triggered if an event field is accessed with an accessor that
+// does not match its type, e.g. `event.GetString("userid")`.
+pub(crate) fn incorrect_event_field_accessor(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::IncorrectEventFieldAccessor,
+) -> Diagnostic {
+    Diagnostic::new_for_s_range(
+        ctx,
+        DiagnosticCode::Lint("incorrect-event-field-accessor", Severity::Warning),
+        format!(
+            "event field `{}` is of type `{}` but is accessed with `{}`",
+            d.field, d.field_type, d.accessor
+        ),
+        d.range,
+    )
+}
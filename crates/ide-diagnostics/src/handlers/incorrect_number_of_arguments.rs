@@ -20,5 +20,5 @@ pub(crate) fn incorrect_number_of_arguments(
             d.expected, d.name, d.actual
         )
     };
-    Diagnostic::new_with_syntax_node_ptr(ctx, DiagnosticCode::SpCompError("E0000"), message, d.expr)
+    Diagnostic::new_with_syntax_node_ptr(ctx, DiagnosticCode::SpCompError("092"), message, d.expr)
 }
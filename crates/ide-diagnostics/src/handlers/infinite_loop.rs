@@ -0,0 +1,62 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::infinite_loop as f;
+
+// Diagnostic: infinite-loop
+//
+// This diagnostic is triggered for a loop whose condition is always true, such as
+// `while (true)`, and which contains no `break` or `return`.
+pub(crate) fn infinite_loop(ctx: &DiagnosticsContext<'_>, d: &hir::InfiniteLoop) -> Diagnostic {
+    Diagnostic::new_for_s_range(
+        ctx,
+        DiagnosticCode::Lint("infinite-loop", Severity::Warning),
+        "this loop never exits",
+        d.range,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn loops_without_exit() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+void forever(int a) {
+    while (true) {
+        a++;
+    }
+}
+
+void exits(int a) {
+    for (;;) {
+        if (a) {
+            break;
+        }
+    }
+    while (true) {
+        return;
+    }
+}
+
+void do_while(int a) {
+    do {
+        a++;
+    } while (true);
+}
+"#,
+            )],
+            "infinite-loop",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                ("while".to_string(), "this loop never exits".to_string()),
+                ("do".to_string(), "this loop never exits".to_string()),
+            ]
+        );
+    }
+}
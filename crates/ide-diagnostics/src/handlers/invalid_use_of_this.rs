@@ -11,7 +11,7 @@ pub(crate) fn invalid_use_of_this(
 ) -> Diagnostic {
     Diagnostic::new_with_syntax_node_ptr(
         ctx,
-        DiagnosticCode::SpCompError("E0000"),
+        DiagnosticCode::SpCompError("166"),
         "`this` can only be used in methods",
         d.expr,
     )
@@ -0,0 +1,79 @@
+use preprocessor::s_range_to_u_range;
+use syntax::utils::ts_range_to_lsp_range;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, RelatedInformation};
+
+pub(crate) use self::loop_variable_used_after_loop as f;
+
+// Diagnostic: loop-variable-used-after-loop
+//
+// This diagnostic is triggered if a variable declared in the initialization of a `for` loop
+// is used after the loop, where it is out of scope. Its severity is set with
+// `SourcePawnLanguageServer.diagnostics.loopVariables.severity`.
+pub(crate) fn loop_variable_used_after_loop(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::LoopVariableUsedAfterLoop,
+) -> Diagnostic {
+    let tree = ctx.sema.parse(d.binding.file_id);
+    let related = d
+        .binding
+        .value
+        .to_node(&tree)
+        .map(|node| {
+            let preprocessing_results = ctx.sema.preprocess_file(d.binding.file_id);
+            RelatedInformation {
+                file_id: d.binding.file_id,
+                range: s_range_to_u_range(
+                    preprocessing_results.offsets(),
+                    ts_range_to_lsp_range(&node.range()),
+                ),
+                message: format!("`{}` is declared in the loop here", d.name),
+            }
+        })
+        .into_iter()
+        .collect();
+
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Lint(
+            "loop-variable-used-after-loop",
+            ctx.config.loop_variables_severity,
+        ),
+        format!("loop variable `{}` is used after its loop", d.name),
+        d.expr,
+    )
+    .with_related(related)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn loop_variables_after_their_loop() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+void main() {
+    for (int i = 0; i < 10; i++) {
+        i++;
+    }
+    i = 0;
+    int j;
+    for (int j = 0; j < 10; j++) {}
+    j = 1;
+}
+"#,
+            )],
+            "loop-variable-used-after-loop",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![(
+                "i".to_string(),
+                "loop variable `i` is used after its loop".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::missing_return as f;
+
+// Diagnostic: missing-return
+//
+// This diagnostic is triggered if a function which returns a value can reach its end
+// without a `return` statement.
+pub(crate) fn missing_return(ctx: &DiagnosticsContext<'_>, d: &hir::MissingReturn) -> Diagnostic {
+    Diagnostic::new_for_s_range(
+        ctx,
+        DiagnosticCode::SpCompWarning("209"),
+        format!("function `{}` should return a value", d.name),
+        d.range,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    fn missing_returns(text: &str) -> Vec<String> {
+        check_diagnostics(&[("/project/main.sp", text)], "209")
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect()
+    }
+
+    #[test]
+    fn if_else() {
+        assert_eq!(
+            missing_returns(
+                r#"
+int both(int a) {
+    if (a) {
+        return 1;
+    } else {
+        return 2;
+    }
+}
+
+int then_only(int a) {
+    if (a) {
+        return 1;
+    }
+}
+"#
+            ),
+            vec!["function `then_only` should return a value"]
+        );
+    }
+
+    #[test]
+    fn switch() {
+        assert_eq!(
+            missing_returns(
+                r#"
+int with_default(int a) {
+    switch (a) {
+        case 1: {
+            return 1;
+        }
+        default: {
+            return 2;
+        }
+    }
+}
+
+int without_default(int a) {
+    switch (a) {
+        case 1: {
+            return 1;
+        }
+    }
+}
+"#
+            ),
+            vec!["function `without_default` should return a value"]
+        );
+    }
+
+    #[test]
+    fn loops() {
+        assert_eq!(
+            missing_returns(
+                r#"
+int forever() {
+    while (true) {
+    }
+}
+
+int until_break(int a) {
+    for (;;) {
+        if (a) {
+            break;
+        }
+    }
+}
+
+int conditional(int a) {
+    while (a) {
+        return 1;
+    }
+}
+"#
+            ),
+            vec![
+                "function `until_break` should return a value",
+                "function `conditional` should return a value"
+            ]
+        );
+    }
+
+    #[test]
+    fn implicit_return_type() {
+        assert_eq!(
+            missing_returns(
+                r#"
+public OnPluginStart() {
+    return;
+}
+
+public Returns(a) {
+    if (a) {
+        return 1;
+    }
+}
+"#
+            ),
+            vec!["function `Returns` should return a value"]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use syntax::utils::ts_range_to_lsp_range;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::missing_switch_cases as f;
+
+// Diagnostic: missing-switch-cases
+//
+// This diagnostic is triggered if a `switch` statement over an enum has no `default` case
+// and does not handle all the variants of the enum. Its severity is set with
+// `SourcePawnLanguageServer.diagnostics.switchCases.severity`.
+pub(crate) fn missing_switch_cases(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::MissingSwitchCases,
+) -> Diagnostic {
+    let missing = d
+        .missing
+        .iter()
+        .map(|it| format!("`{}`", it))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = format!("missing cases for `{}`: {}", d.enum_name, missing);
+    let code = DiagnosticCode::Lint("missing-switch-cases", Severity::Warning);
+
+    // Only highlight the condition of the switch, not its whole body.
+    let tree = ctx.sema.parse(d.expr.file_id);
+    let diagnostic = match d
+        .expr
+        .value
+        .to_node(&tree)
+        .and_then(|node| node.child_by_field_name("condition"))
+    {
+        Some(condition) => Diagnostic::new_for_s_range(
+            ctx,
+            code,
+            message,
+            ts_range_to_lsp_range(&condition.range()),
+        ),
+        None => Diagnostic::new_with_syntax_node_ptr(ctx, code, message, d.expr),
+    };
+
+    diagnostic.with_severity(ctx.config.switch_cases_severity)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn missing_variants() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+enum Team {
+    Team_Red,
+    Team_Blue,
+    Team_Green,
+}
+
+void main(Team team) {
+    switch (team) {
+        case Team_Red: {}
+    }
+    switch (team) {
+        case Team_Red: {}
+        default: {}
+    }
+    switch (team) {
+        case Team_Red, Team_Blue, Team_Green: {}
+    }
+}
+"#,
+            )],
+            "missing-switch-cases",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![(
+                "team".to_string(),
+                "missing cases for `Team`: `Team_Blue`, `Team_Green`".to_string()
+            )]
+        );
+    }
+}
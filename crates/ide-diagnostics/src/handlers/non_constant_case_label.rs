@@ -0,0 +1,56 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::non_constant_case_label as f;
+
+// Diagnostic: non-constant-case-label
+//
+// This diagnostic is triggered if a `case` label is not a compile-time constant, such as a
+// local variable or a function call.
+pub(crate) fn non_constant_case_label(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::NonConstantCaseLabel,
+) -> Diagnostic {
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::SpCompError("008"),
+        "`case` labels must be constant expressions",
+        d.expr,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn non_constant_labels() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+const int MAX = 10;
+int g_Count;
+
+void main(int value) {
+    int local = 2;
+    switch (value) {
+        case MAX: {}
+        case g_Count: {}
+        case local: {}
+        case MAX + 1: {}
+    }
+}
+"#,
+            )],
+            "008",
+        );
+        let message = "`case` labels must be constant expressions".to_string();
+        assert_eq!(
+            diagnostics,
+            vec![
+                ("g_Count".to_string(), message.clone()),
+                ("local".to_string(), message),
+            ]
+        );
+    }
+}
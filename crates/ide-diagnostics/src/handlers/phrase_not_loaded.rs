@@ -0,0 +1,28 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::phrase_not_loaded as f;
+
+// Diagnostic: phrase-not-loaded
+//
+// This diagnostic is triggered if a translation phrase exists, but none of the
+// translation files that define it are loaded with `LoadTranslations` in the project.
+pub(crate) fn phrase_not_loaded(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::PhraseNotLoaded,
+) -> Diagnostic {
+    let files = d
+        .translation_files
+        .iter()
+        .map(|it| format!("`{}.phrases`", it))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Diagnostic::new_for_s_range(
+        ctx,
+        DiagnosticCode::Lint("phrase-not-loaded", Severity::Warning),
+        format!(
+            "translation phrase `{}` is defined in {} which is not loaded by `LoadTranslations`",
+            d.name, files
+        ),
+        d.range,
+    )
+}
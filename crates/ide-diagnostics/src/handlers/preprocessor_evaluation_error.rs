@@ -1,17 +1,17 @@
-use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
 
 pub(crate) use self::preprocessor_evaluation_error as f;
 
-// Diagnostic: unresolved-field
+// Diagnostic: preprocessor-evaluation-error
 //
-// This diagnostic is triggered if a field does not exist on a given type.
+// This diagnostic is triggered if the condition of an `#if` cannot be evaluated.
 pub(crate) fn preprocessor_evaluation_error(
     ctx: &DiagnosticsContext<'_>,
     d: &hir::PreprocessorEvaluationError,
 ) -> Diagnostic {
     Diagnostic::new_for_s_range(
         ctx,
-        DiagnosticCode::SpCompError("E0000"),
+        DiagnosticCode::Lint("preprocessor-evaluation-error", Severity::Error),
         d.text.to_owned(),
         d.range,
     )
@@ -0,0 +1,113 @@
+use hir::ShadowedVariableKind;
+use hir_def::{InFile, NodePtr};
+use preprocessor::s_range_to_u_range;
+use syntax::utils::ts_range_to_lsp_range;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, RelatedInformation};
+
+pub(crate) use self::shadowed_variable as f;
+
+// Diagnostic: shadowed-variable
+//
+// This diagnostic is triggered if a local variable has the name of a parameter, a global or
+// a local variable of an enclosing block. Its severity is set with
+// `SourcePawnLanguageServer.diagnostics.shadowing.severity`.
+pub(crate) fn shadowed_variable(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::ShadowedVariable,
+) -> Diagnostic {
+    let kind = match d.kind {
+        ShadowedVariableKind::Parameter => "parameter",
+        ShadowedVariableKind::Local => "local variable",
+        ShadowedVariableKind::Global => "global variable",
+    };
+    let related = declaration_range(ctx, d.shadowed)
+        .map(|range| {
+            let preprocessing_results = ctx.sema.preprocess_file(d.shadowed.file_id);
+            RelatedInformation {
+                file_id: d.shadowed.file_id,
+                range: s_range_to_u_range(preprocessing_results.offsets(), range),
+                message: format!("shadowed {} `{}` is declared here", kind, d.name),
+            }
+        })
+        .into_iter()
+        .collect();
+    let message = format!("local variable `{}` shadows a {}", d.name, kind);
+    let diagnostic = match declaration_range(ctx, d.expr) {
+        Some(range) => {
+            Diagnostic::new_for_s_range(ctx, DiagnosticCode::SpCompWarning("219"), message, range)
+        }
+        None => Diagnostic::new_with_syntax_node_ptr(
+            ctx,
+            DiagnosticCode::SpCompWarning("219"),
+            message,
+            d.expr,
+        ),
+    };
+
+    diagnostic
+        .with_severity(ctx.config.shadowing_severity)
+        .with_related(related)
+}
+
+/// Returns the range of the name of a declaration.
+fn declaration_range(
+    ctx: &DiagnosticsContext<'_>,
+    ptr: InFile<NodePtr>,
+) -> Option<lsp_types::Range> {
+    let tree = ctx.sema.parse(ptr.file_id);
+    let node = ptr.value.to_node(&tree)?;
+    let name = node.child_by_field_name("name").unwrap_or(node);
+
+    Some(ts_range_to_lsp_range(&name.range()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn shadowed_variables() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+int g_Count;
+
+void main(int client) {
+    int client = 1;
+    int g_Count = 2;
+    int i;
+    for (int i = 0; i < 10; i++) {
+        int i = 3;
+    }
+    for (int j = 0; j < 10; j++) {}
+    for (int j = 0; j < 10; j++) {}
+}
+"#,
+            )],
+            "219",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                (
+                    "client".to_string(),
+                    "local variable `client` shadows a parameter".to_string()
+                ),
+                (
+                    "g_Count".to_string(),
+                    "local variable `g_Count` shadows a global variable".to_string()
+                ),
+                (
+                    "i".to_string(),
+                    "local variable `i` shadows a local variable".to_string()
+                ),
+                (
+                    "i".to_string(),
+                    "local variable `i` shadows a local variable".to_string()
+                ),
+            ]
+        );
+    }
+}
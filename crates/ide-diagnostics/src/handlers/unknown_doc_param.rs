@@ -0,0 +1,72 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::unknown_doc_param as f;
+
+// Diagnostic: unknown-doc-param
+//
+// This diagnostic is triggered if the documentation of a function or a method has a `@param`
+// tag whose name does not match any of its parameters.
+pub(crate) fn unknown_doc_param(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnknownDocParam,
+) -> Diagnostic {
+    Diagnostic::new_for_s_range(
+        ctx,
+        DiagnosticCode::Lint("unknown-doc-param", Severity::Warning),
+        format!(
+            "`@param {}` does not match any parameter of `{}`",
+            d.name, d.function
+        ),
+        d.range,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn unknown_params() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+/**
+ * Adds two values.
+ *
+ * @param a     First value.
+ * @param c     Second value.
+ * @return      The sum.
+ */
+int Add(int a, int b) { return a + b; }
+
+// @param format    Format.
+// @param ...       Arguments.
+native void Print(const char[] format, any ...);
+
+methodmap Counter < Handle {
+    /**
+     * @param amount    Amount to add.
+     * @param client    Client index.
+     */
+    public native void Add(int amount);
+}
+"#,
+            )],
+            "unknown-doc-param",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                (
+                    "c".to_string(),
+                    "`@param c` does not match any parameter of `Add`".to_string()
+                ),
+                (
+                    "client".to_string(),
+                    "`@param client` does not match any parameter of `Add`".to_string()
+                ),
+            ]
+        );
+    }
+}
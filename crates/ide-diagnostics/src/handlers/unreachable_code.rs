@@ -0,0 +1,142 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::unreachable_code as f;
+
+// Diagnostic: unreachable-code
+//
+// This diagnostic is triggered for the code which follows a `return`, a `break` or a
+// `continue`, and can never be executed.
+pub(crate) fn unreachable_code(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnreachableCode,
+) -> Diagnostic {
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::SpCompWarning("225"),
+        "unreachable code",
+        d.expr,
+    )
+    .with_unused(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    fn unreachable(text: &str) -> Vec<String> {
+        check_diagnostics(&[("/project/main.sp", text)], "225")
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect()
+    }
+
+    #[test]
+    fn after_early_return() {
+        assert_eq!(
+            unreachable(
+                r#"
+void main(int a) {
+    if (a) {
+        return;
+        a++;
+        a--;
+    }
+    a = 1;
+}
+"#
+            ),
+            vec!["a++"]
+        );
+    }
+
+    #[test]
+    fn after_break_and_continue() {
+        assert_eq!(
+            unreachable(
+                r#"
+void main(int a) {
+    for (int i = 0; i < a; i++) {
+        if (i == 2) {
+            continue;
+            a--;
+        }
+        break;
+        a = 3;
+    }
+    while (a) {
+        break;
+    }
+    a = 4;
+}
+"#
+            ),
+            vec!["a--", "a = 3"]
+        );
+    }
+
+    #[test]
+    fn after_do_while() {
+        assert_eq!(
+            unreachable(
+                r#"
+void main(int a) {
+    do {
+        break;
+        a = 2;
+    } while (a);
+    a = 3;
+}
+"#
+            ),
+            vec!["a = 2"]
+        );
+    }
+
+    #[test]
+    fn after_switch() {
+        assert_eq!(
+            unreachable(
+                r#"
+void main(int a) {
+    switch (a) {
+        case 1: {
+            return;
+        }
+        default: {
+            return;
+        }
+    }
+    a = 1;
+}
+
+void other(int a) {
+    switch (a) {
+        case 1: {
+            return;
+        }
+    }
+    a = 1;
+}
+"#
+            ),
+            vec!["a = 1"]
+        );
+    }
+
+    #[test]
+    fn while_false() {
+        assert_eq!(
+            unreachable(
+                r#"
+void main(int a) {
+    while (false) {
+        a = 1;
+    }
+    a = 2;
+}
+"#
+            ),
+            vec!["a = 1"]
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::unresolved_event_field as f;
+
+// Diagnostic: unresolved-event-field
+//
+// This diagnostic is triggered if an event callback accesses a field that does not
+// exist on the events it is hooked to.
+pub(crate) fn unresolved_event_field(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedEventField,
+) -> Diagnostic {
+    let events = d
+        .events
+        .iter()
+        .map(|it| format!("`{}`", it))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Diagnostic::new_for_s_range(
+        ctx,
+        DiagnosticCode::Lint("unresolved-event-field", Severity::Warning),
+        format!("no field `{}` on event {}", d.field, events),
+        d.range,
+    )
+}
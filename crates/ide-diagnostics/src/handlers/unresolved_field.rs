@@ -16,7 +16,7 @@ pub(crate) fn unresolved_field(
     };
     Diagnostic::new_with_syntax_node_ptr(
         ctx,
-        DiagnosticCode::SpCompError("E0000"),
+        DiagnosticCode::SpCompError("105"),
         format!(
             "no field `{}` on type `{}`{method_suffix}",
             d.name, d.receiver
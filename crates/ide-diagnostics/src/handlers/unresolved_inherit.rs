@@ -14,5 +14,5 @@ pub(crate) fn unresolved_inherit(
     } else {
         format!("methodmap `{}` does not exist", d.inherit)
     };
-    Diagnostic::new_with_syntax_node_ptr(ctx, DiagnosticCode::SpCompError("E0000"), message, d.expr)
+    Diagnostic::new_with_syntax_node_ptr(ctx, DiagnosticCode::SpCompError("017"), message, d.expr)
 }
@@ -11,7 +11,7 @@ pub(crate) fn unresolved_macro(
 ) -> Diagnostic {
     Diagnostic::new_for_s_range(
         ctx,
-        DiagnosticCode::SpCompError("E0000"),
+        DiagnosticCode::SpCompError("017"),
         format!("no macro `{}` found", d.name),
         d.range,
     )
@@ -16,7 +16,7 @@ pub(crate) fn unresolved_method_call(
     };
     Diagnostic::new_with_syntax_node_ptr(
         ctx,
-        DiagnosticCode::SpCompError("E0000"),
+        DiagnosticCode::SpCompError("105"),
         format!(
             "no method `{}` on type `{}`{field_suffix}",
             d.name, d.receiver
@@ -1,4 +1,4 @@
-use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
 
 pub(crate) use self::unresolved_named_arg as f;
 
@@ -11,8 +11,35 @@ pub(crate) fn unresolved_named_arg(
 ) -> Diagnostic {
     Diagnostic::new_with_syntax_node_ptr(
         ctx,
-        DiagnosticCode::SpCompError("E0000"),
+        DiagnosticCode::Lint("unresolved-named-argument", Severity::Error),
         format!("no parameter `{}` found for `{}`", d.name, d.callee),
         d.expr,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn unknown_named_argument() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+void Foo(int bar, int baz = 0) {}
+
+void main() {
+    Foo(.bar = 1, .qux = 2);
+}
+"#,
+            )],
+            "unresolved-named-argument",
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].1,
+            "no parameter `qux` found for `Foo`".to_string()
+        );
+    }
+}
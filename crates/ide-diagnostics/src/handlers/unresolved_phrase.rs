@@ -0,0 +1,67 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::unresolved_phrase as f;
+
+// Diagnostic: unresolved-phrase
+//
+// This diagnostic is triggered if a translation phrase used with `%t`/`%T` is not
+// defined in any of the translation files loaded by the project.
+pub(crate) fn unresolved_phrase(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedPhrase,
+) -> Diagnostic {
+    Diagnostic::new_for_s_range(
+        ctx,
+        DiagnosticCode::Lint("unresolved-phrase", Severity::Warning),
+        format!("no translation phrase `{}` found", d.name),
+        d.range,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn phrases_of_declared_format_functions() {
+        let diagnostics = check_diagnostics(
+            &[(
+                "/project/main.sp",
+                r#"
+native void ReplaceString(char[] text, int maxlength, const char[] search, const char[] replace);
+void PrintAll(const char[] format, any ...) {}
+
+methodmap Panel < Handle {
+    public native void SetTitle(const char[] text, bool onlyIfEmpty = false);
+}
+
+methodmap Menu < Handle {
+    public native void SetTitle(const char[] fmt, any ...);
+}
+
+void Test(Panel panel, Menu menu) {
+    char buffer[64];
+    ReplaceString(buffer, sizeof(buffer), "%t", "Replacement");
+    panel.SetTitle("%t", true);
+    menu.SetTitle("%t", "Menu Phrase");
+    PrintAll("%T", "Print Phrase", 0);
+}
+"#,
+            )],
+            "unresolved-phrase",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                (
+                    r#""Menu Phrase""#.to_string(),
+                    "no translation phrase `Menu Phrase` found".to_string()
+                ),
+                (
+                    r#""Print Phrase""#.to_string(),
+                    "no translation phrase `Print Phrase` found".to_string()
+                ),
+            ]
+        );
+    }
+}
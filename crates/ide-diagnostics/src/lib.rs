@@ -11,9 +11,14 @@ use vfs::FileId;
 
 mod handlers;
 mod queries;
+#[cfg(test)]
+mod tests;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum DiagnosticCode {
+    /// Error which spcomp also reports, with the code of the equivalent spcomp error, or
+    /// `syntax-error` and `missing-node` for the syntax errors, which match several spcomp
+    /// errors. The server uses the code to only show one of them.
     SpCompError(&'static str),
     SpCompWarning(&'static str),
     Lint(&'static str, Severity),
@@ -36,7 +41,10 @@ pub struct Diagnostic {
     pub range: lsp_types::Range,
     pub severity: Severity,
     pub unused: bool,
+    pub deprecated: bool,
     pub experimental: bool,
+    /// Other locations related to the diagnostic, such as a previous definition.
+    pub related: Vec<RelatedInformation>,
     // pub fixes: Option<Vec<Assist>>,
     // The node that will be affected by `#[allow]` and similar attributes.
 }
@@ -76,17 +84,31 @@ impl Diagnostic {
     ) -> Self {
         let preprocessing_results = ctx.sema.preprocess_file(ctx.file_id);
 
+        Self::new_for_u_range(
+            code,
+            message,
+            s_range_to_u_range(preprocessing_results.offsets(), s_range),
+        )
+    }
+
+    fn new_for_u_range(
+        code: DiagnosticCode,
+        message: impl Into<String>,
+        u_range: lsp_types::Range,
+    ) -> Self {
         Diagnostic {
             code,
             message: message.into(),
-            range: s_range_to_u_range(preprocessing_results.offsets(), s_range),
+            range: u_range,
             severity: match code {
                 DiagnosticCode::SpCompError(_) => Severity::Error,
                 DiagnosticCode::SpCompWarning(_) => Severity::Warning,
                 DiagnosticCode::Lint(_, s) => s,
             },
             unused: false,
+            deprecated: false,
             experimental: false,
+            related: Vec::new(),
         }
     }
 
@@ -105,6 +127,29 @@ impl Diagnostic {
         self.unused = unused;
         self
     }
+
+    fn with_deprecated(mut self, deprecated: bool) -> Diagnostic {
+        self.deprecated = deprecated;
+        self
+    }
+
+    fn with_severity(mut self, severity: Severity) -> Diagnostic {
+        self.severity = severity;
+        self
+    }
+
+    fn with_related(mut self, related: Vec<RelatedInformation>) -> Diagnostic {
+        self.related = related;
+        self
+    }
+}
+
+/// A location related to a [`Diagnostic`], in user coordinates.
+#[derive(Debug)]
+pub struct RelatedInformation {
+    pub file_id: FileId,
+    pub range: lsp_types::Range,
+    pub message: String,
 }
 
 fn byte_to_row_col(input: &str, byte_index: usize) -> Option<Point> {
@@ -144,7 +189,6 @@ pub enum Severity {
 }
 
 struct DiagnosticsContext<'a> {
-    #[allow(unused)]
     config: &'a DiagnosticsConfig,
     sema: Semantics<'a, RootDatabase>,
     file_id: FileId,
@@ -155,6 +199,12 @@ pub struct DiagnosticsConfig {
     pub enabled: bool,
     pub disable_experimental: bool,
     pub disabled: FxHashSet<String>,
+    /// Severity of the locals which shadow another variable.
+    pub shadowing_severity: Severity,
+    /// Severity of the loop variables used after their loop.
+    pub loop_variables_severity: Severity,
+    /// Severity of the `switch` statements which miss variants of their enum.
+    pub switch_cases_severity: Severity,
 }
 
 pub fn diagnostics(
@@ -175,6 +225,7 @@ pub fn diagnostics(
     };
 
     syntax_error_diagnostics(&ctx, &source, &tree, &mut res);
+    handlers::deprecated_usage::deprecated_macro_usages(&ctx, &mut res);
 
     let mut diags = Vec::new();
     file.diagnostics(db, &mut diags);
@@ -197,6 +248,31 @@ pub fn diagnostics(
             AnyDiagnostic::UnresolvedMacro(d) => handlers::unresolved_macro::f(&ctx, &d),
             AnyDiagnostic::InactiveCode(d) => handlers::inactive_code::f(&ctx, &d),
             AnyDiagnostic::InvalidUseOfThis(d) => handlers::invalid_use_of_this::f(&ctx, &d),
+            AnyDiagnostic::UnresolvedPhrase(d) => handlers::unresolved_phrase::f(&ctx, &d),
+            AnyDiagnostic::PhraseNotLoaded(d) => handlers::phrase_not_loaded::f(&ctx, &d),
+            AnyDiagnostic::UnresolvedEventField(d) => handlers::unresolved_event_field::f(&ctx, &d),
+            AnyDiagnostic::IncorrectEventFieldAccessor(d) => {
+                handlers::incorrect_event_field_accessor::f(&ctx, &d)
+            }
+            AnyDiagnostic::ForwardSignatureMismatch(d) => {
+                handlers::forward_signature_mismatch::f(&ctx, &d)
+            }
+            AnyDiagnostic::DuplicateDefinition(d) => handlers::duplicate_definition::f(&ctx, &d),
+            AnyDiagnostic::MissingReturn(d) => handlers::missing_return::f(&ctx, &d),
+            AnyDiagnostic::UnreachableCode(d) => handlers::unreachable_code::f(&ctx, &d),
+            AnyDiagnostic::InfiniteLoop(d) => handlers::infinite_loop::f(&ctx, &d),
+            AnyDiagnostic::ConstantCondition(d) => handlers::constant_condition::f(&ctx, &d),
+            AnyDiagnostic::DuplicateCaseValue(d) => handlers::duplicate_case_value::f(&ctx, &d),
+            AnyDiagnostic::NonConstantCaseLabel(d) => {
+                handlers::non_constant_case_label::f(&ctx, &d)
+            }
+            AnyDiagnostic::MissingSwitchCases(d) => handlers::missing_switch_cases::f(&ctx, &d),
+            AnyDiagnostic::ShadowedVariable(d) => handlers::shadowed_variable::f(&ctx, &d),
+            AnyDiagnostic::LoopVariableUsedAfterLoop(d) => {
+                handlers::loop_variable_used_after_loop::f(&ctx, &d)
+            }
+            AnyDiagnostic::DeprecatedUsage(d) => handlers::deprecated_usage::f(&ctx, &d),
+            AnyDiagnostic::UnknownDocParam(d) => handlers::unknown_doc_param::f(&ctx, &d),
         };
         res.push(d);
     }
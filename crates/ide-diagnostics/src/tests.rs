@@ -0,0 +1,94 @@
+//! Diagnostics of in-memory projects, for the tests of the handlers.
+
+use std::sync::Arc;
+
+use base_db::{Change, FileExtension, SourceDatabaseExt, SourceRootConfig};
+use fxhash::FxHashSet;
+use ide_db::RootDatabase;
+use vfs::{Vfs, VfsPath};
+
+use crate::{diagnostics, DiagnosticsConfig, Severity};
+
+/// Root of the workspace of the fixtures.
+const PROJECT_ROOT: &str = "/project";
+
+/// Returns the diagnostics of the first of the `(path, text)` files with the code `code`, as
+/// the text of their range and their message, sorted by range.
+///
+/// Paths are virtual and must be under [`PROJECT_ROOT`].
+pub(crate) fn check_diagnostics(files: &[(&str, &str)], code: &str) -> Vec<(String, String)> {
+    let (db, file_ids) = database(files);
+    let text = files[0].1;
+    let mut res = diagnostics(&db, &config(), file_ids[0])
+        .into_iter()
+        .filter(|diagnostic| diagnostic.code.as_str() == code)
+        .map(|diagnostic| (diagnostic.range, diagnostic.message))
+        .collect::<Vec<_>>();
+    res.sort_by_key(|(range, _)| (range.start, range.end));
+
+    res.into_iter()
+        .map(|(range, message)| (range_text(text, range), message))
+        .collect()
+}
+
+/// Default configuration of the diagnostics in the tests.
+fn config() -> DiagnosticsConfig {
+    DiagnosticsConfig {
+        enabled: true,
+        disable_experimental: false,
+        disabled: FxHashSet::default(),
+        shadowing_severity: Severity::Warning,
+        loop_variables_severity: Severity::Warning,
+        switch_cases_severity: Severity::WeakWarning,
+    }
+}
+
+fn database(files: &[(&str, &str)]) -> (RootDatabase, Vec<vfs::FileId>) {
+    let mut vfs = Vfs::default();
+    for (path, text) in files {
+        vfs.set_file_contents(
+            VfsPath::new_virtual_path(path.to_string()),
+            Some(text.as_bytes().to_vec()),
+        );
+    }
+
+    let mut source_root_config = SourceRootConfig::default();
+    source_root_config
+        .fsc
+        .set_roots(vec![VfsPath::new_virtual_path(PROJECT_ROOT.to_string())]);
+    let mut change = Change::new();
+    change.set_roots(source_root_config.partition(&vfs));
+    let mut file_ids = Vec::new();
+    let mut known_files = Vec::new();
+    for (path, text) in files {
+        let path = VfsPath::new_virtual_path(path.to_string());
+        let file_id = vfs.file_id(&path).expect("the file was added to the vfs");
+        change.change_file(file_id, Some(Arc::from(*text)));
+        if let Some((_, Some(ext))) = path.name_and_extension() {
+            if let Ok(ext) = FileExtension::try_from(ext) {
+                known_files.push((file_id, ext));
+            }
+        }
+        file_ids.push(file_id);
+    }
+    known_files.sort();
+
+    let mut db = RootDatabase::new(None);
+    db.apply_change(change);
+    db.set_known_files(known_files);
+
+    (db, file_ids)
+}
+
+/// Returns the text of `range` in `text`, whose characters are all ASCII.
+fn range_text(text: &str, range: lsp_types::Range) -> String {
+    let offset = |position: lsp_types::Position| {
+        text.split_inclusive('\n')
+            .take(position.line as usize)
+            .map(str::len)
+            .sum::<usize>()
+            + position.character as usize
+    };
+
+    text[offset(range.start)..offset(range.end)].to_string()
+}
@@ -0,0 +1,222 @@
+//! This module provides the annotations shown above the items of a file as code lenses: the
+//! references of functions, methodmaps and globals, the implementations of forwards and
+//! callback types, and the build of the projects above their main file.
+//!
+//! The references and implementations are only computed when an annotation is resolved.
+
+use base_db::{FileExtension, FilePosition, FileRange, SourceDatabase};
+use hir::{DefResolution, File, FileDef};
+use hir_def::DefDatabase;
+use ide_db::RootDatabase;
+use lsp_types::{Position, Range};
+use preprocessor::{db::PreprocDatabase, s_range_to_u_range};
+use syntax::{utils::ts_range_to_lsp_range, TSKind};
+use vfs::FileId;
+
+use crate::{
+    hover::actions::{implementation_action_for_def, reference_action_for_def},
+    implementations::implementations,
+    references::references,
+    HoverAction, NavigationTarget,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub range: Range,
+    pub kind: AnnotationKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// Build the project whose main file is `file_id`.
+    Build { file_id: FileId },
+    HasImpls {
+        pos: FilePosition,
+        data: Option<Vec<NavigationTarget>>,
+    },
+    HasReferences {
+        pos: FilePosition,
+        data: Option<Vec<FileRange>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationConfig {
+    pub annotate_builds: bool,
+    pub annotate_impls: bool,
+    pub annotate_references: bool,
+}
+
+/// Returns the unresolved annotations of a file.
+pub(crate) fn annotations(
+    db: &RootDatabase,
+    config: &AnnotationConfig,
+    file_id: FileId,
+) -> Vec<Annotation> {
+    let mut res = Vec::new();
+
+    if config.annotate_builds
+        && db.graph().find_subgraphs().iter().any(|subgraph| {
+            subgraph.root.file_id == file_id && subgraph.root.extension == FileExtension::Sp
+        })
+    {
+        let start = build_position(db, file_id);
+        res.push(Annotation {
+            range: Range::new(start, start),
+            kind: AnnotationKind::Build { file_id },
+        });
+    }
+
+    for file_def in File::from(file_id).declarations(db) {
+        let def = match file_def {
+            FileDef::Function(it) => DefResolution::Function(it),
+            FileDef::Methodmap(it) => DefResolution::Methodmap(it),
+            FileDef::Global(it) => DefResolution::Global(it),
+            FileDef::Typedef(it) => DefResolution::Typedef(it),
+            FileDef::Typeset(it) => DefResolution::Typeset(it),
+            FileDef::Functag(it) => DefResolution::Functag(it),
+            FileDef::Funcenum(it) => DefResolution::Funcenum(it),
+            _ => continue,
+        };
+        if config.annotate_impls {
+            if let Some(HoverAction::Implementation(pos)) =
+                implementation_action_for_def(db, def.clone())
+            {
+                res.push(Annotation {
+                    range: Range::new(pos.position, pos.position),
+                    kind: AnnotationKind::HasImpls { pos, data: None },
+                });
+            }
+        }
+        if config.annotate_references {
+            if let Some(HoverAction::Reference(pos)) = reference_action_for_def(db, def) {
+                res.push(Annotation {
+                    range: Range::new(pos.position, pos.position),
+                    kind: AnnotationKind::HasReferences { pos, data: None },
+                });
+            }
+        }
+    }
+
+    res
+}
+
+/// Returns the position of the `Build` annotation of a main file, which is the start of its
+/// first node which is not a comment, below the license header of the file.
+fn build_position(db: &RootDatabase, file_id: FileId) -> Position {
+    let tree = db.parse(file_id);
+    let root_node = tree.root_node();
+    let Some(node) = root_node
+        .children(&mut root_node.walk())
+        .find(|child| TSKind::from(child) != TSKind::comment)
+    else {
+        return Position::new(0, 0);
+    };
+    let range = s_range_to_u_range(
+        db.preprocess_file(file_id).offsets(),
+        ts_range_to_lsp_range(&node.range()),
+    );
+
+    range.start
+}
+
+/// Computes the references or the implementations of an annotation.
+pub(crate) fn resolve_annotation(db: &RootDatabase, mut annotation: Annotation) -> Annotation {
+    match &mut annotation.kind {
+        AnnotationKind::Build { .. } => (),
+        AnnotationKind::HasImpls { pos, data } => {
+            *data = implementations(db, *pos);
+        }
+        AnnotationKind::HasReferences { pos, data } => {
+            *data = references(db, *pos).map(|references| {
+                references
+                    .into_iter()
+                    // Do not count the declaration.
+                    .filter(|frange| {
+                        frange.file_id != pos.file_id || frange.range.start != pos.position
+                    })
+                    .collect()
+            });
+        }
+    }
+
+    annotation
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    const MAIN: &str = r#"// Plugin header.
+// Second line.
+
+#include "types.sp"
+
+forward void OnThing();
+
+native void Register(Callback callback);
+
+void Handler(int value) {}
+
+void Other(int value) {}
+
+public void OnThing() {}
+
+public void OnPluginStart()
+{
+    Register(Handler);
+    Register(Handler);
+}
+"#;
+
+    const TYPES: &str = "typedef Callback = function void (int value);\n";
+
+    const CONFIG: AnnotationConfig = AnnotationConfig {
+        annotate_builds: true,
+        annotate_impls: true,
+        annotate_references: true,
+    };
+
+    #[test]
+    fn build_below_header() {
+        let (host, file_ids) =
+            analysis_host(&[("/project/main.sp", MAIN), ("/project/types.sp", TYPES)]);
+        let start = Position::new(3, 0);
+        assert_eq!(
+            annotations(&host.db, &CONFIG, file_ids[0]).first(),
+            Some(&Annotation {
+                range: Range::new(start, start),
+                kind: AnnotationKind::Build {
+                    file_id: file_ids[0]
+                },
+            })
+        );
+        // The included file is not the main file of a project.
+        assert!(!annotations(&host.db, &CONFIG, file_ids[1])
+            .iter()
+            .any(|annotation| matches!(annotation.kind, AnnotationKind::Build { .. })));
+    }
+
+    #[test]
+    fn resolved_references() {
+        let (host, file_ids) =
+            analysis_host(&[("/project/main.sp", MAIN), ("/project/types.sp", TYPES)]);
+        let handler = annotations(&host.db, &CONFIG, file_ids[0])
+            .into_iter()
+            .find(|annotation| {
+                matches!(
+                    annotation.kind,
+                    AnnotationKind::HasReferences { pos, .. } if pos.position == Position::new(9, 5)
+                )
+            })
+            .expect("the function has a references annotation");
+        let AnnotationKind::HasReferences { data, .. } = resolve_annotation(&host.db, handler).kind
+        else {
+            unreachable!()
+        };
+        // The declaration is not counted.
+        assert_eq!(data.map(|references| references.len()), Some(2));
+    }
+}
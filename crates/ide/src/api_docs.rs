@@ -0,0 +1,341 @@
+//! This module provides the API of a file, with the documentation of its items, used to
+//! generate the documentation of an include library.
+
+use hir::{DefResolution, File, FileDef};
+use hir_def::{DefDatabase, FunctionKind};
+use ide_db::{DocComment, DocumentationDatabase, RootDatabase};
+use serde::Serialize;
+use vfs::FileId;
+
+use crate::hover::{render_def, Render};
+
+/// An item of the API of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiItem {
+    pub kind: ApiItemKind,
+    pub name: String,
+
+    /// Declaration of the item, as shown on hover.
+    pub signature: Option<String>,
+    pub deprecated: bool,
+    pub docs: Option<DocComment>,
+
+    /// Members of the item, such as the methods and properties of a methodmap, or the
+    /// variants of an enum.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ApiItem>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiItemKind {
+    Native,
+    Forward,
+    Function,
+    Define,
+    Enum,
+    Variant,
+    EnumStruct,
+    Field,
+    Methodmap,
+    Method,
+    Property,
+    Typedef,
+    Typeset,
+    Functag,
+    Funcenum,
+}
+
+impl ApiItemKind {
+    /// Returns the name of the kind, as shown in the titles of the documentation.
+    pub fn label(self) -> &'static str {
+        match self {
+            ApiItemKind::Native => "Natives",
+            ApiItemKind::Forward => "Forwards",
+            ApiItemKind::Function => "Functions",
+            ApiItemKind::Define => "Defines",
+            ApiItemKind::Enum => "Enums",
+            ApiItemKind::Variant => "Variants",
+            ApiItemKind::EnumStruct => "Enum structs",
+            ApiItemKind::Field => "Fields",
+            ApiItemKind::Methodmap => "Methodmaps",
+            ApiItemKind::Method => "Methods",
+            ApiItemKind::Property => "Properties",
+            ApiItemKind::Typedef => "Typedefs",
+            ApiItemKind::Typeset => "Typesets",
+            ApiItemKind::Functag => "Functags",
+            ApiItemKind::Funcenum => "Funcenums",
+        }
+    }
+}
+
+/// Returns the items declared in a file, in their order of declaration.
+///
+/// Globals, old style structs and variables are not part of the API.
+pub(crate) fn api_items(db: &RootDatabase, file_id: FileId) -> Vec<ApiItem> {
+    File::from(file_id)
+        .declarations(db)
+        .into_iter()
+        .filter_map(|def| match def {
+            FileDef::Function(it) => {
+                let kind = match db.function_data(it.id()).kind {
+                    FunctionKind::Native => ApiItemKind::Native,
+                    FunctionKind::Forward => ApiItemKind::Forward,
+                    FunctionKind::Def => ApiItemKind::Function,
+                };
+                Some(api_item(db, kind, it.into(), None, Vec::new()))
+            }
+            FileDef::Macro(it) => Some(api_item(
+                db,
+                ApiItemKind::Define,
+                it.into(),
+                None,
+                Vec::new(),
+            )),
+            FileDef::Enum(it) => {
+                let name = it.name(db).to_string();
+                let variants = it
+                    .variants(db)
+                    .into_iter()
+                    .map(|variant| {
+                        api_item(
+                            db,
+                            ApiItemKind::Variant,
+                            DefResolution::Variant(variant),
+                            Some(&name),
+                            Vec::new(),
+                        )
+                    })
+                    .collect();
+                Some(api_item(
+                    db,
+                    ApiItemKind::Enum,
+                    DefResolution::Enum(it),
+                    None,
+                    variants,
+                ))
+            }
+            FileDef::EnumStruct(it) => {
+                let name = it.name(db).to_string();
+                let fields = it.fields(db).into_iter().map(|field| {
+                    api_item(
+                        db,
+                        ApiItemKind::Field,
+                        field.into(),
+                        Some(&name),
+                        Vec::new(),
+                    )
+                });
+                let methods = it.methods(db).into_iter().map(|method| {
+                    api_item(
+                        db,
+                        ApiItemKind::Method,
+                        method.into(),
+                        Some(&name),
+                        Vec::new(),
+                    )
+                });
+                let children = fields.chain(methods).collect();
+                Some(api_item(
+                    db,
+                    ApiItemKind::EnumStruct,
+                    it.into(),
+                    None,
+                    children,
+                ))
+            }
+            FileDef::Methodmap(it) => {
+                let name = it.name(db).to_string();
+                let properties = it.properties(db).into_iter().map(|property| {
+                    api_item(
+                        db,
+                        ApiItemKind::Property,
+                        property.into(),
+                        Some(&name),
+                        Vec::new(),
+                    )
+                });
+                let methods = it.methods(db).into_iter().map(|method| {
+                    api_item(
+                        db,
+                        ApiItemKind::Method,
+                        method.into(),
+                        Some(&name),
+                        Vec::new(),
+                    )
+                });
+                let children = methods.chain(properties).collect();
+                Some(api_item(
+                    db,
+                    ApiItemKind::Methodmap,
+                    it.into(),
+                    None,
+                    children,
+                ))
+            }
+            FileDef::Typedef(it) => Some(api_item(
+                db,
+                ApiItemKind::Typedef,
+                it.into(),
+                None,
+                Vec::new(),
+            )),
+            FileDef::Typeset(it) => {
+                let children = it
+                    .children(db)
+                    .into_iter()
+                    .map(|typedef| {
+                        api_item(db, ApiItemKind::Typedef, typedef.into(), None, Vec::new())
+                    })
+                    .collect();
+                Some(api_item(
+                    db,
+                    ApiItemKind::Typeset,
+                    it.into(),
+                    None,
+                    children,
+                ))
+            }
+            FileDef::Functag(it) => Some(api_item(
+                db,
+                ApiItemKind::Functag,
+                it.into(),
+                None,
+                Vec::new(),
+            )),
+            FileDef::Funcenum(it) => {
+                let children = it
+                    .children(db)
+                    .into_iter()
+                    .map(|functag| {
+                        api_item(db, ApiItemKind::Functag, functag.into(), None, Vec::new())
+                    })
+                    .collect();
+                Some(api_item(
+                    db,
+                    ApiItemKind::Funcenum,
+                    it.into(),
+                    None,
+                    children,
+                ))
+            }
+            FileDef::Global(_) | FileDef::Variant(_) | FileDef::Struct(_) => None,
+        })
+        .collect()
+}
+
+fn api_item(
+    db: &RootDatabase,
+    kind: ApiItemKind,
+    def: DefResolution,
+    parent: Option<&str>,
+    children: Vec<ApiItem>,
+) -> ApiItem {
+    let signature = match render_def(db, def.clone()) {
+        // The members are rendered with the name of their parent on the first line.
+        Some(Render::String(it)) => Some(
+            parent
+                .and_then(|parent| it.strip_prefix(&format!("{}\n", parent)))
+                .map(String::from)
+                .unwrap_or(it),
+        ),
+        _ => None,
+    };
+
+    ApiItem {
+        kind,
+        name: def.name(db).map(|it| it.to_string()).unwrap_or_default(),
+        signature,
+        deprecated: def.is_deprecated(db),
+        docs: db.doc_comment(def).as_deref().cloned(),
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    /// Returns the kind and name of the items, with the ones of their children.
+    fn outline(items: &[ApiItem]) -> Vec<(ApiItemKind, String, Vec<(ApiItemKind, String)>)> {
+        items
+            .iter()
+            .map(|item| {
+                (
+                    item.kind,
+                    item.name.clone(),
+                    item.children
+                        .iter()
+                        .map(|child| (child.kind, child.name.clone()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn items_of_include() {
+        let (host, file_ids) = analysis_host(&[(
+            "/project/api.inc",
+            r#"
+int g_Count;
+
+enum Team {
+    Team_Red,
+    Team_Blue,
+}
+
+/**
+ * Kicks a client.
+ *
+ * @param client    Client index.
+ * @noreturn
+ */
+native void Kick(int client);
+
+forward void OnKicked(int client);
+
+methodmap Counter < Handle {
+    public native void Reset();
+    property int Value {
+        public native get();
+    }
+}
+"#,
+        )]);
+        let items = api_items(&host.db, file_ids[0]);
+        assert_eq!(
+            outline(&items),
+            vec![
+                (
+                    ApiItemKind::Enum,
+                    "Team".to_string(),
+                    vec![
+                        (ApiItemKind::Variant, "Team_Red".to_string()),
+                        (ApiItemKind::Variant, "Team_Blue".to_string()),
+                    ]
+                ),
+                (ApiItemKind::Native, "Kick".to_string(), vec![]),
+                (ApiItemKind::Forward, "OnKicked".to_string(), vec![]),
+                (
+                    ApiItemKind::Methodmap,
+                    "Counter".to_string(),
+                    vec![
+                        (ApiItemKind::Method, "Reset".to_string()),
+                        (ApiItemKind::Property, "Value".to_string()),
+                    ]
+                ),
+            ]
+        );
+
+        let kick = &items[1];
+        assert!(kick.signature.is_some());
+        let docs = kick.docs.as_ref().unwrap();
+        assert_eq!(docs.description, "Kicks a client.");
+        assert_eq!(docs.param("client"), Some("Client index."));
+        assert_eq!(docs.others, vec![("noreturn".to_string(), String::new())]);
+    }
+}
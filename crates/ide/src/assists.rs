@@ -0,0 +1,41 @@
+//! This module provides the assists (code actions) available for a range of a file.
+
+use base_db::{FilePosition, FileRange};
+use hir::Semantics;
+use ide_db::{RootDatabase, SourceChange};
+
+use crate::{
+    auto_include::add_include, callbacks::generate_callback, doc_comments::generate_doc_comment,
+    forwards::fix_forward_signature, switches::fill_switch_cases,
+};
+
+/// An edit that can be applied on demand to a range of a file.
+#[derive(Debug, Clone)]
+pub struct Assist {
+    /// Label of the assist, shown in the code actions menu.
+    pub label: String,
+
+    /// Edits to apply when the assist is selected.
+    pub source_change: SourceChange,
+}
+
+/// Returns the assists available for the range.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `frange`: The range the assists were requested for
+pub(crate) fn assists(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let sema = &Semantics::new(db);
+    let pos = FilePosition {
+        file_id: frange.file_id,
+        position: frange.range.start,
+    };
+    let mut res = Vec::new();
+    generate_callback(sema, pos, &mut res);
+    add_include(sema, pos, &mut res);
+    fix_forward_signature(sema, pos, &mut res);
+    fill_switch_cases(sema, pos, &mut res);
+    generate_doc_comment(sema, pos, &mut res);
+
+    res
+}
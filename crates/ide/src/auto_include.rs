@@ -0,0 +1,225 @@
+//! This module provides completions and assists for the definitions of the include
+//! directories which are not included by the project yet, along with the edit which adds
+//! the corresponding `#include <...>` directive.
+
+use base_db::{FilePosition, SourceDatabase, SourceDatabaseExt};
+use hir::{db::HirDatabase, DefResolution, Semantics};
+use hir_def::Name;
+use ide_db::{RootDatabase, SourceChange};
+use itertools::Itertools;
+use lsp_types::{Position, Range, TextEdit};
+use preprocessor::u_pos_to_s_pos;
+use syntax::{utils::lsp_position_to_ts_point, TSKind};
+use vfs::FileId;
+
+use crate::assists::Assist;
+
+/// Definitions of the files of the include directories which are not included by the
+/// project, and which get included by the same `#include <...>` directive.
+#[derive(Debug, Clone)]
+pub(crate) struct UnincludedDefs {
+    /// Path to use in the `#include <...>` directive, e.g. `sdktools`.
+    pub(crate) include_path: String,
+
+    /// The file included by the directive.
+    pub(crate) include_file_id: FileId,
+
+    /// Definitions of the files, with their names.
+    pub(crate) defs: Vec<(Name, DefResolution)>,
+}
+
+/// Returns the definitions of the include directories which are not included by the
+/// project of `file_id`, grouped by the file to include.
+pub(crate) fn unincluded_defs(db: &RootDatabase, file_id: FileId) -> Vec<UnincludedDefs> {
+    db.unincluded_files(file_id)
+        .iter()
+        .map(|unincluded| UnincludedDefs {
+            include_path: unincluded.include_path.clone(),
+            include_file_id: unincluded.include_file_id,
+            defs: unincluded
+                .file_ids
+                .iter()
+                .flat_map(|file_id| db.file_definitions(*file_id).iter().cloned().collect_vec())
+                .collect_vec(),
+        })
+        .collect_vec()
+}
+
+/// Returns the edit which adds `#include <include_path>` to the file, or `None` if the
+/// file already includes `include_file_id`.
+///
+/// The directive is inserted after the last `#include` of the file. If the file has no
+/// includes, it is inserted before the first `#pragma`, so that it stays above
+/// `#pragma newdecls required`, or at the top of the file.
+pub(crate) fn include_edit(
+    db: &RootDatabase,
+    file_id: FileId,
+    include_path: &str,
+    include_file_id: FileId,
+) -> Option<(Range, String)> {
+    let (includes, unresolved) = db.file_includes(file_id);
+    if includes.iter().any(|it| it.file_id() == include_file_id) {
+        return None;
+    }
+    let last_include = includes
+        .iter()
+        .filter_map(|it| it.line())
+        .chain(unresolved.iter().map(|it| it.range.start.line))
+        .max();
+    let line = match last_include {
+        Some(line) => line + 1,
+        None => db
+            .file_text(file_id)
+            .lines()
+            .position(|line| line.trim_start().starts_with("#pragma"))
+            .unwrap_or_default() as u32,
+    };
+    let position = Position::new(line, 0);
+
+    Some((
+        Range::new(position, position),
+        format!("#include <{}>\n", include_path),
+    ))
+}
+
+/// Returns whether the characters of `prefix` appear in order in `label`, ignoring case.
+pub(crate) fn fuzzy_match(label: &str, prefix: &str) -> bool {
+    let mut chars = label.chars().map(|c| c.to_ascii_lowercase());
+    prefix
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .all(|c| chars.any(|it| it == c))
+}
+
+/// Assist which includes the file defining an unresolved identifier.
+///
+/// ```sourcepawn
+/// SDKHook(client, SDKHook_OnTakeDamage, OnTakeDamage);
+/// ```
+/// ->
+/// ```sourcepawn
+/// #include <sdkhooks>
+/// ```
+pub(crate) fn add_include(
+    sema: &Semantics<RootDatabase>,
+    mut pos: FilePosition,
+    acc: &mut Vec<Assist>,
+) -> Option<()> {
+    let preprocessing_results = sema.preprocess_file(pos.file_id);
+    let _ = u_pos_to_s_pos(
+        preprocessing_results.args_map(),
+        preprocessing_results.offsets(),
+        &mut pos.position,
+    );
+    let tree = sema.parse(pos.file_id);
+    let source = sema.preprocessed_text(pos.file_id);
+    let point = lsp_position_to_ts_point(&pos.position);
+    let node = tree.root_node().descendant_for_point_range(point, point)?;
+    if TSKind::from(node) != TSKind::identifier || sema.find_def(pos.file_id, &node).is_some() {
+        return None;
+    }
+    let name = node.utf8_text(source.as_bytes()).ok()?;
+
+    for unincluded in unincluded_defs(sema.db, pos.file_id) {
+        if !unincluded.defs.iter().any(|(it, _)| it.to_string() == name) {
+            continue;
+        }
+        let Some((range, text)) = include_edit(
+            sema.db,
+            pos.file_id,
+            &unincluded.include_path,
+            unincluded.include_file_id,
+        ) else {
+            continue;
+        };
+        let mut source_change = SourceChange::default();
+        source_change.insert(pos.file_id, TextEdit::new(range, text));
+        acc.push(Assist {
+            label: format!("Add `#include <{}>`", unincluded.include_path),
+            source_change,
+        });
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use base_db::FileRange;
+
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    const SDKHOOKS: &str = "native void SDKHook(int client);\n";
+
+    /// Returns the edit which includes `/include/sdkhooks.inc` in the first file.
+    fn edit(text: &str) -> Option<(Range, String)> {
+        let (host, file_ids) = analysis_host(&[
+            ("/project/main.sp", text),
+            ("/include/sdkhooks.inc", SDKHOOKS),
+        ]);
+        include_edit(&host.db, file_ids[0], "sdkhooks", file_ids[1])
+    }
+
+    fn insertion(line: u32) -> Option<(Range, String)> {
+        let position = Position::new(line, 0);
+        Some((
+            Range::new(position, position),
+            "#include <sdkhooks>\n".to_string(),
+        ))
+    }
+
+    #[test]
+    fn after_last_include() {
+        assert_eq!(
+            edit("#include <sourcemod>\n#include <sdktools>\n\n#pragma semicolon 1\n"),
+            insertion(2)
+        );
+    }
+
+    #[test]
+    fn before_first_pragma() {
+        assert_eq!(
+            edit("// My plugin\n\n#pragma semicolon 1\n#pragma newdecls required\n"),
+            insertion(2)
+        );
+    }
+
+    #[test]
+    fn top_of_file() {
+        assert_eq!(edit("void main() {}\n"), insertion(0));
+    }
+
+    #[test]
+    fn already_included() {
+        assert_eq!(edit("#include <sdkhooks>\n\nvoid main() {}\n"), None);
+    }
+
+    #[test]
+    fn assist_for_unresolved_identifier() {
+        let text = "#include <sourcemod>\n\nvoid main() {\n    SDKHook(1);\n}\n";
+        let (host, file_ids) = analysis_host(&[
+            ("/project/main.sp", text),
+            ("/include/sdkhooks.inc", SDKHOOKS),
+        ]);
+        let position = Position::new(3, 4);
+        let assists = host
+            .analysis()
+            .assists(FileRange {
+                file_id: file_ids[0],
+                range: Range::new(position, position),
+            })
+            .unwrap()
+            .into_iter()
+            .filter(|assist| assist.label.starts_with("Add `#include"))
+            .collect::<Vec<_>>();
+        assert_eq!(assists.len(), 1);
+        assert_eq!(assists[0].label, "Add `#include <sdkhooks>`");
+        let (range, text) = insertion(1).unwrap();
+        assert_eq!(
+            assists[0].source_change.source_file_edits[&file_ids[0]],
+            vec![TextEdit::new(range, text)]
+        );
+    }
+}
@@ -1,10 +1,9 @@
 use base_db::FilePosition;
 use fxhash::FxHashMap;
-use hir::{DefResolution, Function, FunctionType, HasSource, Semantics};
+use hir::{DefResolution, Function, FunctionType, HasSource, Property, Semantics};
 use hir_def::DefDatabase;
 use ide_db::{CallItem, IncomingCallItem, OutgoingCallItem, RootDatabase, SymbolKind};
 use itertools::Itertools;
-use lazy_static::lazy_static;
 use lsp_types::Range;
 use preprocessor::{db::PreprocDatabase, s_range_to_u_range, u_pos_to_s_pos};
 use smol_str::ToSmolStr;
@@ -12,7 +11,8 @@ use syntax::{
     utils::{lsp_position_to_ts_point, ts_range_to_lsp_range},
     TSKind,
 };
-use tree_sitter::QueryCursor;
+use tree_sitter::Node;
+use vfs::FileId;
 
 pub(crate) fn call_hierarchy_prepare(
     db: &RootDatabase,
@@ -39,12 +39,22 @@ pub(crate) fn call_hierarchy_prepare(
         lsp_position_to_ts_point(&fpos.position),
     )?;
 
-    let def = sema.find_def(fpos.file_id, &node)?;
-    let DefResolution::Function(func) = def else {
-        return None;
+    let funcs = match sema.find_def(fpos.file_id, &node)? {
+        DefResolution::Function(func) => vec![func],
+        // A property is called through its getter and its setter.
+        DefResolution::Property(property) => property
+            .getter(db)
+            .into_iter()
+            .chain(property.setter(db))
+            .collect_vec(),
+        _ => return None,
     };
 
-    vec![func_to_call_item(sema, func)?].into()
+    funcs
+        .into_iter()
+        .flat_map(|func| func_to_call_item(sema, func))
+        .collect_vec()
+        .into()
 }
 
 fn func_to_call_item(sema: &Semantics<RootDatabase>, func: Function) -> Option<CallItem> {
@@ -54,10 +64,11 @@ fn func_to_call_item(sema: &Semantics<RootDatabase>, func: Function) -> Option<C
     let source_node = func.source(sema.db, &tree)?.value;
     let preprocessing_data = sema.preprocess_file(file_id);
     let source = preprocessing_data.preprocessed_text();
-    let name_node = source_node.child_by_field_name("name");
+    let name_node = function_name_node(source_node);
+    let kind = func.kind(sema.db);
     let res = CallItem {
-        name: source_node.utf8_text(source.as_bytes()).ok()?.to_smolstr(),
-        kind: match func.kind(sema.db) {
+        name: qualified_name(sema, func, kind, source_node, &source).to_smolstr(),
+        kind: match kind {
             FunctionType::Function => SymbolKind::Function,
             FunctionType::Constructor => SymbolKind::Constructor,
             FunctionType::Destructor => SymbolKind::Destructor,
@@ -83,6 +94,120 @@ fn func_to_call_item(sema: &Semantics<RootDatabase>, func: Function) -> Option<C
     Some(res)
 }
 
+/// Returns the name of a function, prefixed with the names of its methodmap or enum struct
+/// and of its property, e.g. `Player.Health.get`.
+fn qualified_name(
+    sema: &Semantics<RootDatabase>,
+    func: Function,
+    kind: FunctionType,
+    source_node: Node,
+    source: &str,
+) -> String {
+    let name = func.name(sema.db).to_string();
+    match kind {
+        FunctionType::Constructor => return name,
+        FunctionType::Destructor => return format!("~{}", name),
+        _ => (),
+    }
+    let mut res = name;
+    let mut parent = source_node.parent();
+    while let Some(node) = parent {
+        if let Some(parent_name) = node
+            .child_by_field_name("name")
+            .and_then(|name_node| name_node.utf8_text(source.as_bytes()).ok())
+        {
+            res = format!("{}.{}", parent_name, res);
+        }
+        parent = node.parent();
+    }
+
+    res
+}
+
+/// Returns the name node of a function, which is the `get` or `set` keyword for the
+/// accessors of a property.
+fn function_name_node(node: Node) -> Option<Node> {
+    match TSKind::from(node) {
+        TSKind::methodmap_property_method | TSKind::methodmap_property_native => node
+            .children(&mut node.walk())
+            .find(|child| {
+                matches!(
+                    TSKind::from(child),
+                    TSKind::methodmap_property_getter | TSKind::methodmap_property_setter
+                )
+            })?
+            .child_by_field_name("name"),
+        _ => node.child_by_field_name("name"),
+    }
+}
+
+/// Returns the functions called by a reference node.
+///
+/// Besides calls, method calls and constructors, a function passed as an argument without
+/// being called, such as a callback passed to `CreateTimer`, is an edge of the call hierarchy,
+/// and accessing a property calls its getter or its setter.
+fn callees(sema: &Semantics<RootDatabase>, file_id: FileId, node: Node) -> Vec<Function> {
+    if !is_call_site(node) {
+        // Only call sites are resolved, the other references are not calls.
+        return Vec::new();
+    }
+    match sema.find_def(file_id, &node) {
+        Some(DefResolution::Function(func)) => vec![func],
+        Some(DefResolution::Property(property)) => property_accessors(sema, property, node),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns whether a node can reference a called function: the function of a call, the class
+/// of a `new` expression, the field of an access, or an identifier passed as an argument.
+fn is_call_site(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    if TSKind::from(node) != TSKind::identifier {
+        // The field of a method call is the call site, not the `field_access` itself.
+        return false;
+    }
+    match TSKind::from(parent) {
+        TSKind::call_expression => parent.child_by_field_name("function") == Some(node),
+        TSKind::new_expression => parent.child_by_field_name("class") == Some(node),
+        TSKind::field_access => parent.child_by_field_name("field") == Some(node),
+        TSKind::named_arg => parent.child_by_field_name("value") == Some(node),
+        TSKind::call_arguments => true,
+        _ => false,
+    }
+}
+
+/// Returns the accessors of a property which are called by the access of its `field` node.
+fn property_accessors(
+    sema: &Semantics<RootDatabase>,
+    property: Property,
+    field: Node,
+) -> Vec<Function> {
+    let getter = property.getter(sema.db);
+    let setter = property.setter(sema.db);
+    let Some(access) = field.parent() else {
+        return getter.into_iter().collect();
+    };
+    match access.parent() {
+        Some(parent)
+            if TSKind::from(parent) == TSKind::assignment_expression
+                && parent.child_by_field_name("left") == Some(access) =>
+        {
+            if parent.child_by_field_name("operator").map(TSKind::from) == Some(TSKind::anon_EQ) {
+                setter.into_iter().collect()
+            } else {
+                // Compound assignments read the property before writing it.
+                getter.into_iter().chain(setter).collect()
+            }
+        }
+        Some(parent) if TSKind::from(parent) == TSKind::update_expression => {
+            getter.into_iter().chain(setter).collect()
+        }
+        _ => getter.into_iter().collect(),
+    }
+}
+
 pub(crate) fn call_hierarchy_incoming(
     db: &RootDatabase,
     func: Function,
@@ -95,8 +220,15 @@ pub(crate) fn call_hierarchy_incoming(
     let source_file_id = def.file_id(db);
     let source_tree = db.parse(source_file_id);
     let source_preprocessing_results = db.preprocess_file(source_file_id);
-    let source_node = func.source(db, &source_tree)?.value;
-    let name_source_node = source_node.child_by_field_name("name")?;
+    // The accessors of a property are called through the references of the property.
+    let name_source_node = match func.kind(db) {
+        FunctionType::Getter | FunctionType::Setter => func
+            .property(db)?
+            .source(db, &source_tree)?
+            .value
+            .child_by_field_name("name")?,
+        _ => function_name_node(func.source(db, &source_tree)?.value)?,
+    };
     let u_name_range = s_range_to_u_range(
         source_preprocessing_results.offsets(),
         ts_range_to_lsp_range(&name_source_node.range()),
@@ -111,10 +243,6 @@ pub(crate) fn call_hierarchy_incoming(
     let _ = references
         .into_iter()
         .flat_map(|frange| {
-            if frange.file_id == def.file_id(db) && frange.range == u_name_range {
-                // Do not include the function definition
-                return None;
-            }
             let file_id = frange.file_id;
             let tree = sema.parse(file_id);
             let preprocessing_results = sema.preprocess_file(file_id);
@@ -128,6 +256,11 @@ pub(crate) fn call_hierarchy_incoming(
                 lsp_position_to_ts_point(&pos),
                 lsp_position_to_ts_point(&pos),
             )?;
+            if !callees(&sema, file_id, node).contains(&func) {
+                // Do not include the declarations of the function, or the accesses to the
+                // property which do not call this accessor.
+                return None;
+            }
 
             let mut container = node.parent()?;
             while !matches!(
@@ -185,50 +318,31 @@ pub(crate) fn call_hierarchy_outgoing(
     let file_id = def.file_id(db);
     let tree = db.parse(file_id);
     let preprocessing_results = db.preprocess_file(file_id);
-    let source = preprocessing_results.preprocessed_text();
     let source_node = func.source(db, &tree)?.value;
-    lazy_static! {
-        static ref CALL_QUERY: tree_sitter::Query = tree_sitter::Query::new(
-            &tree_sitter_sourcepawn::language(),
-            "(call_expression) @call_expression"
-        )
-        .expect("Could not build identifier query.");
-    }
 
     let mut res: FxHashMap<Function, Vec<Range>> = FxHashMap::default();
 
-    let mut cursor = QueryCursor::new();
-    let matches = cursor.captures(&CALL_QUERY, source_node, source.as_bytes());
-    for (match_, _) in matches {
-        for c in match_.captures {
-            let Some(mut node) = c.node.child_by_field_name("function") else {
-                continue;
-            };
-            match TSKind::from(&node) {
-                TSKind::identifier => (),
-                TSKind::field_access => {
-                    let Some(node_) = node.child_by_field_name("field") else {
-                        continue;
-                    };
-                    node = node_;
-                }
-                _ => continue,
-            }
-            let u_range = s_range_to_u_range(
-                preprocessing_results.offsets(),
-                ts_range_to_lsp_range(&node.range()),
-            );
-            let Some(DefResolution::Function(func)) = sema.find_def(file_id, &node) else {
-                continue;
-            };
-            res.entry(func)
+    let mut stack = vec![source_node];
+    while let Some(node) = stack.pop() {
+        stack.extend(node.children(&mut node.walk()));
+        let callees = callees(&sema, file_id, node);
+        if callees.is_empty() {
+            continue;
+        }
+        let u_range = s_range_to_u_range(
+            preprocessing_results.offsets(),
+            ts_range_to_lsp_range(&node.range()),
+        );
+        for callee in callees {
+            res.entry(callee)
                 .and_modify(|v| v.push(u_range))
                 .or_insert(vec![u_range]);
         }
     }
 
     res.into_iter()
-        .flat_map(|(func, ranges)| {
+        .flat_map(|(func, mut ranges)| {
+            ranges.sort_by_key(|range| (range.start, range.end));
             Some(OutgoingCallItem {
                 call_item: func_to_call_item(&sema, func)?,
                 ranges,
@@ -237,3 +351,93 @@ pub(crate) fn call_hierarchy_outgoing(
         .collect_vec()
         .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    const TEXT: &str = r#"
+methodmap Counter < Handle {
+    public Counter() { return view_as<Counter>(0); }
+    public void Add(int amount) {}
+    property int Value {
+        public get() { return 0; }
+        public set(int value) {}
+    }
+}
+
+native void CreateTimer(float interval, Function callback);
+
+void Timer_Tick(Handle timer) {}
+
+void Caller() {
+    Counter counter = new Counter();
+    counter.Add(1);
+    counter.Value += 1;
+    int amount = counter.Value;
+    int Timer_Tick_Count = amount;
+    CreateTimer(1.0, Timer_Tick);
+}
+"#;
+
+    /// Returns the position of the first occurrence of `needle` in `text`.
+    fn position(text: &str, needle: &str) -> Position {
+        let offset = text.find(needle).expect("the needle is in the text");
+        let line = text[..offset].matches('\n').count();
+        let column = offset - text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        Position::new(line as u32, column as u32)
+    }
+
+    fn prepare(db: &RootDatabase, file_id: FileId, needle: &str) -> Function {
+        call_hierarchy_prepare(
+            db,
+            FilePosition {
+                file_id,
+                position: position(TEXT, needle),
+            },
+        )
+        .and_then(|items| items.into_iter().next())
+        .and_then(|item| item.data)
+        .expect("the position is on a function")
+    }
+
+    #[test]
+    fn outgoing_calls() {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", TEXT)]);
+        let func = prepare(&host.db, file_ids[0], "Caller");
+        let mut res = call_hierarchy_outgoing(&host.db, func)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.call_item.name.to_string(), item.ranges.len()))
+            .collect_vec();
+        res.sort();
+        assert_eq!(
+            res,
+            vec![
+                ("Counter".to_string(), 1),
+                ("Counter.Add".to_string(), 1),
+                ("Counter.Value.get".to_string(), 2),
+                ("Counter.Value.set".to_string(), 1),
+                ("CreateTimer".to_string(), 1),
+                ("Timer_Tick".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn incoming_callback() {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", TEXT)]);
+        let func = prepare(&host.db, file_ids[0], "Timer_Tick(Handle");
+        let res = call_hierarchy_incoming(&host.db, func).unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].call_item.name, "Caller");
+        assert_eq!(
+            res[0].ranges,
+            vec![Range::new(Position::new(20, 21), Position::new(20, 31))]
+        );
+    }
+}
@@ -0,0 +1,487 @@
+//! This module provides completions and assists which generate a new callback from the
+//! typedef, typeset, functag or funcenum expected by an argument of a function call.
+
+use hir::{DefResolution, Semantics};
+use ide_db::{RootDatabase, SourceChange, SymbolKind};
+use lsp_types::{Position, Range, TextEdit};
+use preprocessor::{s_range_to_u_range, u_pos_to_s_pos};
+use syntax::{
+    utils::{lsp_position_to_ts_point, ts_range_to_lsp_range},
+    TSKind,
+};
+use tree_sitter::{Node, Point};
+use vfs::FileId;
+
+use crate::{assists::Assist, CompletionItem, FilePosition};
+
+/// A callback that can be generated for an argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CallbackStub {
+    /// Name of the new callback.
+    pub(crate) name: String,
+
+    /// Declaration of the new callback.
+    pub(crate) text: String,
+}
+
+/// Returns the callbacks which can be generated for the argument at index `arg_idx` of a
+/// call, one per variant of the expected typeset or funcenum.
+///
+/// # Parameters
+/// - `sema`: The semantics
+/// - `file_id`: The [`FileId`] of the file of the call
+/// - `callee`: The node of the name of the called function
+/// - `arg_idx`: The index of the argument
+/// - `name`: Returns the name of the callback from the name of the expected type
+fn callback_stubs(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    callee: &Node,
+    arg_idx: usize,
+    name: impl FnOnce(&str) -> String,
+) -> Vec<CallbackStub> {
+    let db = sema.db;
+    let Some(DefResolution::Function(function)) = sema.find_def(file_id, callee) else {
+        return Vec::new();
+    };
+    let Some(type_def) = function.parameter_type_def(db, arg_idx) else {
+        return Vec::new();
+    };
+    let Some(type_name) = type_def.name(db) else {
+        return Vec::new();
+    };
+    let name = name(&type_name.to_string());
+    let stubs = match type_def {
+        DefResolution::Typedef(it) => vec![it.as_callback_stub(db, &name)],
+        DefResolution::Typeset(it) => it
+            .children(db)
+            .into_iter()
+            .map(|it| it.as_callback_stub(db, &name))
+            .collect(),
+        DefResolution::Functag(it) => vec![it.as_callback_stub(db, &name)],
+        DefResolution::Funcenum(it) => it
+            .children(db)
+            .into_iter()
+            .map(|it| it.as_callback_stub(db, &name))
+            .collect(),
+        _ => return Vec::new(),
+    };
+
+    stubs
+        .into_iter()
+        .flatten()
+        .map(|text| CallbackStub {
+            name: name.clone(),
+            text,
+        })
+        .collect()
+}
+
+/// Returns a name for a new callback which does not clash with an existing definition,
+/// e.g. `EventHook_PlayerDeath` for `HookEvent("player_death", |`.
+fn callback_name(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    type_name: &str,
+    hint: Option<&str>,
+) -> String {
+    let suffix = hint
+        .map(|hint| {
+            hint.split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|it| !it.is_empty())
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                })
+                .collect::<String>()
+        })
+        .filter(|it| !it.is_empty())
+        .unwrap_or_else(|| "Callback".to_string());
+    let base = format!("{}_{}", type_name, suffix);
+    let existing = sema
+        .defs_in_scope(file_id)
+        .into_iter()
+        .filter_map(|def| def.name(sema.db))
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    let mut name = base.clone();
+    let mut idx = 1;
+    while existing.contains(&name) {
+        idx += 1;
+        name = format!("{}{}", base, idx);
+    }
+
+    name
+}
+
+/// Returns the arguments of a call, without the comments.
+fn call_args<'tree>(call_arguments: &Node<'tree>) -> Vec<Node<'tree>> {
+    call_arguments
+        .named_children(&mut call_arguments.walk())
+        .filter(|n| TSKind::from(n) != TSKind::comment)
+        .collect()
+}
+
+/// Returns the node of the name of the called function of a call.
+fn callee<'tree>(call_arguments: &Node<'tree>) -> Option<Node<'tree>> {
+    let function = call_arguments.prev_named_sibling()?;
+    match TSKind::from(function) {
+        TSKind::identifier => Some(function),
+        TSKind::field_access => function.child_by_field_name("field"),
+        _ => None,
+    }
+}
+
+/// Returns the first string literal argument of a call, without its quotes.
+fn string_hint<'a>(args: &[Node], source: &'a str) -> Option<&'a str> {
+    args.iter()
+        .find(|n| TSKind::from(*n) == TSKind::string_literal)
+        .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+        .map(|it| it.trim_matches('"'))
+}
+
+/// Returns the position after the function which contains `point`, where a new callback
+/// can be inserted, in user coordinates.
+fn insertion_position(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    point: Point,
+) -> Option<Position> {
+    let tree = sema.parse(file_id);
+    let mut container = tree.root_node().descendant_for_point_range(point, point)?;
+    while TSKind::from(container) != TSKind::function_definition {
+        container = container.parent()?;
+    }
+    let end = ts_range_to_lsp_range(&container.range()).end;
+    let preprocessing_results = sema.preprocess_file(file_id);
+
+    Some(s_range_to_u_range(preprocessing_results.offsets(), Range::new(end, end)).end)
+}
+
+/// Returns completions which generate a new callback for the argument of `node`.
+///
+/// # Parameters
+/// - `sema`: The semantics
+/// - `pos`: The position of the completion
+/// - `node`: The node at the position of the completion, in a tree where a placeholder
+///   identifier was inserted at the position
+/// - `source`: The source code of that tree
+pub(crate) fn callback_completions(
+    sema: &Semantics<RootDatabase>,
+    pos: FilePosition,
+    node: &Node,
+    source: &str,
+) -> Vec<CompletionItem> {
+    let Some(call_arguments) = node.parent() else {
+        return Vec::new();
+    };
+    if TSKind::from(node) != TSKind::identifier
+        || TSKind::from(call_arguments) != TSKind::call_arguments
+    {
+        return Vec::new();
+    }
+    let args = call_args(&call_arguments);
+    let Some(arg_idx) = args.iter().position(|it| it == node) else {
+        return Vec::new();
+    };
+    // The callee is before the placeholder, so its position is the same in the original tree.
+    let tree = sema.parse(pos.file_id);
+    let Some(callee) = callee(&call_arguments).and_then(|callee| {
+        tree.root_node()
+            .descendant_for_point_range(callee.start_position(), callee.end_position())
+    }) else {
+        return Vec::new();
+    };
+    let point = lsp_position_to_ts_point(&pos.position);
+    let Some(insert_pos) = insertion_position(sema, pos.file_id, point) else {
+        return Vec::new();
+    };
+
+    let hint = string_hint(&args, source);
+    callback_stubs(sema, pos.file_id, &callee, arg_idx, |type_name| {
+        callback_name(sema, pos.file_id, type_name, hint)
+    })
+    .into_iter()
+    .map(|stub| CompletionItem {
+        label: stub.name.clone().into(),
+        kind: SymbolKind::Function.into(),
+        detail: Some(format!("Generate callback\n{}", stub.text)),
+        insert_text: Some(stub.name),
+        additional_text_edits: vec![(
+            Range::new(insert_pos, insert_pos),
+            format!("\n\n{}", stub.text),
+        )],
+        ..Default::default()
+    })
+    .collect()
+}
+
+/// Assist which generates a new callback for an unresolved identifier argument of a call.
+///
+/// ```sourcepawn
+/// CreateTimer(1.0, Timer_Callback);
+/// ```
+/// ->
+/// ```sourcepawn
+/// public Action Timer_Callback(Handle timer, any data)
+/// {
+///     return Plugin_Continue;
+/// }
+/// ```
+pub(crate) fn generate_callback(
+    sema: &Semantics<RootDatabase>,
+    mut pos: FilePosition,
+    acc: &mut Vec<Assist>,
+) -> Option<()> {
+    let preprocessing_results = sema.preprocess_file(pos.file_id);
+    let _ = u_pos_to_s_pos(
+        preprocessing_results.args_map(),
+        preprocessing_results.offsets(),
+        &mut pos.position,
+    );
+    let tree = sema.parse(pos.file_id);
+    let source = sema.preprocessed_text(pos.file_id);
+    let point = lsp_position_to_ts_point(&pos.position);
+    let node = tree.root_node().descendant_for_point_range(point, point)?;
+    if TSKind::from(node) != TSKind::identifier {
+        return None;
+    }
+    let call_arguments = node.parent()?;
+    if TSKind::from(call_arguments) != TSKind::call_arguments {
+        return None;
+    }
+    if sema.find_def(pos.file_id, &node).is_some() {
+        // The callback already exists.
+        return None;
+    }
+    let args = call_args(&call_arguments);
+    let arg_idx = args.iter().position(|it| *it == node)?;
+    let callee = callee(&call_arguments)?;
+    let name = node.utf8_text(source.as_bytes()).ok()?;
+    let insert_pos = insertion_position(sema, pos.file_id, point)?;
+
+    let stubs = callback_stubs(sema, pos.file_id, &callee, arg_idx, |_| name.to_string());
+    let several = stubs.len() > 1;
+    for stub in stubs {
+        let text = stub.text;
+        let signature = text.lines().next().unwrap_or_default().to_string();
+        let mut source_change = SourceChange::default();
+        source_change.insert(
+            pos.file_id,
+            TextEdit::new(Range::new(insert_pos, insert_pos), format!("\n\n{}", text)),
+        );
+        acc.push(Assist {
+            label: if several {
+                format!("Generate callback `{}`", signature)
+            } else {
+                format!("Generate callback `{}`", name)
+            },
+            source_change,
+        });
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use base_db::FileRange;
+    use lsp_types::Url;
+
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    const TEXT: &str = r#"functag public Action:SrvCmd(args);
+typedef Timer = function void (Handle timer, any data);
+typeset EventHook {
+    function Action (Event event, const char[] name, bool dontBroadcast);
+    function void (Event event, const char[] name, bool dontBroadcast);
+};
+
+native void RegServerCmd(const char[] cmd, SrvCmd callback);
+native void CreateTimer(float interval, Timer callback);
+native void HookEvent(const char[] name, EventHook callback);
+
+void EventHook_PlayerDeath() {}
+
+void main() {
+    RegServerCmd("sm_test", Cmd_Test);
+    CreateTimer(1.0, Timer_Tick);
+    HookEvent("player_death", );
+}
+"#;
+
+    /// Returns the position of the first occurrence of `needle` in `text`.
+    fn position(text: &str, needle: &str) -> Position {
+        let offset = text.find(needle).expect("the needle is in the text");
+        let line = text[..offset].matches('\n').count();
+        let column = offset - text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        Position::new(line as u32, column as u32)
+    }
+
+    /// Returns the declarations of the callbacks named `Foo` for the argument at index
+    /// `arg_idx` of the call of the function at `needle`.
+    fn stubs(needle: &str, arg_idx: usize) -> Vec<String> {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", TEXT)]);
+        let sema = Semantics::new(&host.db);
+        let tree = sema.parse(file_ids[0]);
+        let point = lsp_position_to_ts_point(&position(TEXT, needle));
+        let callee = tree
+            .root_node()
+            .descendant_for_point_range(point, point)
+            .unwrap();
+        callback_stubs(&sema, file_ids[0], &callee, arg_idx, |_| "Foo".to_string())
+            .into_iter()
+            .map(|stub| stub.text)
+            .collect()
+    }
+
+    #[test]
+    fn functag_stub() {
+        assert_eq!(
+            stubs("RegServerCmd(\"", 1),
+            vec!["public Action Foo(args)\n{\n\treturn Plugin_Continue;\n}".to_string()]
+        );
+    }
+
+    #[test]
+    fn typedef_stub() {
+        assert_eq!(
+            stubs("CreateTimer(1.0", 1),
+            vec!["public void Foo(Handle timer, any data)\n{\n}".to_string()]
+        );
+    }
+
+    #[test]
+    fn typeset_stubs() {
+        assert_eq!(
+            stubs("HookEvent(\"", 1),
+            vec![
+                "public Action Foo(Event event, const char[] name, bool dontBroadcast)\n{\n\treturn Plugin_Continue;\n}"
+                    .to_string(),
+                "public void Foo(Event event, const char[] name, bool dontBroadcast)\n{\n}"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_stub_for_other_types() {
+        assert!(stubs("RegServerCmd(\"", 0).is_empty());
+    }
+
+    #[test]
+    fn names() {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", TEXT)]);
+        let sema = Semantics::new(&host.db);
+        assert_eq!(
+            callback_name(&sema, file_ids[0], "Timer", None),
+            "Timer_Callback"
+        );
+        assert_eq!(
+            callback_name(&sema, file_ids[0], "SrvCmd", Some("sm_test")),
+            "SrvCmd_SmTest"
+        );
+        // `EventHook_PlayerDeath` is already defined.
+        assert_eq!(
+            callback_name(&sema, file_ids[0], "EventHook", Some("player_death")),
+            "EventHook_PlayerDeath2"
+        );
+    }
+
+    #[test]
+    fn insertion_after_enclosing_function() {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", TEXT)]);
+        let sema = Semantics::new(&host.db);
+        let point = lsp_position_to_ts_point(&position(TEXT, "Timer_Tick"));
+        assert_eq!(
+            insertion_position(&sema, file_ids[0], point),
+            Some(Position::new(17, 1))
+        );
+        let point = lsp_position_to_ts_point(&position(TEXT, "native void HookEvent"));
+        assert_eq!(insertion_position(&sema, file_ids[0], point), None);
+    }
+
+    #[test]
+    fn completions_of_event_hook() {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", TEXT)]);
+        let mut pos = position(TEXT, "\"player_death\", ");
+        pos.character += "\"player_death\", ".len() as u32;
+        let file_id_to_url = |_| Url::parse("file:///project/main.sp").unwrap();
+        let res = host
+            .analysis()
+            .completions(
+                FilePosition {
+                    file_id: file_ids[0],
+                    position: pos,
+                },
+                None,
+                Vec::new(),
+                AssertUnwindSafe(&file_id_to_url),
+            )
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|item| {
+                item.detail
+                    .as_deref()
+                    .is_some_and(|it| it.starts_with("Generate callback"))
+            })
+            .map(|item| (item.label.to_string(), item.additional_text_edits))
+            .collect::<Vec<_>>();
+        let insert_pos = Position::new(17, 1);
+        assert_eq!(
+            res,
+            vec![
+                (
+                    "EventHook_PlayerDeath2".to_string(),
+                    vec![(
+                        Range::new(insert_pos, insert_pos),
+                        "\n\npublic Action EventHook_PlayerDeath2(Event event, const char[] name, bool dontBroadcast)\n{\n\treturn Plugin_Continue;\n}"
+                            .to_string()
+                    )]
+                ),
+                (
+                    "EventHook_PlayerDeath2".to_string(),
+                    vec![(
+                        Range::new(insert_pos, insert_pos),
+                        "\n\npublic void EventHook_PlayerDeath2(Event event, const char[] name, bool dontBroadcast)\n{\n}"
+                            .to_string()
+                    )]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn assist_for_unresolved_argument() {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", TEXT)]);
+        let pos = position(TEXT, "Timer_Tick");
+        let assists = host
+            .analysis()
+            .assists(FileRange {
+                file_id: file_ids[0],
+                range: Range::new(pos, pos),
+            })
+            .unwrap()
+            .into_iter()
+            .filter(|assist| assist.label.starts_with("Generate callback"))
+            .collect::<Vec<_>>();
+        assert_eq!(assists.len(), 1);
+        assert_eq!(assists[0].label, "Generate callback `Timer_Tick`");
+        let insert_pos = Position::new(17, 1);
+        assert_eq!(
+            assists[0].source_change.source_file_edits[&file_ids[0]],
+            vec![TextEdit::new(
+                Range::new(insert_pos, insert_pos),
+                "\n\npublic void Timer_Tick(Handle timer, any data)\n{\n}".to_string()
+            )]
+        );
+    }
+}
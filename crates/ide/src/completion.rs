@@ -1,20 +1,21 @@
+mod cases;
 mod defaults;
-mod documentation;
+pub(crate) mod documentation;
 mod includes;
 mod item;
 
 use std::panic::AssertUnwindSafe;
 
 use base_db::FilePosition;
-use hir::{DefResolution, Field, Function, HasSource, LocalDef, Property, Semantics};
+use fxhash::FxHashSet;
+use hir::{DefResolution, Field, Function, LocalDef, Property, Semantics};
 use hir_def::{DefDatabase, FieldId, FunctionKind};
-use ide_db::{Documentation, RootDatabase, SymbolKind};
+use ide_db::{DocumentationDatabase, RootDatabase, SymbolKind};
 pub use item::{CompletionItem, CompletionKind};
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use lsp_types::Url;
 use paths::AbsPathBuf;
-use preprocessor::db::PreprocDatabase;
 use regex::Regex;
 use smol_str::{SmolStr, ToSmolStr};
 use syntax::{utils::lsp_position_to_ts_point, TSKind};
@@ -22,13 +23,18 @@ use tree_sitter::{InputEdit, Point};
 use vfs::FileId;
 
 use crate::{
+    auto_include::{fuzzy_match, include_edit, unincluded_defs},
+    callbacks::callback_completions,
     completion::{
-        defaults::get_default_completions,
+        cases::{get_case_completions, is_case_label},
+        defaults::{get_default_completions, get_preprocessor_completions, KeywordContext},
         documentation::{get_doc_completion, is_documentation_start},
         includes::{get_include_completions, is_include_statement},
     },
-    events::{event_name, events_completions},
+    events::{event_fields_completions, event_name, events_completions},
+    game_data::{game_data_completions, game_data_kind},
     hover::{render_def, Render},
+    translations::phrases_completions,
 };
 
 pub fn completions(
@@ -37,7 +43,6 @@ pub fn completions(
     trigger_character: Option<char>,
     include_directories: Vec<AbsPathBuf>,
     file_id_to_url: AssertUnwindSafe<&dyn Fn(FileId) -> Url>,
-    events_game_name: Option<&str>,
 ) -> Option<Vec<CompletionItem>> {
     let sema = &Semantics::new(db);
     let preprocessing_results = sema.preprocess_file(pos.file_id);
@@ -66,6 +71,23 @@ pub fn completions(
         return None;
     }
 
+    // Preprocessor directives are not in the preprocessed text, use the user's source code.
+    let text = sema.file_text(pos.file_id);
+    if let Some(res) = text
+        .lines()
+        .nth(pos.position.line as usize)
+        .and_then(|line| {
+            let pre_line = line.split_at(utf16_column_to_byte(line, pos.position.character));
+            get_preprocessor_completions(pre_line.0, pos.position.line)
+        })
+    {
+        return res.into();
+    }
+    if trigger_character == Some('#') {
+        // We are past the preprocessor directive check, so we can return early.
+        return None;
+    }
+
     if is_documentation_start(split_line.0, split_line.1) {
         return get_doc_completion(db, point, pos.file_id);
     }
@@ -74,6 +96,12 @@ pub fn completions(
         return None;
     }
 
+    if is_case_label(split_line.0) {
+        if let Some(res) = get_case_completions(sema, &tree, pos.file_id, point) {
+            return res.into();
+        }
+    }
+
     lazy_static! {
         pub static ref NEW_REGEX: Regex = Regex::new(r"new\s+$").unwrap();
     }
@@ -111,7 +139,20 @@ pub fn completions(
 
     // Check if we are in an event such as "EventHook"
     if event_name(&node, &preprocessed_text).is_some() {
-        return events_completions(events_game_name).into();
+        return events_completions(db).into();
+    }
+    // Check if we are in an event field such as `event.GetInt("userid")`
+    if let Some(access) = hir::events::event_field_access(db, pos.file_id, &node, &new_source_code)
+    {
+        return event_fields_completions(db, &access).into();
+    }
+    // Check if we are in a phrase name such as `PrintToChat(client, "%t", "Welcome")`
+    if hir::translations::phrase_name(db, pos.file_id, &node, &new_source_code).is_some() {
+        return phrases_completions(db, pos.file_id).into();
+    }
+    // Check if we are in game data such as `FindConVar("sv_cheats")`
+    if let Some(kind) = game_data_kind(&node, &new_source_code) {
+        return game_data_completions(db, kind).into();
     }
     if trigger_character == Some('"') {
         return None;
@@ -157,7 +198,8 @@ pub fn completions(
             break;
         }
     }
-    let mut add_defaults = false;
+    let mut keyword_context = None;
+    let mut add_unincluded = false;
     let mut local_context = true;
 
     log::debug!("completion container kind: {:?}", container.kind());
@@ -171,7 +213,12 @@ pub fn completions(
         | TSKind::enum_struct_method
         | TSKind::methodmap_method_constructor
         | TSKind::methodmap_method_destructor => {
-            add_defaults = true;
+            keyword_context = Some(KeywordContext::in_function(
+                container,
+                node,
+                &new_source_code,
+            ));
+            add_unincluded = true;
             if let Some(res) = in_function_completion(container, tree, sema, pos, point) {
                 res
             } else {
@@ -205,16 +252,36 @@ pub fn completions(
         }
         _ => {
             local_context = false;
+            add_unincluded = true;
+            let context = (TSKind::from(container) == TSKind::source_file)
+                .then(|| KeywordContext::in_global_scope(node));
+            let only_types = matches!(
+                context,
+                Some(KeywordContext::Methodmap | KeywordContext::EnumStruct)
+            );
+            keyword_context = context;
             sema.defs_in_scope(pos.file_id)
                 .into_iter()
                 .filter(|it| !matches!(it, DefResolution::Local(_) | DefResolution::Global(_)))
+                .filter(|it| {
+                    // Only types can be declared in the body of a methodmap or an enum struct.
+                    !only_types
+                        || matches!(
+                            it,
+                            DefResolution::Methodmap(_)
+                                | DefResolution::EnumStruct(_)
+                                | DefResolution::Enum(_)
+                                | DefResolution::Typedef(_)
+                                | DefResolution::Typeset(_)
+                                | DefResolution::Functag(_)
+                                | DefResolution::Funcenum(_)
+                        )
+                })
                 .collect_vec()
         }
     };
 
-    let mut res = Vec::new();
-
-    defs.into_iter().for_each(|def| match &def {
+    let push_def = |res: &mut Vec<CompletionItem>, def: DefResolution| match &def {
         DefResolution::Function(it) => {
             let data = sema.db.function_data(it.id());
             match data.kind {
@@ -448,15 +515,83 @@ pub fn completions(
             });
         }
         DefResolution::File(_) => (),
-    });
+    };
 
-    if add_defaults {
-        res.extend(get_default_completions(local_context));
+    let mut res = Vec::new();
+    defs.into_iter().for_each(|def| push_def(&mut res, def));
+
+    if let Some(context) = &keyword_context {
+        res.extend(get_default_completions(context));
+    }
+    if matches!(keyword_context, Some(KeywordContext::Function { .. })) {
+        // Check if we are in a callback argument such as `CreateTimer(1.0, Timer_)`
+        res.extend(callback_completions(sema, pos, &node, &new_source_code));
+    }
+
+    if add_unincluded {
+        // Only suggest the definitions of the include directories once something has been
+        // typed, as there can be a lot of them.
+        let prefix = split_line
+            .0
+            .rsplit(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .unwrap_or_default();
+        if !prefix.is_empty() {
+            let mut names = res
+                .iter()
+                .map(|it| it.label.clone())
+                .collect::<FxHashSet<_>>();
+            for unincluded in unincluded_defs(db, pos.file_id) {
+                // Filter on the names first, to only render the matching definitions.
+                let mut items = Vec::new();
+                unincluded
+                    .defs
+                    .into_iter()
+                    .filter(|(name, _)| fuzzy_match(&name.to_string(), prefix))
+                    .for_each(|(_, def)| push_def(&mut items, def));
+                if items.is_empty() {
+                    continue;
+                }
+                let Some(edit) = include_edit(
+                    db,
+                    pos.file_id,
+                    &unincluded.include_path,
+                    unincluded.include_file_id,
+                ) else {
+                    continue;
+                };
+                res.extend(
+                    items
+                        .into_iter()
+                        .filter(|it| it.kind != CompletionKind::Snippet)
+                        .filter(|it| names.insert(it.label.clone()))
+                        .map(|it| CompletionItem {
+                            detail: Some(format!("#include <{}>", unincluded.include_path)),
+                            additional_text_edits: vec![edit.clone()],
+                            ..it
+                        }),
+                );
+            }
+        }
     }
 
     res.into()
 }
 
+/// Returns the byte offset of a UTF-16 column in a line, or the length of the line if the
+/// column is past its end.
+fn utf16_column_to_byte(line: &str, column: u32) -> usize {
+    let mut utf16 = 0;
+    for (idx, c) in line.char_indices() {
+        if utf16 >= column as usize {
+            return idx;
+        }
+        utf16 += c.len_utf16();
+    }
+
+    line.len()
+}
+
 fn field_access_completions(
     container: tree_sitter::Node,
     sema: &Semantics<RootDatabase>,
@@ -630,13 +765,8 @@ pub fn resolve_completion(
         }
         item.insert_text_format = Some(lsp_types::InsertTextFormat::SNIPPET);
     }
-    let file_id = def.file_id(db);
-    let source = db.preprocessed_text(file_id);
-    let tree = db.parse(file_id);
-    if let Some(def_node) = def.clone().source(db, &tree).map(|it| it.value) {
-        if let Some(docs) = Documentation::from_node(def_node, source.as_bytes()) {
-            item.documentation = Some(docs.into());
-        }
+    if let Some(docs) = db.doc_comment(def.clone()) {
+        item.documentation = Some(docs.as_ref().into());
     }
     if let Some(Render::String(render)) = render_def(db, def) {
         item.detail = Some(render);
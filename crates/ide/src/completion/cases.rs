@@ -0,0 +1,54 @@
+use base_db::Tree;
+use hir::{DefResolution, Semantics};
+use ide_db::{RootDatabase, SymbolKind};
+use lazy_static::lazy_static;
+use regex::Regex;
+use smol_str::ToSmolStr;
+use syntax::TSKind;
+use tree_sitter::Point;
+use vfs::FileId;
+
+use crate::completion::item::CompletionItem;
+
+/// Check whether the current prefix line is a `case` label being written, such as `case Foo, B`.
+///
+/// # Arguments
+///
+/// * `pre_line` - Prefix line to process.
+pub(super) fn is_case_label(pre_line: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^\s*case\s+(?:[^:]*,\s*)?\w*$").unwrap();
+    }
+
+    RE.is_match(pre_line)
+}
+
+/// Returns the variants of the enum the enclosing `switch` statement is over, which do not
+/// have a `case` yet.
+///
+/// Returns `None` if the `switch` is not over an enum, or if all its variants are handled.
+pub(super) fn get_case_completions(
+    sema: &Semantics<RootDatabase>,
+    tree: &Tree,
+    file_id: FileId,
+    point: Point,
+) -> Option<Vec<CompletionItem>> {
+    let mut switch_node = tree.root_node().descendant_for_point_range(point, point)?;
+    while TSKind::from(switch_node) != TSKind::switch_statement {
+        switch_node = switch_node.parent()?;
+    }
+    let cases = hir::switches::switch_cases(sema.db, file_id, &switch_node)?;
+    let res = cases
+        .missing
+        .into_iter()
+        .map(|variant| CompletionItem {
+            label: variant.name(sema.db).to_smolstr(),
+            kind: SymbolKind::Variant.into(),
+            deprecated: variant.is_deprecated(sema.db),
+            data: Some(DefResolution::Variant(variant)),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    (!res.is_empty()).then_some(res)
+}
@@ -1,7 +1,11 @@
-use std::str::FromStr;
-
 use ide_db::SymbolKind;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use lsp_types::{Position, Range};
+use regex::Regex;
 use smol_str::SmolStr;
+use syntax::TSKind;
+use tree_sitter::Node;
 
 use crate::CompletionItem;
 
@@ -13,12 +17,225 @@ const DEFAULT_KEYWORD: &[&str] = &[
     "any", "bool", "char", "float", "Float", "int", "String", "const", "static",
 ];
 
-const DEFAULT_GLOBAL_KEYWORDS: &[&str] = &["stock", "public", "forward", "native", "void"];
+const DEFAULT_GLOBAL_KEYWORDS: &[&str] = &[
+    "stock",
+    "public",
+    "forward",
+    "native",
+    "void",
+    "enum",
+    "methodmap",
+    "typedef",
+    "typeset",
+];
 
 const DEFAULT_LOCAL_KEYWORDS: &[&str] = &[
-    "continue", "break", "return", "sizeof", "switch", "case", "view_as", "this",
+    "continue", "break", "sizeof", "view_as", "this", "new", "delete", "decl",
+];
+
+const METHODMAP_KEYWORDS: &[&str] = &["public", "native", "static", "property"];
+
+const ENUM_STRUCT_KEYWORDS: &[&str] = &["any", "bool", "char", "float", "int", "void"];
+
+const GLOBAL_SNIPPETS: &[(&str, &str)] = &[
+    ("enum struct", "enum struct ${1:Name}\n{\n\t$0\n}"),
+    ("methodmap", "methodmap ${1:Name} < ${2:Handle}\n{\n\t$0\n}"),
+];
+
+const METHODMAP_SNIPPETS: &[(&str, &str)] = &[
+    ("public native", "public native ${1:void} ${2:Name}($3);"),
+    (
+        "property",
+        "property ${1:int} ${2:Name}\n{\n\tpublic get()\n\t{\n\t\t$0\n\t}\n}",
+    ),
+];
+
+const LOCAL_SNIPPETS: &[(&str, &str)] = &[
+    ("if", "if ($1)\n{\n\t$0\n}"),
+    ("else", "else\n{\n\t$0\n}"),
+    (
+        "for",
+        "for (int ${1:i} = 0; $1 < ${2:length}; $1++)\n{\n\t$0\n}",
+    ),
+    ("while", "while ($1)\n{\n\t$0\n}"),
+    ("do", "do\n{\n\t$0\n}\nwhile ($1);"),
+    ("switch", "switch ($1)\n{\n\tcase $2:\n\t{\n\t\t$0\n\t}\n}"),
+];
+
+const SWITCH_SNIPPETS: &[(&str, &str)] = &[
+    ("case", "case $1:\n{\n\t$0\n}"),
+    ("default", "default:\n{\n\t$0\n}"),
+];
+
+const PREPROCESSOR_SNIPPETS: &[(&str, &str)] = &[
+    ("#include", "#include <$0>"),
+    ("#tryinclude", "#tryinclude <$0>"),
+    ("#define", "#define ${1:NAME} $0"),
+    ("#undef", "#undef $0"),
+    ("#if", "#if $0"),
+    ("#if defined", "#if defined $0"),
+    ("#elseif", "#elseif $0"),
+    ("#else", "#else"),
+    ("#endif", "#endif"),
+    ("#endinput", "#endinput"),
+    ("#pragma semicolon", "#pragma semicolon 1"),
+    (
+        "#pragma newdecls",
+        "#pragma newdecls ${1|required,optional|}",
+    ),
+    ("#pragma dynamic", "#pragma dynamic ${1:131072}"),
+    ("#pragma deprecated", "#pragma deprecated $0"),
+    ("#pragma unused", "#pragma unused $0"),
+    ("#error", "#error \"$0\""),
+    ("#warning", "#warning \"$0\""),
+    ("#assert", "#assert $0"),
 ];
 
+/// Syntactic context of a completion, used to only suggest the keywords and snippets
+/// which are valid there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum KeywordContext {
+    /// At the top level of a file.
+    TopLevel,
+
+    /// In the body of a methodmap, outside of its methods.
+    Methodmap,
+
+    /// In the body of an enum struct, outside of its methods.
+    EnumStruct,
+
+    /// In the body of a function.
+    Function {
+        /// Return type of the function, if it is known.
+        return_type: Option<String>,
+
+        /// Whether the completion is directly in the body of a `switch`.
+        in_switch: bool,
+    },
+}
+
+impl KeywordContext {
+    /// Returns the context of a completion in the body of a function.
+    ///
+    /// # Arguments
+    ///
+    /// * `container` - Node of the function.
+    /// * `node` - Node of the completion.
+    /// * `source` - Source code of the tree of the nodes.
+    pub(super) fn in_function(container: Node, node: Node, source: &str) -> Self {
+        let return_type = container
+            .child_by_field_name("returnType")
+            .and_then(|it| it.utf8_text(source.as_bytes()).ok())
+            .map(|it| it.trim().trim_end_matches(':').trim().to_string())
+            .filter(|it| !it.is_empty());
+        let mut in_switch = false;
+        let mut parent = node.parent();
+        while let Some(candidate) = parent {
+            if candidate == container {
+                break;
+            }
+            match TSKind::from(candidate) {
+                TSKind::switch_case => break,
+                TSKind::switch_statement => {
+                    in_switch = true;
+                    break;
+                }
+                _ => parent = candidate.parent(),
+            }
+        }
+
+        Self::Function {
+            return_type,
+            in_switch,
+        }
+    }
+
+    /// Returns the context of a completion at the top level of a file, or in the body of
+    /// a methodmap or of an enum struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - Node of the completion.
+    pub(super) fn in_global_scope(node: Node) -> Self {
+        let mut parent = node.parent();
+        while let Some(candidate) = parent {
+            match TSKind::from(candidate) {
+                TSKind::methodmap => return Self::Methodmap,
+                TSKind::enum_struct => return Self::EnumStruct,
+                _ => parent = candidate.parent(),
+            }
+        }
+
+        Self::TopLevel
+    }
+}
+
+/// Returns the `return` snippet for a function returning `return_type`.
+fn return_snippet(return_type: Option<&str>) -> &'static str {
+    match return_type {
+        Some("void") => "return;",
+        Some("Action") => "return Plugin_${1|Continue,Handled,Stop,Changed|};",
+        Some("bool") => "return ${1|true,false|};",
+        _ => "return $1;",
+    }
+}
+
+fn snippets(
+    items: &'static [(&'static str, &'static str)],
+) -> impl Iterator<Item = CompletionItem> {
+    items.iter().map(|(label, snippet)| CompletionItem {
+        label: SmolStr::new(label),
+        kind: CompletionKind::Snippet,
+        insert_text: Some(snippet.to_string()),
+        ..Default::default()
+    })
+}
+
+fn keywords(items: &'static [&'static str]) -> impl Iterator<Item = CompletionItem> {
+    items.iter().map(|label| CompletionItem {
+        label: SmolStr::new(label),
+        kind: CompletionKind::Keyword,
+        ..Default::default()
+    })
+}
+
+fn literals() -> impl Iterator<Item = CompletionItem> {
+    keywords(DEFAULT_LITERAL).map(|it| CompletionItem {
+        kind: CompletionKind::Literal,
+        ..it
+    })
+}
+
+/// Returns the completions of the preprocessor directives, if `pre_line` is the beginning
+/// of a directive, e.g. `#if`, `#pragma sem` or `#`. The columns of the edits are in UTF-16
+/// code units.
+///
+/// # Arguments
+///
+/// * `pre_line` - Prefix line to process, in the user's source code.
+/// * `line` - Line of the completion.
+pub(super) fn get_preprocessor_completions(
+    pre_line: &str,
+    line: u32,
+) -> Option<Vec<CompletionItem>> {
+    lazy_static! {
+        // `#if` and `#pragma` can be followed by a second word, e.g. `#pragma semicolon`.
+        static ref RE: Regex = Regex::new(r"^(\s*)#(?:\w*|(?:if|pragma)\s+\w*)$").unwrap();
+    }
+    let captures = RE.captures(pre_line)?;
+    let start = captures.get(1)?.as_str().encode_utf16().count() as u32;
+    let end = pre_line.encode_utf16().count() as u32;
+    let range = Range::new(Position::new(line, start), Position::new(line, end));
+
+    snippets(PREPROCESSOR_SNIPPETS)
+        .map(|item| CompletionItem {
+            text_edit: item.insert_text.clone().map(|text| (range, text)),
+            ..item
+        })
+        .collect_vec()
+        .into()
+}
+
 const HARDCODED_DEFINES: &[&str] = &[
     "INVALID_FUNCTION",
     "__DATE__",
@@ -33,44 +250,219 @@ const HARDCODED_DEFINES: &[&str] = &[
 ];
 
 // FIXME: Return an iterator here instead.
-pub(super) fn get_default_completions(locals: bool) -> Vec<CompletionItem> {
+pub(super) fn get_default_completions(context: &KeywordContext) -> Vec<CompletionItem> {
     let mut res = vec![];
-    res.extend(DEFAULT_LITERAL.iter().filter_map(|label| {
-        CompletionItem {
-            label: SmolStr::from_str(label).ok()?,
-            kind: CompletionKind::Literal,
-            ..Default::default()
+    match context {
+        KeywordContext::TopLevel => {
+            res.extend(keywords(DEFAULT_KEYWORD));
+            res.extend(keywords(DEFAULT_GLOBAL_KEYWORDS));
+            res.extend(snippets(GLOBAL_SNIPPETS));
+            res.extend(literals());
         }
-        .into()
+        KeywordContext::Methodmap => {
+            res.extend(keywords(DEFAULT_KEYWORD));
+            res.extend(keywords(METHODMAP_KEYWORDS));
+            res.extend(snippets(METHODMAP_SNIPPETS));
+            return res;
+        }
+        KeywordContext::EnumStruct => {
+            res.extend(keywords(ENUM_STRUCT_KEYWORDS));
+            return res;
+        }
+        KeywordContext::Function {
+            return_type,
+            in_switch,
+        } => {
+            res.extend(literals());
+            res.extend(keywords(DEFAULT_KEYWORD));
+            res.extend(keywords(DEFAULT_LOCAL_KEYWORDS));
+            res.extend(snippets(LOCAL_SNIPPETS));
+            if *in_switch {
+                res.extend(snippets(SWITCH_SNIPPETS));
+            }
+            res.push(CompletionItem {
+                label: SmolStr::new("return"),
+                kind: CompletionKind::Snippet,
+                insert_text: Some(return_snippet(return_type.as_deref()).to_string()),
+                detail: return_type.clone(),
+                ..Default::default()
+            });
+        }
+    }
+
+    res.extend(HARDCODED_DEFINES.iter().map(|label| CompletionItem {
+        label: SmolStr::new(label),
+        kind: SymbolKind::Macro.into(),
+        detail: Some("Hardcoded constant".to_string()),
+        ..Default::default()
     }));
 
-    res.extend(
-        DEFAULT_KEYWORD
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use base_db::FilePosition;
+    use lsp_types::Url;
+
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    /// Returns the completions at `position` of `text`.
+    fn completions(text: &str, position: Position) -> Vec<CompletionItem> {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", text)]);
+        let file_id_to_url = |_| Url::parse("file:///project/main.sp").unwrap();
+        host.analysis()
+            .completions(
+                FilePosition {
+                    file_id: file_ids[0],
+                    position,
+                },
+                None,
+                Vec::new(),
+                AssertUnwindSafe(&file_id_to_url),
+            )
+            .unwrap()
+            .unwrap_or_default()
+    }
+
+    fn has_label(items: &[CompletionItem], label: &str) -> bool {
+        items.iter().any(|it| it.label == label)
+    }
+
+    /// Returns the `return` snippet and its detail.
+    fn return_item(items: &[CompletionItem]) -> Option<(String, Option<String>)> {
+        items
             .iter()
-            .chain(if locals {
-                DEFAULT_LOCAL_KEYWORDS
-            } else {
-                DEFAULT_GLOBAL_KEYWORDS
+            .find(|it| it.label == "return" && it.kind == CompletionKind::Snippet)
+            .map(|it| {
+                (
+                    it.insert_text.clone().unwrap_or_default(),
+                    it.detail.clone(),
+                )
             })
-            .filter_map(|label| {
-                CompletionItem {
-                    label: SmolStr::from_str(label).ok()?,
-                    kind: CompletionKind::Keyword,
-                    ..Default::default()
-                }
-                .into()
-            }),
-    );
-
-    res.extend(HARDCODED_DEFINES.iter().filter_map(|label| {
-        CompletionItem {
-            label: SmolStr::from_str(label).ok()?,
-            kind: SymbolKind::Macro.into(),
-            detail: Some("Hardcoded constant".to_string()),
-            ..Default::default()
-        }
-        .into()
-    }));
+    }
 
-    res
+    #[test]
+    fn methodmap_body() {
+        let items = completions(
+            "methodmap Counter < Handle {\n    \n}\n",
+            Position::new(1, 4),
+        );
+        assert!(has_label(&items, "property"));
+        assert!(has_label(&items, "public native"));
+        assert!(!has_label(&items, "stock"));
+        assert!(!has_label(&items, "if"));
+    }
+
+    #[test]
+    fn enum_struct_body() {
+        let items = completions("enum struct Point {\n    \n}\n", Position::new(1, 4));
+        assert!(has_label(&items, "int"));
+        assert!(has_label(&items, "void"));
+        assert!(!has_label(&items, "public native"));
+        assert!(!has_label(&items, "methodmap"));
+    }
+
+    #[test]
+    fn directly_in_switch() {
+        let items = completions(
+            "void main(int value) {\n    switch (value)\n    {\n        \n    }\n}\n",
+            Position::new(3, 8),
+        );
+        assert!(has_label(&items, "case"));
+        assert!(has_label(&items, "default"));
+    }
+
+    #[test]
+    fn in_case_block() {
+        let items = completions(
+            "void main(int value) {\n    switch (value)\n    {\n        case 1:\n        {\n            \n        }\n    }\n}\n",
+            Position::new(5, 12),
+        );
+        assert!(has_label(&items, "if"));
+        assert!(!has_label(&items, "case"));
+        assert!(!has_label(&items, "default"));
+    }
+
+    #[test]
+    fn typed_return() {
+        let items = completions(
+            "Action OnCommand(int client) {\n    \n}\n",
+            Position::new(1, 4),
+        );
+        assert_eq!(
+            return_item(&items),
+            Some((
+                "return Plugin_${1|Continue,Handled,Stop,Changed|};".to_string(),
+                Some("Action".to_string())
+            ))
+        );
+
+        let items = completions("bool IsReady() {\n    \n}\n", Position::new(1, 4));
+        assert_eq!(
+            return_item(&items),
+            Some((
+                "return ${1|true,false|};".to_string(),
+                Some("bool".to_string())
+            ))
+        );
+
+        let items = completions("void Reset() {\n    \n}\n", Position::new(1, 4));
+        assert_eq!(
+            return_item(&items),
+            Some(("return;".to_string(), Some("void".to_string())))
+        );
+    }
+
+    #[test]
+    fn old_style_return() {
+        let items = completions(
+            "public Action:Command_Test(client, args) {\n    \n}\n",
+            Position::new(1, 4),
+        );
+        assert_eq!(
+            return_item(&items),
+            Some((
+                "return Plugin_${1|Continue,Handled,Stop,Changed|};".to_string(),
+                Some("Action".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn untyped_return() {
+        assert_eq!(return_snippet(None), "return $1;");
+        assert_eq!(return_snippet(Some("int")), "return $1;");
+    }
+
+    #[test]
+    fn preprocessor_directives() {
+        let items = get_preprocessor_completions("    #pra", 3).unwrap();
+        let range = Range::new(Position::new(3, 4), Position::new(3, 8));
+        assert!(items
+            .iter()
+            .all(|it| it.text_edit.as_ref().unwrap().0 == range));
+        assert!(has_label(&items, "#pragma semicolon"));
+
+        assert!(get_preprocessor_completions("int x; #", 0).is_none());
+        assert!(get_preprocessor_completions("#include <", 0).is_none());
+    }
+
+    #[test]
+    fn preprocessor_directive_utf16_columns() {
+        // The ideographic space is 3 bytes long in UTF-8, but 1 code unit in UTF-16.
+        let items = get_preprocessor_completions("\u{3000}#if def", 0).unwrap();
+        let range = Range::new(Position::new(0, 1), Position::new(0, 8));
+        assert_eq!(
+            items
+                .iter()
+                .find(|it| it.label == "#if defined")
+                .and_then(|it| it.text_edit.clone()),
+            Some((range, "#if defined $0".to_string()))
+        );
+    }
 }
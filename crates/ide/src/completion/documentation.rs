@@ -1,4 +1,4 @@
-use hir::{DefResolution, Semantics};
+use hir::{DefResolution, FunctionType, Semantics};
 use ide_db::RootDatabase;
 use lazy_static::lazy_static;
 use lsp_types::{Position, Range};
@@ -34,7 +34,15 @@ pub(super) fn get_doc_completion(
 ) -> Option<Vec<crate::CompletionItem>> {
     let sema = &Semantics::new(db);
     let tree = sema.parse(file_id);
-    let mut point_below = Point::new(point.row.saturating_add(1), point.column);
+    let source = sema.preprocessed_text(file_id);
+    // The item starts after its `#pragma deprecated` directive.
+    let row_below = source
+        .lines()
+        .enumerate()
+        .skip(point.row.saturating_add(1))
+        .find(|(_, line)| !line.trim_start().starts_with("#pragma"))?
+        .0;
+    let mut point_below = Point::new(row_below, point.column);
     let mut node = tree
         .root_node()
         .descendant_for_point_range(point_below, point_below)
@@ -56,6 +64,9 @@ pub(super) fn get_doc_completion(
                 | TSKind::methodmap_method_constructor
                 | TSKind::methodmap_method_destructor
                 | TSKind::methodmap_method
+                | TSKind::methodmap_native
+                | TSKind::methodmap_native_constructor
+                | TSKind::methodmap_native_destructor
                 | TSKind::methodmap_property_getter
                 | TSKind::methodmap_property_setter
                 | TSKind::enum_struct_method
@@ -75,29 +86,8 @@ pub(super) fn get_doc_completion(
     node = node.parent()?;
     let name = node.child_by_field_name("name")?;
     let def = sema.find_name_def(file_id, &name)?;
-    let tab_str = tab_str(
-        sema.preprocessed_text(file_id)
-            .lines()
-            .nth(point_below.row)?,
-    )?;
-    let res = match def {
-        DefResolution::Function(it) => {
-            snippet_builder(it.parameters(db), it.type_ref(db), &tab_str)
-        }
-        DefResolution::Typedef(it) => {
-            snippet_builder(it.parameters(db), it.return_type(db).into(), &tab_str)
-        }
-        DefResolution::Functag(it) => {
-            snippet_builder(it.parameters(db), it.return_type(db), &tab_str)
-        }
-        DefResolution::Enum(_)
-        | DefResolution::Methodmap(_)
-        | DefResolution::EnumStruct(_)
-        | DefResolution::Typeset(_)
-        | DefResolution::Funcenum(_)
-        | DefResolution::Property(_) => snippet_builder(vec![], None, &tab_str),
-        _ => unreachable!(),
-    };
+    let tab_str = tab_str(source.lines().nth(point_below.row)?)?;
+    let res = snippet_builder(&doc_signature(db, &def)?, &tab_str);
 
     Some(vec![CompletionItem {
         label: "Insert documentation".to_smolstr(),
@@ -115,7 +105,50 @@ pub(super) fn get_doc_completion(
     }])
 }
 
-fn snippet_builder(params: Vec<String>, ret_type: Option<String>, tab_str: &str) -> String {
+/// Parameters and return value of a definition, as documented by the `@param` and `@return`
+/// tags of its documentation comment.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct DocSignature {
+    pub(crate) params: Vec<String>,
+    pub(crate) returns_value: bool,
+}
+
+/// Returns the signature to document for a definition, or `None` if it cannot have a
+/// documentation comment.
+///
+/// Constructors and destructors do not document a return value.
+pub(crate) fn doc_signature(db: &RootDatabase, def: &DefResolution) -> Option<DocSignature> {
+    let returns_value = |ret_type: Option<String>| ret_type.is_some_and(|it| it != "void");
+    let res = match def {
+        DefResolution::Function(it) => DocSignature {
+            params: it.parameters(db),
+            returns_value: !matches!(
+                it.kind(db),
+                FunctionType::Constructor | FunctionType::Destructor
+            ) && returns_value(it.type_ref(db)),
+        },
+        DefResolution::Typedef(it) => DocSignature {
+            params: it.parameters(db),
+            returns_value: returns_value(it.return_type(db).into()),
+        },
+        DefResolution::Functag(it) => DocSignature {
+            params: it.parameters(db),
+            returns_value: returns_value(it.return_type(db)),
+        },
+        DefResolution::Enum(_)
+        | DefResolution::Methodmap(_)
+        | DefResolution::EnumStruct(_)
+        | DefResolution::Typeset(_)
+        | DefResolution::Funcenum(_)
+        | DefResolution::Property(_) => DocSignature::default(),
+        _ => return None,
+    };
+
+    Some(res)
+}
+
+fn snippet_builder(signature: &DocSignature, tab_str: &str) -> String {
+    let params = &signature.params;
     let mut buf = Vec::new();
     buf.push("${1:Description}".to_string());
     let mut max = 0;
@@ -132,13 +165,11 @@ fn snippet_builder(params: Vec<String>, ret_type: Option<String>, tab_str: &str)
             i + 2
         ))
     }
-    if let Some(ret) = ret_type {
-        if ret != "void" {
-            buf.push(format!(
-                "@return  ${{{}:Return description}}",
-                params.len() + 2
-            ));
-        }
+    if signature.returns_value {
+        buf.push(format!(
+            "@return  ${{{}:Return description}}",
+            params.len() + 2
+        ));
     }
     let mut res = format!("{}/**", tab_str);
     for line in buf.iter() {
@@ -165,3 +196,45 @@ fn tab_str(pre_line: &str) -> Option<String> {
         .to_string()
         .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    fn doc_completion(text: &str, point: Point) -> Option<String> {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", text)]);
+        get_doc_completion(&host.db, point, file_ids[0])?
+            .pop()?
+            .insert_text
+    }
+
+    #[test]
+    fn deprecated_native() {
+        assert_eq!(
+            doc_completion(
+                "int g_Count;\n\n#pragma deprecated Use Add2\nnative int Add(int amount);\n",
+                Point::new(1, 0)
+            ),
+            Some(
+                "/**\n * ${1:Description}\n * @param amount  ${2:Parameter description}\n * @return  ${3:Return description}\n */"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn methodmap_native() {
+        assert_eq!(
+            doc_completion(
+                "methodmap Counter < Handle {\n\n    public native void Reset(int value, bool notify);\n}\n",
+                Point::new(1, 4)
+            ),
+            Some(
+                "    /**\n     * ${1:Description}\n     * @param value   ${2:Parameter description}\n     * @param notify  ${3:Parameter description}\n     */"
+                    .to_string()
+            )
+        );
+    }
+}
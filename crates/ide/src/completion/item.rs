@@ -35,6 +35,9 @@ pub struct CompletionItem {
 
     pub text_edit: Option<(Range, String)>,
 
+    /// Edits to apply elsewhere in the file when the completion is accepted.
+    pub additional_text_edits: Vec<(Range, String)>,
+
     pub data: Option<DefResolution>,
 }
 
@@ -0,0 +1,485 @@
+//! This module provides the generation of the SourceMod style documentation comments of
+//! functions, methods and properties, either as an assist or when typing `/**` above them.
+//! The comments are also completed when typing `/*` above an item, see
+//! [`crate::completion::documentation`].
+
+use hir::Semantics;
+use ide_db::{DocComment, Documentation, RootDatabase, SourceChange};
+use lsp_types::{Position, Range, TextEdit};
+use preprocessor::{s_range_to_u_range, u_pos_to_s_pos};
+use syntax::{
+    utils::{lsp_position_to_ts_point, ts_range_to_lsp_range},
+    TSKind,
+};
+use tree_sitter::{Node, Point};
+use vfs::FileId;
+
+use crate::{assists::Assist, completion::documentation::doc_signature, FilePosition};
+
+/// Assist which generates the documentation comment of the function, method or property at
+/// the cursor, or updates it after a change of its signature.
+///
+/// The descriptions of the existing comment are kept.
+///
+/// ```sourcepawn
+/// /**
+///  * Slaps a client.
+///  *
+///  * @param client  Client index.
+///  */
+/// native void SlapClient(int client, int damage);
+/// ```
+/// ->
+/// ```sourcepawn
+/// /**
+///  * Slaps a client.
+///  *
+///  * @param client  Client index.
+///  * @param damage
+///  */
+/// native void SlapClient(int client, int damage);
+/// ```
+pub(crate) fn generate_doc_comment(
+    sema: &Semantics<RootDatabase>,
+    mut pos: FilePosition,
+    acc: &mut Vec<Assist>,
+) -> Option<()> {
+    let preprocessing_results = sema.preprocess_file(pos.file_id);
+    let _ = u_pos_to_s_pos(
+        preprocessing_results.args_map(),
+        preprocessing_results.offsets(),
+        &mut pos.position,
+    );
+    let tree = sema.parse(pos.file_id);
+    let source = sema.preprocessed_text(pos.file_id);
+    let point = lsp_position_to_ts_point(&pos.position);
+    let mut node = tree.root_node().descendant_for_point_range(point, point)?;
+    while !is_documentable(&node) {
+        node = node.parent()?;
+    }
+    // Only offer the assist on the signature, not in the body.
+    if point < node.start_position() || point > header_end(&node) {
+        return None;
+    }
+
+    let (comments, anchor) = doc_comment_nodes(node);
+    let existing = Documentation::from_comments(
+        comments
+            .iter()
+            .rev()
+            .filter_map(|comment| comment.utf8_text(source.as_bytes()).ok()),
+    )
+    .parse();
+    let indent = indentation(&source, node.start_position().row);
+    let text = render_doc_comment(sema, pos.file_id, &node, &existing, &indent)?;
+
+    let (range, new_text, label) = match (comments.last(), comments.first()) {
+        (Some(first), Some(last)) => {
+            let range = Range::new(
+                ts_range_to_lsp_range(&first.range()).start,
+                ts_range_to_lsp_range(&last.range()).end,
+            );
+            let old_text = source
+                .get(first.start_byte()..last.end_byte())
+                .unwrap_or_default();
+            if old_text == text {
+                return None;
+            }
+            (range, text, "Update documentation comment")
+        }
+        _ => {
+            let start = Position::new(anchor.start_position().row as u32, 0);
+            (
+                Range::new(start, start),
+                format!("{}{}\n", indent, text),
+                "Generate documentation comment",
+            )
+        }
+    };
+    let mut source_change = SourceChange::default();
+    source_change.insert(
+        pos.file_id,
+        TextEdit::new(
+            s_range_to_u_range(preprocessing_results.offsets(), range),
+            new_text,
+        ),
+    );
+    acc.push(Assist {
+        label: label.to_string(),
+        source_change,
+    });
+
+    Some(())
+}
+
+/// Returns the edits to apply after a character was typed at `pos`.
+///
+/// Typing `/**` on the line above a function, method or property expands it into its
+/// documentation comment.
+pub(crate) fn on_char_typed(
+    db: &RootDatabase,
+    mut pos: FilePosition,
+    char_typed: char,
+) -> Option<Vec<TextEdit>> {
+    if char_typed != '*' {
+        return None;
+    }
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(pos.file_id);
+    let _ = u_pos_to_s_pos(
+        preprocessing_results.args_map(),
+        preprocessing_results.offsets(),
+        &mut pos.position,
+    );
+    let source = sema.preprocessed_text(pos.file_id);
+    let mut lines = source.lines().enumerate().skip(pos.position.line as usize);
+    let (row, line) = lines.next()?;
+    let before = line.get(..pos.position.character as usize)?;
+    // The editor may have closed the comment already.
+    if before.trim_start() != "/**" || !matches!(line.trim(), "/**" | "/** */") {
+        return None;
+    }
+
+    // The item documented by the comment starts on the next line, after the directives.
+    let (next_row, next_line) =
+        lines.find(|(_, line)| !line.trim_start().starts_with("#pragma"))?;
+    let column = next_line.len() - next_line.trim_start().len();
+    let point = Point::new(next_row, column);
+    let tree = sema.parse(pos.file_id);
+    let mut node = tree.root_node().descendant_for_point_range(point, point)?;
+    while !is_documentable(&node) {
+        node = node.parent().filter(|it| it.start_position() == point)?;
+    }
+
+    let indent = indentation(&source, row);
+    let text = render_doc_comment(sema, pos.file_id, &node, &DocComment::default(), &indent)?;
+    let range = Range::new(
+        Position::new(row as u32, indent.len() as u32),
+        Position::new(row as u32, line.len() as u32),
+    );
+
+    Some(vec![TextEdit::new(
+        s_range_to_u_range(preprocessing_results.offsets(), range),
+        text,
+    )])
+}
+
+/// Whether the node is an item which can have a documentation comment generated.
+fn is_documentable(node: &Node) -> bool {
+    matches!(
+        TSKind::from(node),
+        TSKind::function_declaration
+            | TSKind::function_definition
+            | TSKind::enum_struct_method
+            | TSKind::methodmap_method
+            | TSKind::methodmap_method_constructor
+            | TSKind::methodmap_method_destructor
+            | TSKind::methodmap_native
+            | TSKind::methodmap_native_constructor
+            | TSKind::methodmap_native_destructor
+            | TSKind::methodmap_property
+    )
+}
+
+/// Returns the end of the signature of an item, before its body.
+fn header_end(node: &Node) -> Point {
+    node.children(&mut node.walk())
+        .find(|child| matches!(TSKind::from(child), TSKind::block | TSKind::anon_LBRACE))
+        .map(|body| body.start_position())
+        .unwrap_or_else(|| node.end_position())
+}
+
+/// Returns the comments above an item which make up its documentation, from the closest to
+/// the farthest, and the topmost node the item starts with, including its
+/// `#pragma deprecated` directive.
+///
+/// This follows the rules of [`Documentation::from_node`].
+fn doc_comment_nodes(node: Node) -> (Vec<Node>, Node) {
+    let mut comments = Vec::new();
+    let mut anchor = node;
+    let mut current = node;
+    while let Some(prev_node) = current.prev_sibling() {
+        if current
+            .start_position()
+            .row
+            .saturating_sub(prev_node.end_position().row)
+            != 1
+        {
+            break;
+        }
+        match TSKind::from(prev_node) {
+            TSKind::preproc_pragma if comments.is_empty() => anchor = prev_node,
+            TSKind::comment => {
+                comments.push(prev_node);
+                if prev_node.start_position().row != prev_node.end_position().row {
+                    // Only keep one multi-line comment
+                    break;
+                }
+            }
+            _ => break,
+        }
+        current = prev_node;
+    }
+
+    (comments, anchor)
+}
+
+/// Returns the whitespace at the start of a line.
+fn indentation(source: &str, row: usize) -> String {
+    source
+        .lines()
+        .nth(row)
+        .map(|line| {
+            line.chars()
+                .take_while(|c| c.is_whitespace())
+                .collect::<String>()
+        })
+        .unwrap_or_default()
+}
+
+/// Renders the documentation comment of an item, with the descriptions of `existing`.
+///
+/// The first line of the comment is not indented and the comment does not end with a new
+/// line.
+fn render_doc_comment(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    node: &Node,
+    existing: &DocComment,
+    indent: &str,
+) -> Option<String> {
+    let name = node.child_by_field_name("name")?;
+    let signature = doc_signature(sema.db, &sema.find_def(file_id, &name)?)?;
+    let mut tags = Vec::new();
+    for param in signature.params {
+        let description = existing.param(&param).unwrap_or_default().to_string();
+        tags.push((format!("@param {}", param), description));
+    }
+    if signature.returns_value {
+        tags.push((
+            "@return".to_string(),
+            existing.returns.clone().unwrap_or_default(),
+        ));
+    }
+    if let Some(errors) = &existing.errors {
+        tags.push(("@error".to_string(), errors.clone()));
+    }
+    tags.extend(
+        existing
+            .notes
+            .iter()
+            .map(|note| ("@note".to_string(), note.clone())),
+    );
+    tags.extend(
+        existing
+            .see
+            .iter()
+            .map(|see| ("@see".to_string(), see.clone())),
+    );
+    if let Some(deprecated) = &existing.deprecated {
+        tags.push(("@deprecated".to_string(), deprecated.clone()));
+    }
+    tags.extend(
+        existing
+            .others
+            .iter()
+            .map(|(tag, text)| (format!("@{}", tag), text.clone())),
+    );
+
+    let mut lines = vec!["/**".to_string()];
+    lines.extend(existing.description.lines().map(comment_line));
+    if existing.description.is_empty() {
+        lines.push(comment_line(""));
+    }
+    if !tags.is_empty() {
+        lines.push(comment_line(""));
+    }
+    // Align the descriptions of the tags.
+    let width = tags.iter().map(|(tag, _)| tag.len()).max().unwrap_or(0) + 2;
+    for (tag, text) in tags {
+        let mut text_lines = text.lines();
+        match text_lines.next() {
+            Some(first) => lines.push(comment_line(&format!("{:width$}{}", tag, first))),
+            None => lines.push(comment_line(&tag)),
+        }
+        lines.extend(text_lines.map(|line| comment_line(&format!("{:width$}{}", "", line))));
+    }
+    lines.push(" */".to_string());
+
+    Some(lines.join(&format!("\n{}", indent)))
+}
+
+/// Returns a line of a documentation comment, without trailing whitespace.
+fn comment_line(text: &str) -> String {
+    format!(" * {}", text).trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use base_db::FileRange;
+
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    /// Returns the edit of the assist with the label `label` at `position` of the first file.
+    fn assist_edit(text: &str, position: Position, label: &str) -> Option<TextEdit> {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", text)]);
+        let assists = host
+            .analysis()
+            .assists(FileRange {
+                file_id: file_ids[0],
+                range: Range::new(position, position),
+            })
+            .unwrap();
+        let assist = assists.into_iter().find(|assist| assist.label == label)?;
+
+        assist.source_change.source_file_edits[&file_ids[0]]
+            .first()
+            .cloned()
+    }
+
+    #[test]
+    fn generate() {
+        assert_eq!(
+            assist_edit(
+                "int Add(int a, int b) { return a + b; }\n",
+                Position::new(0, 5),
+                "Generate documentation comment"
+            ),
+            Some(TextEdit::new(
+                Range::new(Position::new(0, 0), Position::new(0, 0)),
+                "/**\n *\n *\n * @param a\n * @param b\n * @return\n */\n".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn update_keeps_descriptions() {
+        let text = r#"/**
+ * Slaps a client.
+ *
+ * @param client  Client index.
+ */
+native void SlapClient(int client, int damage);
+"#;
+        assert_eq!(
+            assist_edit(text, Position::new(5, 14), "Update documentation comment"),
+            Some(TextEdit::new(
+                Range::new(Position::new(0, 0), Position::new(4, 3)),
+                "/**\n * Slaps a client.\n *\n * @param client  Client index.\n * @param damage\n */"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn no_assist_in_body() {
+        assert_eq!(
+            assist_edit(
+                "int Add(int a, int b) {\n    return a + b;\n}\n",
+                Position::new(1, 6),
+                "Generate documentation comment"
+            ),
+            None
+        );
+    }
+
+    /// Returns the edits after typing `char_typed` at `position` of the first file.
+    fn typed(text: &str, position: Position, char_typed: char) -> Option<Vec<TextEdit>> {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", text)]);
+        host.analysis()
+            .on_char_typed(
+                FilePosition {
+                    file_id: file_ids[0],
+                    position,
+                },
+                char_typed,
+            )
+            .unwrap()
+    }
+
+    fn edit(start: Position, end: Position, text: &str) -> Option<Vec<TextEdit>> {
+        Some(vec![TextEdit::new(
+            Range::new(start, end),
+            text.to_string(),
+        )])
+    }
+
+    #[test]
+    fn on_type_above_function() {
+        assert_eq!(
+            typed(
+                "/** */\nint Add(int a, int b) { return a + b; }\n",
+                Position::new(0, 3),
+                '*'
+            ),
+            edit(
+                Position::new(0, 0),
+                Position::new(0, 6),
+                "/**\n *\n *\n * @param a\n * @param b\n * @return\n */"
+            )
+        );
+    }
+
+    #[test]
+    fn on_type_above_native_and_forward() {
+        assert_eq!(
+            typed(
+                "/** */\nnative void SlapClient(int client, int damage);\n",
+                Position::new(0, 3),
+                '*'
+            ),
+            edit(
+                Position::new(0, 0),
+                Position::new(0, 6),
+                "/**\n *\n *\n * @param client\n * @param damage\n */"
+            )
+        );
+        assert_eq!(
+            typed(
+                "/** */\nforward void OnPluginStart();\n",
+                Position::new(0, 3),
+                '*'
+            ),
+            edit(Position::new(0, 0), Position::new(0, 6), "/**\n *\n */")
+        );
+    }
+
+    #[test]
+    fn on_type_above_method_and_property() {
+        assert_eq!(
+            typed(
+                "methodmap Counter < Handle {\n    /** */\n    public void Add(int amount) {}\n}\n",
+                Position::new(1, 7),
+                '*'
+            ),
+            edit(
+                Position::new(1, 4),
+                Position::new(1, 10),
+                "/**\n     *\n     *\n     * @param amount\n     */"
+            )
+        );
+        assert_eq!(
+            typed(
+                "methodmap Counter < Handle {\n    /** */\n    property int Value {\n        public get() { return 0; }\n    }\n}\n",
+                Position::new(1, 7),
+                '*'
+            ),
+            edit(Position::new(1, 4), Position::new(1, 10), "/**\n     *\n     */")
+        );
+    }
+
+    #[test]
+    fn on_type_ignored() {
+        let text = "/** */\nint Add(int a, int b) { return a + b; }\n";
+        // Another character, or not right after `/**`.
+        assert_eq!(typed(text, Position::new(0, 3), '/'), None);
+        assert_eq!(typed(text, Position::new(0, 2), '*'), None);
+        // Not above a documentable item.
+        assert_eq!(
+            typed("/** */\nint g_Count;\n", Position::new(0, 3), '*'),
+            None
+        );
+    }
+}
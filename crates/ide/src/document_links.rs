@@ -0,0 +1,182 @@
+//! This module provides the links of a file: the paths of its includes, and the files
+//! referenced by the string literals passed to `LoadTranslations`, `BuildPath(Path_SM, ...)`
+//! and `AutoExecConfig`.
+
+use base_db::{PathLiteralKind, SourceDatabase, RE_CHEVRON, RE_QUOTE};
+use ide_db::RootDatabase;
+use lsp_types::{Position, Range};
+use vfs::FileId;
+
+use crate::extensions::utf16_range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentLink {
+    pub range: Range,
+    pub target: DocumentLinkTarget,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentLinkTarget {
+    /// A file known by the server.
+    File(FileId),
+
+    /// A path relative to the `addons/sourcemod` folder, which is resolved by the client of
+    /// the analysis.
+    SourceModPath(String),
+
+    /// A path relative to the game folder, which is resolved by the client of the analysis.
+    GamePath(String),
+
+    /// The path of an include which could not be resolved.
+    Unresolved(String),
+}
+
+/// Returns the links of a file, in user coordinates with UTF-16 columns.
+pub(crate) fn document_links(db: &RootDatabase, file_id: FileId) -> Vec<DocumentLink> {
+    let text = db.file_text(file_id);
+    let mut res = include_links(db, file_id, &text);
+
+    let translation_files = db.known_translation_files();
+    res.extend(
+        db.file_loaded_translations(file_id)
+            .iter()
+            .filter_map(|loaded| {
+                let (target, _) = translation_files
+                    .iter()
+                    .find(|(_, name)| *name == loaded.name)?;
+                Some(DocumentLink {
+                    range: utf16_range(&text, string_content_range(loaded.range)),
+                    target: DocumentLinkTarget::File(*target),
+                })
+            }),
+    );
+
+    res.extend(
+        db.file_path_literals(file_id)
+            .iter()
+            .map(|literal| DocumentLink {
+                range: utf16_range(&text, string_content_range(literal.range)),
+                target: match literal.kind {
+                    PathLiteralKind::SourceMod => {
+                        DocumentLinkTarget::SourceModPath(literal.path.clone())
+                    }
+                    PathLiteralKind::Game => DocumentLinkTarget::GamePath(literal.path.clone()),
+                },
+            }),
+    );
+
+    res
+}
+
+/// Returns the links of the paths of the `#include` and `#tryinclude` directives of a file,
+/// from the includes found when building the include graph.
+///
+/// An unresolved `#tryinclude` has no link: the include is optional, so
+/// [`SourceDatabase::file_includes`] only records the unresolved `#include` directives, which
+/// are errors.
+fn include_links(db: &RootDatabase, file_id: FileId, text: &str) -> Vec<DocumentLink> {
+    let (includes, unresolved) = db.file_includes(file_id);
+    let resolved = includes.iter().filter_map(|include| {
+        // The implicit include of `sourcemod.inc` has no directive.
+        let range = include.range()?;
+        Some((range, DocumentLinkTarget::File(include.file_id())))
+    });
+    let unresolved = unresolved.iter().filter_map(|include| {
+        Some((
+            unresolved_path_range(text, include.range)?,
+            DocumentLinkTarget::Unresolved(include.path.clone()),
+        ))
+    });
+    let mut res = resolved
+        .chain(unresolved)
+        .map(|(range, target)| DocumentLink {
+            range: utf16_range(text, string_content_range(range)),
+            target,
+        })
+        .collect::<Vec<_>>();
+    res.sort_by_key(|link| (link.range.start, link.range.end));
+
+    res
+}
+
+/// Returns the range of the path of an unresolved include, delimiters included, from the
+/// range of its directive. Columns are in bytes.
+fn unresolved_path_range(text: &str, range: Range) -> Option<Range> {
+    let line = text.lines().nth(range.start.line as usize)?;
+    let directive = line.get(range.start.character as usize..range.end.character as usize)?;
+    let path = RE_QUOTE
+        .captures(directive)
+        .or_else(|| RE_CHEVRON.captures(directive))?
+        .get(0)?;
+
+    Some(Range::new(
+        Position::new(
+            range.start.line,
+            range.start.character + path.start() as u32,
+        ),
+        Position::new(range.start.line, range.start.character + path.end() as u32),
+    ))
+}
+
+/// Returns the range of a single line string literal, without its delimiters.
+fn string_content_range(range: Range) -> Range {
+    Range::new(
+        Position::new(range.start.line, range.start.character + 1),
+        Position::new(range.end.line, range.end.character.saturating_sub(1)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    #[test]
+    fn links_of_includes_and_paths() {
+        let (host, file_ids) = analysis_host(&[
+            (
+                "/project/main.sp",
+                r#"/* 😀 */ #include "helpers.sp"
+#include <missing>
+#tryinclude <absent>
+#tryinclude <present>
+
+public void OnPluginStart()
+{
+    BuildPath(Path_SM, path, sizeof(path), "configs/foo.cfg");
+    AutoExecConfig(true, "plugin");
+}
+"#,
+            ),
+            ("/project/helpers.sp", ""),
+            ("/include/present.inc", ""),
+        ]);
+        assert_eq!(
+            document_links(&host.db, file_ids[0]),
+            vec![
+                DocumentLink {
+                    range: Range::new(Position::new(0, 19), Position::new(0, 29)),
+                    target: DocumentLinkTarget::File(file_ids[1]),
+                },
+                DocumentLink {
+                    range: Range::new(Position::new(1, 10), Position::new(1, 17)),
+                    target: DocumentLinkTarget::Unresolved("missing.inc".to_string()),
+                },
+                // The unresolved `#tryinclude <absent>` has no link.
+                DocumentLink {
+                    range: Range::new(Position::new(3, 13), Position::new(3, 20)),
+                    target: DocumentLinkTarget::File(file_ids[2]),
+                },
+                DocumentLink {
+                    range: Range::new(Position::new(7, 44), Position::new(7, 59)),
+                    target: DocumentLinkTarget::SourceModPath("configs/foo.cfg".to_string()),
+                },
+                DocumentLink {
+                    range: Range::new(Position::new(8, 26), Position::new(8, 32)),
+                    target: DocumentLinkTarget::GamePath("cfg/sourcemod/plugin.cfg".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -1,9 +1,10 @@
-//! This module provides completions/hovers for event names and utilities to
-//! check if events completions/hovers should be provided for a given node.
+//! This module provides completions/hovers for event names and event fields, and
+//! utilities to check if events completions/hovers should be provided for a given node.
 
-use completion_data::DATABASE;
+use completion_data::Event;
 use fxhash::FxHashMap;
-use ide_db::Documentation;
+use hir::{db::HirDatabase, events::EventFieldAccess};
+use ide_db::{Documentation, RootDatabase, SymbolKind};
 use preprocessor::{s_range_to_u_range, Offset};
 use smol_str::ToSmolStr;
 use syntax::{utils::ts_range_to_lsp_range, TSKind};
@@ -55,93 +56,146 @@ pub fn event_name(node: &Node, source: &str) -> Option<String> {
     raw_name.trim_matches('"').to_string().into()
 }
 
-/// Returns completions for event names.
-///
-/// If `events_game_name` is `Some`, and if the game exits in the database,
-/// only completions for the given game will be returned.
+/// Returns completions for event names, for the games selected by the user.
 ///
 /// # Parameters
-/// - `events_game_name`: The name of the game to get completions for
-pub fn events_completions(events_game_name: Option<&str>) -> Vec<CompletionItem> {
-    if let Some(game_name) = events_game_name {
-        if let Some(game) = DATABASE.get(game_name) {
-            return game
-                .events()
-                .iter()
-                .cloned()
-                .chain(DATABASE.generic_events())
-                .map(|ev| CompletionItem {
-                    label: ev.name().to_smolstr(),
-                    kind: CompletionKind::Literal,
-                    detail: Some(game_name.to_string()),
-                    documentation: Documentation::from(&ev).into(),
-                    ..Default::default()
-                })
-                .collect();
-        }
-    }
-    DATABASE
-        .iter()
-        .flat_map(|(_, game)| {
-            game.events().iter().map(|ev| CompletionItem {
+/// - `db`: The database
+pub fn events_completions(db: &RootDatabase) -> Vec<CompletionItem> {
+    let data = db.completion_data();
+    let games = data.games(&db.selected_games());
+    // Only document the events when some games are selected, to keep the response small.
+    let documented = games.len() != data.iter().count();
+    games
+        .into_iter()
+        .flat_map(|game| {
+            game.events().iter().map(move |ev| CompletionItem {
                 label: ev.name().to_smolstr(),
                 kind: CompletionKind::Literal,
                 detail: Some(game.name().to_string()),
+                documentation: documented.then(|| Documentation::from(ev)),
                 ..Default::default()
             })
         })
         .collect()
 }
 
-/// Returns hover information for an event.
-///
-/// If `events_game_name` is `Some`, and if the game exits in the database, only return the
-/// hover information for the given game. Otherwise, return hover information for all games.
+/// Returns hover information for an event, for the games selected by the user.
 ///
 /// # Parameters
-/// - `events_game_name`: The name of the game to get hover information for
+/// - `db`: The database
 /// - `name`: The name of the event
 /// - `node`: The node of the string literal of the event name
 /// - `offsets`: The preprocessor offsets
 pub fn event_hover(
-    events_game_name: Option<&str>,
+    db: &RootDatabase,
     name: &str,
     node: &Node,
     offsets: &FxHashMap<u32, Vec<Offset>>,
 ) -> Option<RangeInfo<HoverResult>> {
-    if let Some(game) = events_game_name {
-        if let Some(ev) = DATABASE.get_event(game, name) {
-            let markup = Markup::from(format!(
-                "## {}\n\n{}",
-                game,
-                Documentation::from(ev).to_markdown()
-            ));
-            let res = HoverResult {
-                markup,
-                actions: Default::default(),
-            };
-            return Some(RangeInfo::new(
-                s_range_to_u_range(offsets, ts_range_to_lsp_range(&node.range())),
-                res,
-            ));
-        }
+    let mut res = Vec::new();
+    for (game, ev) in events_by_name(db, &[name.to_string()]) {
+        res.push(format!("## {}", game));
+        res.push(Documentation::from(&ev).to_markdown());
+    }
+    if res.is_empty() {
+        return None;
+    }
 
-        None
-    } else {
-        let mut res = Vec::new();
-        DATABASE
-            .get_events(name)
-            .into_iter()
-            .for_each(|(game, ev)| {
-                res.push(format!("## {}", game));
-                res.push(Documentation::from(&ev).to_markdown());
+    Some(RangeInfo::new(
+        s_range_to_u_range(offsets, ts_range_to_lsp_range(&node.range())),
+        HoverResult {
+            markup: Markup::from(res.join("\n\n")),
+            actions: Default::default(),
+        },
+    ))
+}
+
+/// Returns the events with the given names, with the name of their game, for the games
+/// selected by the user.
+fn events_by_name(db: &RootDatabase, names: &[String]) -> Vec<(String, Event<'static>)> {
+    let data = db.completion_data();
+    let games = data.games(&db.selected_games());
+    games
+        .into_iter()
+        .flat_map(|game| {
+            game.events()
+                .iter()
+                .filter(|ev| names.iter().any(|name| name == ev.name()))
+                .map(|ev| (game.name().to_string(), ev.clone()))
+        })
+        .collect()
+}
+
+/// Returns completions for the fields of the events hooked with the callback of an
+/// event field access.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `access`: The event field access
+pub fn event_fields_completions(
+    db: &RootDatabase,
+    access: &EventFieldAccess,
+) -> Vec<CompletionItem> {
+    let mut res: Vec<CompletionItem> = Vec::new();
+    for (_, ev) in events_by_name(db, &access.events) {
+        for attr in ev.attributes() {
+            if res.iter().any(|it| it.label == attr.name()) {
+                continue;
+            }
+            res.push(CompletionItem {
+                label: attr.name().to_smolstr(),
+                kind: SymbolKind::Field.into(),
+                detail: Some(format!("{} ({})", attr.r#type(), ev.name())),
+                documentation: attr.description().map(Documentation::from),
+                ..Default::default()
             });
-        Some(RangeInfo::new(
-            s_range_to_u_range(offsets, ts_range_to_lsp_range(&node.range())),
-            HoverResult {
-                markup: Markup::from(res.join("\n\n")),
-                actions: Default::default(),
-            },
-        ))
+        }
     }
+
+    res
+}
+
+/// Returns hover information for an event field.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `access`: The event field access
+/// - `node`: The node of the string literal of the field name
+/// - `offsets`: The preprocessor offsets
+pub fn event_field_hover(
+    db: &RootDatabase,
+    access: &EventFieldAccess,
+    node: &Node,
+    offsets: &FxHashMap<u32, Vec<Offset>>,
+) -> Option<RangeInfo<HoverResult>> {
+    let mut res = Vec::new();
+    for (game, ev) in events_by_name(db, &access.events) {
+        let Some(attr) = ev.attributes().iter().find(|it| it.name() == access.field) else {
+            continue;
+        };
+        let mut buf = format!(
+            "## {}\n\n`{}` (__{}__) of `{}`",
+            game,
+            attr.name(),
+            attr.r#type(),
+            ev.name()
+        );
+        if let Some(desc) = attr.description() {
+            buf.push_str(&format!(" — {}", desc));
+        }
+        if !res.contains(&buf) {
+            res.push(buf);
+        }
+    }
+    if res.is_empty() {
+        return None;
+    }
+
+    Some(RangeInfo::new(
+        s_range_to_u_range(offsets, ts_range_to_lsp_range(&node.range())),
+        HoverResult {
+            markup: Markup::from(res.join("\n\n")),
+            actions: Default::default(),
+        },
+    ))
 }
@@ -0,0 +1,213 @@
+//! Finds the natives provided by the SourceMod extensions a project includes.
+
+use base_db::{FileRange, SourceDatabase};
+use fxhash::{FxHashMap, FxHashSet};
+use hir_def::{DefDatabase, FileItem, FunctionKind};
+use ide_db::RootDatabase;
+use lsp_types::{Position, Range};
+use sourcepawn_lexer::{Literal, TokenKind};
+use vfs::FileId;
+
+/// The include of a SourceMod extension, which declares an `Extension __ext_*` variable
+/// followed by the natives of the extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionInclude {
+    /// Natives declared by the include.
+    pub natives: Vec<String>,
+
+    /// Path of an `#include` directive of the project which includes the include, with
+    /// UTF-16 columns. The directives of the main file come first.
+    pub directive: Option<FileRange>,
+}
+
+/// Returns the includes of extensions in the project of `root`, keyed by the name of the
+/// `__ext_*` variable which declares the extension.
+pub(crate) fn extension_natives(
+    db: &RootDatabase,
+    root: FileId,
+) -> FxHashMap<String, ExtensionInclude> {
+    let graph = db.graph();
+    let Some(subgraph) = graph
+        .find_subgraphs()
+        .into_iter()
+        .find(|subgraph| subgraph.root.file_id == root)
+    else {
+        return FxHashMap::default();
+    };
+    let mut file_ids = subgraph.file_ids().into_iter().collect::<Vec<_>>();
+    file_ids.sort_by_key(|file_id| (*file_id != root, *file_id));
+
+    let mut res = FxHashMap::default();
+    for file_id in file_ids.iter().copied() {
+        let item_tree = db.file_item_tree(file_id);
+        let Some(variable) = item_tree
+            .top_level_items()
+            .iter()
+            .find_map(|item| match item {
+                FileItem::Variable(id) => {
+                    let name = item_tree[*id].name.to_string();
+                    name.starts_with("__ext_").then_some(name)
+                }
+                _ => None,
+            })
+        else {
+            continue;
+        };
+        let natives = item_tree
+            .top_level_items()
+            .iter()
+            .filter_map(|item| match item {
+                FileItem::Function(id) if item_tree[*id].kind == FunctionKind::Native => {
+                    Some(item_tree[*id].name.to_string())
+                }
+                _ => None,
+            })
+            .collect();
+        let directive = file_ids.iter().find_map(|includer| {
+            let range = db
+                .file_includes(*includer)
+                .0
+                .iter()
+                .find(|include| include.file_id() == file_id)?
+                .range()?;
+            Some(FileRange {
+                file_id: *includer,
+                range: utf16_range(&db.file_text(*includer), range),
+            })
+        });
+        res.insert(variable, ExtensionInclude { natives, directive });
+    }
+
+    res
+}
+
+/// Returns the natives the project of `root` marks as optional, by passing their name to
+/// `MarkNativeAsOptional`.
+pub(crate) fn optional_natives(db: &RootDatabase, root: FileId) -> FxHashSet<String> {
+    let file_ids = db
+        .projet_subgraph(root)
+        .map(|subgraph| subgraph.file_ids())
+        .unwrap_or_else(|| [root].into_iter().collect());
+
+    file_ids
+        .into_iter()
+        .flat_map(|file_id| marked_optional_natives(&db.file_text(file_id)))
+        .collect()
+}
+
+/// Returns the string literals passed to `MarkNativeAsOptional` in `text`.
+fn marked_optional_natives(text: &str) -> Vec<String> {
+    let mut res = vec![];
+    let mut lexer = sourcepawn_lexer::SourcepawnLexer::new(text).filter(|symbol| {
+        !matches!(
+            symbol.token_kind,
+            TokenKind::Newline | TokenKind::Comment(_)
+        )
+    });
+    while let Some(symbol) = lexer.next() {
+        if symbol.token_kind != TokenKind::Identifier || symbol.text() != "MarkNativeAsOptional" {
+            continue;
+        }
+        if !matches!(lexer.next(), Some(symbol) if symbol.token_kind == TokenKind::LParen) {
+            continue;
+        }
+        let Some(arg) = lexer.next() else {
+            break;
+        };
+        if arg.token_kind == TokenKind::Literal(Literal::StringLiteral) {
+            res.push(arg.text().trim_matches('"').to_string());
+        }
+    }
+
+    res
+}
+
+/// Convert `range`, on a single line of `text` with columns in bytes, to UTF-16 columns.
+pub(crate) fn utf16_range(text: &str, range: Range) -> Range {
+    let line = text
+        .lines()
+        .nth(range.start.line as usize)
+        .unwrap_or_default();
+    let column = |byte: u32| {
+        line.get(..byte as usize)
+            .map_or(byte, |prefix| prefix.encode_utf16().count() as u32)
+    };
+    Range::new(
+        Position::new(range.start.line, column(range.start.character)),
+        Position::new(range.end.line, column(range.end.character)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    const SDKHOOKS: &str = r#"
+public Extension __ext_sdkhooks =
+{
+    name = "SDKHooks",
+    file = "sdkhooks.ext",
+    autoload = 1,
+    required = 0,
+};
+
+native void SDKHook(int entity, int type, Function callback);
+native void SDKUnhook(int entity, int type, Function callback);
+stock void Helper() {}
+"#;
+
+    #[test]
+    fn natives_of_extensions() {
+        let (host, file_ids) = analysis_host(&[
+            ("/project/main.sp", "#include \"helpers.sp\"\n"),
+            ("/project/helpers.sp", "// Hooks 😀\n#include <sdkhooks>\n"),
+            ("/include/sdkhooks.inc", SDKHOOKS),
+        ]);
+        let res = extension_natives(&host.db, file_ids[0]);
+        assert_eq!(
+            res,
+            [(
+                "__ext_sdkhooks".to_string(),
+                ExtensionInclude {
+                    natives: vec!["SDKHook".to_string(), "SDKUnhook".to_string()],
+                    directive: Some(FileRange {
+                        file_id: file_ids[1],
+                        range: Range::new(Position::new(1, 9), Position::new(1, 19)),
+                    }),
+                }
+            )]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn natives_marked_optional() {
+        assert_eq!(
+            marked_optional_natives(
+                r#"
+public APLRes AskPluginLoad2(Handle myself, bool late, char[] error, int err_max)
+{
+    MarkNativeAsOptional("SDKHook");
+    // MarkNativeAsOptional("Commented");
+    MarkNativeAsOptional(name);
+    PrintToServer("SDKUnhook");
+    return APLRes_Success;
+}
+"#
+            ),
+            vec!["SDKHook"]
+        );
+    }
+
+    #[test]
+    fn utf16_columns() {
+        let range = Range::new(Position::new(1, 20), Position::new(1, 30));
+        assert_eq!(
+            utf16_range("\n/* 😀 */ #include <sdkhooks>", range),
+            Range::new(Position::new(1, 18), Position::new(1, 28))
+        );
+    }
+}
@@ -0,0 +1,57 @@
+//! In-memory projects for the tests.
+
+use std::sync::Arc;
+
+use base_db::{Change, FileExtension, SourceRootConfig};
+use vfs::{FileId, Vfs, VfsPath};
+
+use crate::AnalysisHost;
+
+/// Root of the workspace of the fixtures.
+pub(crate) const PROJECT_ROOT: &str = "/project";
+
+/// Root of the include directory of the fixtures.
+pub(crate) const INCLUDE_ROOT: &str = "/include";
+
+/// Build an [`AnalysisHost`] with the `(path, text)` files, and return it with the ids of the
+/// files, in the same order.
+///
+/// Paths are virtual. The files under [`INCLUDE_ROOT`] form an include directory, the other
+/// ones must be under [`PROJECT_ROOT`].
+pub(crate) fn analysis_host(files: &[(&str, &str)]) -> (AnalysisHost, Vec<FileId>) {
+    let mut vfs = Vfs::default();
+    for (path, text) in files {
+        vfs.set_file_contents(
+            VfsPath::new_virtual_path(path.to_string()),
+            Some(text.as_bytes().to_vec()),
+        );
+    }
+
+    let mut source_root_config = SourceRootConfig::default();
+    source_root_config.fsc.set_roots(vec![
+        VfsPath::new_virtual_path(PROJECT_ROOT.to_string()),
+        VfsPath::new_virtual_path(INCLUDE_ROOT.to_string()),
+    ]);
+    let mut change = Change::new();
+    change.set_roots(source_root_config.partition(&vfs));
+    let mut file_ids = Vec::new();
+    let mut known_files = Vec::new();
+    for (path, text) in files {
+        let path = VfsPath::new_virtual_path(path.to_string());
+        let file_id = vfs.file_id(&path).expect("the file was added to the vfs");
+        change.change_file(file_id, Some(Arc::from(*text)));
+        if let Some((_, Some(ext))) = path.name_and_extension() {
+            if let Ok(ext) = FileExtension::try_from(ext) {
+                known_files.push((file_id, ext));
+            }
+        }
+        file_ids.push(file_id);
+    }
+    known_files.sort();
+
+    let mut host = AnalysisHost::new(None);
+    host.apply_change(change);
+    host.set_known_files(known_files);
+
+    (host, file_ids)
+}
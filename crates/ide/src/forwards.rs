@@ -0,0 +1,49 @@
+//! This module provides the assist which rewrites the signature of a `public` function from
+//! the forward it implements.
+
+use hir::Semantics;
+use ide_db::{RootDatabase, SourceChange};
+use lsp_types::TextEdit;
+use preprocessor::{s_range_to_u_range, u_pos_to_s_pos};
+use syntax::range_contains_pos;
+
+use crate::{assists::Assist, FilePosition};
+
+/// Assist which rewrites the types of the signature of a `public` function from the forward
+/// it implements, when they do not match. The names of the parameters are kept.
+///
+/// ```sourcepawn
+/// public void OnClientPutInServer(float player, int team)
+/// ```
+/// ->
+/// ```sourcepawn
+/// public void OnClientPutInServer(int player)
+/// ```
+pub(crate) fn fix_forward_signature(
+    sema: &Semantics<RootDatabase>,
+    mut pos: FilePosition,
+    acc: &mut Vec<Assist>,
+) -> Option<()> {
+    let preprocessing_results = sema.preprocess_file(pos.file_id);
+    let _ = u_pos_to_s_pos(
+        preprocessing_results.args_map(),
+        preprocessing_results.offsets(),
+        &mut pos.position,
+    );
+    let mismatch = hir::forwards::forward_signature_mismatches(sema.db, pos.file_id)
+        .into_iter()
+        .find(|it| range_contains_pos(&it.signature_range, &pos.position))?;
+
+    let range = s_range_to_u_range(preprocessing_results.offsets(), mismatch.signature_range);
+    let mut source_change = SourceChange::default();
+    source_change.insert(
+        pos.file_id,
+        TextEdit::new(range, mismatch.fixed_signature.clone()),
+    );
+    acc.push(Assist {
+        label: format!("Use the signature of the forward `{}`", mismatch.name),
+        source_change,
+    });
+
+    Some(())
+}
@@ -0,0 +1,175 @@
+//! This module provides completions for string literals which name game data, such as
+//! ConVars, console commands, netprops, entity classnames and sounds, and utilities to
+//! check if such completions should be provided for a given node.
+
+use completion_data::NetPropKind;
+use fxhash::FxHashSet;
+use hir::db::HirDatabase;
+use ide_db::{Documentation, RootDatabase};
+use smol_str::{SmolStr, ToSmolStr};
+use syntax::TSKind;
+use tree_sitter::Node;
+
+use crate::{CompletionItem, CompletionKind};
+
+/// Kind of game data expected by a string literal argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameDataKind {
+    ConVar,
+    Command,
+    /// A netprop, with the `PropType` of the call if it could be determined.
+    NetProp(Option<NetPropKind>),
+    Classname,
+    Sound,
+}
+
+/// Functions which take game data as a string argument, with the index of that argument.
+const GAME_DATA_ARGUMENTS: &[(&str, usize, GameDataKind)] = &[
+    ("FindConVar", 0, GameDataKind::ConVar),
+    ("CommandExists", 0, GameDataKind::Command),
+    ("GetCommandFlags", 0, GameDataKind::Command),
+    ("SetCommandFlags", 0, GameDataKind::Command),
+    ("AddCommandListener", 1, GameDataKind::Command),
+    ("RemoveCommandListener", 1, GameDataKind::Command),
+    (
+        "FindSendPropInfo",
+        1,
+        GameDataKind::NetProp(Some(NetPropKind::Send)),
+    ),
+    (
+        "FindDataMapInfo",
+        1,
+        GameDataKind::NetProp(Some(NetPropKind::Data)),
+    ),
+    ("CreateEntityByName", 0, GameDataKind::Classname),
+    ("FindEntityByClassname", 1, GameDataKind::Classname),
+    ("GivePlayerItem", 1, GameDataKind::Classname),
+    ("PrecacheSound", 0, GameDataKind::Sound),
+    ("PrecacheScriptSound", 0, GameDataKind::Sound),
+    ("EmitSoundToAll", 0, GameDataKind::Sound),
+    ("EmitSoundToClient", 1, GameDataKind::Sound),
+    ("EmitAmbientSound", 0, GameDataKind::Sound),
+    ("EmitGameSoundToAll", 0, GameDataKind::Sound),
+    ("EmitGameSoundToClient", 1, GameDataKind::Sound),
+];
+
+/// Functions which take a `PropType` as their second argument and a netprop name as
+/// their third argument.
+const NETPROP_FUNCTIONS: &[&str] = &[
+    "GetEntProp",
+    "SetEntProp",
+    "GetEntPropFloat",
+    "SetEntPropFloat",
+    "GetEntPropEnt",
+    "SetEntPropEnt",
+    "GetEntPropVector",
+    "SetEntPropVector",
+    "GetEntPropString",
+    "SetEntPropString",
+    "GetEntPropArraySize",
+    "HasEntProp",
+];
+
+/// Returns the kind of game data expected by the node, if it is a string literal argument
+/// of a function which takes game data, such as `FindConVar` or `GetEntProp`.
+///
+/// # Parameters
+/// - `node`: The node to check
+/// - `source`: The preprocessed source code
+pub fn game_data_kind(node: &Node, source: &str) -> Option<GameDataKind> {
+    if TSKind::from(node) != TSKind::string_literal {
+        return None;
+    }
+    let parent = node.parent()?;
+    if TSKind::from(&parent) != TSKind::call_arguments {
+        return None;
+    }
+    let function = parent.prev_named_sibling()?;
+    if TSKind::from(&function) != TSKind::identifier {
+        return None;
+    }
+    let name = function.utf8_text(source.as_bytes()).ok()?;
+    let args = parent
+        .named_children(&mut parent.walk())
+        .filter(|n| TSKind::from(n) != TSKind::comment)
+        .collect::<Vec<_>>();
+    let idx = args.iter().position(|arg| arg == node)?;
+
+    if NETPROP_FUNCTIONS.contains(&name) {
+        if idx != 2 {
+            return None;
+        }
+        let kind = match args[1].utf8_text(source.as_bytes()).ok()? {
+            "Prop_Send" => Some(NetPropKind::Send),
+            "Prop_Data" => Some(NetPropKind::Data),
+            _ => None,
+        };
+        return Some(GameDataKind::NetProp(kind));
+    }
+
+    GAME_DATA_ARGUMENTS
+        .iter()
+        .find(|(function, arg_idx, _)| *function == name && *arg_idx == idx)
+        .map(|(_, _, kind)| *kind)
+}
+
+/// Returns completions for game data of the given kind, for the games selected by the user.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `kind`: The kind of game data to complete
+pub fn game_data_completions(db: &RootDatabase, kind: GameDataKind) -> Vec<CompletionItem> {
+    let data = db.completion_data();
+    let mut res: Vec<CompletionItem> = Vec::new();
+    let mut labels: FxHashSet<SmolStr> = FxHashSet::default();
+    let mut push = |label: &str, detail: Option<String>, description: Option<&str>| {
+        let label = label.to_smolstr();
+        if !labels.insert(label.clone()) {
+            return;
+        }
+        res.push(CompletionItem {
+            label,
+            kind: CompletionKind::Literal,
+            detail,
+            documentation: description.map(Documentation::from),
+            ..Default::default()
+        });
+    };
+    for game in data.games(&db.selected_games()) {
+        match kind {
+            GameDataKind::ConVar => game.convars().iter().for_each(|it| {
+                push(
+                    it.name(),
+                    it.default_value()
+                        .map(|value| format!("default: \"{}\"", value)),
+                    it.description(),
+                )
+            }),
+            GameDataKind::Command => game
+                .commands()
+                .iter()
+                .for_each(|it| push(it.name(), None, it.description())),
+            GameDataKind::NetProp(prop_kind) => game
+                .netprops()
+                .iter()
+                .filter(|it| prop_kind.map_or(true, |kind| it.kind() == kind))
+                .for_each(|it| {
+                    let detail = match it.r#type() {
+                        Some(type_) => format!("{} ({})", it.class(), type_),
+                        None => it.class().to_string(),
+                    };
+                    push(it.name(), Some(detail), None)
+                }),
+            GameDataKind::Classname => game
+                .classnames()
+                .iter()
+                .for_each(|it| push(it.name(), None, it.description())),
+            GameDataKind::Sound => game
+                .sounds()
+                .iter()
+                .for_each(|it| push(it.name(), None, None)),
+        }
+    }
+
+    res
+}
@@ -1,7 +1,7 @@
 use std::hash::Hash;
 
 use base_db::FilePosition;
-use hir::{HasSource, Semantics};
+use hir::{translations::phrase_name, DefResolution, HasSource, Semantics};
 
 use preprocessor::{s_range_to_u_range, u_pos_to_s_pos};
 use smol_str::{SmolStr, ToSmolStr};
@@ -11,7 +11,7 @@ use syntax::{
 };
 use vfs::FileId;
 
-use crate::{RangeInfo, RootDatabase};
+use crate::{translations::phrase_definition, RangeInfo, RootDatabase};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct NavigationTarget {
@@ -41,6 +41,25 @@ impl NavigationTarget {
     pub fn focus_or_full_range(&self) -> lsp_types::Range {
         self.focus_range.unwrap_or(self.full_range)
     }
+
+    /// Returns the target of a definition, in user coordinates.
+    pub(crate) fn from_def(db: &RootDatabase, def: DefResolution) -> Option<Self> {
+        let sema = Semantics::new(db);
+        let file_id = def.file_id(db);
+        let source_tree = sema.parse(file_id);
+        let name = def.name(db).map(|it| it.to_smolstr()).unwrap_or_default();
+        let def_node = def.source(db, &source_tree)?.value;
+        let name_range = find_inner_name_range(&def_node);
+
+        let preprocessing_results = sema.preprocess_file(file_id);
+        let offsets = preprocessing_results.offsets();
+        Some(NavigationTarget {
+            name,
+            file_id,
+            full_range: s_range_to_u_range(offsets, ts_range_to_lsp_range(&def_node.range())),
+            focus_range: s_range_to_u_range(offsets, name_range).into(),
+        })
+    }
 }
 
 pub(crate) fn goto_definition(
@@ -77,6 +96,14 @@ pub(crate) fn goto_definition(
         lsp_position_to_ts_point(&pos.position),
         lsp_position_to_ts_point(&pos.position),
     )?;
+    if let Some(name) = phrase_name(
+        db,
+        pos.file_id,
+        &node,
+        &preprocessing_results.preprocessed_text(),
+    ) {
+        return phrase_definition(db, pos.file_id, &name, &node, offsets);
+    }
     let def = sema.find_def(pos.file_id, &node)?;
     let u_range = match source_u_range {
         Some(u_range) => u_range,
@@ -1,10 +1,10 @@
-mod actions;
+pub(crate) mod actions;
 mod render;
 
 use std::panic::AssertUnwindSafe;
 
-use hir::{DefResolution, HasSource, Semantics};
-use ide_db::{Documentation, RootDatabase};
+use hir::{translations::phrase_name, DefResolution, HasSource, Semantics};
+use ide_db::{DocumentationDatabase, RootDatabase};
 use itertools::Itertools;
 use preprocessor::{db::PreprocDatabase, s_range_to_u_range, u_pos_to_s_pos, PreprocessingResult};
 use smol_str::ToSmolStr;
@@ -12,13 +12,16 @@ use syntax::utils::{lsp_position_to_ts_point, ts_range_to_lsp_range};
 use vfs::FileId;
 
 use crate::{
-    events::{event_hover, event_name},
+    events::{event_field_hover, event_hover, event_name},
     goto_definition::find_inner_name_range,
     markup::Markup,
+    translations::phrase_hover,
     FilePosition, NavigationTarget, RangeInfo,
 };
 
-use self::actions::goto_type_action_for_def;
+use self::actions::{
+    goto_type_action_for_def, implementation_action_for_def, reference_action_for_def,
+};
 pub(crate) use render::{render_def, Render};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -27,6 +30,13 @@ pub struct HoverConfig {
     pub documentation: bool,
     pub keywords: bool,
     pub format: HoverDocFormat,
+    /// Version of the compiler of the project of the hovered file, shown when hovering the
+    /// `SOURCEMOD_V_*` macros.
+    pub compiler_version: Option<String>,
+    /// Whether to add the action listing the implementations of forwards and callback types.
+    pub implementations_action: bool,
+    /// Whether to add the action listing the references of functions, methodmaps and globals.
+    pub references_action: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -95,14 +105,13 @@ pub(crate) fn hover(
     mut fpos: FilePosition,
     config: &HoverConfig,
     file_id_to_url: AssertUnwindSafe<&dyn Fn(FileId) -> Option<String>>,
-    events_game_name: Option<&str>,
 ) -> Option<RangeInfo<HoverResult>> {
     let sema = &Semantics::new(db);
     let preprocessing_results = sema.preprocess_file(fpos.file_id);
     let offsets = preprocessing_results.offsets();
     let tree = sema.parse(fpos.file_id);
     let root_node = tree.root_node();
-    if let Some(hover) = find_macro_hover(&preprocessing_results, sema, &fpos) {
+    if let Some(hover) = find_macro_hover(&preprocessing_results, sema, &fpos, config) {
         return Some(hover);
     }
 
@@ -118,7 +127,23 @@ pub(crate) fn hover(
     )?;
 
     if let Some(name) = event_name(&node, &preprocessing_results.preprocessed_text()) {
-        return event_hover(events_game_name, &name, &node, offsets);
+        return event_hover(db, &name, &node, offsets);
+    }
+    if let Some(access) = hir::events::event_field_access(
+        db,
+        fpos.file_id,
+        &node,
+        &preprocessing_results.preprocessed_text(),
+    ) {
+        return event_field_hover(db, &access, &node, offsets);
+    }
+    if let Some(name) = phrase_name(
+        db,
+        fpos.file_id,
+        &node,
+        &preprocessing_results.preprocessed_text(),
+    ) {
+        return phrase_hover(db, fpos.file_id, &name, &node, offsets);
     }
 
     let def = sema.find_def(fpos.file_id, &node)?;
@@ -127,16 +152,22 @@ pub(crate) fn hover(
         None => s_range_to_u_range(offsets, ts_range_to_lsp_range(&node.range())),
     };
 
-    let file_id = def.file_id(db);
-    let source_tree = sema.parse(file_id);
-    let text = db.preprocessed_text(file_id);
     let render = render::render_def(db, def.clone())?;
-    let mut actions = [goto_type_action_for_def(db, def.clone())]
-        .into_iter()
-        .flatten()
-        .collect_vec();
+    let mut actions = [
+        config
+            .references_action
+            .then(|| reference_action_for_def(db, def.clone()))
+            .flatten(),
+        config
+            .implementations_action
+            .then(|| implementation_action_for_def(db, def.clone()))
+            .flatten(),
+        goto_type_action_for_def(db, def.clone()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect_vec();
     actions.dedup();
-    let def_node = def.source(db, &source_tree)?.value;
 
     let markup = match render {
         Render::FileId(file_id) => Markup::from(file_id_to_url(file_id).unwrap_or_default()),
@@ -147,7 +178,7 @@ pub(crate) fn hover(
         let res = HoverResult { markup, actions };
         return Some(RangeInfo::new(u_range, res));
     }
-    if let Some(docs) = Documentation::from_node(def_node, text.as_bytes()) {
+    if let Some(docs) = db.doc_comment(def) {
         let res = HoverResult {
             markup: Markup::from(format!(
                 "{}\n\n---\n\n{}",
@@ -166,13 +197,14 @@ fn find_macro_hover(
     preprocessing_results: &PreprocessingResult,
     sema: &Semantics<RootDatabase>,
     fpos: &FilePosition,
+    config: &HoverConfig,
 ) -> Option<RangeInfo<HoverResult>> {
     let (offset, def) = sema.find_macro_def(fpos)?;
     let offsets = preprocessing_results.offsets();
     let preprocessed_text = preprocessing_results.preprocessed_text();
     let file_id = def.file_id(sema.db);
     let source_tree = sema.parse(file_id);
-    let def_node = def.source(sema.db, &source_tree)?.value;
+    let def_node = def.clone().source(sema.db, &source_tree)?.value;
     let source = sema.db.preprocessed_text(file_id);
     let source_text = def_node.utf8_text(source.as_bytes()).ok()?;
 
@@ -205,13 +237,22 @@ fn find_macro_hover(
         .map(|it| it.to_string())
         .unwrap_or_default();
 
-    let markup = Markup::from(format!(
+    let mut markup = Markup::from(format!(
         "{}\nExpands to:\n{}",
         Markup::fenced_block(source_text),
         Markup::fenced_block(hover_text.trim())
     ));
+    let is_version_macro = def
+        .name(sema.db)
+        .is_some_and(|name| name.to_string().starts_with("SOURCEMOD_V_"));
+    if let (true, Some(version)) = (is_version_macro, &config.compiler_version) {
+        markup = Markup::from(format!(
+            "{}\nCompiler of the project: `spcomp {}`",
+            markup, version
+        ));
+    }
 
-    let res = if let Some(docs) = Documentation::from_node(def_node, source.as_bytes()) {
+    let res = if let Some(docs) = sema.db.doc_comment(def) {
         HoverResult {
             markup: Markup::from(format!(
                 "{}\n\n---\n\n{}",
@@ -1,5 +1,10 @@
-use hir::DefResolution;
+use base_db::FilePosition;
+use hir::{DefResolution, HasSource, Semantics};
+use hir_def::{DefDatabase, FunctionKind};
 use ide_db::RootDatabase;
+use preprocessor::s_range_to_u_range;
+
+use crate::goto_definition::find_inner_name_range;
 
 use super::HoverAction;
 
@@ -26,3 +31,45 @@ pub fn goto_type_action_for_def(db: &RootDatabase, def: DefResolution) -> Option
 
     Some(HoverAction::goto_type_from_targets(db, res))
 }
+
+/// Returns the action listing the implementations of forwards and callback types.
+pub fn implementation_action_for_def(db: &RootDatabase, def: DefResolution) -> Option<HoverAction> {
+    match def {
+        DefResolution::Function(it) if db.function_data(it.id()).kind == FunctionKind::Forward => {}
+        DefResolution::Typedef(_)
+        | DefResolution::Typeset(_)
+        | DefResolution::Functag(_)
+        | DefResolution::Funcenum(_) => (),
+        _ => return None,
+    }
+
+    def_name_position(db, def).map(HoverAction::Implementation)
+}
+
+/// Returns the action listing the references of functions, methodmaps and globals.
+pub fn reference_action_for_def(db: &RootDatabase, def: DefResolution) -> Option<HoverAction> {
+    match def {
+        DefResolution::Function(it) if db.function_data(it.id()).kind != FunctionKind::Forward => {}
+        DefResolution::Methodmap(_) | DefResolution::Global(_) => (),
+        _ => return None,
+    }
+
+    def_name_position(db, def).map(HoverAction::Reference)
+}
+
+/// Returns the position of the name of a definition, in user coordinates.
+fn def_name_position(db: &RootDatabase, def: DefResolution) -> Option<FilePosition> {
+    let sema = Semantics::new(db);
+    let file_id = def.file_id(db);
+    let tree = sema.parse(file_id);
+    let node = def.source(db, &tree)?.value;
+    let range = s_range_to_u_range(
+        sema.preprocess_file(file_id).offsets(),
+        find_inner_name_range(&node),
+    );
+
+    Some(FilePosition {
+        file_id,
+        position: range.start,
+    })
+}
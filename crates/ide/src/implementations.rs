@@ -0,0 +1,176 @@
+//! This module provides the implementations of forwards, which are the functions with the
+//! same name, and of callback types, which are the functions passed as arguments where a
+//! typedef, typeset, functag or funcenum is expected.
+
+use base_db::{FilePosition, SourceDatabase};
+use fxhash::FxHashSet;
+use hir::{db::HirDatabase, DefResolution, File, FileDef, Function, Semantics};
+use hir_def::{DefDatabase, FunctionKind};
+use ide_db::RootDatabase;
+use preprocessor::u_pos_to_s_pos;
+use syntax::utils::lsp_position_to_ts_point;
+use vfs::FileId;
+
+use crate::NavigationTarget;
+
+/// Returns the implementations of the forward or of the callback type at `fpos`.
+pub(crate) fn implementations(
+    db: &RootDatabase,
+    mut fpos: FilePosition,
+) -> Option<Vec<NavigationTarget>> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(fpos.file_id);
+    let _ = u_pos_to_s_pos(
+        preprocessing_results.args_map(),
+        preprocessing_results.offsets(),
+        &mut fpos.position,
+    );
+    let tree = sema.parse(fpos.file_id);
+    let point = lsp_position_to_ts_point(&fpos.position);
+    let node = tree.root_node().descendant_for_point_range(point, point)?;
+    let def = sema.find_def(fpos.file_id, &node)?;
+    let file_ids = project_file_ids(db, def.file_id(db));
+
+    let functions = match &def {
+        DefResolution::Function(it) if db.function_data(it.id()).kind == FunctionKind::Forward => {
+            forward_implementations(db, *it, &file_ids)
+        }
+        DefResolution::Typedef(_)
+        | DefResolution::Typeset(_)
+        | DefResolution::Functag(_)
+        | DefResolution::Funcenum(_) => callback_implementations(db, &def, &file_ids),
+        _ => return None,
+    };
+
+    functions
+        .into_iter()
+        .filter_map(|it| NavigationTarget::from_def(db, it.into()))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Returns the files of all the projects which include the file.
+fn project_file_ids(db: &RootDatabase, file_id: FileId) -> FxHashSet<FileId> {
+    db.graph()
+        .find_subgraphs()
+        .into_iter()
+        .filter(|subgraph| subgraph.contains_file(file_id))
+        .flat_map(|subgraph| subgraph.file_ids())
+        .collect()
+}
+
+/// Returns the functions which implement a forward, in the given files.
+fn forward_implementations(
+    db: &RootDatabase,
+    forward: Function,
+    file_ids: &FxHashSet<FileId>,
+) -> Vec<Function> {
+    let name = forward.name(db);
+    file_ids
+        .iter()
+        .flat_map(|file_id| File::from(*file_id).declarations(db))
+        .filter_map(|def| match def {
+            FileDef::Function(it)
+                if db.function_data(it.id()).kind == FunctionKind::Def && it.name(db) == name =>
+            {
+                Some(it)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the functions passed as arguments of calls where the callback type `def` is
+/// expected, in the given files.
+fn callback_implementations(
+    db: &RootDatabase,
+    def: &DefResolution,
+    file_ids: &FxHashSet<FileId>,
+) -> Vec<Function> {
+    let mut file_ids = file_ids.iter().copied().collect::<Vec<_>>();
+    file_ids.sort();
+
+    let mut res = Vec::new();
+    for file_id in file_ids {
+        for (type_def, callback) in db.file_callbacks(file_id).iter() {
+            if type_def == def && !res.contains(callback) {
+                res.push(*callback);
+            }
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    const MAIN: &str = r#"#include "types.sp"
+
+forward void OnThing();
+
+native void Register(Callback callback);
+
+void Handler(int value) {}
+
+void Other(int value) {}
+
+public void OnThing() {}
+
+public void OnPluginStart()
+{
+    Register(Handler);
+    Other(0);
+}
+"#;
+
+    const TYPES: &str = "typedef Callback = function void (int value);\n";
+
+    fn implementation_names(
+        files: &[(&str, &str)],
+        file_idx: usize,
+        position: Position,
+    ) -> Vec<String> {
+        let (host, file_ids) = analysis_host(files);
+        implementations(
+            &host.db,
+            FilePosition {
+                file_id: file_ids[file_idx],
+                position,
+            },
+        )
+        .unwrap_or_default()
+        .into_iter()
+        .map(|nav| nav.name.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn callback_type() {
+        assert_eq!(
+            implementation_names(
+                &[("/project/main.sp", MAIN), ("/project/types.sp", TYPES)],
+                1,
+                Position::new(0, 8)
+            ),
+            vec!["Handler"]
+        );
+    }
+
+    #[test]
+    fn forward() {
+        assert_eq!(
+            implementation_names(
+                &[("/project/main.sp", MAIN), ("/project/types.sp", TYPES)],
+                0,
+                Position::new(2, 13)
+            ),
+            vec!["OnThing"]
+        );
+    }
+}
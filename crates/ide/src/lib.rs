@@ -1,44 +1,66 @@
 //! base_db defines basic database traits. The concrete DB is defined by ide.
 
+mod annotations;
+mod api_docs;
+mod assists;
+mod auto_include;
 mod call_hierarchy;
+mod callbacks;
 mod completion;
+mod doc_comments;
+mod document_links;
 mod events;
+mod extensions;
+#[cfg(test)]
+mod fixture;
+mod forwards;
+mod game_data;
 mod goto_definition;
 mod hover;
+mod implementations;
 mod markup;
+mod overlay;
 mod prime_caches;
 mod references;
 mod rename;
 mod signature_help;
 mod status;
+mod switches;
 mod symbols;
 mod syntax_highlighting;
+mod translations;
 
 use std::{panic::AssertUnwindSafe, sync::Arc};
 
 use base_db::{
     Change, FileExtension, FilePosition, FileRange, Graph, SourceDatabase, SourceDatabaseExt, Tree,
 };
-use fxhash::FxHashMap;
-use hir::{DefResolution, Function};
+use fxhash::{FxHashMap, FxHashSet};
+use hir::{db::HirDatabase, DefResolution, Function};
 use hir_def::{print_item_tree, DefDatabase};
 use hover::HoverResult;
 use ide_db::{CallItem, IncomingCallItem, OutgoingCallItem, RootDatabase, SourceChange, Symbols};
 use itertools::Itertools;
-use lsp_types::Url;
+use lsp_types::{TextEdit, Url};
 use paths::AbsPathBuf;
 use preprocessor::db::PreprocDatabase;
-use salsa::{Cancelled, ParallelDatabase};
+use salsa::{Cancelled, Durability, ParallelDatabase};
 use serde_json::Value;
 use vfs::FileId;
 
+pub use annotations::{Annotation, AnnotationConfig, AnnotationKind};
+pub use api_docs::{ApiItem, ApiItemKind};
+pub use assists::Assist;
 pub use completion::{CompletionItem, CompletionKind};
+pub use document_links::{DocumentLink, DocumentLinkTarget};
+pub use extensions::ExtensionInclude;
 pub use goto_definition::NavigationTarget;
 pub use hover::{HoverAction, HoverConfig, HoverDocFormat, HoverGotoTypeData};
 pub use ide_db::Cancellable;
 pub use ide_diagnostics::{Diagnostic, DiagnosticsConfig, Severity};
 pub use line_index::{LineCol, LineIndex, WideEncoding, WideLineCol};
 pub use markup::Markup;
+pub use overlay::OverlayFile;
 pub use prime_caches::ParallelPrimeCachesProgress;
 pub use signature_help::SignatureHelp;
 pub use syntax_highlighting::{Highlight, HlMod, HlMods, HlRange, HlTag};
@@ -93,6 +115,20 @@ impl AnalysisHost {
         self.db.set_known_files(files);
     }
 
+    pub fn set_known_translation_files(&mut self, files: Vec<(FileId, String)>) {
+        self.db.set_known_translation_files(Arc::new(files));
+    }
+
+    pub fn set_completion_data(&mut self, data: completion_data::Database<'static>) {
+        self.db
+            .set_completion_data_with_durability(Arc::new(data), Durability::HIGH);
+    }
+
+    pub fn set_selected_games(&mut self, games: Vec<String>) {
+        self.db
+            .set_selected_games_with_durability(Arc::new(games), Durability::HIGH);
+    }
+
     /// Applies changes to the current state of the world.
     pub fn apply_change(&mut self, change: Change) {
         self.db.apply_change(change)
@@ -154,6 +190,30 @@ impl Analysis {
         })
     }
 
+    /// Get the files of the project of `root` to copy to a temporary directory, to check the
+    /// unsaved contents of the `modified` files with spcomp.
+    pub fn overlay_files(
+        &self,
+        root: FileId,
+        modified: Vec<FileId>,
+    ) -> Cancellable<Vec<OverlayFile>> {
+        self.with_db(|db| overlay::overlay_files(db, root, &modified))
+    }
+
+    /// Get the includes of extensions in the project of `root`, keyed by the name of the
+    /// `__ext_*` variable which declares the extension.
+    pub fn extension_natives(
+        &self,
+        root: FileId,
+    ) -> Cancellable<FxHashMap<String, ExtensionInclude>> {
+        self.with_db(|db| extensions::extension_natives(db, root))
+    }
+
+    /// Get the natives the project of `root` marks as optional with `MarkNativeAsOptional`.
+    pub fn optional_natives(&self, root: FileId) -> Cancellable<FxHashSet<String>> {
+        self.with_db(|db| extensions::optional_natives(db, root))
+    }
+
     /// Debug info about the current state of the analysis.
     pub fn status(&self, file_id: Option<FileId>) -> Cancellable<String> {
         self.with_db(|db| status::status(db, file_id))
@@ -210,6 +270,20 @@ impl Analysis {
         self.with_db(|db| ide_diagnostics::diagnostics(db, config, file_id))
     }
 
+    /// Returns the assists available for `frange`.
+    pub fn assists(&self, frange: FileRange) -> Cancellable<Vec<Assist>> {
+        self.with_db(|db| assists::assists(db, frange))
+    }
+
+    /// Returns the edits to apply after `char_typed` was typed at `fpos`.
+    pub fn on_char_typed(
+        &self,
+        fpos: FilePosition,
+        char_typed: char,
+    ) -> Cancellable<Option<Vec<TextEdit>>> {
+        self.with_db(|db| doc_comments::on_char_typed(db, fpos, char_typed))
+    }
+
     /// Returns the definitions from the symbol at `position`.
     pub fn goto_definition(
         &self,
@@ -223,11 +297,40 @@ impl Analysis {
         self.with_db(|db| references::references(db, pos))
     }
 
+    /// Returns the links of the includes and of the file paths of a file.
+    pub fn document_links(&self, file_id: FileId) -> Cancellable<Vec<DocumentLink>> {
+        self.with_db(|db| document_links::document_links(db, file_id))
+    }
+
+    /// Returns the implementations of the forward or of the callback type at `position`.
+    pub fn implementations(&self, pos: FilePosition) -> Cancellable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| implementations::implementations(db, pos))
+    }
+
+    /// Returns the annotations of the file, without their references and implementations.
+    pub fn annotations(
+        &self,
+        config: &AnnotationConfig,
+        file_id: FileId,
+    ) -> Cancellable<Vec<Annotation>> {
+        self.with_db(|db| annotations::annotations(db, config, file_id))
+    }
+
+    /// Computes the references or the implementations of an annotation.
+    pub fn resolve_annotation(&self, annotation: Annotation) -> Cancellable<Annotation> {
+        self.with_db(|db| annotations::resolve_annotation(db, annotation))
+    }
+
     /// Returns the source change to rename the symbol at `position` to `new_name`.
     pub fn rename(&self, fpos: FilePosition, new_name: &str) -> Cancellable<Option<SourceChange>> {
         self.with_db(|db| rename::rename(db, fpos, new_name))
     }
 
+    /// Returns the items declared in the file, with their documentation.
+    pub fn api_items(&self, file_id: FileId) -> Cancellable<Vec<ApiItem>> {
+        self.with_db(|db| api_docs::api_items(db, file_id))
+    }
+
     /// Returns the document symbol that corresponds to the `file_id`.
     pub fn symbols(&self, file_id: FileId) -> Cancellable<Option<Symbols>> {
         self.with_db(|db| symbols::symbols(db, file_id))
@@ -239,9 +342,8 @@ impl Analysis {
         pos: FilePosition,
         config: &HoverConfig,
         file_id_to_url: AssertUnwindSafe<&dyn Fn(FileId) -> Option<String>>,
-        events_game_name: Option<&str>,
     ) -> Cancellable<Option<RangeInfo<HoverResult>>> {
-        self.with_db(|db| hover::hover(db, pos, config, file_id_to_url, events_game_name))
+        self.with_db(|db| hover::hover(db, pos, config, file_id_to_url))
     }
 
     /// Returns the hover information at `position`.
@@ -256,7 +358,6 @@ impl Analysis {
         trigger_character: Option<char>,
         include_directories: Vec<AbsPathBuf>,
         file_id_to_url: AssertUnwindSafe<&dyn Fn(FileId) -> Url>,
-        events_game_name: Option<&str>,
     ) -> Cancellable<Option<Vec<CompletionItem>>> {
         self.with_db(|db| {
             completion::completions(
@@ -265,7 +366,6 @@ impl Analysis {
                 trigger_character,
                 include_directories,
                 file_id_to_url,
-                events_game_name,
             )
             .map(Into::into)
         })
@@ -0,0 +1,83 @@
+//! Computes the files of a project which must be copied to a temporary directory to check
+//! their unsaved contents with spcomp.
+
+use std::sync::Arc;
+
+use base_db::{IncludeKind, SourceDatabase};
+use fxhash::FxHashSet;
+use ide_db::RootDatabase;
+use itertools::Itertools;
+use lsp_types::Range;
+use vfs::FileId;
+
+/// A file of a project to copy to the overlay checked by spcomp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlayFile {
+    pub file_id: FileId,
+
+    /// Contents of the file, as seen by the server.
+    pub text: Arc<str>,
+
+    /// Includes of the file which must be rewritten in the overlay, with the range of the
+    /// path of their directive, with columns in bytes, and the included file.
+    ///
+    /// These are the includes of the files of the overlay, whose copies must be used
+    /// instead, and the quoted includes, which are resolved relative to the including file.
+    pub includes: Vec<(Range, FileId)>,
+}
+
+/// Returns the files of the project of `root` to copy to the overlay, which are the
+/// `modified` files and the files including them, directly or not.
+///
+/// Returns an empty list if none of the `modified` files are in the project.
+pub(crate) fn overlay_files(
+    db: &RootDatabase,
+    root: FileId,
+    modified: &[FileId],
+) -> Vec<OverlayFile> {
+    let graph = db.graph();
+    let Some(subgraph) = graph
+        .find_subgraphs()
+        .into_iter()
+        .find(|subgraph| subgraph.root.file_id == root)
+    else {
+        return Vec::new();
+    };
+    let mut files: FxHashSet<FileId> = modified
+        .iter()
+        .copied()
+        .filter(|file_id| subgraph.contains_file(*file_id))
+        .collect();
+    loop {
+        let includers = subgraph
+            .edges
+            .iter()
+            .filter(|edge| {
+                files.contains(&edge.target.file_id) && !files.contains(&edge.source.file_id)
+            })
+            .map(|edge| edge.source.file_id)
+            .collect_vec();
+        if includers.is_empty() {
+            break;
+        }
+        files.extend(includers);
+    }
+
+    files
+        .iter()
+        .sorted()
+        .map(|file_id| OverlayFile {
+            file_id: *file_id,
+            text: db.file_text(*file_id),
+            includes: db
+                .file_includes(*file_id)
+                .0
+                .iter()
+                .filter(|include| {
+                    include.kind() == IncludeKind::Quotes || files.contains(&include.file_id())
+                })
+                .filter_map(|include| Some((include.range()?, include.file_id())))
+                .collect(),
+        })
+        .collect()
+}
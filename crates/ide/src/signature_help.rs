@@ -1,12 +1,14 @@
+use std::sync::Arc;
+
 use base_db::FilePosition;
-use hir::{DefResolution, HasSource, Semantics};
-use ide_db::{Documentation, RootDatabase};
+use hir::{DefResolution, Semantics};
+use ide_db::{DocComment, DocumentationDatabase, RootDatabase};
 use preprocessor::u_pos_to_s_pos;
 use syntax::{utils::lsp_position_to_ts_point, TSKind};
 
 #[derive(Debug)]
 pub struct SignatureHelp {
-    pub doc: Option<Documentation>,
+    pub doc: Option<Arc<DocComment>>,
     pub signature: String,
     pub active_parameter: Option<u32>,
     pub parameters: Vec<String>,
@@ -73,12 +75,8 @@ pub(crate) fn signature_help(
         return None;
     };
 
-    let file_id = def.file_id(db);
-    let tree = sema.parse(file_id);
-    let source = sema.preprocessed_text(file_id);
-    let node = func.source(db, &tree)?;
     SignatureHelp {
-        doc: Documentation::from_node(node.value, source.as_bytes()),
+        doc: db.doc_comment(def),
         signature: func.render(db)?,
         active_parameter: active_parameter.into(),
         parameters: func.parameters(db),
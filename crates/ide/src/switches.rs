@@ -0,0 +1,206 @@
+//! This module provides the assist which adds the missing `case` arms of a `switch` statement
+//! over an enum.
+
+use hir::Semantics;
+use ide_db::{RootDatabase, SourceChange};
+use lsp_types::{Position, Range, TextEdit};
+use preprocessor::{s_range_to_u_range, u_pos_to_s_pos};
+use syntax::{utils::lsp_position_to_ts_point, TSKind};
+
+use crate::{assists::Assist, FilePosition};
+
+/// Assist which adds a `case` arm for each variant of the enum a `switch` statement is over,
+/// when the variant is not handled yet. The arms are added before the `default` arm, if any.
+///
+/// ```sourcepawn
+/// switch (team)
+/// {
+///     case Team_Red:
+///     {
+///     }
+/// }
+/// ```
+/// ->
+/// ```sourcepawn
+/// switch (team)
+/// {
+///     case Team_Red:
+///     {
+///     }
+///     case Team_Blue:
+///     {
+///     }
+/// }
+/// ```
+pub(crate) fn fill_switch_cases(
+    sema: &Semantics<RootDatabase>,
+    mut pos: FilePosition,
+    acc: &mut Vec<Assist>,
+) -> Option<()> {
+    let preprocessing_results = sema.preprocess_file(pos.file_id);
+    let _ = u_pos_to_s_pos(
+        preprocessing_results.args_map(),
+        preprocessing_results.offsets(),
+        &mut pos.position,
+    );
+    let tree = sema.parse(pos.file_id);
+    let point = lsp_position_to_ts_point(&pos.position);
+    let mut switch_node = tree.root_node().descendant_for_point_range(point, point)?;
+    while TSKind::from(switch_node) != TSKind::switch_statement {
+        switch_node = switch_node.parent()?;
+    }
+    let cases = hir::switches::switch_cases(sema.db, pos.file_id, &switch_node)?;
+    if cases.missing.is_empty() {
+        return None;
+    }
+    let closing_brace = switch_node.child(switch_node.child_count().checked_sub(1)?)?;
+    if TSKind::from(closing_brace) != TSKind::anon_RBRACE {
+        return None;
+    }
+
+    // Indent the new arms like the existing ones.
+    let source = sema.preprocessed_text(pos.file_id);
+    let switch_indent = indentation(&source, switch_node.start_position().row);
+    let case_indent = switch_node
+        .children(&mut switch_node.walk())
+        .find(|child| TSKind::from(child) == TSKind::switch_case)
+        .map(|case| indentation(&source, case.start_position().row))
+        .unwrap_or_else(|| {
+            if switch_indent.contains('\t') {
+                format!("{}\t", switch_indent)
+            } else {
+                format!("{}    ", switch_indent)
+            }
+        });
+    let mut arms = String::new();
+    for variant in cases.missing.iter() {
+        arms.push_str(&format!(
+            "{indent}case {}:\n{indent}{{\n{indent}}}\n",
+            variant.name(sema.db),
+            indent = case_indent
+        ));
+    }
+
+    // The new arms go before the `default` arm, which must be the last one, or before the
+    // closing brace.
+    let default_case = switch_node
+        .children(&mut switch_node.walk())
+        .find(|child| {
+            TSKind::from(child) == TSKind::switch_case
+                && child
+                    .children_by_field_name("value", &mut child.walk())
+                    .next()
+                    .is_none()
+        })
+        .filter(|_| cases.has_default);
+    let (anchor, anchor_indent) = match default_case {
+        Some(default_case) => (default_case, case_indent.as_str()),
+        None => (closing_brace, switch_indent.as_str()),
+    };
+    let anchor_start = anchor.start_position();
+    let anchor_on_own_line = source
+        .lines()
+        .nth(anchor_start.row)
+        .and_then(|line| line.get(..anchor_start.column))
+        .is_some_and(|before| before.trim().is_empty());
+    let (position, text) = if anchor_on_own_line {
+        (Position::new(anchor_start.row as u32, 0), arms)
+    } else {
+        (
+            Position::new(anchor_start.row as u32, anchor_start.column as u32),
+            format!("\n{}{}", arms, anchor_indent),
+        )
+    };
+    let range = s_range_to_u_range(
+        preprocessing_results.offsets(),
+        Range::new(position, position),
+    );
+    let mut source_change = SourceChange::default();
+    source_change.insert(pos.file_id, TextEdit::new(range, text));
+    acc.push(Assist {
+        label: format!("Add the missing cases of `{}`", cases.enum_.name(sema.db)),
+        source_change,
+    });
+
+    Some(())
+}
+
+/// Returns the whitespaces at the start of a line.
+fn indentation(source: &str, row: usize) -> String {
+    source
+        .lines()
+        .nth(row)
+        .map(|line| line.chars().take_while(|c| c.is_whitespace()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use base_db::FileRange;
+
+    use crate::fixture::analysis_host;
+
+    use super::*;
+
+    /// Returns the edits of the assist for the `switch` at `position` of the first file.
+    fn fill(text: &str, position: Position) -> Vec<TextEdit> {
+        let (host, file_ids) = analysis_host(&[("/project/main.sp", text)]);
+        let assists = host
+            .analysis()
+            .assists(FileRange {
+                file_id: file_ids[0],
+                range: Range::new(position, position),
+            })
+            .unwrap();
+        assists
+            .into_iter()
+            .find(|assist| assist.label.starts_with("Add the missing cases"))
+            .map(|assist| assist.source_change.source_file_edits[&file_ids[0]].clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn arms_before_closing_brace() {
+        let text = r#"enum Team { Team_Red, Team_Blue }
+void main(Team team) {
+    switch (team)
+    {
+        case Team_Red:
+        {
+        }
+    }
+}
+"#;
+        assert_eq!(
+            fill(text, Position::new(2, 5)),
+            vec![TextEdit::new(
+                Range::new(Position::new(8, 0), Position::new(8, 0)),
+                "        case Team_Blue:\n        {\n        }\n".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn arms_before_default() {
+        let text = r#"enum Team { Team_Red, Team_Blue }
+void main(Team team) {
+    switch (team)
+    {
+        case Team_Red:
+        {
+        }
+        default:
+        {
+        }
+    }
+}
+"#;
+        assert_eq!(
+            fill(text, Position::new(2, 5)),
+            vec![TextEdit::new(
+                Range::new(Position::new(7, 0), Position::new(7, 0)),
+                "        case Team_Blue:\n        {\n        }\n".to_string()
+            )]
+        );
+    }
+}
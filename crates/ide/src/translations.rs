@@ -0,0 +1,120 @@
+//! This module provides completions/hovers/definitions for translation phrases.
+
+use fxhash::FxHashMap;
+use hir::translations::{available_phrases, resolve_phrase, PhraseDef, PhraseResolution};
+use ide_db::{Documentation, RootDatabase};
+use preprocessor::{s_range_to_u_range, Offset};
+use smol_str::ToSmolStr;
+use syntax::utils::ts_range_to_lsp_range;
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::{
+    hover::HoverResult, CompletionItem, CompletionKind, Markup, NavigationTarget, RangeInfo,
+};
+
+/// Returns completions for the phrases of the translation files loaded by the project.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `file_id`: The [`FileId`] of the file where the completion was requested
+pub fn phrases_completions(db: &RootDatabase, file_id: FileId) -> Vec<CompletionItem> {
+    available_phrases(db, file_id)
+        .into_iter()
+        .map(|def| CompletionItem {
+            label: def.phrase.name.to_smolstr(),
+            kind: CompletionKind::Literal,
+            detail: Some(format!("{}.phrases", def.file_name)),
+            documentation: Some(Documentation::new(render_phrase(&def))),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Returns hover information for a phrase.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `file_id`: The [`FileId`] of the file of the node
+/// - `name`: The name of the phrase
+/// - `node`: The node of the string literal of the phrase name
+/// - `offsets`: The preprocessor offsets
+pub fn phrase_hover(
+    db: &RootDatabase,
+    file_id: FileId,
+    name: &str,
+    node: &Node,
+    offsets: &FxHashMap<u32, Vec<Offset>>,
+) -> Option<RangeInfo<HoverResult>> {
+    let defs = match resolve_phrase(db, file_id, name) {
+        PhraseResolution::Loaded(defs) | PhraseResolution::NotLoaded(defs) => defs,
+        PhraseResolution::NotFound => return None,
+    };
+    let markup = defs
+        .iter()
+        .map(render_phrase)
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n");
+
+    Some(RangeInfo::new(
+        s_range_to_u_range(offsets, ts_range_to_lsp_range(&node.range())),
+        HoverResult {
+            markup: Markup::from(markup),
+            actions: Default::default(),
+        },
+    ))
+}
+
+/// Returns the definitions of a phrase in the translation files.
+///
+/// # Parameters
+/// - `db`: The database
+/// - `file_id`: The [`FileId`] of the file of the node
+/// - `name`: The name of the phrase
+/// - `node`: The node of the string literal of the phrase name
+/// - `offsets`: The preprocessor offsets
+pub fn phrase_definition(
+    db: &RootDatabase,
+    file_id: FileId,
+    name: &str,
+    node: &Node,
+    offsets: &FxHashMap<u32, Vec<Offset>>,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let defs = match resolve_phrase(db, file_id, name) {
+        PhraseResolution::Loaded(defs) | PhraseResolution::NotLoaded(defs) => defs,
+        PhraseResolution::NotFound => return None,
+    };
+    let navs = defs
+        .into_iter()
+        .map(|def| NavigationTarget {
+            name: def.phrase.name.to_smolstr(),
+            file_id: def.file_id,
+            full_range: def.phrase.range,
+            focus_range: Some(def.phrase.range),
+        })
+        .collect();
+
+    Some(RangeInfo::new(
+        s_range_to_u_range(offsets, ts_range_to_lsp_range(&node.range())),
+        navs,
+    ))
+}
+
+/// Render a phrase as markdown, with its `#format` and all its translations.
+fn render_phrase(def: &PhraseDef) -> String {
+    let mut res = vec![format!(
+        "```\n\"{}\" ({}.phrases)\n```",
+        def.phrase.name, def.file_name
+    )];
+    if let Some(format) = &def.phrase.format {
+        res.push(format!("`#format` `{}`", format));
+    }
+    res.extend(
+        def.phrase
+            .translations
+            .iter()
+            .map(|(lang, text)| format!("- **{}**: {}", lang, text)),
+    );
+
+    res.join("\n\n")
+}
@@ -0,0 +1,76 @@
+//! Human readable dump of a plugin, shared by the server and the command line.
+
+use std::fmt;
+
+use crate::{Compression, Plugin};
+
+impl fmt::Display for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let compression = match self.compression {
+            Compression::None => "uncompressed",
+            Compression::Zlib => "zlib compressed",
+        };
+        writeln!(
+            f,
+            "SMX version {:#06x}, {} ({} bytes on disk, {} bytes in memory)",
+            self.version, compression, self.disk_size, self.image_size
+        )?;
+
+        writeln!(f, "\nSections ({}):", self.sections.len())?;
+        for section in self.sections.iter() {
+            writeln!(f, "  {} ({} bytes)", section.name, section.size)?;
+        }
+
+        writeln!(f, "\nNatives ({}):", self.natives.len())?;
+        for native in self.natives.iter() {
+            writeln!(f, "  {native}")?;
+        }
+
+        writeln!(f, "\nPublics ({}):", self.publics.len())?;
+        for public in self.publics.iter() {
+            writeln!(f, "  {} @ {:#x}", public.name, public.address)?;
+        }
+
+        writeln!(f, "\nPubvars ({}):", self.pubvars.len())?;
+        for pubvar in self.pubvars.iter() {
+            writeln!(f, "  {} @ {:#x}", pubvar.name, pubvar.address)?;
+        }
+
+        writeln!(f, "\nTags ({}):", self.tags.len())?;
+        for tag in self.tags.iter() {
+            writeln!(f, "  {:#x} {}", tag.id, tag.name)?;
+        }
+
+        writeln!(f, "\nExtensions ({}):", self.extensions.len())?;
+        for extension in self.extensions.iter() {
+            writeln!(
+                f,
+                "  {} ({}), {}{}",
+                extension.name,
+                extension.file,
+                if extension.required {
+                    "required"
+                } else {
+                    "optional"
+                },
+                if extension.autoload { ", autoload" } else { "" }
+            )?;
+        }
+
+        match &self.debug {
+            Some(debug) => {
+                writeln!(
+                    f,
+                    "\nDebug information ({} lines, {} symbols):",
+                    debug.num_lines, debug.num_symbols
+                )?;
+                for file in debug.files.iter() {
+                    writeln!(f, "  {} @ {:#x}", file.name, file.address)?;
+                }
+            }
+            None => writeln!(f, "\nNo debug information")?,
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,515 @@
+//! Reader for the SMX files produced by spcomp, which are the compiled SourcePawn plugins.
+//!
+//! An SMX file starts with a header, followed by a table of named sections. Everything after
+//! the header and the section table may be compressed with zlib. The sections reference their
+//! names in string tables, such as `.names` for the natives, publics, pubvars and tags.
+
+use std::{fmt, io::Read, path::Path};
+
+use flate2::read::ZlibDecoder;
+
+mod dump;
+
+/// `FFPS` in little endian.
+const MAGIC: u32 = 0x5350_4646;
+const HEADER_SIZE: usize = 24;
+const SECTION_HEADER_SIZE: usize = 12;
+const EXTENSION_PREFIX: &str = "__ext_";
+/// Upper bound of the compression ratio of zlib, used to bound the size of the decompressed
+/// image by the size of the file rather than by the size declared in the header.
+const MAX_ZLIB_RATIO: usize = 1032;
+
+#[derive(Debug)]
+pub enum SmxError {
+    Io(std::io::Error),
+    BadMagic(u32),
+    UnknownCompression(u8),
+    Decompression(std::io::Error),
+    /// The file ends in the middle of the given structure.
+    Truncated(&'static str),
+}
+
+impl fmt::Display for SmxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmxError::Io(err) => write!(f, "failed to read the plugin: {err}"),
+            SmxError::BadMagic(magic) => write!(f, "not an SMX file (magic {magic:#010x})"),
+            SmxError::UnknownCompression(it) => write!(f, "unknown compression {it}"),
+            SmxError::Decompression(err) => write!(f, "failed to decompress the plugin: {err}"),
+            SmxError::Truncated(what) => write!(f, "the plugin is truncated ({what})"),
+        }
+    }
+}
+
+impl std::error::Error for SmxError {}
+
+impl From<std::io::Error> for SmxError {
+    fn from(err: std::io::Error) -> Self {
+        SmxError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Zlib,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    /// Offset of the section in the decompressed image.
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// A public function, which SourceMod can call, such as `OnPluginStart`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Public {
+    pub name: String,
+    /// Address of the function in the code section.
+    pub address: u32,
+}
+
+/// A public variable, such as `myinfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PubVar {
+    pub name: String,
+    /// Address of the variable in the data section.
+    pub address: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub id: u32,
+}
+
+/// An extension the plugin depends on, declared by an `Extension __ext_*` pubvar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extension {
+    /// Name of the pubvar declaring the extension, for example `__ext_sdkhooks`.
+    pub variable: String,
+    pub name: String,
+    pub file: String,
+    pub autoload: bool,
+    /// Whether the plugin fails to load if the extension is missing.
+    pub required: bool,
+}
+
+impl Extension {
+    /// Name of the public function which marks the natives of the extension as optional,
+    /// which the includes of the extensions define when the extension is not required.
+    pub fn set_optional_public(&self) -> String {
+        format!("{}_SetNTVOptional", self.variable)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugInfo {
+    /// Source files of the plugin, with the address of their first instruction.
+    pub files: Vec<DebugFile>,
+    pub num_lines: u32,
+    pub num_symbols: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugFile {
+    pub name: String,
+    pub address: u32,
+}
+
+/// A parsed SMX file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugin {
+    pub version: u16,
+    pub compression: Compression,
+    pub disk_size: u32,
+    pub image_size: u32,
+    pub sections: Vec<Section>,
+    /// Natives the plugin requires.
+    pub natives: Vec<String>,
+    pub publics: Vec<Public>,
+    pub pubvars: Vec<PubVar>,
+    pub tags: Vec<Tag>,
+    pub extensions: Vec<Extension>,
+    pub debug: Option<DebugInfo>,
+}
+
+impl Plugin {
+    pub fn read(path: &Path) -> Result<Self, SmxError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SmxError> {
+        let file = Image(bytes);
+        let magic = file.u32_at(0, "header")?;
+        if magic != MAGIC {
+            return Err(SmxError::BadMagic(magic));
+        }
+        let version = file.u16_at(4, "header")?;
+        let compression = match file.u8_at(6, "header")? {
+            0 => Compression::None,
+            1 => Compression::Zlib,
+            it => return Err(SmxError::UnknownCompression(it)),
+        };
+        let disk_size = file.u32_at(7, "header")?;
+        let image_size = file.u32_at(11, "header")?;
+        let num_sections = file.u8_at(15, "header")? as usize;
+        let string_table = file.u32_at(16, "header")? as usize;
+        let data_offset = file.u32_at(20, "header")? as usize;
+
+        let image = match compression {
+            Compression::None => bytes.to_vec(),
+            Compression::Zlib => {
+                let end = (disk_size as usize).min(bytes.len());
+                let compressed = bytes
+                    .get(data_offset..end)
+                    .ok_or(SmxError::Truncated("compressed data"))?;
+                // The header is not trusted, a corrupted image size must not allocate more
+                // than what the compressed data can expand to.
+                let max_size = (image_size as usize)
+                    .min(data_offset + compressed.len().saturating_mul(MAX_ZLIB_RATIO));
+                let mut image = Vec::with_capacity(max_size);
+                image.extend_from_slice(&bytes[..data_offset]);
+                ZlibDecoder::new(compressed)
+                    .take(max_size.saturating_sub(data_offset) as u64)
+                    .read_to_end(&mut image)
+                    .map_err(SmxError::Decompression)?;
+                image
+            }
+        };
+        let image = Image(&image);
+
+        let sections = (0..num_sections)
+            .map(|idx| {
+                let header = HEADER_SIZE + idx * SECTION_HEADER_SIZE;
+                let name = image.u32_at(header, "section table")? as usize;
+                Ok(Section {
+                    name: image.str_at(string_table + name, "section names")?,
+                    offset: image.u32_at(header + 4, "section table")?,
+                    size: image.u32_at(header + 8, "section table")?,
+                })
+            })
+            .collect::<Result<Vec<_>, SmxError>>()?;
+        let section = |name: &str| sections.iter().find(|section| section.name == name);
+        let names = section(".names").map_or(0, |it| it.offset as usize);
+
+        let natives = entries(section(".natives"), 4)
+            .map(|entry| image.str_at(names + image.u32_at(entry, ".natives")? as usize, ".names"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let publics = entries(section(".publics"), 8)
+            .map(|entry| {
+                Ok(Public {
+                    address: image.u32_at(entry, ".publics")?,
+                    name: image.str_at(
+                        names + image.u32_at(entry + 4, ".publics")? as usize,
+                        ".names",
+                    )?,
+                })
+            })
+            .collect::<Result<Vec<_>, SmxError>>()?;
+        let pubvars = entries(section(".pubvars"), 8)
+            .map(|entry| {
+                Ok(PubVar {
+                    address: image.u32_at(entry, ".pubvars")?,
+                    name: image.str_at(
+                        names + image.u32_at(entry + 4, ".pubvars")? as usize,
+                        ".names",
+                    )?,
+                })
+            })
+            .collect::<Result<Vec<_>, SmxError>>()?;
+        let tags = entries(section(".tags"), 8)
+            .map(|entry| {
+                Ok(Tag {
+                    id: image.u32_at(entry, ".tags")?,
+                    name: image
+                        .str_at(names + image.u32_at(entry + 4, ".tags")? as usize, ".names")?,
+                })
+            })
+            .collect::<Result<Vec<_>, SmxError>>()?;
+
+        let data = match section(".data") {
+            Some(section) => {
+                let offset = section.offset as usize;
+                let size = image.u32_at(offset, ".data")? as usize;
+                let start = offset + image.u32_at(offset + 8, ".data")? as usize;
+                image
+                    .0
+                    .get(start..start + size)
+                    .ok_or(SmxError::Truncated(".data"))?
+                    .to_vec()
+            }
+            None => Vec::new(),
+        };
+        let extensions = pubvars
+            .iter()
+            .filter(|pubvar| pubvar.name.starts_with(EXTENSION_PREFIX))
+            .map(|pubvar| {
+                let data = Image(&data);
+                let address = pubvar.address as usize;
+                Ok(Extension {
+                    variable: pubvar.name.clone(),
+                    name: data.str_at(data.u32_at(address, "extension")? as usize, "extension")?,
+                    file: data
+                        .str_at(data.u32_at(address + 4, "extension")? as usize, "extension")?,
+                    autoload: data.u32_at(address + 8, "extension")? != 0,
+                    required: data.u32_at(address + 12, "extension")? != 0,
+                })
+            })
+            .collect::<Result<Vec<_>, SmxError>>()?;
+
+        let debug = match (section(".dbg.info"), section(".dbg.strings")) {
+            (Some(info), Some(strings)) => {
+                let info = info.offset as usize;
+                let strings = strings.offset as usize;
+                let files = entries(section(".dbg.files"), 8)
+                    .map(|entry| {
+                        Ok(DebugFile {
+                            address: image.u32_at(entry, ".dbg.files")?,
+                            name: image.str_at(
+                                strings + image.u32_at(entry + 4, ".dbg.files")? as usize,
+                                ".dbg.strings",
+                            )?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, SmxError>>()?;
+                Some(DebugInfo {
+                    files,
+                    num_lines: image.u32_at(info + 4, ".dbg.info")?,
+                    num_symbols: image.u32_at(info + 8, ".dbg.info")?,
+                })
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            version,
+            compression,
+            disk_size,
+            image_size,
+            sections,
+            natives,
+            publics,
+            pubvars,
+            tags,
+            extensions,
+            debug,
+        })
+    }
+
+    pub fn has_native(&self, name: &str) -> bool {
+        self.natives.iter().any(|it| it == name)
+    }
+
+    pub fn has_public(&self, name: &str) -> bool {
+        self.publics.iter().any(|it| it.name == name)
+    }
+}
+
+/// Offsets of the entries of `section`, which is a table of `entry_size` bytes entries.
+fn entries(section: Option<&Section>, entry_size: usize) -> impl Iterator<Item = usize> {
+    let (offset, count) = section.map_or((0, 0), |section| {
+        (section.offset as usize, section.size as usize / entry_size)
+    });
+    (0..count).map(move |idx| offset + idx * entry_size)
+}
+
+/// Little endian view over the bytes of a file.
+struct Image<'a>(&'a [u8]);
+
+impl Image<'_> {
+    fn bytes<const N: usize>(
+        &self,
+        offset: usize,
+        what: &'static str,
+    ) -> Result<[u8; N], SmxError> {
+        self.0
+            .get(offset..offset + N)
+            .and_then(|it| it.try_into().ok())
+            .ok_or(SmxError::Truncated(what))
+    }
+
+    fn u8_at(&self, offset: usize, what: &'static str) -> Result<u8, SmxError> {
+        Ok(self.bytes::<1>(offset, what)?[0])
+    }
+
+    fn u16_at(&self, offset: usize, what: &'static str) -> Result<u16, SmxError> {
+        Ok(u16::from_le_bytes(self.bytes(offset, what)?))
+    }
+
+    fn u32_at(&self, offset: usize, what: &'static str) -> Result<u32, SmxError> {
+        Ok(u32::from_le_bytes(self.bytes(offset, what)?))
+    }
+
+    /// Read the nul-terminated string at `offset`.
+    fn str_at(&self, offset: usize, what: &'static str) -> Result<String, SmxError> {
+        let bytes = self.0.get(offset..).ok_or(SmxError::Truncated(what))?;
+        let end = bytes
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(SmxError::Truncated(what))?;
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::ZlibEncoder, Compression as Level};
+
+    use super::*;
+
+    /// Build an SMX file with the given sections, placed after the header, the section
+    /// table and the section names.
+    fn smx(sections: &[(&str, Vec<u8>)], compress: bool) -> Vec<u8> {
+        let mut section_names = Vec::new();
+        let name_offsets = sections
+            .iter()
+            .map(|(name, _)| {
+                let offset = section_names.len() as u32;
+                section_names.extend_from_slice(name.as_bytes());
+                section_names.push(0);
+                offset
+            })
+            .collect::<Vec<_>>();
+        let string_table = HEADER_SIZE + sections.len() * SECTION_HEADER_SIZE;
+        let data_offset = string_table + section_names.len();
+
+        let mut table = Vec::new();
+        let mut body = Vec::new();
+        for ((_, contents), name) in sections.iter().zip(name_offsets) {
+            table.extend_from_slice(&name.to_le_bytes());
+            table.extend_from_slice(&((data_offset + body.len()) as u32).to_le_bytes());
+            table.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            body.extend_from_slice(contents);
+        }
+        let image_size = data_offset + body.len();
+        let body = if compress {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Level::default());
+            encoder.write_all(&body).unwrap();
+            encoder.finish().unwrap()
+        } else {
+            body
+        };
+
+        let mut file = Vec::new();
+        file.extend_from_slice(&MAGIC.to_le_bytes());
+        file.extend_from_slice(&0x0102u16.to_le_bytes());
+        file.push(compress as u8);
+        file.extend_from_slice(&((data_offset + body.len()) as u32).to_le_bytes());
+        file.extend_from_slice(&(image_size as u32).to_le_bytes());
+        file.push(sections.len() as u8);
+        file.extend_from_slice(&(string_table as u32).to_le_bytes());
+        file.extend_from_slice(&(data_offset as u32).to_le_bytes());
+        file.extend_from_slice(&table);
+        file.extend_from_slice(&section_names);
+        file.extend_from_slice(&body);
+        file
+    }
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|it| it.to_le_bytes()).collect()
+    }
+
+    fn plugin_sections() -> Vec<(&'static str, Vec<u8>)> {
+        let names =
+            b"SDKHook\0MarkNativeAsOptional\0OnPluginStart\0__ext_sdkhooks\0Float\0".to_vec();
+        // Extension struct followed by its strings, then a string literal.
+        let mut data = words(&[16, 25, 1, 0]);
+        data.extend_from_slice(b"SDKHooks\0sdkhooks.ext\0SDKHook\0");
+        let mut data_section = words(&[data.len() as u32, data.len() as u32, 12]);
+        data_section.extend_from_slice(&data);
+        vec![
+            (".natives", words(&[0, 8])),
+            (".publics", words(&[0x40, 29])),
+            (".pubvars", words(&[0, 43])),
+            (".tags", words(&[1, 58])),
+            (".names", names),
+            (".data", data_section),
+            (".dbg.info", words(&[1, 12, 3, 0])),
+            (".dbg.files", words(&[0, 0])),
+            (".dbg.strings", b"plugin.sp\0".to_vec()),
+        ]
+    }
+
+    #[test]
+    fn read_plugin() {
+        let plugin = Plugin::from_bytes(&smx(&plugin_sections(), false)).unwrap();
+        assert_eq!(plugin.version, 0x0102);
+        assert_eq!(plugin.compression, Compression::None);
+        assert_eq!(plugin.sections.len(), 9);
+        assert_eq!(plugin.natives, vec!["SDKHook", "MarkNativeAsOptional"]);
+        assert_eq!(
+            plugin.publics,
+            vec![Public {
+                name: "OnPluginStart".to_string(),
+                address: 0x40
+            }]
+        );
+        assert_eq!(
+            plugin.tags,
+            vec![Tag {
+                name: "Float".to_string(),
+                id: 1
+            }]
+        );
+        assert_eq!(
+            plugin.extensions,
+            vec![Extension {
+                variable: "__ext_sdkhooks".to_string(),
+                name: "SDKHooks".to_string(),
+                file: "sdkhooks.ext".to_string(),
+                autoload: true,
+                required: false,
+            }]
+        );
+        let debug = plugin.debug.unwrap();
+        assert_eq!(debug.files[0].name, "plugin.sp");
+        assert_eq!(debug.num_lines, 12);
+    }
+
+    #[test]
+    fn read_compressed_plugin() {
+        let plugin = Plugin::from_bytes(&smx(&plugin_sections(), false)).unwrap();
+        let compressed = Plugin::from_bytes(&smx(&plugin_sections(), true)).unwrap();
+        assert_eq!(compressed.compression, Compression::Zlib);
+        assert_eq!(compressed.natives, plugin.natives);
+        assert_eq!(compressed.extensions, plugin.extensions);
+        assert_eq!(compressed.debug, plugin.debug);
+    }
+
+    #[test]
+    fn bound_decompressed_size() {
+        let mut bytes = smx(&plugin_sections(), true);
+        // Declare a huge image, which must neither be allocated nor decompressed.
+        bytes[11..15].copy_from_slice(&u32::MAX.to_le_bytes());
+        let plugin = Plugin::from_bytes(&bytes).unwrap();
+        assert_eq!(plugin.natives, vec!["SDKHook", "MarkNativeAsOptional"]);
+
+        // Declare a smaller image than the actual one, the decompression stops at its size.
+        let mut bytes = smx(&plugin_sections(), true);
+        bytes[11..15].copy_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+        assert!(matches!(
+            Plugin::from_bytes(&bytes),
+            Err(SmxError::Truncated(_))
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_files() {
+        let bytes = smx(&plugin_sections(), false);
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = 0;
+        assert!(matches!(
+            Plugin::from_bytes(&bad_magic),
+            Err(SmxError::BadMagic(_))
+        ));
+        assert!(matches!(
+            Plugin::from_bytes(&bytes[..10]),
+            Err(SmxError::Truncated("header"))
+        ));
+    }
+}
@@ -0,0 +1,449 @@
+//! Bookkeeping for building all the projects of the workspace with spcomp, running a
+//! bounded number of flychecks at a time and summarizing their results.
+
+use std::collections::VecDeque;
+
+use anyhow::bail;
+use flycheck::SpCompSeverity;
+use fxhash::FxHashMap;
+use itertools::Itertools;
+use lsp_server::RequestId;
+use lsp_types::Url;
+use vfs::FileId;
+
+use crate::{
+    global_state::GlobalState,
+    lsp::{ext::PluginBuildSummary, to_proto},
+};
+
+/// A build of all the projects, which answers its `sourcepawn-studio/buildAll` request
+/// once every project is done.
+///
+/// This only tracks the state of the build, the flychecks are started by the caller of
+/// [`BuildAll::start_next_builds`].
+pub(crate) struct BuildAll {
+    request_id: RequestId,
+    check_only: bool,
+    /// Maximum number of projects built at the same time.
+    max_running: usize,
+    /// Roots of all the projects with their URL, in the order of the summaries.
+    projects: Vec<(FileId, Url)>,
+    /// Roots of the projects waiting for a free flycheck slot.
+    queue: VecDeque<FileId>,
+    running: FxHashMap<FileId, RunningProject>,
+    summaries: FxHashMap<FileId, PluginBuildSummary>,
+}
+
+#[derive(Debug, Default)]
+struct RunningProject {
+    /// Whether the flycheck of the project reported that it started. Progress reported
+    /// before that belongs to a previous check of the project.
+    started: bool,
+    errors: u32,
+    warnings: u32,
+}
+
+/// Result of the flycheck of a project.
+struct ProjectResult {
+    output: Option<paths::AbsPathBuf>,
+    size: Option<u64>,
+    error: Option<String>,
+}
+
+impl ProjectResult {
+    fn error(error: impl ToString) -> Self {
+        Self {
+            output: None,
+            size: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+impl BuildAll {
+    pub(crate) fn new(
+        request_id: RequestId,
+        check_only: bool,
+        projects: Vec<(FileId, Url)>,
+        max_running: usize,
+    ) -> Self {
+        Self {
+            request_id,
+            check_only,
+            max_running: max_running.max(1),
+            queue: projects.iter().map(|(root, _)| *root).collect(),
+            projects,
+            running: FxHashMap::default(),
+            summaries: FxHashMap::default(),
+        }
+    }
+
+    /// Whether the projects are only checked, without writing the plugins.
+    pub(crate) fn check_only(&self) -> bool {
+        self.check_only
+    }
+
+    /// Whether all the projects are done.
+    pub(crate) fn is_done(&self) -> bool {
+        self.queue.is_empty() && self.running.is_empty()
+    }
+
+    /// Pop the queued projects while there are free slots, and start their flychecks with
+    /// `start`, which returns `false` if the project cannot be built anymore.
+    pub(crate) fn start_next_builds(&mut self, mut start: impl FnMut(FileId) -> bool) {
+        while self.running.len() < self.max_running {
+            let Some(root) = self.queue.pop_front() else {
+                break;
+            };
+            if start(root) {
+                self.running.insert(root, RunningProject::default());
+            }
+        }
+    }
+
+    /// Record a message of a flycheck.
+    ///
+    /// Returns whether the message belongs to a project of the build.
+    pub(crate) fn handle_msg(&mut self, message: &flycheck::Message) -> bool {
+        match message {
+            flycheck::Message::AddDiagnostic { id, diagnostic, .. } => {
+                self.handle_diagnostic(FileId(*id), diagnostic.severity())
+            }
+            flycheck::Message::Progress { id, progress } => {
+                self.handle_progress(FileId(*id), progress)
+            }
+        }
+    }
+
+    fn handle_diagnostic(&mut self, root: FileId, severity: SpCompSeverity) -> bool {
+        let Some(project) = self.running.get_mut(&root) else {
+            return false;
+        };
+        if !project.started {
+            // The diagnostic belongs to a previous check of the project.
+            return true;
+        }
+        match severity {
+            SpCompSeverity::Warning => project.warnings += 1,
+            SpCompSeverity::Error | SpCompSeverity::FatalError => project.errors += 1,
+        }
+
+        true
+    }
+
+    fn handle_progress(&mut self, root: FileId, progress: &flycheck::Progress) -> bool {
+        let check_only = self.check_only;
+        let Some(project) = self.running.get_mut(&root) else {
+            return false;
+        };
+        if let flycheck::Progress::DidStart = progress {
+            *project = RunningProject {
+                started: true,
+                ..Default::default()
+            };
+            return true;
+        }
+        if !project.started {
+            // The progress belongs to a previous check of the project.
+            return true;
+        }
+        let result = match progress {
+            flycheck::Progress::DidStart | flycheck::Progress::DidCheckCrate(_) => return true,
+            // A cancelled build is either restarted, or reported as failed.
+            flycheck::Progress::DidCancel if !check_only => return true,
+            flycheck::Progress::DidCancel => ProjectResult::error("spcomp was cancelled"),
+            flycheck::Progress::DidFailToRestart(err) => ProjectResult::error(err),
+            flycheck::Progress::DidFinish(Ok(())) => ProjectResult {
+                output: None,
+                size: None,
+                error: None,
+            },
+            flycheck::Progress::DidFinish(Err(err)) => ProjectResult::error(err),
+            flycheck::Progress::DidBuild(Ok(plugin)) => ProjectResult {
+                output: Some(plugin.path.clone()),
+                size: Some(plugin.size),
+                error: None,
+            },
+            flycheck::Progress::DidBuild(Err(err)) => ProjectResult::error(err),
+        };
+        self.finish_build(root, result);
+
+        true
+    }
+
+    fn finish_build(&mut self, root: FileId, result: ProjectResult) {
+        let project = self.running.remove(&root).unwrap_or_default();
+        let Some((_, url)) = self.projects.iter().find(|(it, _)| *it == root) else {
+            return;
+        };
+        self.summaries.insert(
+            root,
+            PluginBuildSummary {
+                project: url.clone(),
+                success: result.error.is_none() && project.errors == 0,
+                errors: project.errors,
+                warnings: project.warnings,
+                output: result.output.map(|path| to_proto::url_from_abs_path(&path)),
+                size: result.size,
+                error: result.error,
+            },
+        );
+    }
+
+    /// Returns the response to the request, with the summaries in the order of the projects.
+    pub(crate) fn into_response(mut self) -> lsp_server::Response {
+        let summaries = self
+            .projects
+            .iter()
+            .filter_map(|(root, _)| self.summaries.remove(root))
+            .collect_vec();
+
+        lsp_server::Response::new_ok(self.request_id, summaries)
+    }
+}
+
+impl GlobalState {
+    /// Start building all the projects of the workspace, running at most one spcomp
+    /// process per physical CPU.
+    pub(crate) fn start_build_all(
+        &mut self,
+        request_id: RequestId,
+        check_only: bool,
+    ) -> anyhow::Result<()> {
+        if self.build_all.is_some() {
+            bail!("A build of all the projects is already running.");
+        }
+        if self.flycheck.is_empty() {
+            bail!("There is no project to build. Make sure the path to spcomp is set.");
+        }
+        let projects = {
+            let vfs = self.vfs.read();
+            self.flycheck
+                .keys()
+                .copied()
+                .sorted_by_cached_key(|root| vfs.file_path(*root).to_string())
+                .filter_map(|root| {
+                    let url = to_proto::url_from_abs_path(vfs.file_path(root).as_path()?);
+                    Some((root, url))
+                })
+                .collect_vec()
+        };
+        self.build_all = Some(BuildAll::new(
+            request_id,
+            check_only,
+            projects,
+            num_cpus::get_physical(),
+        ));
+        self.start_next_builds();
+
+        Ok(())
+    }
+
+    /// Record a message of a flycheck for the build of all the projects.
+    ///
+    /// Returns whether the message belongs to a project of the build.
+    pub(crate) fn handle_build_all_msg(&mut self, message: &flycheck::Message) -> bool {
+        let Some(build_all) = &mut self.build_all else {
+            return false;
+        };
+        if !build_all.handle_msg(message) {
+            return false;
+        }
+        self.start_next_builds();
+
+        true
+    }
+
+    /// Answer the pending build of all the projects with an error, e.g. because the projects
+    /// were reloaded.
+    pub(crate) fn cancel_build_all(&mut self, message: &str) {
+        let Some(build_all) = self.build_all.take() else {
+            return;
+        };
+        self.respond(lsp_server::Response::new_err(
+            build_all.request_id,
+            lsp_server::ErrorCode::ContentModified as i32,
+            message.to_string(),
+        ));
+    }
+
+    /// Start the flychecks of the queued projects while there are free slots, and respond
+    /// to the request once all the projects are done.
+    fn start_next_builds(&mut self) {
+        let Some(build_all) = &mut self.build_all else {
+            return;
+        };
+        let check_only = build_all.check_only();
+        let flycheck = &self.flycheck;
+        build_all.start_next_builds(|root| {
+            let Some(flycheck) = flycheck.get(&root) else {
+                // The projects were reloaded since the build started.
+                return false;
+            };
+            if check_only {
+                flycheck.restart();
+            } else {
+                flycheck.build();
+            }
+            true
+        });
+        if !build_all.is_done() {
+            return;
+        }
+
+        if let Some(build_all) = self.build_all.take() {
+            self.respond(build_all.into_response());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flycheck::{BuiltPlugin, Message, Progress};
+    use paths::AbsPathBuf;
+
+    use super::*;
+
+    fn url(name: &str) -> Url {
+        Url::parse(&format!("file:///scripting/{}.sp", name)).unwrap()
+    }
+
+    /// Returns a build of the projects `0..count`, started with at most `max_running`
+    /// projects at a time, along with the started projects.
+    fn build_all(check_only: bool, count: u32, max_running: usize) -> (BuildAll, Vec<FileId>) {
+        let projects = (0..count)
+            .map(|id| (FileId(id), url(&id.to_string())))
+            .collect_vec();
+        let mut build_all = BuildAll::new(RequestId::from(1), check_only, projects, max_running);
+        let mut started = Vec::new();
+        build_all.start_next_builds(|root| {
+            started.push(root);
+            true
+        });
+
+        (build_all, started)
+    }
+
+    fn progress(id: u32, progress: Progress) -> Message {
+        Message::Progress { id, progress }
+    }
+
+    fn finish(build_all: &mut BuildAll, id: u32) {
+        assert!(build_all.handle_msg(&progress(id, Progress::DidStart)));
+        assert!(build_all.handle_msg(&progress(id, Progress::DidFinish(Ok(())))));
+    }
+
+    fn summaries(build_all: BuildAll) -> Vec<PluginBuildSummary> {
+        let response = build_all.into_response();
+        serde_json::from_value(response.result.unwrap()).unwrap()
+    }
+
+    #[test]
+    fn bounded_parallelism() {
+        let (mut build_all, started) = build_all(true, 3, 2);
+        assert_eq!(started, vec![FileId(0), FileId(1)]);
+
+        finish(&mut build_all, 1);
+        let mut started = Vec::new();
+        build_all.start_next_builds(|root| {
+            started.push(root);
+            true
+        });
+        assert_eq!(started, vec![FileId(2)]);
+        assert!(!build_all.is_done());
+
+        finish(&mut build_all, 0);
+        finish(&mut build_all, 2);
+        assert!(build_all.is_done());
+    }
+
+    #[test]
+    fn skip_projects_which_cannot_start() {
+        let projects = vec![(FileId(0), url("0")), (FileId(1), url("1"))];
+        let mut build_all = BuildAll::new(RequestId::from(1), true, projects, 1);
+        build_all.start_next_builds(|root| root != FileId(0));
+        assert!(!build_all.handle_msg(&progress(0, Progress::DidStart)));
+        finish(&mut build_all, 1);
+        assert!(build_all.is_done());
+        assert_eq!(
+            summaries(build_all)
+                .into_iter()
+                .map(|it| it.project)
+                .collect_vec(),
+            vec![url("1")]
+        );
+    }
+
+    #[test]
+    fn drop_messages_before_start() {
+        let (mut build_all, _) = build_all(true, 1, 1);
+        // Progress of a previous check of the project.
+        assert!(build_all.handle_diagnostic(FileId(0), SpCompSeverity::Error));
+        assert!(build_all.handle_msg(&progress(0, Progress::DidFinish(Ok(())))));
+        assert!(!build_all.is_done());
+
+        assert!(build_all.handle_msg(&progress(0, Progress::DidStart)));
+        assert!(build_all.handle_diagnostic(FileId(0), SpCompSeverity::Warning));
+        assert!(build_all.handle_msg(&progress(0, Progress::DidFinish(Ok(())))));
+        assert!(build_all.is_done());
+        let summary = summaries(build_all).pop().unwrap();
+        assert!(summary.success);
+        assert_eq!((summary.errors, summary.warnings), (0, 1));
+    }
+
+    #[test]
+    fn ignore_other_flychecks() {
+        let (mut build_all, _) = build_all(true, 1, 1);
+        assert!(!build_all.handle_msg(&progress(7, Progress::DidStart)));
+        assert!(!build_all.handle_diagnostic(FileId(7), SpCompSeverity::Error));
+    }
+
+    #[test]
+    fn cancel_of_check() {
+        let (mut build_all, _) = build_all(true, 1, 1);
+        assert!(build_all.handle_msg(&progress(0, Progress::DidStart)));
+        assert!(build_all.handle_msg(&progress(0, Progress::DidCancel)));
+        assert!(build_all.is_done());
+        let summary = summaries(build_all).pop().unwrap();
+        assert!(!summary.success);
+        assert_eq!(summary.error, Some("spcomp was cancelled".to_string()));
+    }
+
+    #[test]
+    fn cancel_of_build_is_restarted() {
+        let (mut build_all, _) = build_all(false, 1, 1);
+        assert!(build_all.handle_msg(&progress(0, Progress::DidStart)));
+        assert!(build_all.handle_msg(&progress(0, Progress::DidCancel)));
+        assert!(!build_all.is_done());
+
+        let path = AbsPathBuf::try_from("/plugins/0.smx").unwrap();
+        assert!(build_all.handle_msg(&progress(0, Progress::DidStart)));
+        assert!(build_all.handle_msg(&progress(
+            0,
+            Progress::DidBuild(Ok(BuiltPlugin {
+                path: path.clone(),
+                size: 42,
+            }))
+        )));
+        assert!(build_all.is_done());
+        let summary = summaries(build_all).pop().unwrap();
+        assert!(summary.success);
+        assert_eq!(summary.output, Some(to_proto::url_from_abs_path(&path)));
+        assert_eq!(summary.size, Some(42));
+    }
+
+    #[test]
+    fn summaries_in_project_order() {
+        let (mut build_all, _) = build_all(true, 3, 3);
+        for id in [2, 0, 1] {
+            finish(&mut build_all, id);
+        }
+        assert_eq!(
+            summaries(build_all)
+                .into_iter()
+                .map(|it| it.project)
+                .collect_vec(),
+            vec![url("0"), url("1"), url("2")]
+        );
+    }
+}
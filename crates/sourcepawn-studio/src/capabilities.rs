@@ -1,10 +1,12 @@
 use ide::WideEncoding;
 use lsp_types::{
-    CallHierarchyOptions, CallHierarchyServerCapability, ClientCapabilities, CompletionOptions,
-    CompletionOptionsCompletionItem, DocumentSymbolOptions, HoverProviderCapability, MarkupKind,
-    OneOf, PositionEncodingKind, ReferencesOptions, RenameOptions, SemanticTokensFullOptions,
-    SemanticTokensLegend, SemanticTokensOptions, ServerCapabilities, SignatureHelpOptions,
-    TextDocumentSyncCapability, TextDocumentSyncKind, WorkDoneProgressOptions,
+    CallHierarchyOptions, CallHierarchyServerCapability, ClientCapabilities,
+    CodeActionProviderCapability, CodeLensOptions, CompletionOptions,
+    CompletionOptionsCompletionItem, DocumentLinkOptions, DocumentOnTypeFormattingOptions,
+    DocumentSymbolOptions, HoverProviderCapability, MarkupKind, OneOf, PositionEncodingKind,
+    ReferencesOptions, RenameOptions, SemanticTokensFullOptions, SemanticTokensLegend,
+    SemanticTokensOptions, ServerCapabilities, SignatureHelpOptions, TextDocumentSyncCapability,
+    TextDocumentSyncKind, WorkDoneProgressOptions,
 };
 
 use crate::{
@@ -53,6 +55,7 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
                 " ".to_string(),
                 "$".to_string(),
                 "*".to_string(),
+                "#".to_string(),
             ]),
             all_commit_characters: None,
             completion_item: completion_item(config),
@@ -76,12 +79,26 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
                 work_done_progress: None,
             },
         })),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(true),
+        }),
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
         document_symbol_provider: Some(OneOf::Right(DocumentSymbolOptions {
             label: Some("SourcePawn".to_string()),
             work_done_progress_options: WorkDoneProgressOptions {
                 work_done_progress: None,
             },
         })),
+        document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+            first_trigger_character: "*".to_string(),
+            more_trigger_character: None,
+        }),
         call_hierarchy_provider: Some(CallHierarchyServerCapability::Options(
             CallHierarchyOptions {
                 work_done_progress_options: WorkDoneProgressOptions {
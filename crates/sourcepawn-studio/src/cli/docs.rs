@@ -0,0 +1,573 @@
+//! The `docs` command, which generates the documentation of a library of include files.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use base_db::{Change, FileExtension, SourceRootConfig};
+use fxhash::FxHashSet;
+use ide::{AnalysisHost, ApiItem, ApiItemKind};
+use ide_db::DocComment;
+use itertools::Itertools;
+use paths::AbsPathBuf;
+use serde::Serialize;
+use vfs::{Vfs, VfsPath};
+use walkdir::WalkDir;
+
+use crate::line_index::LineEndings;
+
+/// Format of the pages of the generated documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DocsFormat {
+    Html,
+    Markdown,
+}
+
+/// The API of an include file, as written to `api.json`.
+#[derive(Debug, Serialize)]
+struct ApiFile {
+    /// Path of the file, relative to the directory it was found in.
+    path: String,
+    items: Vec<ApiItem>,
+
+    /// Name of the page of the file, without extension, unique among the files.
+    #[serde(skip)]
+    page: String,
+}
+
+/// Generates the documentation of the include files in `paths` into `out_dir`.
+///
+/// # Parameters
+/// - `paths`: The include files to document, or directories to search for include files
+/// - `include_dirs`: Additional directories used to resolve the `#include` directives
+/// - `out_dir`: The directory the documentation is written to
+/// - `format`: The format of the pages of the documentation
+pub fn generate_docs(
+    paths: &[PathBuf],
+    include_dirs: &[PathBuf],
+    out_dir: &Path,
+    format: DocsFormat,
+) -> anyhow::Result<()> {
+    let mut roots = Vec::new();
+    let mut documented = Vec::new();
+    for path in paths {
+        let path = canonicalize(path)?;
+        if path.is_dir() {
+            documented.extend(
+                sp_files(&path, true)
+                    .filter(|file| file.extension().is_some_and(|ext| ext == "inc"))
+                    .map(|file| (relative_path(&file, &path), file)),
+            );
+            roots.push(Root {
+                path,
+                recursive: true,
+            });
+        } else {
+            // The files next to the include are loaded for its `#include` directives.
+            let root = path
+                .parent()
+                .context("include file without a parent directory")?
+                .to_path_buf();
+            documented.push((relative_path(&path, &root), path));
+            roots.push(Root {
+                path: root,
+                recursive: false,
+            });
+        }
+    }
+    for include_dir in include_dirs {
+        roots.push(Root {
+            path: canonicalize(include_dir)?,
+            recursive: true,
+        });
+    }
+    if documented.is_empty() {
+        anyhow::bail!("No include file to document.");
+    }
+
+    let (host, vfs) = load(&roots)?;
+    let analysis = host.analysis();
+    let mut api = Vec::new();
+    let mut pages = FxHashSet::default();
+    for (name, path) in documented
+        .into_iter()
+        .sorted()
+        .unique_by(|(_, path)| path.clone())
+    {
+        let Some(file_id) = vfs.file_id(&VfsPath::from(AbsPathBuf::assert(path))) else {
+            continue;
+        };
+        let page = unique_page_name(&name, &mut pages);
+        api.push(ApiFile {
+            path: name,
+            items: analysis.api_items(file_id)?,
+            page,
+        });
+    }
+
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create {}", out_dir.display()))?;
+    fs::write(
+        out_dir.join("api.json"),
+        serde_json::to_string_pretty(&api)?,
+    )?;
+    match format {
+        DocsFormat::Html => {
+            fs::write(out_dir.join("index.html"), html_index(&api))?;
+            for file in api.iter() {
+                fs::write(out_dir.join(page_name(file, "html")), html_page(file))?;
+            }
+        }
+        DocsFormat::Markdown => {
+            fs::write(out_dir.join("index.md"), markdown_index(&api))?;
+            for file in api.iter() {
+                fs::write(out_dir.join(page_name(file, "md")), markdown_page(file))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn canonicalize(path: &Path) -> anyhow::Result<PathBuf> {
+    dunce::canonicalize(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// A directory whose files are loaded, as a source root.
+struct Root {
+    path: PathBuf,
+
+    /// Whether the files of the subdirectories are loaded too.
+    recursive: bool,
+}
+
+/// Returns the `.sp` and `.inc` files of a directory, and of its subdirectories if
+/// `recursive` is set.
+fn sp_files(dir: &Path, recursive: bool) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(dir)
+        .max_depth(if recursive { usize::MAX } else { 1 })
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| FileExtension::try_from(ext).is_ok())
+        })
+}
+
+fn relative_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Loads the files of the roots into a new [`AnalysisHost`], with one source root per root.
+fn load(roots: &[Root]) -> anyhow::Result<(AnalysisHost, Vfs)> {
+    let mut vfs = Vfs::default();
+    for root in roots {
+        for path in sp_files(&root.path, root.recursive) {
+            let contents =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            vfs.set_file_contents(VfsPath::from(AbsPathBuf::assert(path)), Some(contents));
+        }
+    }
+
+    let mut source_root_config = SourceRootConfig::default();
+    source_root_config.fsc.set_roots(
+        roots
+            .iter()
+            .map(|root| VfsPath::from(AbsPathBuf::assert(root.path.clone())))
+            .collect(),
+    );
+    let mut change = Change::new();
+    change.set_roots(source_root_config.partition(&vfs));
+    let mut files = Vec::new();
+    for (file_id, path) in vfs.iter() {
+        let text = String::from_utf8_lossy(vfs.file_contents(file_id)).into_owned();
+        let (text, _) = LineEndings::normalize(text);
+        change.change_file(file_id, Some(Arc::from(text)));
+        if let Some((_, Some(ext))) = path.name_and_extension() {
+            if let Ok(ext) = FileExtension::try_from(ext) {
+                files.push((file_id, ext));
+            }
+        }
+    }
+    files.sort();
+
+    let mut host = AnalysisHost::new(None);
+    host.apply_change(change);
+    host.set_known_files(files);
+
+    Ok((host, vfs))
+}
+
+/// Returns the name of the page of a file without extension, e.g. `sourcemod_clients` for
+/// `sourcemod/clients.inc`, and adds it to `taken`.
+///
+/// A number is appended to the name if it is already taken, e.g. by `sourcemod_clients.inc`,
+/// or by a file with the same relative path in another directory.
+fn unique_page_name(path: &str, taken: &mut FxHashSet<String>) -> String {
+    let stem = path.strip_suffix(".inc").unwrap_or(path).replace('/', "_");
+    let mut res = stem.clone();
+    let mut suffix = 1;
+    while !taken.insert(res.clone()) {
+        suffix += 1;
+        res = format!("{}_{}", stem, suffix);
+    }
+
+    res
+}
+
+fn page_name(file: &ApiFile, extension: &str) -> String {
+    format!("{}.{}", file.page, extension)
+}
+
+/// Returns the kinds of the items, in their order of first appearance, with the items of
+/// each kind.
+fn items_by_kind(items: &[ApiItem]) -> Vec<(ApiItemKind, Vec<&ApiItem>)> {
+    let mut res: Vec<(ApiItemKind, Vec<&ApiItem>)> = Vec::new();
+    for item in items {
+        match res.iter_mut().find(|(kind, _)| *kind == item.kind) {
+            Some((_, group)) => group.push(item),
+            None => res.push((item.kind, vec![item])),
+        }
+    }
+
+    res
+}
+
+fn markdown_index(api: &[ApiFile]) -> String {
+    let mut buf = String::from("# API\n\n");
+    for file in api {
+        buf.push_str(&format!(
+            "- [{}]({}) — {} items\n",
+            file.path,
+            page_name(file, "md"),
+            file.items.len()
+        ));
+    }
+
+    buf
+}
+
+fn markdown_page(file: &ApiFile) -> String {
+    let mut buf = format!("# {}\n", file.path);
+    for (kind, items) in items_by_kind(&file.items) {
+        buf.push_str(&format!("\n## {}\n", kind.label()));
+        for item in items {
+            markdown_item(&mut buf, item, 3);
+        }
+    }
+
+    buf
+}
+
+fn markdown_item(buf: &mut String, item: &ApiItem, level: usize) {
+    buf.push_str(&format!("\n{} {}\n", "#".repeat(level), item.name));
+    if let Some(signature) = &item.signature {
+        buf.push_str(&format!("\n```sourcepawn\n{}\n```\n", signature));
+    }
+    if item.deprecated
+        && item
+            .docs
+            .as_ref()
+            .and_then(|it| it.deprecated.as_ref())
+            .is_none()
+    {
+        buf.push_str("\n**Deprecated**\n");
+    }
+    if let Some(docs) = &item.docs {
+        markdown_docs(buf, docs);
+    }
+    for (kind, children) in items_by_kind(&item.children) {
+        buf.push_str(&format!("\n{} {}\n", "#".repeat(level + 1), kind.label()));
+        for child in children {
+            markdown_item(buf, child, (level + 2).min(6));
+        }
+    }
+}
+
+fn markdown_docs(buf: &mut String, docs: &DocComment) {
+    match docs.deprecated.as_deref().map(str::trim) {
+        Some("") => buf.push_str("\n**Deprecated**\n"),
+        Some(deprecated) => buf.push_str(&format!("\n**Deprecated:** {}\n", deprecated)),
+        None => (),
+    }
+    if !docs.description.is_empty() {
+        buf.push_str(&format!("\n{}\n", docs.description));
+    }
+    if !docs.params.is_empty() {
+        buf.push_str("\n| Parameter | Description |\n| --- | --- |\n");
+        for param in docs.params.iter() {
+            buf.push_str(&format!(
+                "| `{}` | {} |\n",
+                param.name,
+                param.description.replace('\n', " ").replace('|', "\\|")
+            ));
+        }
+    }
+    if let Some(returns) = &docs.returns {
+        buf.push_str(&format!("\n**Return:** {}\n", returns));
+    }
+    if let Some(errors) = &docs.errors {
+        buf.push_str(&format!("\n**Error:** {}\n", errors));
+    }
+    for note in docs.notes.iter() {
+        buf.push_str(&format!("\n**Note:** {}\n", note));
+    }
+    for see in docs.see.iter() {
+        buf.push_str(&format!("\n**See:** {}\n", see));
+    }
+    for (tag, text) in docs.others.iter() {
+        buf.push_str(&format!("\n**@{}** {}", tag, text).trim_end());
+        buf.push('\n');
+    }
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;max-width:60em;margin:auto;padding:1em}\
+pre{background:#f4f4f4;padding:.5em;overflow-x:auto}\
+table{border-collapse:collapse}td,th{border:1px solid #ccc;padding:.25em .5em;text-align:left}\
+.deprecated{color:#b00}";
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        HTML_STYLE,
+        body
+    )
+}
+
+fn html_index(api: &[ApiFile]) -> String {
+    let mut body = String::from("<h1>API</h1>\n<ul>\n");
+    for file in api {
+        body.push_str(&format!(
+            "<li><a href=\"{}\">{}</a> — {} items</li>\n",
+            page_name(file, "html"),
+            escape_html(&file.path),
+            file.items.len()
+        ));
+    }
+    body.push_str("</ul>\n");
+
+    html_document("API", &body)
+}
+
+fn html_page(file: &ApiFile) -> String {
+    let mut body = format!(
+        "<p><a href=\"index.html\">API</a></p>\n<h1>{}</h1>\n",
+        escape_html(&file.path)
+    );
+    for (kind, items) in items_by_kind(&file.items) {
+        body.push_str(&format!("<h2>{}</h2>\n", kind.label()));
+        for item in items {
+            html_item(&mut body, item, 3, None);
+        }
+    }
+
+    html_document(&file.path, &body)
+}
+
+fn html_item(buf: &mut String, item: &ApiItem, level: usize, parent: Option<&str>) {
+    let anchor = match parent {
+        Some(parent) => format!("{}.{}", parent, item.name),
+        None => item.name.clone(),
+    };
+    buf.push_str(&format!(
+        "<h{level} id=\"{}\">{}</h{level}>\n",
+        escape_html(&anchor),
+        escape_html(&item.name),
+    ));
+    if let Some(signature) = &item.signature {
+        buf.push_str(&format!(
+            "<pre><code>{}</code></pre>\n",
+            escape_html(signature)
+        ));
+    }
+    if item.deprecated
+        && item
+            .docs
+            .as_ref()
+            .and_then(|it| it.deprecated.as_ref())
+            .is_none()
+    {
+        buf.push_str("<p class=\"deprecated\"><strong>Deprecated</strong></p>\n");
+    }
+    if let Some(docs) = &item.docs {
+        html_docs(buf, docs);
+    }
+    for (kind, children) in items_by_kind(&item.children) {
+        buf.push_str(&format!(
+            "<h{level}>{}</h{level}>\n",
+            kind.label(),
+            level = (level + 1).min(6)
+        ));
+        for child in children {
+            html_item(buf, child, (level + 2).min(6), Some(&anchor));
+        }
+    }
+}
+
+fn html_docs(buf: &mut String, docs: &DocComment) {
+    if let Some(deprecated) = &docs.deprecated {
+        buf.push_str(&format!(
+            "<p class=\"deprecated\"><strong>Deprecated</strong> {}</p>\n",
+            escape_html(deprecated)
+        ));
+    }
+    for paragraph in docs.description.split("\n\n").filter(|it| !it.is_empty()) {
+        buf.push_str(&format!("<p>{}</p>\n", html_text(paragraph)));
+    }
+    if !docs.params.is_empty() {
+        buf.push_str("<table>\n<tr><th>Parameter</th><th>Description</th></tr>\n");
+        for param in docs.params.iter() {
+            buf.push_str(&format!(
+                "<tr><td><code>{}</code></td><td>{}</td></tr>\n",
+                escape_html(&param.name),
+                html_text(&param.description)
+            ));
+        }
+        buf.push_str("</table>\n");
+    }
+    let sections = docs
+        .returns
+        .iter()
+        .map(|it| ("Return", it))
+        .chain(docs.errors.iter().map(|it| ("Error", it)))
+        .chain(docs.notes.iter().map(|it| ("Note", it)))
+        .chain(docs.see.iter().map(|it| ("See", it)));
+    for (title, text) in sections {
+        buf.push_str(&format!(
+            "<p><strong>{}:</strong> {}</p>\n",
+            title,
+            html_text(text)
+        ));
+    }
+    for (tag, text) in docs.others.iter() {
+        buf.push_str(&format!(
+            "<p><strong>@{}</strong> {}</p>\n",
+            escape_html(tag),
+            html_text(text)
+        ));
+    }
+}
+
+/// Escapes a text for HTML, keeping its line breaks.
+fn html_text(text: &str) -> String {
+    escape_html(text).replace('\n', "<br>\n")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::ParamDoc;
+
+    use super::*;
+
+    fn api_file() -> ApiFile {
+        ApiFile {
+            path: "sourcemod/clients.inc".to_string(),
+            items: vec![ApiItem {
+                kind: ApiItemKind::Native,
+                name: "KickClient".to_string(),
+                signature: Some("native void KickClient(int client)".to_string()),
+                deprecated: false,
+                docs: Some(DocComment {
+                    description: "Kicks a client.\n\nThe client is <disconnected>.".to_string(),
+                    params: vec![ParamDoc {
+                        name: "client".to_string(),
+                        description: "Client index | serial.".to_string(),
+                    }],
+                    errors: Some("Invalid client.".to_string()),
+                    others: vec![("noreturn".to_string(), String::new())],
+                    ..Default::default()
+                }),
+                children: Vec::new(),
+            }],
+            page: "sourcemod_clients".to_string(),
+        }
+    }
+
+    #[test]
+    fn unique_page_names() {
+        let mut taken = FxHashSet::default();
+        assert_eq!(
+            unique_page_name("sourcemod/clients.inc", &mut taken),
+            "sourcemod_clients"
+        );
+        assert_eq!(
+            unique_page_name("sourcemod_clients.inc", &mut taken),
+            "sourcemod_clients_2"
+        );
+        assert_eq!(
+            unique_page_name("sourcemod/clients.inc", &mut taken),
+            "sourcemod_clients_3"
+        );
+        assert_eq!(unique_page_name("sdktools.inc", &mut taken), "sdktools");
+    }
+
+    #[test]
+    fn markdown() {
+        let file = api_file();
+        assert_eq!(
+            markdown_index(std::slice::from_ref(&file)),
+            "# API\n\n- [sourcemod/clients.inc](sourcemod_clients.md) — 1 items\n"
+        );
+        assert_eq!(
+            markdown_page(&file),
+            r#"# sourcemod/clients.inc
+
+## Natives
+
+### KickClient
+
+```sourcepawn
+native void KickClient(int client)
+```
+
+Kicks a client.
+
+The client is <disconnected>.
+
+| Parameter | Description |
+| --- | --- |
+| `client` | Client index \| serial. |
+
+**Error:** Invalid client.
+
+**@noreturn**
+"#
+        );
+    }
+
+    #[test]
+    fn html() {
+        let file = api_file();
+        let page = html_page(&file);
+        assert!(page.contains("<h3 id=\"KickClient\">KickClient</h3>\n"));
+        assert!(
+            page.contains("<p>Kicks a client.</p>\n<p>The client is &lt;disconnected&gt;.</p>\n")
+        );
+        assert!(
+            page.contains("<tr><td><code>client</code></td><td>Client index | serial.</td></tr>\n")
+        );
+        assert!(page.contains("<p><strong>Error:</strong> Invalid client.</p>\n"));
+        assert!(page.contains("<p><strong>@noreturn</strong> </p>\n"));
+        assert!(html_index(std::slice::from_ref(&file)).contains(
+            "<li><a href=\"sourcemod_clients.html\">sourcemod/clients.inc</a> — 1 items</li>\n"
+        ));
+    }
+}
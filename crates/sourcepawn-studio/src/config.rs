@@ -4,11 +4,11 @@
 //! We currently get this config from `initialize` LSP request, which is not the
 //! best way to do it, but was the simplest thing we could implement.
 
-use ide::{DiagnosticsConfig, HoverConfig, HoverDocFormat};
+use ide::{DiagnosticsConfig, HoverConfig, HoverDocFormat, Severity};
 use itertools::Itertools;
 use lsp_types::{ClientCapabilities, MarkupKind};
-use paths::AbsPathBuf;
-use serde::de::DeserializeOwned;
+use paths::{AbsPath, AbsPathBuf};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::iter;
 use std::{collections::HashSet, fmt, path::PathBuf};
 
@@ -49,10 +49,45 @@ config_data! {
         /// Linter arguments that will be passed to spcomp.
         /// Note that the compilation target, include directories and output path are already handled by the server.
         compiler_arguments: Vec<String> = "[]",
+        /// Build the projects of the saved file with spcomp on save, writing the plugins to
+        /// `#SourcePawnLanguageServer.compiler.outputDirectory#`.
+        compiler_buildOnSave: bool = "false",
+        /// Compute spcomp diagnostics while typing, by checking a copy of the unsaved files in a
+        /// temporary directory.
+        compiler_onChange: bool = "false",
         /// Compute spcomp diagnostics on save.
         compiler_onSave: bool = "true",
+        /// Directory where the plugins are written when building, for example `addons/sourcemod/plugins/`.
+        /// Relative paths are resolved against the root of the workspace.
+        /// Defaults to the directory of each project's main file.
+        compiler_outputDirectory: Option<PathBuf> = "null",
         /// Path to the SourcePawn compiler (spcomp).
         compiler_path: Option<String> = "null",
+        /// Compilers to use instead of `#SourcePawnLanguageServer.compiler.path#` for some projects,
+        /// for example `[{ "project": "scripting/legacy", "path": "/opt/sourcemod-1.10/spcomp" }]`.
+        /// `project` is the main file of a project, or a directory containing main files, and
+        /// relative paths are resolved against the root of the workspace. The most specific entry wins.
+        /// The `include` directory shipped next to a compiler is added to the include directories
+        /// of its projects.
+        compiler_projects: Vec<CompilerProject> = "[]",
+        /// Paths to additional completion data files (JSON), with the same schema as the built-in data.
+        /// Games defined in these files are merged with the built-in ones, and can provide ConVars,
+        /// commands, netprops, entity classnames and sounds.
+        completionData_files: Vec<PathBuf> = "[]",
+        /// Names of the games whose completion data is used, in addition to
+        /// `#SourcePawnLanguageServer.eventsGameName#`. The data of the generic games is always
+        /// used. When none of the games are known, the data of all the games is used.
+        completionData_games: Vec<String> = "[]",
+
+        /// Severity of the variables declared in the initialization of a `for` loop which are
+        /// used after the loop.
+        diagnostics_loopVariables_severity: LintSeverity = "\"warning\"",
+        /// Severity of the local variables which shadow a parameter, a global or a local variable
+        /// of an enclosing block (spcomp warning 219).
+        diagnostics_shadowing_severity: LintSeverity = "\"warning\"",
+        /// Severity of the `switch` statements over an enum which do not handle all the variants
+        /// of the enum and have no `default` case.
+        diagnostics_switchCases_severity: LintSeverity = "\"hint\"",
 
         /// Name of the game we want the events for, as it appears on the Alliedmodders website.
         /// For example, "Counter-Strike: Global Offensive" or "Team Fortress 2".
@@ -79,6 +114,18 @@ config_data! {
         /// Include directories paths for the compiler and the linter.
         includeDirectories: Vec<PathBuf> = "[]",
 
+        /// Whether to show `Build` lens above the main file of a project. Only applies when
+        /// `#SourcePawnLanguageServer.lens.enable#` is set.
+        lens_build_enable: bool = "true",
+        /// Whether to show CodeLens in SourcePawn files.
+        lens_enable: bool = "true",
+        /// Whether to show `Implementations` lens above forwards, typedefs, typesets, functags
+        /// and funcenums. Only applies when `#SourcePawnLanguageServer.lens.enable#` is set.
+        lens_implementations_enable: bool = "true",
+        /// Whether to show `References` lens above functions, methodmaps and global variables.
+        /// Only applies when `#SourcePawnLanguageServer.lens.enable#` is set.
+        lens_references_enable: bool = "true",
+
         /// Disable the language server's syntax linter. This is independant from spcomp.
         linter_disable: bool = "false",
         /// How many worker threads in the main loop. The default `null` means to pick automatically.
@@ -101,6 +148,12 @@ pub struct Config {
     root_path: AbsPathBuf,
     data: ConfigData,
     is_visual_studio_code: bool,
+    /// Directories that may be the `addons/sourcemod` folder of a SourceMod installation,
+    /// resolved when the config is loaded.
+    sourcemod_directories: Vec<AbsPathBuf>,
+    /// Directories that may be the game folder of a SourceMod installation, resolved when the
+    /// config is loaded.
+    game_directories: Vec<AbsPathBuf>,
 }
 
 #[derive(Debug)]
@@ -133,13 +186,17 @@ impl Config {
         workspace_roots: Vec<PathBuf>,
         is_visual_studio_code: bool,
     ) -> Self {
-        Config {
+        let mut config = Config {
             caps,
             data: ConfigData::default(),
             root_path,
             workspace_roots,
             is_visual_studio_code,
-        }
+            sourcemod_directories: Vec::new(),
+            game_directories: Vec::new(),
+        };
+        config.resolve_installation_directories();
+        config
     }
 
     pub fn update(&mut self, json: serde_json::Value) -> Result<(), ConfigError> {
@@ -150,6 +207,7 @@ impl Config {
         let mut errors = Vec::new();
         self.data = ConfigData::from_json(json, &mut errors);
         tracing::debug!("deserialized config data: {:#?}", self.data);
+        self.resolve_installation_directories();
 
         if errors.is_empty() {
             Ok(())
@@ -199,6 +257,9 @@ impl Config {
             enabled: true,
             disable_experimental: false,
             disabled: HashSet::default(),
+            shadowing_severity: self.data.diagnostics_shadowing_severity.into(),
+            loop_variables_severity: self.data.diagnostics_loopVariables_severity.into(),
+            switch_cases_severity: self.data.diagnostics_switchCases_severity.into(),
         }
     }
 
@@ -212,6 +273,71 @@ impl Config {
             .collect_vec()
     }
 
+    /// Directories that may contain translation files (`*.phrases.txt`).
+    ///
+    /// This is the `translations` folder of the workspace, and the `translations` folder of
+    /// the SourceMod installations the include directories belong to
+    /// (`addons/sourcemod/scripting/include` -> `addons/sourcemod/translations`).
+    ///
+    /// The directories are not required to exist, so that their translation files are picked
+    /// up when they are created later.
+    pub fn translations_directories(&self) -> Vec<AbsPathBuf> {
+        let mut res = vec![self.root_path.join("translations")];
+        for include_dir in self.include_directories() {
+            let Some(scripting_dir) = include_dir.parent() else {
+                continue;
+            };
+            if let Some(sourcemod_dir) = scripting_dir.parent() {
+                res.push(sourcemod_dir.join("translations"));
+            }
+        }
+        res.dedup();
+        res
+    }
+
+    /// Directories that may be the `addons/sourcemod` folder of a SourceMod installation.
+    ///
+    /// This is the workspace, its `addons/sourcemod` folder, and the SourceMod folders the
+    /// include directories belong to (`addons/sourcemod/scripting/include` ->
+    /// `addons/sourcemod`).
+    pub fn sourcemod_directories(&self) -> &[AbsPathBuf] {
+        &self.sourcemod_directories
+    }
+
+    /// Directories that may be the game folder of a SourceMod installation, e.g. `cstrike`
+    /// for `cstrike/addons/sourcemod`.
+    pub fn game_directories(&self) -> &[AbsPathBuf] {
+        &self.game_directories
+    }
+
+    /// Finds the existing SourceMod and game directories, once per config instead of once per
+    /// request.
+    fn resolve_installation_directories(&mut self) {
+        let mut sourcemod_dirs = vec![
+            self.root_path.clone(),
+            self.root_path.join("addons").join("sourcemod"),
+        ];
+        for include_dir in self.include_directories() {
+            if let Some(sourcemod_dir) = include_dir.parent().and_then(AbsPath::parent) {
+                sourcemod_dirs.push(sourcemod_dir.to_path_buf());
+            }
+        }
+        sourcemod_dirs.retain(|it| it.is_dir());
+        sourcemod_dirs.dedup();
+
+        let mut game_dirs = vec![self.root_path.clone()];
+        for sourcemod_dir in sourcemod_dirs.iter() {
+            if let Some(game_dir) = sourcemod_dir.parent().and_then(AbsPath::parent) {
+                game_dirs.push(game_dir.to_path_buf());
+            }
+        }
+        game_dirs.retain(|it| it.is_dir());
+        game_dirs.dedup();
+
+        self.sourcemod_directories = sourcemod_dirs;
+        self.game_directories = game_dirs;
+    }
+
     pub fn prime_caches_num_threads(&self) -> u8 {
         match self.data.cachePriming_numThreads {
             0 => num_cpus::get_physical().try_into().unwrap_or(u8::MAX),
@@ -230,8 +356,34 @@ impl Config {
         }
     }
 
-    pub fn events_game_name(&self) -> Option<&str> {
-        self.data.eventsGameName.as_deref()
+    pub fn lens(&self) -> LensConfig {
+        LensConfig {
+            build: self.data.lens_enable && self.data.lens_build_enable,
+            implementations: self.data.lens_enable && self.data.lens_implementations_enable,
+            references: self.data.lens_enable && self.data.lens_references_enable,
+        }
+    }
+
+    /// Names of the games selected by the user, from `eventsGameName` and
+    /// `completionData.games`.
+    pub fn selected_games(&self) -> Vec<String> {
+        self.data
+            .eventsGameName
+            .iter()
+            .chain(self.data.completionData_games.iter())
+            .unique()
+            .cloned()
+            .collect_vec()
+    }
+
+    /// Paths of the completion data files provided by the user. Relative paths are
+    /// resolved against the root path.
+    pub fn completion_data_files(&self) -> Vec<AbsPathBuf> {
+        self.data
+            .completionData_files
+            .iter()
+            .map(|it| self.root_path.join(it))
+            .collect_vec()
     }
 
     pub fn client_commands(&self) -> ClientCommandsConfig {
@@ -248,8 +400,9 @@ impl Config {
         ClientCommandsConfig {
             // run_single: get("sourcepawn-vscode.runSingle"),
             // debug_single: get("sourcepawn-vscode.debugSingle"),
-            // show_reference: get("sourcepawn-vscode.showReferences"),
+            show_reference: get("sourcepawn-vscode.showReferences"),
             goto_location: get("sourcepawn-vscode.gotoLocation"),
+            build_projects: get("sourcepawn-vscode.buildProjects"),
             // trigger_parameter_hints: get("editor.action.triggerParameterHints"),
         }
     }
@@ -279,6 +432,17 @@ impl Config {
         )
     }
 
+    pub fn code_lens_refresh(&self) -> bool {
+        try_or_def!(
+            self.caps
+                .workspace
+                .as_ref()?
+                .code_lens
+                .as_ref()?
+                .refresh_support?
+        )
+    }
+
     pub fn completion_label_details_support(&self) -> bool {
         try_!(self
             .caps
@@ -322,6 +486,39 @@ impl Config {
         self.data.compiler_path.as_deref()
     }
 
+    /// Path to the compiler of the project whose main file is `root`, which is the compiler of
+    /// the most specific entry of `compiler.projects` containing it, or `compiler.path`.
+    pub fn compiler_path_for(&self, root: &AbsPath) -> Option<&str> {
+        self.data
+            .compiler_projects
+            .iter()
+            .filter_map(|it| {
+                let project = self.root_path.join(&it.project);
+                root.starts_with(&project).then_some((project, it))
+            })
+            .max_by_key(|(project, _)| project.as_os_str().len())
+            .map(|(_, it)| it.path.as_str())
+            .or_else(|| self.compiler_path())
+    }
+
+    /// Paths to all the configured compilers, without duplicates.
+    pub fn compiler_paths(&self) -> Vec<&str> {
+        self.compiler_path()
+            .into_iter()
+            .chain(
+                self.data
+                    .compiler_projects
+                    .iter()
+                    .map(|it| it.path.as_str()),
+            )
+            .unique()
+            .collect_vec()
+    }
+
+    pub fn compiler_projects(&self) -> &[CompilerProject] {
+        &self.data.compiler_projects
+    }
+
     pub fn compiler_arguments(&self) -> Vec<String> {
         self.data.compiler_arguments.clone()
     }
@@ -330,6 +527,21 @@ impl Config {
         self.data.compiler_onSave
     }
 
+    pub fn compiler_on_change(&self) -> bool {
+        self.data.compiler_onChange
+    }
+
+    pub fn compiler_build_on_save(&self) -> bool {
+        self.data.compiler_buildOnSave
+    }
+
+    pub fn compiler_output_directory(&self) -> Option<AbsPathBuf> {
+        self.data
+            .compiler_outputDirectory
+            .as_ref()
+            .map(|it| self.root_path.join(it))
+    }
+
     pub fn hover(&self) -> HoverConfig {
         HoverConfig {
             // TODO: Impl these configs
@@ -356,20 +568,69 @@ impl Config {
             },
             // keywords: self.data.hover_documentation_keywords_enable,
             keywords: true,
+            compiler_version: None,
+            implementations_action: self.hover_actions().implementations
+                && self.client_commands().show_reference,
+            references_action: self.hover_actions().references
+                && self.client_commands().show_reference,
         }
     }
 }
 
 type ParallelCachePrimingNumThreads = u8;
 
+/// Severity of a lint of the server which can be configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Warning,
+    Hint,
+}
+
+impl From<LintSeverity> for Severity {
+    fn from(severity: LintSeverity) -> Self {
+        match severity {
+            LintSeverity::Warning => Severity::Warning,
+            LintSeverity::Hint => Severity::WeakWarning,
+        }
+    }
+}
+
+/// Compiler to use for a project, or for the projects of a directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompilerProject {
+    /// Main file of a project, or directory containing the main files of projects.
+    pub project: PathBuf,
+    /// Path to spcomp.
+    pub path: String,
+}
+
 pub struct ClientCommandsConfig {
     // pub run_single: bool,
     // pub debug_single: bool,
-    // pub show_reference: bool,
+    pub show_reference: bool,
     pub goto_location: bool,
+    pub build_projects: bool,
     // pub trigger_parameter_hints: bool,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LensConfig {
+    pub build: bool,
+    pub implementations: bool,
+    pub references: bool,
+}
+
+impl LensConfig {
+    pub fn any(&self) -> bool {
+        self.build || self.implementations || self.references
+    }
+
+    pub fn none(&self) -> bool {
+        !self.any()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HoverActionsConfig {
     pub implementations: bool,
@@ -538,6 +799,17 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
             "type": "array",
             "items": { "type": "string" },
         },
+        "Vec<CompilerProject>" => set! {
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "project": { "type": "string" },
+                    "path": { "type": "string" },
+                },
+                "required": ["project", "path"],
+            },
+        },
         "FxHashSet<String>" => set! {
             "type": "array",
             "items": { "type": "string" },
@@ -572,6 +844,14 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
             "type": ["null", "array"],
             "items": { "type": "string" },
         },
+        "LintSeverity" => set! {
+            "type": "string",
+            "enum": ["warning", "hint"],
+            "enumDescriptions": [
+                "Report the lint as a warning.",
+                "Report the lint as a hint."
+            ],
+        },
         "ParallelCachePrimingNumThreads" => set! {
             "type": "number",
             "minimum": 0,
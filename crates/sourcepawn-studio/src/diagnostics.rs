@@ -2,7 +2,9 @@
 use std::mem;
 
 use fxhash::{FxHashMap, FxHashSet};
+use ide::ExtensionInclude;
 use nohash_hasher::{IntMap, IntSet};
+use paths::AbsPathBuf;
 use vfs::FileId;
 
 use crate::{global_state::GlobalStateSnapshot, lsp};
@@ -23,12 +25,19 @@ pub struct DiagnosticsMapConfig {
 pub(crate) struct DiagnosticCollection {
     // FIXME: should be IntMap<FileId, Vec<ra_id::Diagnostic>>
     pub(crate) native: IntMap<FileId, Vec<lsp_types::Diagnostic>>,
-    // FIXME: should be Vec<flycheck::Diagnostic>
-    pub(crate) check: IntMap<u32, IntMap<FileId, Vec<lsp_types::Diagnostic>>>,
+    pub(crate) check: IntMap<u32, IntMap<FileId, Vec<CheckDiagnostic>>>,
     // pub(crate) check_fixes: CheckFixes,
     changes: IntSet<FileId>,
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct CheckDiagnostic {
+    pub(crate) diagnostic: lsp_types::Diagnostic,
+    /// Text of the first line of the diagnostic when spcomp reported it, used to drop the
+    /// diagnostic once that line is edited.
+    line: Option<String>,
+}
+
 // #[derive(Debug, Clone)]
 // pub(crate) struct Fix {
 //     // Fixes may be triggerable from multiple ranges.
@@ -62,12 +71,13 @@ impl DiagnosticCollection {
         self.changes.insert(file_id);
     }
 
-    #[allow(unused)]
+    /// Add a diagnostic reported by spcomp for `file_id`, whose current contents are `text`.
     pub(crate) fn add_check_diagnostic(
         &mut self,
         flycheck_id: u32,
         file_id: FileId,
         diagnostic: lsp_types::Diagnostic,
+        text: Option<&str>,
         // fix: Option<Fix>,
     ) {
         let diagnostics = self
@@ -77,7 +87,7 @@ impl DiagnosticCollection {
             .entry(file_id)
             .or_default();
         for existing_diagnostic in diagnostics.iter() {
-            if are_diagnostics_equal(existing_diagnostic, &diagnostic) {
+            if are_diagnostics_equal(&existing_diagnostic.diagnostic, &diagnostic) {
                 return;
             }
         }
@@ -89,10 +99,34 @@ impl DiagnosticCollection {
         //     .entry(file_id)
         //     .or_default()
         //     .extend(fix);
-        diagnostics.push(diagnostic);
+        let line = text
+            .and_then(|text| text.lines().nth(diagnostic.range.start.line as usize))
+            .map(ToOwned::to_owned);
+        diagnostics.push(CheckDiagnostic { diagnostic, line });
         self.changes.insert(file_id);
     }
 
+    /// Drop the spcomp diagnostics of `file_id` whose first line was edited since spcomp
+    /// reported them, as they point at code which does not exist anymore.
+    pub(crate) fn clear_stale_check_for(&mut self, file_id: FileId, text: &str) {
+        let lines = text.lines().collect::<Vec<_>>();
+        for diagnostics in self.check.values_mut() {
+            let Some(diagnostics) = diagnostics.get_mut(&file_id) else {
+                continue;
+            };
+            let len = diagnostics.len();
+            diagnostics.retain(|it| match &it.line {
+                Some(line) => {
+                    lines.get(it.diagnostic.range.start.line as usize) == Some(&line.as_str())
+                }
+                None => true,
+            });
+            if diagnostics.len() != len {
+                self.changes.insert(file_id);
+            }
+        }
+    }
+
     pub(crate) fn set_native_diagnostics(
         &mut self,
         file_id: FileId,
@@ -118,11 +152,20 @@ impl DiagnosticCollection {
         file_id: FileId,
     ) -> impl Iterator<Item = &lsp_types::Diagnostic> {
         let native = self.native.get(&file_id).into_iter().flatten();
+        // spcomp errors which a native check also reports are only shown once, with the
+        // range of the native diagnostic, which is more precise and follows the edits.
+        let natives = native.clone();
         let check = self
             .check
             .values()
             .filter_map(move |it| it.get(&file_id))
-            .flatten();
+            .flatten()
+            .map(|it| &it.diagnostic)
+            .filter(move |check| {
+                !natives
+                    .clone()
+                    .any(|native| is_duplicate_of_native(check, native))
+            });
         native.chain(check)
     }
 
@@ -141,6 +184,44 @@ fn are_diagnostics_equal(left: &lsp_types::Diagnostic, right: &lsp_types::Diagno
         && left.message == right.message
 }
 
+/// Codes of the spcomp errors which only follow from another error of the same line, such as
+/// `029: invalid expression, assumed zero` or `213: tag mismatch` after an undefined symbol.
+const SPCOMP_FOLLOW_UP_ERRORS: &[&str] = &["029", "213"];
+
+/// Whether spcomp reports the native diagnostic with the code `native_code` with `check_code`.
+///
+/// Native checks which have an equivalent spcomp error use its code, the syntax errors of
+/// tree-sitter match several errors of spcomp.
+fn is_spcomp_code_of(native_code: &str, check_code: &str) -> bool {
+    match native_code {
+        "syntax-error" | "missing-node" => ["001", "010", "029"].contains(&check_code),
+        _ => native_code == check_code,
+    }
+}
+
+/// Whether the spcomp diagnostic `check` reports the same problem as the native diagnostic
+/// `native`, or follows from it.
+fn is_duplicate_of_native(check: &lsp_types::Diagnostic, native: &lsp_types::Diagnostic) -> bool {
+    if check.range == native.range && check.message == native.message {
+        return true;
+    }
+    let (
+        Some(lsp_types::NumberOrString::String(check_code)),
+        Some(lsp_types::NumberOrString::String(native_code)),
+    ) = (&check.code, &native.code)
+    else {
+        return false;
+    };
+    let same_line = check.range.start.line <= native.range.start.line
+        && native.range.start.line <= check.range.end.line;
+    if !same_line {
+        return false;
+    }
+    is_spcomp_code_of(native_code, check_code)
+        || (SPCOMP_FOLLOW_UP_ERRORS.contains(&check_code.as_str())
+            && native.severity == Some(lsp_types::DiagnosticSeverity::ERROR))
+}
+
 pub(crate) fn fetch_native_diagnostics(
     snapshot: GlobalStateSnapshot,
     subscriptions: Vec<FileId>,
@@ -148,6 +229,7 @@ pub(crate) fn fetch_native_diagnostics(
     subscriptions
         .into_iter()
         .filter_map(|file_id| {
+            let snap = &snapshot;
             let diagnostics = snapshot
                 .analysis
                 .diagnostics(&snapshot.config.diagnostics(), file_id)
@@ -165,10 +247,29 @@ pub(crate) fn fetch_native_diagnostics(
                     code_description: None,
                     source: Some("sourcepawn-studio".to_string()),
                     message: d.message,
-                    related_information: None,
-                    tags: d
-                        .unused
-                        .then(|| vec![lsp_types::DiagnosticTag::UNNECESSARY]),
+                    related_information: Some(
+                        d.related
+                            .into_iter()
+                            .map(|related| lsp_types::DiagnosticRelatedInformation {
+                                location: lsp_types::Location::new(
+                                    lsp::to_proto::url(snap, related.file_id),
+                                    related.range,
+                                ),
+                                message: related.message,
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                    .filter(|it| !it.is_empty()),
+                    tags: Some(
+                        [
+                            d.unused.then_some(lsp_types::DiagnosticTag::UNNECESSARY),
+                            d.deprecated.then_some(lsp_types::DiagnosticTag::DEPRECATED),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>(),
+                    )
+                    .filter(|tags| !tags.is_empty()),
                     data: None,
                 })
                 .collect::<Vec<_>>();
@@ -176,3 +277,269 @@ pub(crate) fn fetch_native_diagnostics(
         })
         .collect()
 }
+
+/// Check the plugin built for the project of `root`, which spcomp wrote to `path`.
+pub(crate) fn fetch_plugin_diagnostics(
+    snapshot: GlobalStateSnapshot,
+    root: FileId,
+    path: AbsPathBuf,
+) -> Vec<(FileId, lsp_types::Diagnostic)> {
+    let plugin = match smx::Plugin::read(path.as_ref()) {
+        Ok(plugin) => plugin,
+        Err(err) => {
+            tracing::warn!("Failed to read the plugin {}: {}", path.display(), err);
+            return Vec::new();
+        }
+    };
+    let (Ok(extensions), Ok(optional_natives)) = (
+        snapshot.analysis.extension_natives(root),
+        snapshot.analysis.optional_natives(root),
+    ) else {
+        return Vec::new();
+    };
+
+    plugin_diagnostics(&plugin, root, &extensions, &optional_natives)
+}
+
+/// Warns about the natives of optional extensions which the plugin requires without marking
+/// them as optional, which prevents the plugin from loading when the extension is missing.
+///
+/// The diagnostics are reported on the directive which includes the extension, or at the
+/// start of `root` if it is unknown.
+fn plugin_diagnostics(
+    plugin: &smx::Plugin,
+    root: FileId,
+    extensions: &FxHashMap<String, ExtensionInclude>,
+    optional_natives: &FxHashSet<String>,
+) -> Vec<(FileId, lsp_types::Diagnostic)> {
+    let can_mark_optional = plugin.has_native("MarkNativeAsOptional");
+
+    plugin
+        .extensions
+        .iter()
+        .filter(|extension| {
+            !extension.required && !plugin.has_public(&extension.set_optional_public())
+        })
+        .filter_map(|extension| Some((extension, extensions.get(&extension.variable)?)))
+        .flat_map(|(extension, include)| {
+            let (file_id, range) = include.directive.map_or(
+                (
+                    root,
+                    lsp_types::Range::new(
+                        lsp_types::Position::new(0, 0),
+                        lsp_types::Position::new(0, 0),
+                    ),
+                ),
+                |it| (it.file_id, it.range),
+            );
+            include
+                .natives
+                .iter()
+                .filter(|native| plugin.has_native(native))
+                .filter(|native| !(can_mark_optional && optional_natives.contains(*native)))
+                .map(move |native| {
+                    let diagnostic = lsp_types::Diagnostic {
+                        range,
+                        severity: Some(lsp_types::DiagnosticSeverity::WARNING),
+                        code: Some(lsp_types::NumberOrString::String(
+                            "optional-native-not-marked".to_string(),
+                        )),
+                        source: Some("smx".to_string()),
+                        message: format!(
+                            "`{}` is provided by the optional extension `{}` but is not marked \
+                            as optional with `MarkNativeAsOptional`, the plugin will fail to \
+                            load without the extension",
+                            native, extension.name
+                        ),
+                        ..Default::default()
+                    };
+                    (file_id, diagnostic)
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use base_db::FileRange;
+    use lsp_types::{DiagnosticSeverity, NumberOrString, Position, Range};
+
+    use super::*;
+
+    fn diagnostic(
+        line: u32,
+        code: &str,
+        severity: DiagnosticSeverity,
+        source: &str,
+    ) -> lsp_types::Diagnostic {
+        lsp_types::Diagnostic {
+            range: Range::new(Position::new(line, 4), Position::new(line, 8)),
+            severity: Some(severity),
+            code: Some(NumberOrString::String(code.to_string())),
+            source: Some(source.to_string()),
+            message: format!("{source} {code}"),
+            ..Default::default()
+        }
+    }
+
+    fn spcomp(line: u32, code: &str) -> lsp_types::Diagnostic {
+        let mut res = diagnostic(line, code, DiagnosticSeverity::ERROR, "spcomp");
+        // spcomp reports whole lines.
+        res.range = Range::new(Position::new(line, 0), Position::new(line, 1000));
+        res
+    }
+
+    fn native(line: u32, code: &str) -> lsp_types::Diagnostic {
+        diagnostic(line, code, DiagnosticSeverity::ERROR, "sourcepawn-studio")
+    }
+
+    fn shown(collection: &DiagnosticCollection, file_id: FileId) -> Vec<lsp_types::Diagnostic> {
+        collection
+            .diagnostics_for(file_id)
+            .cloned()
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn same_code_on_same_line() {
+        let file_id = FileId(0);
+        let mut collection = DiagnosticCollection::default();
+        collection.set_native_diagnostics(file_id, vec![native(1, "017")]);
+        collection.add_check_diagnostic(0, file_id, spcomp(1, "017"), None);
+        collection.add_check_diagnostic(0, file_id, spcomp(2, "017"), None);
+        collection.add_check_diagnostic(0, file_id, spcomp(1, "092"), None);
+        assert_eq!(
+            shown(&collection, file_id),
+            [native(1, "017"), spcomp(2, "017"), spcomp(1, "092")]
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let file_id = FileId(0);
+        let mut collection = DiagnosticCollection::default();
+        collection.set_native_diagnostics(file_id, vec![native(3, "syntax-error")]);
+        collection.add_check_diagnostic(0, file_id, spcomp(3, "001"), None);
+        collection.add_check_diagnostic(0, file_id, spcomp(3, "017"), None);
+        assert_eq!(
+            shown(&collection, file_id),
+            [native(3, "syntax-error"), spcomp(3, "017")]
+        );
+    }
+
+    #[test]
+    fn follow_up_errors() {
+        let file_id = FileId(0);
+        let mut collection = DiagnosticCollection::default();
+        let warning = diagnostic(4, "219", DiagnosticSeverity::WARNING, "sourcepawn-studio");
+        collection.set_native_diagnostics(file_id, vec![native(1, "017"), warning.clone()]);
+        collection.add_check_diagnostic(0, file_id, spcomp(1, "213"), None);
+        collection.add_check_diagnostic(0, file_id, spcomp(1, "029"), None);
+        collection.add_check_diagnostic(0, file_id, spcomp(4, "213"), None);
+        assert_eq!(
+            shown(&collection, file_id),
+            [native(1, "017"), warning, spcomp(4, "213")]
+        );
+    }
+
+    fn plugin(natives: &[&str], publics: &[&str]) -> smx::Plugin {
+        smx::Plugin {
+            version: 0x0102,
+            compression: smx::Compression::None,
+            disk_size: 0,
+            image_size: 0,
+            sections: Vec::new(),
+            natives: natives.iter().map(|it| it.to_string()).collect(),
+            publics: publics
+                .iter()
+                .map(|it| smx::Public {
+                    name: it.to_string(),
+                    address: 0,
+                })
+                .collect(),
+            pubvars: Vec::new(),
+            tags: Vec::new(),
+            extensions: vec![smx::Extension {
+                variable: "__ext_sdkhooks".to_string(),
+                name: "SDKHooks".to_string(),
+                file: "sdkhooks.ext".to_string(),
+                autoload: true,
+                required: false,
+            }],
+            debug: None,
+        }
+    }
+
+    fn sdkhooks(directive: Option<FileRange>) -> FxHashMap<String, ExtensionInclude> {
+        [(
+            "__ext_sdkhooks".to_string(),
+            ExtensionInclude {
+                natives: vec!["SDKHook".to_string(), "SDKUnhook".to_string()],
+                directive,
+            },
+        )]
+        .into_iter()
+        .collect()
+    }
+
+    fn unmarked_natives(diagnostics: &[(FileId, lsp_types::Diagnostic)]) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .map(|(_, it)| it.message.split('`').nth(1).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn plugin_optional_natives() {
+        let root = FileId(0);
+        let directive = FileRange {
+            file_id: FileId(1),
+            range: Range::new(Position::new(2, 9), Position::new(2, 19)),
+        };
+        let plugin = plugin(&["SDKHook", "SDKUnhook", "MarkNativeAsOptional"], &[]);
+
+        let diagnostics = plugin_diagnostics(
+            &plugin,
+            root,
+            &sdkhooks(Some(directive)),
+            &FxHashSet::default(),
+        );
+        assert_eq!(unmarked_natives(&diagnostics), ["SDKHook", "SDKUnhook"]);
+        assert!(diagnostics
+            .iter()
+            .all(|(file_id, it)| *file_id == directive.file_id && it.range == directive.range));
+
+        let optional = ["SDKHook".to_string()].into_iter().collect();
+        let diagnostics = plugin_diagnostics(&plugin, root, &sdkhooks(None), &optional);
+        assert_eq!(unmarked_natives(&diagnostics), ["SDKUnhook"]);
+        assert_eq!(diagnostics[0].0, root);
+    }
+
+    #[test]
+    fn plugin_optional_extension_handled() {
+        let root = FileId(0);
+        // The natives are only marked as optional if the plugin calls MarkNativeAsOptional.
+        let optional = ["SDKHook".to_string()].into_iter().collect();
+        let diagnostics =
+            plugin_diagnostics(&plugin(&["SDKHook"], &[]), root, &sdkhooks(None), &optional);
+        assert_eq!(unmarked_natives(&diagnostics), ["SDKHook"]);
+
+        // The include of the extension marks all its natives as optional.
+        let plugin = plugin(&["SDKHook"], &["__ext_sdkhooks_SetNTVOptional"]);
+        assert!(
+            plugin_diagnostics(&plugin, root, &sdkhooks(None), &FxHashSet::default()).is_empty()
+        );
+    }
+
+    #[test]
+    fn clear_stale_check() {
+        let file_id = FileId(0);
+        let mut collection = DiagnosticCollection::default();
+        let text = "#include <sourcemod>\nint foo = bar;\n";
+        collection.add_check_diagnostic(0, file_id, spcomp(1, "017"), Some(text));
+        collection.clear_stale_check_for(file_id, text);
+        assert_eq!(shown(&collection, file_id), [spcomp(1, "017")]);
+        collection.clear_stale_check_for(file_id, "#include <sourcemod>\nint foo = baz;\n");
+        assert!(shown(&collection, file_id).is_empty());
+    }
+}
@@ -1,25 +1,39 @@
 use flycheck::SpCompDiagnostic;
-use lsp_types::{Position, Range};
+use lsp_types::{DiagnosticRelatedInformation, Location, Position, Range};
+
+use crate::lsp::to_proto::url_from_abs_path;
 
 pub fn map_spcomp_diagnostic_to_lsp(diagnostic: &SpCompDiagnostic) -> lsp_types::Diagnostic {
-    let range = Range {
-        start: Position {
-            line: diagnostic.line_index(),
-            character: 0,
-        },
-        end: Position {
-            line: diagnostic.line_index(),
-            character: 1000,
-        },
-    };
+    let related_information = diagnostic
+        .notes()
+        .iter()
+        .map(|note| DiagnosticRelatedInformation {
+            location: Location {
+                uri: url_from_abs_path(&note.path),
+                range: Range {
+                    start: Position {
+                        line: note.line_index,
+                        character: 0,
+                    },
+                    end: Position {
+                        line: note.line_index,
+                        character: 1000,
+                    },
+                },
+            },
+            message: note.message.clone(),
+        })
+        .collect::<Vec<_>>();
     lsp_types::Diagnostic {
-        range,
+        range: diagnostic.range(),
         severity: diagnostic.severity().to_lsp_severity().into(),
         code: Some(lsp_types::NumberOrString::String(
             diagnostic.code().to_string(),
-        )),
+        ))
+        .filter(|_| !diagnostic.code().is_empty()),
         source: Some("spcomp".to_string()),
         message: diagnostic.message().to_string(),
+        related_information: Some(related_information).filter(|it| !it.is_empty()),
         ..Default::default()
     }
 }
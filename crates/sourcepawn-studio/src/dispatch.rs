@@ -54,6 +54,32 @@ impl RequestDispatcher<'_> {
         self
     }
 
+    /// Dispatches the request onto the current thread, given full access to
+    /// mutable global state, without responding to it. The handler is responsible
+    /// for responding later, using the [`lsp_server::RequestId`] of the request,
+    /// unless it fails.
+    pub(crate) fn on_sync_mut_deferred<R>(
+        &mut self,
+        f: fn(&mut GlobalState, lsp_server::RequestId, R::Params) -> anyhow::Result<()>,
+    ) -> &mut Self
+    where
+        R: lsp_types::request::Request,
+        R::Params: DeserializeOwned + panic::UnwindSafe + fmt::Debug,
+        R::Result: Serialize,
+    {
+        let (req, params, _panic_context) = match self.parse::<R>() {
+            Some(it) => it,
+            None => return self,
+        };
+        if let Err(err) = f(self.global_state, req.id.clone(), params) {
+            if let Ok(response) = result_to_response::<R>(req.id, Err(err)) {
+                self.global_state.respond(response);
+            }
+        }
+
+        self
+    }
+
     /// Dispatches a non-latency-sensitive request onto the thread pool.
     pub(crate) fn on<R>(
         &mut self,
@@ -1,6 +1,6 @@
-use base_db::{Change, FileExtension, SourceRootConfig};
+use base_db::{normalize_translation_name, Change, FileExtension, SourceRootConfig};
 use crossbeam::channel::{unbounded, Receiver, Sender};
-use flycheck::FlycheckHandle;
+use flycheck::{Compiler, FlycheckHandle};
 use fxhash::FxHashMap;
 use ide::{Analysis, AnalysisHost};
 
@@ -15,6 +15,7 @@ use threadpool::ThreadPool;
 use vfs::{FileId, Vfs};
 
 use crate::{
+    build_all::BuildAll,
     client::LspClient,
     config::{Config, ConfigError},
     diagnostics::DiagnosticCollection,
@@ -68,10 +69,20 @@ pub struct GlobalState {
 
     // Flycheck
     pub(crate) flycheck: Arc<FxHashMap<FileId, FlycheckHandle>>,
+    /// Compilers of the projects, keyed by the root of the project.
+    pub(crate) compilers: Arc<FxHashMap<FileId, Compiler>>,
+    /// Compilers detected so far, keyed by their command.
+    pub(crate) detected_compilers: FxHashMap<String, Compiler>,
     pub(crate) flycheck_tempdir: TempDir,
     pub(crate) flycheck_sender: Sender<flycheck::Message>,
     pub(crate) flycheck_receiver: Receiver<flycheck::Message>,
     pub(crate) last_flycheck_error: Option<String>,
+    /// Number of checks started by each flycheck, used to drop the results of the previous
+    /// checks which arrive late.
+    pub(crate) flycheck_generations: FxHashMap<u32, u32>,
+    pub(crate) build_all: Option<BuildAll>,
+    /// Files changed since the last check of their unsaved contents.
+    pub(crate) overlay_check_requested: Vec<FileId>,
 
     // VFS
     pub(crate) loader: Handle<Box<dyn vfs::loader::Handle>, Receiver<vfs::loader::Message>>,
@@ -126,10 +137,15 @@ impl GlobalState {
             analysis_host: AnalysisHost::default(),
 
             flycheck: Arc::new(FxHashMap::default()),
+            compilers: Arc::new(FxHashMap::default()),
+            detected_compilers: FxHashMap::default(),
             flycheck_tempdir: TempDir::new().expect("failed to create temp dir"),
             flycheck_sender,
             flycheck_receiver,
             last_flycheck_error: None,
+            flycheck_generations: FxHashMap::default(),
+            build_all: None,
+            overlay_check_requested: Vec::new(),
 
             loader,
             vfs: Arc::new(RwLock::new(Vfs::default())),
@@ -149,6 +165,7 @@ impl GlobalState {
             mem_docs: self.mem_docs.clone(),
             semantic_tokens_cache: Arc::clone(&self.semantic_tokens_cache),
             flycheck: self.flycheck.clone(),
+            compilers: self.compilers.clone(),
             vfs: Arc::clone(&self.vfs),
         }
     }
@@ -357,6 +374,22 @@ impl GlobalState {
         files.sort(); // FIXME: Maybe we can avoid sorting here? This was done to make the query deterministic.
         self.analysis_host.set_known_files(files);
 
+        let mut translation_files = self
+            .vfs
+            .read()
+            .iter()
+            .flat_map(|(id, path)| {
+                let (name, ext) = path.name_and_extension()?;
+                if ext? != "txt" || !name.ends_with(".phrases") {
+                    return None;
+                }
+                Some((id, normalize_translation_name(name).to_string()))
+            })
+            .collect_vec();
+        translation_files.sort();
+        self.analysis_host
+            .set_known_translation_files(translation_files);
+
         true
     }
 }
@@ -369,6 +402,7 @@ pub(crate) struct GlobalStateSnapshot {
     pub(crate) mem_docs: MemDocs,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
     pub(crate) flycheck: Arc<FxHashMap<FileId, FlycheckHandle>>,
+    pub(crate) compilers: Arc<FxHashMap<FileId, Compiler>>,
     vfs: Arc<RwLock<vfs::Vfs>>,
 }
 
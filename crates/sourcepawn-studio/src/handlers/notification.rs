@@ -1,5 +1,6 @@
 use std::panic::AssertUnwindSafe;
 
+use flycheck::{Overlay, OverlayFile};
 use itertools::Itertools;
 use lsp_types::{
     DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
@@ -38,6 +39,12 @@ pub(crate) fn handle_did_change_text_document(
             }
         };
 
+        if state.config.compiler_on_change() {
+            if let Some(file_id) = state.vfs.read().file_id(&path) {
+                state.overlay_check_requested.push(file_id);
+            }
+        }
+
         let text = apply_document_changes(
             state.config.position_encoding(),
             || {
@@ -70,7 +77,10 @@ pub(crate) fn handle_did_open_text_document(
             .mem_docs
             .insert(
                 path.clone(),
-                DocumentData::new(params.text_document.version),
+                DocumentData::new(
+                    params.text_document.version,
+                    params.text_document.text.as_bytes(),
+                ),
             )
             .is_err();
         if already_exists {
@@ -120,14 +130,28 @@ pub(crate) fn handle_did_save_text_document(
     state: &mut GlobalState,
     params: DidSaveTextDocumentParams,
 ) -> anyhow::Result<()> {
+    let build = state.config.compiler_build_on_save();
+    let check = state.config.compiler_on_save() || build;
     if let Ok(vfs_path) = from_proto::vfs_path(&params.text_document.uri) {
-        if !state.config.compiler_on_save() || run_flycheck(state, vfs_path) {
+        {
+            let vfs = state.vfs.read();
+            if let (Some(file_id), Some(doc)) =
+                (vfs.file_id(&vfs_path), state.mem_docs.get_mut(&vfs_path))
+            {
+                doc.set_saved(vfs.file_contents(file_id));
+            }
+        }
+        if !check || run_flycheck(state, vfs_path, build) {
             return Ok(());
         }
-    } else if state.config.compiler_on_save() {
+    } else if check {
         // No specific flycheck was triggered, so let's trigger all of them.
         for flycheck in state.flycheck.values() {
-            flycheck.restart();
+            if build {
+                flycheck.build();
+            } else {
+                flycheck.restart();
+            }
         }
     }
     Ok(())
@@ -219,7 +243,7 @@ pub(crate) fn handle_did_change_configuration(
     Ok(())
 }
 
-fn run_flycheck(state: &mut GlobalState, vfs_path: VfsPath) -> bool {
+fn run_flycheck(state: &mut GlobalState, vfs_path: VfsPath, build: bool) -> bool {
     let file_id = state.vfs.read().file_id(&vfs_path);
     let Some(file_id) = file_id else {
         return false;
@@ -243,13 +267,21 @@ fn run_flycheck(state: &mut GlobalState, vfs_path: VfsPath) -> bool {
             .flat_map(|root_id| world.flycheck.get(root_id))
         {
             updated = true;
-            flycheck.restart();
+            if build {
+                flycheck.build();
+            } else {
+                flycheck.restart();
+            }
         }
 
         // No specific flycheck was triggered, so let's trigger all of them.
         if !updated {
             for flycheck in world.flycheck.values() {
-                flycheck.restart();
+                if build {
+                    flycheck.build();
+                } else {
+                    flycheck.restart();
+                }
             }
         }
         Ok(())
@@ -265,3 +297,70 @@ fn run_flycheck(state: &mut GlobalState, vfs_path: VfsPath) -> bool {
         });
     true
 }
+
+/// Check the projects of the changed files with spcomp, using the unsaved contents of the
+/// open files which differ from the files on disk.
+pub(crate) fn run_overlay_flycheck(state: &mut GlobalState, file_ids: Vec<FileId>) {
+    let world = state.snapshot();
+    let task = move || -> std::result::Result<(), Cancelled> {
+        let modified = {
+            let vfs = world.vfs.read();
+            world
+                .mem_docs
+                .iter()
+                .filter_map(|path| {
+                    let file_id = vfs.file_id(path)?;
+                    let doc = world.mem_docs.get(path)?;
+                    doc.is_modified(vfs.file_contents(file_id))
+                        .then_some(file_id)
+                })
+                .collect_vec()
+        };
+
+        let mut root_ids = Vec::new();
+        for file_id in file_ids.into_iter().unique() {
+            root_ids.extend(world.analysis.projects_for_file(file_id)?);
+        }
+        for root_id in root_ids.into_iter().sorted().unique() {
+            let Some(flycheck) = world.flycheck.get(&root_id) else {
+                continue;
+            };
+            let files = world.analysis.overlay_files(root_id, modified.clone())?;
+            if files.is_empty() {
+                // The files on disk are up to date.
+                flycheck.restart();
+                continue;
+            }
+            let mut overlay = Overlay::default();
+            {
+                let vfs = world.vfs.read();
+                let path = |file_id| vfs.file_path(file_id).as_path().map(|it| it.to_owned());
+                for file in files {
+                    let Some(file_path) = path(file.file_id) else {
+                        continue;
+                    };
+                    overlay.push(OverlayFile {
+                        path: file_path,
+                        text: file.text.to_string(),
+                        includes: file
+                            .includes
+                            .into_iter()
+                            .filter_map(|(range, file_id)| Some((range, path(file_id)?)))
+                            .collect(),
+                    });
+                }
+            }
+            flycheck.check_overlay(overlay);
+        }
+        Ok(())
+    };
+    state
+        .task_pool
+        .handle
+        .spawn_with_sender(stdx::thread::ThreadIntent::Worker, move |_| {
+            // FIXME: The `AssertUnwindSafe` is a workaround. Not sure why this causes problems.
+            if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(task)) {
+                tracing::error!("flycheck task panicked: {e:?}")
+            }
+        });
+}
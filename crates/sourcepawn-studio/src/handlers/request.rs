@@ -1,9 +1,10 @@
 use std::panic::AssertUnwindSafe;
 
 use anyhow::{bail, Context};
-use base_db::FileRange;
-use ide::{CompletionKind, HoverAction, HoverGotoTypeData};
+use base_db::{FilePosition, FileRange};
+use ide::{AnnotationConfig, CompletionKind, HoverAction, HoverGotoTypeData};
 use ide_db::SymbolKind;
+use itertools::Itertools;
 use lsp_types::{
     CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
     CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
@@ -15,12 +16,14 @@ use stdx::format_to;
 use vfs::FileId;
 
 use crate::{
-    global_state::GlobalStateSnapshot,
+    global_state::{GlobalState, GlobalStateSnapshot},
     lsp::{
         self,
         ext::{
-            AnalyzerStatusParams, ItemTreeParams, PreprocessedDocumentParams,
-            ProjectMainPathParams, ProjectsGraphvizParams, SyntaxTreeParams,
+            AnalyzerStatusParams, BuildAllParams, BuildParams, CodeLensResolveData,
+            DocumentLinkResolveData, InspectPluginParams, ItemTreeParams,
+            PreprocessedDocumentParams, ProjectMainPathParams, ProjectsGraphvizParams,
+            SyntaxTreeParams,
         },
         from_proto, to_proto,
     },
@@ -56,14 +59,23 @@ pub(crate) fn handle_completion(
     let file_id_to_url = &|id: FileId| snap.file_id_to_url(id);
     let file_id_to_url: AssertUnwindSafe<&dyn Fn(FileId) -> Url> = AssertUnwindSafe(file_id_to_url);
 
-    let include_directories = snap.config.include_directories();
+    let include_directories = snap
+        .config
+        .include_directories()
+        .into_iter()
+        .chain(
+            snap.compilers
+                .values()
+                .filter_map(|it| it.include_directory.clone()),
+        )
+        .unique()
+        .collect_vec();
 
     if let Some(completions) = snap.analysis.completions(
         position,
         trigger_character,
         include_directories,
         file_id_to_url,
-        snap.config.events_game_name(),
     )? {
         return Ok(Some(lsp_types::CompletionResponse::Array(
             completions
@@ -141,6 +153,41 @@ pub(crate) fn handle_rename(
     Ok(Some(to_proto::workspace_edit(&snap, source_change)))
 }
 
+pub(crate) fn handle_code_action(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::CodeActionParams,
+) -> anyhow::Result<Option<lsp_types::CodeActionResponse>> {
+    let frange = from_proto::file_range(&snap, &params.text_document, params.range)?;
+
+    let res = snap
+        .analysis
+        .assists(frange)?
+        .into_iter()
+        .map(|assist| {
+            lsp_types::CodeActionOrCommand::CodeAction(lsp_types::CodeAction {
+                title: assist.label,
+                kind: Some(lsp_types::CodeActionKind::QUICKFIX),
+                edit: Some(to_proto::workspace_edit(&snap, assist.source_change)),
+                ..Default::default()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_on_type_formatting(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::DocumentOnTypeFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    let Some(char_typed) = params.ch.chars().next() else {
+        return Ok(None);
+    };
+    let pos = from_proto::file_position(&snap, params.text_document_position)?;
+
+    Ok(snap.analysis.on_char_typed(pos, char_typed)?)
+}
+
 pub(crate) fn handle_symbol(
     snap: GlobalStateSnapshot,
     params: lsp_types::DocumentSymbolParams,
@@ -172,12 +219,15 @@ pub(crate) fn handle_hover(
     let file_id_to_url: AssertUnwindSafe<&dyn Fn(FileId) -> Option<String>> =
         AssertUnwindSafe(file_id_to_url);
 
-    let info = match snap.analysis.hover(
-        pos,
-        &snap.config.hover(),
-        file_id_to_url,
-        snap.config.events_game_name(),
-    )? {
+    let mut hover_config = snap.config.hover();
+    hover_config.compiler_version = snap
+        .analysis
+        .projects_for_file(pos.file_id)?
+        .into_iter()
+        .sorted()
+        .find_map(|root| snap.compilers.get(&root)?.version.clone());
+
+    let info = match snap.analysis.hover(pos, &hover_config, file_id_to_url)? {
         None => return Ok(None),
         Some(it) => it,
     };
@@ -223,6 +273,66 @@ fn goto_type_action_links(
     })
 }
 
+fn show_impl_command_link(
+    snap: &GlobalStateSnapshot,
+    position: &FilePosition,
+) -> Option<lsp::ext::CommandLinkGroup> {
+    if !snap.config.hover_actions().implementations || !snap.config.client_commands().show_reference
+    {
+        return None;
+    }
+    let targets = snap.analysis.implementations(*position).ok()??;
+    let uri = to_proto::url(snap, position.file_id);
+    let locations = targets
+        .into_iter()
+        .filter_map(|nav| {
+            to_proto::location(
+                snap,
+                FileRange {
+                    file_id: nav.file_id,
+                    range: nav.focus_or_full_range(),
+                },
+            )
+            .ok()
+        })
+        .collect_vec();
+    let title = to_proto::implementation_title(locations.len());
+    let command = to_proto::command::show_references(title, &uri, position.position, locations);
+
+    Some(lsp::ext::CommandLinkGroup {
+        commands: vec![to_command_link(command, "Go to implementations".into())],
+        ..Default::default()
+    })
+}
+
+fn show_ref_command_link(
+    snap: &GlobalStateSnapshot,
+    position: &FilePosition,
+) -> Option<lsp::ext::CommandLinkGroup> {
+    if !snap.config.hover_actions().references || !snap.config.client_commands().show_reference {
+        return None;
+    }
+    let franges = snap
+        .analysis
+        .references(*position)
+        .ok()??
+        .into_iter()
+        // Do not count the declaration.
+        .filter(|frange| {
+            frange.file_id != position.file_id || frange.range.start != position.position
+        })
+        .collect_vec();
+    let uri = to_proto::url(snap, position.file_id);
+    let locations = to_proto::references_response(snap, franges).ok()?;
+    let title = to_proto::reference_title(locations.len());
+    let command = to_proto::command::show_references(title, &uri, position.position, locations);
+
+    Some(lsp::ext::CommandLinkGroup {
+        commands: vec![to_command_link(command, "Go to references".into())],
+        ..Default::default()
+    })
+}
+
 fn to_command_link(command: lsp_types::Command, tooltip: String) -> lsp::ext::CommandLink {
     lsp::ext::CommandLink {
         tooltip: Some(tooltip),
@@ -237,13 +347,99 @@ fn prepare_hover_actions(
     actions
         .iter()
         .filter_map(|it| match it {
-            HoverAction::Implementation(_) => todo!(),
-            HoverAction::Reference(_) => todo!(),
+            HoverAction::Implementation(position) => show_impl_command_link(snap, position),
+            HoverAction::Reference(position) => show_ref_command_link(snap, position),
             HoverAction::GoToType(targets) => goto_type_action_links(snap, targets),
         })
         .collect()
 }
 
+pub(crate) fn handle_document_link(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::DocumentLinkParams,
+) -> anyhow::Result<Option<Vec<lsp_types::DocumentLink>>> {
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let res = snap
+        .analysis
+        .document_links(file_id)?
+        .into_iter()
+        .map(|link| to_proto::document_link(&snap, link))
+        .collect();
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_document_link_resolve(
+    snap: GlobalStateSnapshot,
+    mut link: lsp_types::DocumentLink,
+) -> anyhow::Result<lsp_types::DocumentLink> {
+    let Some(data) = link.data.take() else {
+        return Ok(link);
+    };
+    let (dirs, path) = match serde_json::from_value(data)? {
+        DocumentLinkResolveData::SourceModPath(path) => (snap.config.sourcemod_directories(), path),
+        DocumentLinkResolveData::GamePath(path) => (snap.config.game_directories(), path),
+    };
+    match dirs
+        .iter()
+        .map(|dir| dir.join(&path))
+        .find(|it| it.exists())
+    {
+        Some(target) => link.target = Some(to_proto::url_from_abs_path(&target)),
+        None => link.tooltip = Some(format!("File `{}` not found", path)),
+    }
+
+    Ok(link)
+}
+
+pub(crate) fn handle_code_lens(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::CodeLensParams,
+) -> anyhow::Result<Option<Vec<lsp_types::CodeLens>>> {
+    let lens_config = snap.config.lens();
+    if lens_config.none() {
+        return Ok(Some(Vec::new()));
+    }
+
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let client_commands = snap.config.client_commands();
+    let annotations = snap.analysis.annotations(
+        &AnnotationConfig {
+            annotate_builds: lens_config.build && client_commands.build_projects,
+            annotate_impls: lens_config.implementations && client_commands.show_reference,
+            annotate_references: lens_config.references && client_commands.show_reference,
+        },
+        file_id,
+    )?;
+
+    let mut res = Vec::new();
+    for annotation in annotations {
+        to_proto::code_lens(&mut res, &snap, annotation)?;
+    }
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_code_lens_resolve(
+    snap: GlobalStateSnapshot,
+    code_lens: lsp_types::CodeLens,
+) -> anyhow::Result<lsp_types::CodeLens> {
+    let Some(data) = code_lens.data.clone() else {
+        return Ok(code_lens);
+    };
+    let data: CodeLensResolveData = serde_json::from_value(data)?;
+    let Some(annotation) = from_proto::annotation(&snap, code_lens.range, data)? else {
+        // The document changed since the code lens was computed.
+        return Ok(code_lens);
+    };
+    let annotation = snap.analysis.resolve_annotation(annotation)?;
+
+    let mut acc = Vec::new();
+    to_proto::code_lens(&mut acc, &snap, annotation)?;
+
+    Ok(acc.pop().unwrap_or(code_lens))
+}
+
 pub(crate) fn handle_signature_help(
     snap: GlobalStateSnapshot,
     params: SignatureHelpParams,
@@ -486,6 +682,24 @@ pub(crate) fn handle_analyzer_status(
         "\nVfs memory usage: {}\n",
         profile::Bytes::new(snap.vfs_memory_usage() as _)
     );
+    if snap.compilers.is_empty() {
+        buf.push_str("\nNo compilers\n");
+    } else {
+        buf.push_str("\nCompilers:\n");
+        for (root, compiler) in snap
+            .compilers
+            .iter()
+            .sorted_by_cached_key(|(root, _)| snap.file_id_to_url(**root))
+        {
+            format_to!(
+                buf,
+                "{}: {} (version {})\n",
+                snap.file_id_to_url(*root),
+                compiler.command,
+                compiler.version.as_deref().unwrap_or("unknown")
+            );
+        }
+    }
     buf.push_str("\nAnalysis:\n");
     buf.push_str(
         &snap
@@ -496,6 +710,51 @@ pub(crate) fn handle_analyzer_status(
     Ok(buf)
 }
 
+pub(crate) fn handle_build(
+    snap: GlobalStateSnapshot,
+    params: BuildParams,
+) -> anyhow::Result<Vec<Url>> {
+    let root_ids = match params.uri {
+        Some(uri) => {
+            let file_id = from_proto::file_id(&snap, &uri)?;
+            snap.analysis
+                .projects_for_file(file_id)
+                .context("Failed to get project for file")?
+        }
+        None => snap.flycheck.keys().copied().collect(),
+    };
+
+    Ok(root_ids
+        .into_iter()
+        .sorted()
+        .unique()
+        .filter_map(|root_id| {
+            let flycheck = snap.flycheck.get(&root_id)?;
+            flycheck.build();
+            Some(to_proto::url_from_abs_path(flycheck.build_output_path()))
+        })
+        .collect())
+}
+
+pub(crate) fn handle_build_all(
+    state: &mut GlobalState,
+    id: lsp_server::RequestId,
+    params: BuildAllParams,
+) -> anyhow::Result<()> {
+    state.start_build_all(id, params.check_only)
+}
+
+pub(crate) fn handle_inspect_plugin(
+    _snap: GlobalStateSnapshot,
+    params: InspectPluginParams,
+) -> anyhow::Result<String> {
+    let path = from_proto::abs_path(&params.uri)?;
+    let plugin = smx::Plugin::read(path.as_ref())
+        .with_context(|| format!("Failed to inspect {}", path.display()))?;
+
+    Ok(plugin.to_string())
+}
+
 pub(crate) fn handle_project_main_path(
     snap: GlobalStateSnapshot,
     params: ProjectMainPathParams,
@@ -1,4 +1,8 @@
+mod build_all;
 mod capabilities;
+pub mod cli {
+    pub mod docs;
+}
 mod client;
 mod diagnostics;
 mod dispatch;
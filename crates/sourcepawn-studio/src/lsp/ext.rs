@@ -108,6 +108,92 @@ pub struct ProjectsGraphvizParams {
     pub text_document: Option<TextDocumentIdentifier>,
 }
 
+/// Returns the paths of the plugins written by the builds.
+pub enum Build {}
+
+impl Request for Build {
+    type Params = BuildParams;
+    type Result = Vec<Url>;
+    const METHOD: &'static str = "sourcepawn-studio/build";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildParams {
+    /// Only build the projects of this file. Build all the projects if it is `None`.
+    pub uri: Option<Url>,
+}
+
+pub enum BuildResultNotification {}
+
+impl Notification for BuildResultNotification {
+    type Params = BuildResultParams;
+    const METHOD: &'static str = "sourcepawn-studio/buildResult";
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildResultParams {
+    /// Main file of the project.
+    pub project: Url,
+    /// Plugin written by the build, if it succeeded.
+    pub output: Option<Url>,
+    /// Size of the plugin, in bytes.
+    pub size: Option<u64>,
+    /// Error of the build, if it failed.
+    pub error: Option<String>,
+}
+
+pub enum BuildAll {}
+
+impl Request for BuildAll {
+    type Params = BuildAllParams;
+    type Result = Vec<PluginBuildSummary>;
+    const METHOD: &'static str = "sourcepawn-studio/buildAll";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildAllParams {
+    /// Only check the projects, without writing the plugins.
+    #[serde(default)]
+    pub check_only: bool,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginBuildSummary {
+    /// Main file of the project.
+    pub project: Url,
+    /// Whether spcomp ran and reported no errors.
+    pub success: bool,
+    /// Number of errors reported by spcomp.
+    pub errors: u32,
+    /// Number of warnings reported by spcomp.
+    pub warnings: u32,
+    /// Plugin written by the build, if it succeeded.
+    pub output: Option<Url>,
+    /// Size of the plugin, in bytes.
+    pub size: Option<u64>,
+    /// Error of spcomp itself, e.g. if it failed to start or was cancelled.
+    pub error: Option<String>,
+}
+
+pub enum InspectPlugin {}
+
+impl Request for InspectPlugin {
+    type Params = InspectPluginParams;
+    type Result = String;
+    const METHOD: &'static str = "sourcepawn-studio/inspectPlugin";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectPluginParams {
+    /// Compiled plugin (`.smx`) to inspect.
+    pub uri: Url,
+}
+
 pub enum ServerStatusNotification {}
 
 impl Notification for ServerStatusNotification {
@@ -177,3 +263,28 @@ pub struct CommandLink {
 pub struct ClientCommandOptions {
     pub commands: Vec<String>,
 }
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeLensResolveData {
+    /// Version of the document when the code lens was computed.
+    pub version: Option<i32>,
+    pub kind: CodeLensResolveDataKind,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum CodeLensResolveDataKind {
+    Impls(lsp_types::TextDocumentPositionParams),
+    References(lsp_types::TextDocumentPositionParams),
+}
+
+/// Path of a document link, which is looked up on disk when the link is resolved.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum DocumentLinkResolveData {
+    /// A path relative to the `addons/sourcemod` folder.
+    SourceModPath(String),
+    /// A path relative to the game folder.
+    GamePath(String),
+}
@@ -1,6 +1,6 @@
 use anyhow::format_err;
 use base_db::{FilePosition, FileRange};
-use ide::{LineCol, WideLineCol};
+use ide::{Annotation, AnnotationKind, LineCol, WideLineCol};
 use lsp_types::Url;
 use paths::AbsPathBuf;
 use rowan::{TextRange, TextSize};
@@ -9,6 +9,7 @@ use vfs::FileId;
 use crate::{
     global_state::GlobalStateSnapshot,
     line_index::{LineIndex, PositionEncoding},
+    lsp::ext::{CodeLensResolveData, CodeLensResolveDataKind},
 };
 
 pub(crate) fn abs_path(url: &lsp_types::Url) -> anyhow::Result<AbsPathBuf> {
@@ -93,3 +94,34 @@ pub(crate) fn file_range_uri(
     let file_id = file_id(snap, document)?;
     Ok(FileRange { file_id, range })
 }
+
+/// Returns the annotation of a code lens to resolve, or `None` if the document changed since
+/// the code lens was computed.
+pub(crate) fn annotation(
+    snap: &GlobalStateSnapshot,
+    range: lsp_types::Range,
+    data: CodeLensResolveData,
+) -> anyhow::Result<Option<Annotation>> {
+    match data.kind {
+        CodeLensResolveDataKind::Impls(params) => {
+            if snap.url_file_version(&params.text_document.uri) != data.version {
+                return Ok(None);
+            }
+            let pos = file_position(snap, params)?;
+            Ok(Some(Annotation {
+                range,
+                kind: AnnotationKind::HasImpls { pos, data: None },
+            }))
+        }
+        CodeLensResolveDataKind::References(params) => {
+            if snap.url_file_version(&params.text_document.uri) != data.version {
+                return Ok(None);
+            }
+            let pos = file_position(snap, params)?;
+            Ok(Some(Annotation {
+                range,
+                kind: AnnotationKind::HasReferences { pos, data: None },
+            }))
+        }
+    }
+}
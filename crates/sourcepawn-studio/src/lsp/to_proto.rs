@@ -5,11 +5,12 @@ use std::{
 
 use base_db::FileRange;
 use ide::{
-    Cancellable, CompletionKind, Highlight, HlMod, HlRange, HlTag, Markup, NavigationTarget,
-    Severity, SignatureHelp,
+    Annotation, AnnotationKind, Cancellable, CompletionKind, DocumentLink, DocumentLinkTarget,
+    Highlight, HlMod, HlRange, HlTag, Markup, NavigationTarget, Severity, SignatureHelp,
 };
 use ide_db::{
-    CallItem, IncomingCallItem, OutgoingCallItem, SourceChange, SymbolId, SymbolKind, Symbols,
+    CallItem, Documentation, IncomingCallItem, OutgoingCallItem, SourceChange, SymbolId,
+    SymbolKind, Symbols,
 };
 use itertools::Itertools;
 use lsp_types::TextEdit;
@@ -17,7 +18,10 @@ use paths::AbsPath;
 use syntax::range_contains_range;
 use vfs::FileId;
 
-use crate::global_state::GlobalStateSnapshot;
+use crate::{
+    global_state::GlobalStateSnapshot,
+    lsp::ext::{CodeLensResolveData, CodeLensResolveDataKind, DocumentLinkResolveData},
+};
 
 use super::semantic_tokens;
 
@@ -239,6 +243,15 @@ pub(crate) fn completion_item(
         text_edit: item.text_edit.map(|(range, new_text)| {
             lsp_types::CompletionTextEdit::Edit(TextEdit::new(range, new_text))
         }),
+        additional_text_edits: if item.additional_text_edits.is_empty() {
+            None
+        } else {
+            item.additional_text_edits
+                .into_iter()
+                .map(|(range, new_text)| TextEdit::new(range, new_text))
+                .collect_vec()
+                .into()
+        },
         deprecated: item.deprecated.into(),
         tags: if item.deprecated {
             Some(vec![lsp_types::CompletionItemTag::DEPRECATED])
@@ -288,17 +301,17 @@ pub(crate) fn signature_help(sig: SignatureHelp) -> lsp_types::SignatureHelp {
     lsp_types::SignatureHelp {
         signatures: vec![lsp_types::SignatureInformation {
             label: sig.signature,
-            documentation: sig.doc.clone().map(|doc| doc.into()),
+            documentation: sig.doc.as_deref().map(Into::into),
             parameters: sig
                 .parameters
                 .into_iter()
                 .map(|it| lsp_types::ParameterInformation {
-                    label: lsp_types::ParameterLabel::Simple(it.clone()),
                     documentation: sig
                         .doc
-                        .clone()
-                        // This is not efficient, but it's not a hot path.
-                        .and_then(|doc| doc.param_description(&it).map(|it| it.into())),
+                        .as_ref()
+                        .and_then(|doc| doc.param(&it))
+                        .map(|description| Documentation::from(description).into()),
+                    label: lsp_types::ParameterLabel::Simple(it),
                 })
                 .collect_vec()
                 .into(),
@@ -463,14 +476,184 @@ fn call_hierarchy_item(
     }
 }
 
+pub(crate) fn code_lens(
+    acc: &mut Vec<lsp_types::CodeLens>,
+    snap: &GlobalStateSnapshot,
+    annotation: Annotation,
+) -> Cancellable<()> {
+    match annotation.kind {
+        AnnotationKind::Build { file_id } => acc.push(lsp_types::CodeLens {
+            range: annotation.range,
+            command: Some(command::build_projects(snap, file_id)),
+            data: None,
+        }),
+        AnnotationKind::HasImpls { pos, data } => {
+            let uri = url(snap, pos.file_id);
+            let command = data
+                .map(|targets| -> Cancellable<_> {
+                    let locations = targets
+                        .into_iter()
+                        .map(|nav| {
+                            location(
+                                snap,
+                                FileRange {
+                                    file_id: nav.file_id,
+                                    range: nav.focus_or_full_range(),
+                                },
+                            )
+                        })
+                        .collect::<Cancellable<Vec<_>>>()?;
+                    Ok(command::show_references(
+                        implementation_title(locations.len()),
+                        &uri,
+                        pos.position,
+                        locations,
+                    ))
+                })
+                .transpose()?;
+            acc.push(lsp_types::CodeLens {
+                range: annotation.range,
+                command,
+                data: code_lens_resolve_data(
+                    snap,
+                    &uri,
+                    CodeLensResolveDataKind::Impls(lsp_types::TextDocumentPositionParams::new(
+                        lsp_types::TextDocumentIdentifier::new(uri.clone()),
+                        pos.position,
+                    )),
+                ),
+            })
+        }
+        AnnotationKind::HasReferences { pos, data } => {
+            let uri = url(snap, pos.file_id);
+            let command = data
+                .map(|franges| -> Cancellable<_> {
+                    let locations = references_response(snap, franges)?;
+                    Ok(command::show_references(
+                        reference_title(locations.len()),
+                        &uri,
+                        pos.position,
+                        locations,
+                    ))
+                })
+                .transpose()?;
+            acc.push(lsp_types::CodeLens {
+                range: annotation.range,
+                command,
+                data: code_lens_resolve_data(
+                    snap,
+                    &uri,
+                    CodeLensResolveDataKind::References(
+                        lsp_types::TextDocumentPositionParams::new(
+                            lsp_types::TextDocumentIdentifier::new(uri.clone()),
+                            pos.position,
+                        ),
+                    ),
+                ),
+            })
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a document link. The SourceMod and game paths are only looked up on disk when
+/// the link is resolved.
+pub(crate) fn document_link(
+    snap: &GlobalStateSnapshot,
+    link: DocumentLink,
+) -> lsp_types::DocumentLink {
+    let (target, tooltip, data) = match link.target {
+        DocumentLinkTarget::File(file_id) => (Some(url(snap, file_id)), None, None),
+        DocumentLinkTarget::SourceModPath(path) => (
+            None,
+            None,
+            Some(DocumentLinkResolveData::SourceModPath(path)),
+        ),
+        DocumentLinkTarget::GamePath(path) => {
+            (None, None, Some(DocumentLinkResolveData::GamePath(path)))
+        }
+        DocumentLinkTarget::Unresolved(path) => {
+            (None, Some(format!("Unresolved include `{}`", path)), None)
+        }
+    };
+
+    lsp_types::DocumentLink {
+        range: link.range,
+        target,
+        tooltip,
+        data: data.and_then(|it| serde_json::to_value(it).ok()),
+    }
+}
+
+fn code_lens_resolve_data(
+    snap: &GlobalStateSnapshot,
+    uri: &lsp_types::Url,
+    kind: CodeLensResolveDataKind,
+) -> Option<serde_json::Value> {
+    serde_json::to_value(CodeLensResolveData {
+        version: snap.url_file_version(uri),
+        kind,
+    })
+    .ok()
+}
+
+pub(crate) fn implementation_title(count: usize) -> String {
+    if count == 1 {
+        "1 implementation".into()
+    } else {
+        format!("{} implementations", count)
+    }
+}
+
+pub(crate) fn reference_title(count: usize) -> String {
+    if count == 1 {
+        "1 reference".into()
+    } else {
+        format!("{} references", count)
+    }
+}
+
 pub(crate) mod command {
     use base_db::FileRange;
     use ide::NavigationTarget;
     use serde_json::to_value;
 
+    use vfs::FileId;
+
     use crate::{global_state::GlobalStateSnapshot, lsp::to_proto::location_link};
 
-    use super::location;
+    use super::{location, url};
+
+    pub(crate) fn show_references(
+        title: String,
+        uri: &lsp_types::Url,
+        position: lsp_types::Position,
+        locations: Vec<lsp_types::Location>,
+    ) -> lsp_types::Command {
+        // `editor.action.showReferences` expects VS Code types, the arguments are converted
+        // by the client.
+        lsp_types::Command {
+            title,
+            command: "sourcepawn-vscode.showReferences".into(),
+            arguments: Some(vec![
+                to_value(uri).unwrap(),
+                to_value(position).unwrap(),
+                to_value(locations).unwrap(),
+            ]),
+        }
+    }
+
+    pub(crate) fn build_projects(
+        snap: &GlobalStateSnapshot,
+        file_id: FileId,
+    ) -> lsp_types::Command {
+        lsp_types::Command {
+            title: "Run build".into(),
+            command: "sourcepawn-vscode.buildProjects".into(),
+            arguments: Some(vec![to_value(url(snap, file_id)).unwrap()]),
+        }
+    }
 
     pub(crate) fn goto_location(
         snap: &GlobalStateSnapshot,
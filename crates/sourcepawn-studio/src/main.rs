@@ -1,5 +1,6 @@
 use clap::ArgAction;
 use clap::Parser;
+use clap::Subcommand;
 use log::LevelFilter;
 use lsp_server::Connection;
 use std::env;
@@ -9,7 +10,10 @@ use std::io;
 use std::path::PathBuf;
 use std::time::SystemTime;
 
-use sourcepawn_studio::GlobalState;
+use sourcepawn_studio::{
+    cli::docs::{generate_docs, DocsFormat},
+    GlobalState,
+};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -36,12 +40,60 @@ pub struct Opts {
     /// Write the logging output to FILE
     #[clap(long, name = "FILE", value_parser)]
     log_file: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Command {
+    /// Dump the natives, publics, pubvars, tags and debug information of a compiled plugin
+    Smx {
+        /// Path to the .smx file
+        path: PathBuf,
+    },
+
+    /// Generate the documentation of a library of include files, with a JSON dump of its API
+    Docs {
+        /// Include files to document, or directories to search for include files
+        #[clap(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Directory the documentation is written to
+        #[clap(short, long, default_value = "docs")]
+        out_dir: PathBuf,
+
+        /// Format of the pages of the documentation
+        #[clap(short, long, value_enum, default_value_t = DocsFormat::Html)]
+        format: DocsFormat,
+
+        /// Additional directory used to resolve the includes of the files
+        #[clap(short, long = "include-dir")]
+        include_dirs: Vec<PathBuf>,
+    },
 }
 
 fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
     let opts = Opts::parse();
     setup_logger(opts.clone());
 
+    match &opts.command {
+        Some(Command::Smx { path }) => {
+            print!("{}", smx::Plugin::read(path)?);
+            return Ok(());
+        }
+        Some(Command::Docs {
+            paths,
+            out_dir,
+            format,
+            include_dirs,
+        }) => {
+            generate_docs(paths, include_dirs, out_dir, *format)?;
+            return Ok(());
+        }
+        None => (),
+    }
+
     let _guard = if !opts.disable_telemetry {
         log::info!("Telemetry is enabled. To disable it, use the --disable-telemetry flag.");
         Some(sentry::init(("https://621f3ac25899467a92414f0cabd31346@o4505249792262144.ingest.sentry.io/4505249800519680", sentry::ClientOptions {
@@ -3,6 +3,7 @@ use std::{env, path::PathBuf, time::Instant};
 use always_assert::always;
 use base_db::SourceDatabase;
 use crossbeam::channel::Receiver;
+use flycheck::Compiler;
 use itertools::Itertools;
 use lsp_server::Message;
 use lsp_types::{
@@ -18,11 +19,16 @@ use vfs::{FileId, VfsPath};
 use crate::{
     capabilities::{server_capabilities, ClientCapabilitiesExt},
     config::Config,
-    diagnostics::fetch_native_diagnostics,
+    diagnostics::{fetch_native_diagnostics, fetch_plugin_diagnostics},
     dispatch::{NotificationDispatcher, RequestDispatcher},
     from_json,
     global_state::file_id_to_url,
-    lsp::{self, ext::SpcompStatusParams, from_proto},
+    handlers::notification::run_overlay_flycheck,
+    lsp::{
+        self,
+        ext::{BuildResultParams, SpcompStatusParams},
+        from_proto, to_proto,
+    },
     progress::Progress,
     version::version,
     GlobalState,
@@ -41,7 +47,16 @@ pub(crate) enum Task {
     Response(lsp_server::Response),
     Retry(lsp_server::Request),
     Diagnostics(Vec<(FileId, Vec<lsp_types::Diagnostic>)>),
+    /// Diagnostics of a plugin built by the `generation`th check of the flycheck
+    /// `flycheck_id`.
+    PluginDiagnostics {
+        flycheck_id: u32,
+        generation: u32,
+        diagnostics: Vec<(FileId, lsp_types::Diagnostic)>,
+    },
     PrimeCaches(PrimeCachesProgress),
+    /// Compilers detected on a worker thread.
+    CompilersDetected(Vec<Compiler>),
 }
 
 #[derive(Debug)]
@@ -72,6 +87,7 @@ impl GlobalState {
             self.handle_event(event)?;
         }
 
+        self.detect_compilers();
         self.reload_flycheck();
 
         self.update_status_or_notify();
@@ -413,7 +429,13 @@ impl GlobalState {
             .on::<lsp_request::SignatureHelpRequest>(handlers::handle_signature_help)
             .on::<lsp_request::References>(handlers::handle_references)
             .on::<lsp_request::Rename>(handlers::handle_rename)
+            .on::<lsp_request::CodeActionRequest>(handlers::handle_code_action)
+            .on::<lsp_request::OnTypeFormatting>(handlers::handle_on_type_formatting)
             .on::<lsp_request::DocumentSymbolRequest>(handlers::handle_symbol)
+            .on::<lsp_request::CodeLensRequest>(handlers::handle_code_lens)
+            .on::<lsp_request::CodeLensResolve>(handlers::handle_code_lens_resolve)
+            .on::<lsp_request::DocumentLinkRequest>(handlers::handle_document_link)
+            .on::<lsp_request::DocumentLinkResolve>(handlers::handle_document_link_resolve)
             .on::<lsp_request::CallHierarchyPrepare>(handlers::handle_call_hierarchy_prepare)
             .on::<lsp_request::CallHierarchyIncomingCalls>(handlers::handle_call_hierarchy_incoming)
             .on::<lsp_request::CallHierarchyOutgoingCalls>(handlers::handle_call_hierarchy_outgoing)
@@ -423,7 +445,10 @@ impl GlobalState {
             .on::<lsp::ext::PreprocessedDocument>(handlers::handle_preprocessed_document)
             .on::<lsp::ext::ItemTree>(handlers::handle_item_tree)
             .on::<lsp::ext::AnalyzerStatus>(handlers::handle_analyzer_status)
+            .on::<lsp::ext::InspectPlugin>(handlers::handle_inspect_plugin)
             .on::<lsp::ext::ProjectMainPath>(handlers::handle_project_main_path)
+            .on::<lsp::ext::Build>(handlers::handle_build)
+            .on_sync_mut_deferred::<lsp::ext::BuildAll>(handlers::handle_build_all)
             .finish();
         log::debug!("Handled request id: {:?}", req_id);
     }
@@ -480,6 +505,24 @@ impl GlobalState {
             });
     }
 
+    /// Check the plugin built by the flycheck `id` in the background.
+    fn check_built_plugin(&mut self, id: u32, path: AbsPathBuf) {
+        let generation = self.flycheck_generation(id);
+        self.task_pool.handle.spawn(ThreadIntent::Worker, {
+            let snapshot = self.snapshot();
+            move || Task::PluginDiagnostics {
+                flycheck_id: id,
+                generation,
+                diagnostics: fetch_plugin_diagnostics(snapshot, FileId(id), path),
+            }
+        });
+    }
+
+    /// Number of checks the flycheck `id` started, which identifies its current check.
+    fn flycheck_generation(&self, id: u32) -> u32 {
+        self.flycheck_generations.get(&id).copied().unwrap_or(0)
+    }
+
     fn handle_vfs_msg(&mut self, message: vfs::loader::Message) {
         match message {
             vfs::loader::Message::Loaded { files } => {
@@ -522,21 +565,22 @@ impl GlobalState {
     }
 
     fn handle_flycheck_msg(&mut self, message: flycheck::Message) {
+        let in_build_all = self.handle_build_all_msg(&message);
         match message {
             flycheck::Message::AddDiagnostic { id, diagnostic, .. } => {
                 let diag = crate::diagnostics::to_proto::map_spcomp_diagnostic_to_lsp(&diagnostic);
-                if let Some(file_id) = self
-                    .vfs
-                    .read()
-                    .file_id(&VfsPath::from(diagnostic.path().to_owned()))
-                {
-                    self.diagnostics.add_check_diagnostic(id, file_id, diag)
+                let vfs = self.vfs.read();
+                if let Some(file_id) = vfs.file_id(&VfsPath::from(diagnostic.path().to_owned())) {
+                    let text = std::str::from_utf8(vfs.file_contents(file_id)).ok();
+                    self.diagnostics
+                        .add_check_diagnostic(id, file_id, diag, text)
                 }
             }
 
             flycheck::Message::Progress { id, progress } => {
                 let (state, message) = match progress {
                     flycheck::Progress::DidStart => {
+                        *self.flycheck_generations.entry(id).or_default() += 1;
                         self.diagnostics.clear_check(id);
                         self.send_notification::<lsp::ext::SpcompStatusNotification>(
                             SpcompStatusParams { quiescent: false },
@@ -554,7 +598,10 @@ impl GlobalState {
                     flycheck::Progress::DidFailToRestart(err) => {
                         self.last_flycheck_error =
                             Some(format!("spcomp check failed to start: {err}"));
-                        return;
+                        self.send_notification::<lsp::ext::SpcompStatusNotification>(
+                            SpcompStatusParams { quiescent: true },
+                        );
+                        (Progress::End, None)
                     }
                     flycheck::Progress::DidFinish(result) => {
                         self.last_flycheck_error = result
@@ -565,6 +612,35 @@ impl GlobalState {
                         );
                         (Progress::End, None)
                     }
+                    flycheck::Progress::DidBuild(result) => {
+                        if let Ok(plugin) = &result {
+                            self.check_built_plugin(id, plugin.path.clone());
+                        }
+                        let project = self.vfs.read().file_path(FileId(id));
+                        // The build of all the projects reports the results in its response.
+                        if let Some(project) = project.as_path().filter(|_| !in_build_all) {
+                            let project = to_proto::url_from_abs_path(project);
+                            let params = match result {
+                                Ok(plugin) => BuildResultParams {
+                                    project,
+                                    output: Some(to_proto::url_from_abs_path(&plugin.path)),
+                                    size: Some(plugin.size),
+                                    error: None,
+                                },
+                                Err(err) => BuildResultParams {
+                                    project,
+                                    output: None,
+                                    size: None,
+                                    error: Some(err.to_string()),
+                                },
+                            };
+                            self.send_notification::<lsp::ext::BuildResultNotification>(params);
+                        }
+                        self.send_notification::<lsp::ext::SpcompStatusNotification>(
+                            SpcompStatusParams { quiescent: true },
+                        );
+                        (Progress::End, None)
+                    }
                 };
 
                 // When we're running multiple flychecks, we have to include a disambiguator in
@@ -591,9 +667,36 @@ impl GlobalState {
             Task::Retry(req) if !self.is_completed(&req) => self.on_request(req),
             Task::Retry(_) => (),
             Task::Diagnostics(diagnostics_per_file) => {
+                let vfs = self.vfs.read();
                 for (file_id, diagnostics) in diagnostics_per_file {
                     self.diagnostics
-                        .set_native_diagnostics(file_id, diagnostics)
+                        .set_native_diagnostics(file_id, diagnostics);
+                    if !vfs.exists(file_id) {
+                        continue;
+                    }
+                    if let Ok(text) = std::str::from_utf8(vfs.file_contents(file_id)) {
+                        self.diagnostics.clear_stale_check_for(file_id, text);
+                    }
+                }
+            }
+            Task::PluginDiagnostics {
+                flycheck_id,
+                generation,
+                diagnostics,
+            } => {
+                // The flycheck started another check since the plugin was built, its results
+                // would be cleared with the ones of the build.
+                if generation != self.flycheck_generation(flycheck_id) {
+                    return;
+                }
+                let vfs = self.vfs.read();
+                for (file_id, diagnostic) in diagnostics {
+                    let text = vfs
+                        .exists(file_id)
+                        .then(|| std::str::from_utf8(vfs.file_contents(file_id)).ok())
+                        .flatten();
+                    self.diagnostics
+                        .add_check_diagnostic(flycheck_id, file_id, diagnostic, text);
                 }
             }
             Task::PrimeCaches(progress) => match progress {
@@ -609,6 +712,7 @@ impl GlobalState {
                 }
                 PrimeCachesProgress::End { .. } => prime_caches_progress.push(progress),
             },
+            Task::CompilersDetected(compilers) => self.on_compilers_detected(compilers),
         }
     }
 
@@ -694,6 +798,11 @@ impl GlobalState {
         let state_changed = self.process_changes();
         let memdocs_added_or_removed = self.mem_docs.take_changes();
 
+        if !self.overlay_check_requested.is_empty() {
+            let file_ids = std::mem::take(&mut self.overlay_check_requested);
+            run_overlay_flycheck(self, file_ids);
+        }
+
         if self.is_quiescent() {
             let became_quiescent = !(was_quiescent);
 
@@ -711,9 +820,9 @@ impl GlobalState {
                 }
 
                 // Refresh code lens if the client supports it.
-                // if self.config.code_lens_refresh() {
-                //     self.send_request::<lsp_types::request::CodeLensRefresh>((), |_, _| ());
-                // }
+                if self.config.code_lens_refresh() {
+                    self.send_request::<lsp_types::request::CodeLensRefresh>((), |_, _| ());
+                }
 
                 // Refresh inlay hints if the client supports it.
                 // if (self.send_hint_refresh_query || self.proc_macro_changed)
@@ -62,10 +62,26 @@ impl MemDocs {
 #[derive(Debug, Clone)]
 pub(crate) struct DocumentData {
     pub(crate) version: i32,
+    /// Hash of the contents of the document when it was last opened or saved, i.e. of the
+    /// contents of the file on disk.
+    saved_hash: u64,
 }
 
 impl DocumentData {
-    pub(crate) fn new(version: i32) -> Self {
-        DocumentData { version }
+    pub(crate) fn new(version: i32, text: &[u8]) -> Self {
+        DocumentData {
+            version,
+            saved_hash: fxhash::hash64(text),
+        }
+    }
+
+    /// Record that the document was saved with the given contents.
+    pub(crate) fn set_saved(&mut self, text: &[u8]) {
+        self.saved_hash = fxhash::hash64(text);
+    }
+
+    /// Whether the given contents of the document differ from the file on disk.
+    pub(crate) fn is_modified(&self, text: &[u8]) -> bool {
+        fxhash::hash64(text) != self.saved_hash
     }
 }
@@ -1,17 +1,22 @@
 use std::sync::Arc;
+use std::time::Duration;
 use std::{mem, vec};
 
-use flycheck::{FlycheckConfig, FlycheckHandle};
+use flycheck::{Compiler, FlycheckConfig, FlycheckHandle};
 use fxhash::FxHashMap;
 use itertools::Itertools;
 use paths::AbsPathBuf;
+use stdx::thread::ThreadIntent;
 use vfs::VfsPath;
 
 use crate::lsp;
-use crate::{config::Config, GlobalState};
+use crate::{config::Config, main_loop::Task, GlobalState};
 
 use stdx::format_to;
 
+/// Time after which a compiler which does not exit is killed during its detection.
+const COMPILER_DETECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
 impl GlobalState {
     pub(crate) fn is_quiescent(&self) -> bool {
         !(self.last_reported_status.is_none()
@@ -36,61 +41,203 @@ impl GlobalState {
     }
 
     pub(crate) fn update_configuration(&mut self, config: Config, initialization: bool) {
+        let old_include_directories = self.include_directories();
         let old_config = mem::replace(&mut self.config, Arc::new(config));
-        if self.config.include_directories() != old_config.include_directories()
+        if self.include_directories() != old_include_directories
             || self.config.root_path() != old_config.root_path()
         {
-            let mut roots = vec![VfsPath::from(self.config.root_path().clone())];
-            roots.extend(
-                self.config
-                    .include_directories()
-                    .into_iter()
-                    .map(VfsPath::from),
-            );
-            self.source_root_config.fsc.set_roots(roots);
-            let mut load = self
-                .config
-                .include_directories()
-                .into_iter()
-                .map(vfs::loader::Entry::sp_files_recursively)
-                .collect_vec();
-            let watch = (0..load.len()).collect_vec();
-            // The root_path can be the FS' root. Do not scrape the whole FS in that case.
-            if self.config.root_path().parent().is_some() {
-                load.push(vfs::loader::Entry::sp_files_recursively(
-                    self.config.root_path().clone(),
-                ));
-            }
-            self.vfs_config_version += 1;
-            self.loader.handle.set_config(vfs::loader::Config {
-                load,
-                watch,
-                version: self.vfs_config_version,
-            });
+            self.reload_vfs_config();
+        }
+        if initialization
+            || self.config.completion_data_files() != old_config.completion_data_files()
+        {
+            self.load_completion_data();
+        }
+        if initialization || self.config.selected_games() != old_config.selected_games() {
+            self.analysis_host
+                .set_selected_games(self.config.selected_games());
         }
         if !initialization
             && (self.config.compiler_path() != old_config.compiler_path()
+                || self.config.compiler_projects() != old_config.compiler_projects())
+        {
+            self.detect_compilers();
+        }
+        if !initialization
+            && (self.config.compiler_path() != old_config.compiler_path()
+                || self.config.compiler_projects() != old_config.compiler_projects()
                 || self.config.compiler_arguments() != old_config.compiler_arguments()
-                || self.config.include_directories() != old_config.include_directories())
+                || self.config.include_directories() != old_config.include_directories()
+                || self.config.compiler_output_directory()
+                    != old_config.compiler_output_directory())
         {
             self.reload_flycheck();
         }
     }
 
+    /// Include directories of the workspace, followed by the `include` directories shipped
+    /// with the detected compilers.
+    pub(crate) fn include_directories(&self) -> Vec<AbsPathBuf> {
+        let compiler_directories = self
+            .config
+            .compiler_paths()
+            .into_iter()
+            .filter_map(|command| {
+                self.detected_compilers
+                    .get(command)?
+                    .include_directory
+                    .clone()
+            });
+        self.config
+            .include_directories()
+            .into_iter()
+            .chain(compiler_directories)
+            .unique()
+            .collect_vec()
+    }
+
+    /// Set the roots of the VFS and the files to load from the workspace, the include
+    /// directories and the translations directories.
+    fn reload_vfs_config(&mut self) {
+        let include_directories = self.include_directories();
+        let translations_directories = self.config.translations_directories();
+        let mut roots = vec![VfsPath::from(self.config.root_path().clone())];
+        roots.extend(include_directories.iter().cloned().map(VfsPath::from));
+        // Translations of the workspace are already covered by the root path.
+        roots.extend(
+            translations_directories
+                .iter()
+                .filter(|it| !it.starts_with(self.config.root_path()))
+                .cloned()
+                .map(VfsPath::from),
+        );
+        self.source_root_config.fsc.set_roots(roots);
+        let mut load = include_directories
+            .into_iter()
+            .map(vfs::loader::Entry::sp_files_recursively)
+            .chain(
+                translations_directories
+                    .into_iter()
+                    .map(vfs::loader::Entry::translation_files_recursively),
+            )
+            .collect_vec();
+        let watch = (0..load.len()).collect_vec();
+        // The root_path can be the FS' root. Do not scrape the whole FS in that case.
+        if self.config.root_path().parent().is_some() {
+            load.push(vfs::loader::Entry::sp_files_recursively(
+                self.config.root_path().clone(),
+            ));
+        }
+        self.vfs_config_version += 1;
+        self.loader.handle.set_config(vfs::loader::Config {
+            load,
+            watch,
+            version: self.vfs_config_version,
+        });
+    }
+
+    /// Detect the configured compilers which were not detected yet on a worker thread, as
+    /// running them can take a while. The flychecks are reloaded once they are detected.
+    pub(crate) fn detect_compilers(&mut self) {
+        let commands = self
+            .config
+            .compiler_paths()
+            .into_iter()
+            .filter(|command| !self.detected_compilers.contains_key(*command))
+            .map(ToOwned::to_owned)
+            .collect_vec();
+        if commands.is_empty() {
+            return;
+        }
+        self.task_pool.handle.spawn(ThreadIntent::Worker, move || {
+            Task::CompilersDetected(
+                commands
+                    .into_iter()
+                    .map(|command| Compiler::detect(command, COMPILER_DETECTION_TIMEOUT))
+                    .collect(),
+            )
+        });
+    }
+
+    pub(crate) fn on_compilers_detected(&mut self, compilers: Vec<Compiler>) {
+        let old_include_directories = self.include_directories();
+        for compiler in compilers {
+            self.detected_compilers
+                .insert(compiler.command.clone(), compiler);
+        }
+        if self.include_directories() != old_include_directories {
+            self.reload_vfs_config();
+        }
+        self.reload_flycheck();
+    }
+
+    /// Load the completion data files provided by the user, and merge them with the
+    /// built-in completion data.
+    fn load_completion_data(&mut self) {
+        let mut databases = Vec::new();
+        for path in self.config.completion_data_files() {
+            let res = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| Ok(completion_data::Database::from_json(&json)?.into_owned()));
+            match res {
+                Ok(database) => databases.push(database),
+                Err(err) => self.show_message(
+                    lsp_types::MessageType::ERROR,
+                    format!(
+                        "Failed to load the completion data file {}: {}",
+                        path.display(),
+                        err
+                    ),
+                    false,
+                ),
+            }
+        }
+        self.analysis_host
+            .set_completion_data(completion_data::merged_database(databases));
+    }
+
     pub fn reload_flycheck(&mut self) {
+        // The flychecks of the pending build are dropped, so it would never finish.
+        self.cancel_build_all("The projects were reloaded during the build.");
         let analysis = self.analysis_host.analysis();
-        let Some(compiler_path) = self.config.compiler_path() else {
-            return;
-        };
         let Ok(graph) = analysis.graph() else {
             // FIXME: report error
             return;
         };
+        let config = Arc::clone(&self.config);
         let tempdir_path = AbsPathBuf::try_from(self.flycheck_tempdir.path().to_path_buf())
             .expect("Failed to convert tempdir path to AbsPathBuf.");
         let mut flycheck = FxHashMap::default();
-        graph.subgraphs_with_roots().keys().for_each(|root| {
+        let mut compilers = FxHashMap::default();
+        for root in graph.subgraphs_with_roots().keys() {
             let root = *root;
+            let Some(root_path) = self
+                .vfs
+                .read()
+                .file_path(root)
+                .as_path()
+                .map(ToOwned::to_owned)
+            else {
+                continue;
+            };
+            let Some(command) = config.compiler_path_for(&root_path) else {
+                continue;
+            };
+            // Compilers which are still being detected are used without their includes until
+            // the flychecks are reloaded.
+            let compiler = self
+                .detected_compilers
+                .get(command)
+                .cloned()
+                .unwrap_or_else(|| Compiler::undetected(command.to_owned()));
+            // The includes shipped with the compiler match its version, so they take
+            // precedence over the ones configured for the whole workspace.
+            let include_directories = compiler
+                .include_directory
+                .iter()
+                .cloned()
+                .chain(config.include_directories())
+                .collect_vec();
             let sender = self.flycheck_sender.clone();
             flycheck.insert(
                 root,
@@ -98,21 +245,19 @@ impl GlobalState {
                     root.0,
                     Box::new(move |msg| sender.send(msg).unwrap()),
                     FlycheckConfig::new(
-                        compiler_path.to_owned(),
-                        self.config.compiler_arguments(),
-                        self.config.include_directories().clone(),
+                        compiler.command.clone(),
+                        config.compiler_arguments(),
+                        include_directories,
+                        config.compiler_output_directory(),
                     ),
-                    self.vfs
-                        .read()
-                        .file_path(root)
-                        .as_path()
-                        .unwrap()
-                        .to_owned(),
+                    root_path,
                     tempdir_path.clone(),
                 ),
             );
-        });
+            compilers.insert(root, compiler);
+        }
 
         self.flycheck = Arc::new(flycheck);
+        self.compilers = Arc::new(compilers);
     }
 }
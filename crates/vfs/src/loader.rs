@@ -93,6 +93,23 @@ impl Entry {
         Entry::Directories(dirs(base, &[".git"]))
     }
 
+    /// Returns:
+    /// ```text
+    /// Entry::Directories(Directories {
+    ///     extensions: ["txt"],
+    ///     include: [base],
+    ///     exclude: [base/.git],
+    /// })
+    /// ```
+    pub fn translation_files_recursively(base: AbsPathBuf) -> Entry {
+        let exclude = vec![base.join(".git")];
+        Entry::Directories(Directories {
+            extensions: vec!["txt".to_string()],
+            include: vec![base],
+            exclude,
+        })
+    }
+
     /// Returns `true` if `path` is included in `self`.
     ///
     /// See [`Directories::contains_file`].
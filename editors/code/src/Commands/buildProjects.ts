@@ -0,0 +1,51 @@
+import * as vscode from "vscode";
+import { build, buildAll, BuildParams } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+export function buildProjectsCommand(ctx: CtxInit): Cmd {
+  return async (uri?: string) => {
+    const params: BuildParams = {};
+    const doc = vscode.window.activeTextEditor?.document;
+    if (uri !== undefined) {
+      // Invoked from the code lens of a main file.
+      params.uri = uri;
+    } else if (doc !== undefined) {
+      params.uri = ctx.client.code2ProtocolConverter.asUri(doc.uri);
+    }
+    const projects = await ctx.client.sendRequest(build, params);
+    if (projects.length === 0) {
+      vscode.window.showErrorMessage("No project to build.");
+    }
+  };
+}
+
+export function buildAllProjectsCommand(ctx: CtxInit): Cmd {
+  return async () => {
+    const summaries = await vscode.window.withProgress(
+      {
+        location: vscode.ProgressLocation.Notification,
+        title: "Building all the projects with spcomp",
+      },
+      () => ctx.client.sendRequest(buildAll, {})
+    );
+    const failed = summaries.filter((summary) => !summary.success);
+    const warnings = summaries.reduce(
+      (acc, summary) => acc + summary.warnings,
+      0
+    );
+    const message = `Built ${summaries.length - failed.length}/${
+      summaries.length
+    } plugins with ${warnings} warning(s).`;
+    if (failed.length === 0) {
+      vscode.window.showInformationMessage(message);
+      return;
+    }
+    const details = failed
+      .map((summary) => {
+        const project = vscode.Uri.parse(summary.project).fsPath;
+        return `${project}: ${summary.error ?? `${summary.errors} error(s)`}`;
+      })
+      .join("\n");
+    vscode.window.showErrorMessage(message, { detail: details, modal: true });
+  };
+}
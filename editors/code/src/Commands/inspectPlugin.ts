@@ -0,0 +1,30 @@
+import * as vscode from "vscode";
+import { inspectPlugin } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+export function inspectPluginCommand(ctx: CtxInit): Cmd {
+  return async (uri?: vscode.Uri) => {
+    if (uri === undefined) {
+      const selected = await vscode.window.showOpenDialog({
+        canSelectMany: false,
+        filters: { Plugins: ["smx"] },
+        openLabel: "Inspect",
+      });
+      if (selected === undefined || selected.length === 0) {
+        return;
+      }
+      uri = selected[0];
+    }
+    const text = await ctx.client.sendRequest(inspectPlugin, {
+      uri: ctx.client.code2ProtocolConverter.asUri(uri),
+    });
+    const document = await vscode.workspace.openTextDocument({
+      content: text,
+      language: "plaintext",
+    });
+    void (await vscode.window.showTextDocument(document, {
+      viewColumn: vscode.ViewColumn.Two,
+      preserveFocus: true,
+    }));
+  };
+}
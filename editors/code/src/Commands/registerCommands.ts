@@ -19,6 +19,8 @@ import { preprocessedDocumentCommand } from "./preprocessedDocument";
 import { syntaxTreeCommand } from "./syntaxTree";
 import { itemTreeCommand } from "./itemTree";
 import { analyzerStatusCommand } from "./analyzerStatus";
+import { buildAllProjectsCommand, buildProjectsCommand } from "./buildProjects";
+import { inspectPluginCommand } from "./inspectPlugin";
 import { Cmd, CommandFactory, Ctx, CtxInit } from "../ctx";
 import * as lc from "vscode-languageclient";
 import { LINKED_COMMANDS } from "../client";
@@ -150,6 +152,22 @@ function gotoLocation(ctx: CtxInit): Cmd {
   };
 }
 
+function showReferences(ctx: CtxInit): Cmd {
+  return async (
+    uri: string,
+    position: lc.Position,
+    locations: lc.Location[]
+  ) => {
+    const client = ctx.client;
+    await vscode.commands.executeCommand(
+      "editor.action.showReferences",
+      client.protocol2CodeConverter.asUri(uri),
+      client.protocol2CodeConverter.asPosition(position),
+      locations.map(client.protocol2CodeConverter.asLocation)
+    );
+  };
+}
+
 /**
  * Prepare a record of server specific commands.
  * @returns Record
@@ -193,9 +211,21 @@ export function createServerCommands(): Record<string, CommandFactory> {
     analyzerStatus: {
       enabled: analyzerStatusCommand,
     },
+    buildProjects: {
+      enabled: buildProjectsCommand,
+    },
+    buildAllProjects: {
+      enabled: buildAllProjectsCommand,
+    },
+    inspectPlugin: {
+      enabled: inspectPluginCommand,
+    },
     gotoLocation: {
       enabled: gotoLocation,
     },
+    showReferences: {
+      enabled: showReferences,
+    },
     linkToCommand: { enabled: linkToCommand },
   };
 }
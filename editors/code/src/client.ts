@@ -153,7 +153,11 @@ class ExperimentalFeatures implements lc.StaticFeature {
       openServerLogs: true,
       localDocs: true,
       commands: {
-        commands: ["sourcepawn-vscode.gotoLocation"],
+        commands: [
+          "sourcepawn-vscode.gotoLocation",
+          "sourcepawn-vscode.showReferences",
+          "sourcepawn-vscode.buildProjects",
+        ],
       },
       ...capabilities.experimental,
     };
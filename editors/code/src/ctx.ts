@@ -169,6 +169,11 @@ export class Ctx {
           this.setSpcompStatus(params)
         )
       );
+      this.pushClientCleanup(
+        this._client.onNotification(lsp_ext.buildResult, (params) =>
+          this.showBuildResult(params)
+        )
+      );
       // this.pushClientCleanup(
       //   this._client.onNotification(lsp_ext.openServerLogs, () => {
       //     this.outputChannel!.show();
@@ -360,6 +365,20 @@ export class Ctx {
     }
   }
 
+  showBuildResult(result: lsp_ext.BuildResultParams) {
+    const project = vscode.Uri.parse(result.project).fsPath;
+    if (result.error !== undefined || result.output === undefined) {
+      vscode.window.showErrorMessage(
+        `Failed to build ${project}: ${result.error ?? "unknown error"}`
+      );
+      return;
+    }
+    const output = vscode.Uri.parse(result.output).fsPath;
+    const size =
+      result.size !== undefined ? ` (${(result.size / 1024).toFixed(1)} KB)` : "";
+    vscode.window.showInformationMessage(`Built ${output}${size}`);
+  }
+
   pushExtCleanup(d: Disposable) {
     this.extCtx.subscriptions.push(d);
   }
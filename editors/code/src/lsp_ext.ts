@@ -60,6 +60,54 @@ export type ProjectsGraphvizParams = {
   textDocument?: lc.TextDocumentIdentifier;
 };
 
+export const build = new lc.RequestType<BuildParams, lc.URI[], void>(
+  "sourcepawn-studio/build"
+);
+
+export type BuildParams = {
+  uri?: lc.URI;
+};
+
+export const buildAll = new lc.RequestType<
+  BuildAllParams,
+  PluginBuildSummary[],
+  void
+>("sourcepawn-studio/buildAll");
+
+export type BuildAllParams = {
+  checkOnly?: boolean;
+};
+
+export type PluginBuildSummary = {
+  project: lc.URI;
+  success: boolean;
+  errors: number;
+  warnings: number;
+  output?: lc.URI;
+  size?: number;
+  error?: string;
+};
+
+export const inspectPlugin = new lc.RequestType<
+  InspectPluginParams,
+  string,
+  void
+>("sourcepawn-studio/inspectPlugin");
+
+export type InspectPluginParams = {
+  uri: lc.URI;
+};
+
+export const buildResult = new lc.NotificationType<BuildResultParams>(
+  "sourcepawn-studio/buildResult"
+);
+export type BuildResultParams = {
+  project: lc.URI;
+  output?: lc.URI;
+  size?: number;
+  error?: string;
+};
+
 export const serverStatus = new lc.NotificationType<ServerStatusParams>(
   "sourcepawn-studio/serverStatus"
 );
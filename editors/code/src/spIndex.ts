@@ -70,7 +70,7 @@ export async function activate(context: vscode.ExtensionContext) {
         workspaceFolder: folder,
         synchronize: {
           fileEvents: vscode.workspace.createFileSystemWatcher(
-            `${folder.uri.fsPath}/**/*.{inc,sp}`
+            `${folder.uri.fsPath}/{**/*.inc,**/*.sp,**/*.phrases.txt}`
           ),
         },
       };